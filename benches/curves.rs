@@ -0,0 +1,61 @@
+//! Rough timings for the native (k256) and hand-rolled (P-384, P-521 and P-256) code paths.
+//!
+//! There's no benchmarking framework among the dependencies, so this is a plain `harness = false` binary that times
+//! each operation over a fixed number of iterations - run it with `cargo bench`. Every key is built from fixed bytes,
+//! so the numbers don't depend on the RNG.
+
+use std::hint::black_box;
+use std::time::Instant;
+
+use static_dh_ecdh::ecdh::ecdh::{FromBytes, KeyExchange, ToBytes, ECDHNISTK256, ECDHNISTP384, ECDHNISTP521};
+use static_dh_ecdh::signatures::{ECDSAP256SHA256Signature, ECDSASHA256Signature, ECDSASHA384Signature, ECSignature};
+
+const MESSAGE: &[u8] = b"static-dh-ecdh benchmark message";
+
+/// Times `iters` calls of `f` and prints the mean.
+fn bench<T, F: FnMut() -> T>(name: &str, iters: u32, mut f: F) {
+    black_box(f()); // warm up the lazily built constants
+    let start = Instant::now();
+    for _ in 0..iters {
+        black_box(f());
+    }
+    let per_iter = start.elapsed() / iters;
+    println!("{:<32} {:>12.3?} / iter ({} iters)", name, per_iter, iters);
+}
+
+/// Key generation, public key derivation and the shared secret for the curve `C`.
+fn bench_ecdh<C: KeyExchange>(curve: &str, iters: u32) {
+    let alice_sk = C::generate_private_key([1; 32]);
+    let bob_pk = C::generate_public_key(&C::generate_private_key([2; 32]));
+    // round-trip the keys through their encodings, as a peer would
+    let alice_sk = C::SKey::from_bytes(&alice_sk.to_bytes()).unwrap();
+    let bob_pk = C::PubKey::from_bytes(&bob_pk.to_bytes()).unwrap();
+
+    bench(&format!("{} generate_private_key", curve), iters, || C::generate_private_key(black_box([3; 32])));
+    bench(&format!("{} generate_public_key", curve), iters, || C::generate_public_key(black_box(&alice_sk)));
+    bench(&format!("{} generate_shared_secret", curve), iters, || {
+        C::generate_shared_secret(black_box(&alice_sk), black_box(&bob_pk)).unwrap()
+    });
+}
+
+/// Signing and verifying with the signer `S`.
+fn bench_ecdsa<S: ECSignature>(name: &str, signer: &S, iters: u32) {
+    let signature = signer.sign(MESSAGE).unwrap();
+    assert!(signer.verify(MESSAGE, signature.as_ref()).unwrap());
+
+    bench(&format!("{} sign", name), iters, || signer.sign(black_box(MESSAGE)).unwrap());
+    bench(&format!("{} verify", name), iters, || signer.verify(black_box(MESSAGE), signature.as_ref()).unwrap());
+}
+
+fn main() {
+    bench_ecdh::<ECDHNISTK256>("secp256k1", 1000);
+    bench_ecdh::<ECDHNISTP384<48>>("P-384", 20);
+    bench_ecdh::<ECDHNISTP521>("P-521", 10);
+
+    bench("secp256k1 signer from_signing_key", 1000, || ECDSASHA256Signature::from_signing_key(&[0x11; 32]).unwrap());
+    bench_ecdsa("secp256k1 ECDSA-SHA256", &ECDSASHA256Signature::from_signing_key(&[0x11; 32]).unwrap(), 1000);
+    bench("P-384 signer from_signing_key", 20, || ECDSASHA384Signature::from_signing_key(&[0x11; 48]).unwrap());
+    bench_ecdsa("P-384 ECDSA-SHA384", &ECDSASHA384Signature::from_signing_key(&[0x11; 48]).unwrap(), 20);
+    bench("P-256 signer from_signing_key", 20, || ECDSAP256SHA256Signature::from_signing_key(&[0x11; 32]).unwrap());
+    bench_ecdsa("P-256 ECDSA-SHA256", &ECDSAP256SHA256Signature::from_signing_key(&[0x11; 32]).unwrap(), 20);
+}
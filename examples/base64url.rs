@@ -0,0 +1,58 @@
+
+// #![allow(warnings)]
+use static_dh_ecdh::ecdh::ecdh::{
+    FromBytes, KeyExchange, PkP384, Pkk256, SkP384, Skk256, ToBytes, ECDHNISTK256, ECDHNISTP384,
+};
+use static_dh_ecdh::signatures::{ECDSAP256SHA256Signature, ECDSASHA256Signature, ECDSASHA384Signature, ECSignature};
+use static_dh_ecdh::CryptoError;
+
+// The public key of the `jwk` example's secp256k1 JWK (`04 || x || y`) and the RFC 6979 A.2.5 P-256/SHA-256
+// signature over "sample", encoded with Python's `base64.urlsafe_b64encode` (padding stripped)
+const K256_JWK_D: &str = "0badc0ffee0ddf00dbadc0ffee0ddf00dbadc0ffee0ddf00dbadc0ffee0ddf00";
+const K256_PK_BASE64URL: &str = "BHS5W-jDcgdDCZcskuq1JOTi9_XIGLrB7YoiavjdU6DcfIfOEmrS5Zh89H-pIeN7v1UtRqwLCHB8IkK3uSZyQUA";
+const RFC6979_SAMPLE_SIG: &str = "efd48b2aacb6a8fd1140dd9cd45e81d69d2c877b56aaf991c34d0ea84eaf3716f7cb1c942d657c41d436c7a1b6e29f65f3e900dbb9aff4064dc4ab2f843acda8";
+const RFC6979_SAMPLE_SIG_BASE64URL: &str = "79SLKqy2qP0RQN2c1F6B1p0sh3tWqvmRw00OqE6vNxb3yxyULWV8QdQ2x6G24p9l8-kA27mv9AZNxKsvhDrNqA";
+
+fn main() {
+    // Padding-free vectors from another implementation
+    let sk = Skk256::from_hex(K256_JWK_D).unwrap();
+    let pk = ECDHNISTK256::generate_public_key(&sk);
+    assert_eq!(pk.to_base64url(), K256_PK_BASE64URL);
+    assert_eq!(Pkk256::from_base64url(K256_PK_BASE64URL).unwrap(), pk);
+    let sig = ECDSAP256SHA256Signature::from_hex(RFC6979_SAMPLE_SIG).unwrap();
+    assert_eq!(ECDSAP256SHA256Signature::to_base64url(&sig), RFC6979_SAMPLE_SIG_BASE64URL);
+    assert_eq!(ECDSAP256SHA256Signature::from_base64url(RFC6979_SAMPLE_SIG_BASE64URL), Ok(sig));
+
+    // Round trips: public keys, private keys, shared secrets and signatures
+    let alice_sk = ECDHNISTP384::<48>::generate_private_key([1; 32]);
+    let alice_pk = ECDHNISTP384::<48>::generate_public_key(&alice_sk);
+    let bob_pk = ECDHNISTP384::<48>::generate_public_key(&ECDHNISTP384::<48>::generate_private_key([2; 32]));
+    assert_eq!(PkP384::from_base64url(&alice_pk.to_base64url()).unwrap(), alice_pk);
+    assert_eq!(SkP384::from_base64url(&alice_sk.to_base64url()).unwrap().to_bytes(), alice_sk.to_bytes());
+    let shared = ECDHNISTP384::<48>::generate_shared_secret(&alice_sk, &bob_pk).unwrap();
+    assert_eq!(shared.to_base64url().len(), 64); // 48 bytes, no padding
+    assert!(!shared.to_base64url().contains('='));
+
+    let signer = ECDSASHA256Signature::generate([3; 32]);
+    let signature = signer.sign(b"base64url").unwrap();
+    let encoded = ECDSASHA256Signature::to_base64url(&signature);
+    assert_eq!(encoded.len(), 86);
+    assert_eq!(ECDSASHA256Signature::from_base64url(&encoded), Ok(signature));
+    let signer = ECDSASHA384Signature::generate([4; 32]);
+    let signature = signer.sign(b"base64url").unwrap();
+    assert_eq!(ECDSASHA384Signature::from_base64url(&ECDSASHA384Signature::to_base64url(&signature)), Ok(signature));
+
+    // Malformed input
+    let padded = format!("{}==", RFC6979_SAMPLE_SIG_BASE64URL);
+    assert_eq!(ECDSAP256SHA256Signature::from_base64url(&padded), Err(CryptoError::InvalidEncoding));
+    let standard_alphabet = RFC6979_SAMPLE_SIG_BASE64URL.replace('-', "+");
+    assert_eq!(ECDSAP256SHA256Signature::from_base64url(&standard_alphabet), Err(CryptoError::InvalidEncoding));
+    assert!(matches!(Pkk256::from_base64url(&K256_PK_BASE64URL.replace('_', "/")), Err(CryptoError::InvalidEncoding)));
+    assert!(matches!(Pkk256::from_base64url(&K256_PK_BASE64URL[..84]), Err(CryptoError::InvalidLength { .. })));
+    assert_eq!(
+        ECDSAP256SHA256Signature::from_base64url(&RFC6979_SAMPLE_SIG_BASE64URL[..84]),
+        Err(CryptoError::InvalidLength { expected: 64, got: 63 })
+    );
+
+    println!("base64url forms round-trip");
+}
@@ -0,0 +1,27 @@
+
+// #![allow(warnings)]
+//! Signs with the `blinding` feature on. The unblinded path is covered by `signatures_test`, and both
+//! produce signatures that the (shared) verifier accepts.
+use static_dh_ecdh::digest::SHA384Digest;
+use static_dh_ecdh::signatures::{verify_with_curve, ECDSASHA384Signature, ECSignature};
+use static_dh_ecdh::CurveId;
+
+fn main() {
+    for seed in 0u8..4 {
+        let signer = ECDSASHA384Signature::generate([seed; 32]);
+        for i in 0u8..8 {
+            let data = [b"blinded signature ".as_ref(), &[seed, i]].concat();
+            let signature = signer.sign(&data).unwrap();
+            assert_eq!(signer.verify(&data, &signature), Ok(true));
+            assert!(verify_with_curve(CurveId::NistP384, signer.1.as_bytes(), &data, &signature).unwrap());
+
+            let digest = SHA384Digest.digest(&data);
+            let prehashed = signer.sign_prehashed(&digest).unwrap();
+            assert_eq!(signer.verify_prehashed(&digest, &prehashed), Ok(true));
+            // The nonce is still random, blinding doesn't change what gets signed
+            assert_ne!(prehashed, signature);
+            assert_eq!(signer.verify(b"some other message", &signature), Ok(false));
+        }
+    }
+    println!("blinded signatures verify");
+}
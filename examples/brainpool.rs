@@ -0,0 +1,77 @@
+
+// #![allow(warnings)]
+use static_dh_ecdh::ecdh::affine_math::{BrainpoolP384r1Params, CurveParams, MyAffinePoint};
+use static_dh_ecdh::ecdh::brainpool::{ECDHBrainpoolP384r1, PkBrainpoolP384r1, SkBrainpoolP384r1};
+use static_dh_ecdh::ecdh::ecdh::{FromBytes, KeyExchange, ToBytes, ECDHNISTP384};
+use static_dh_ecdh::encoding::from_hex;
+use static_dh_ecdh::{constants, CryptoError};
+
+use num_bigint_dig::{BigInt, BigUint};
+
+// RFC 7027 Appendix A.2 - the brainpoolP384r1 ECDH test vector
+const D_A: &str = "1e20f5e048a5886f1f157c74e91bde2b98c8b52d58e5003d57053fc4b0bd65d6f15eb5d1ee1610df870795143627d042";
+const Q_A: &str = "0468b665dd91c195800650cdd363c625f4e742e8134667b767b1b476793588f885ab698c852d4a6e77a252d6380fcaf06855bc91a39c9ec01dee36017b7d673a931236d2f1f5c83942d049e3fa20607493e0d038ff2fd30c2ab67d15c85f7faa59";
+const D_B: &str = "032640bc6003c59260f7250c3db58ce647f98e1260acce4acda3dd869f74e01f8ba5e0324309db6a9831497abac96670";
+const Q_B: &str = "044d44326f269a597a5b58bba565da5556ed7fd9a8a9eb76c25f46db69d19dc8ce6ad18e404b15738b2086df37e71d1eb462d692136de56cbe93bf5fa3188ef58bc8a3a0ec6c1e151a21038a42e9185329b5b275903d192f8d4e1f32fe9cc78c48";
+const Z: &str = "0bd9d3a7ea0b3d519d09d8e48d0785fb744a6b355e6304bc51c229fbbce239bbadf6403715c35d4fb2a5444f575d4f42";
+
+fn main() {
+    // `a` isn't `-3`, so these exercise the general doubling formula
+    assert_ne!(BrainpoolP384r1Params::a(), &(BrainpoolP384r1Params::modp() - BigInt::from(3)));
+    let g = MyAffinePoint::<BrainpoolP384r1Params>::generator();
+    assert!(g.is_on_curve());
+    let n = from_hex(constants::ECDH_BRAINPOOL_P384R1_GROUP_ORDER).unwrap();
+    assert!(MyAffinePoint::double_and_add(g.clone(), BigUint::from_bytes_be(&n)).is_identity());
+    let doubled = g.do_the_math(g.clone());
+    assert!(doubled.is_on_curve());
+    assert_eq!(MyAffinePoint::double_and_add(g.clone(), BigUint::from(2u8)), doubled);
+    assert_eq!(doubled.do_the_math(g.clone()), MyAffinePoint::double_and_add(g, BigUint::from(3u8)));
+
+    // The RFC 7027 vector, from both sides
+    let alice_sk = SkBrainpoolP384r1::from_bytes(&from_hex(D_A).unwrap()).unwrap();
+    let bob_sk = SkBrainpoolP384r1::from_bytes(&from_hex(D_B).unwrap()).unwrap();
+    let alice_pk = ECDHBrainpoolP384r1::generate_public_key(&alice_sk);
+    let bob_pk = ECDHBrainpoolP384r1::generate_public_key(&bob_sk);
+    assert_eq!(alice_pk.to_bytes().to_vec(), from_hex(Q_A).unwrap());
+    assert_eq!(bob_pk.to_bytes().to_vec(), from_hex(Q_B).unwrap());
+    assert_eq!(PkBrainpoolP384r1::from_bytes(&from_hex(Q_A).unwrap()).unwrap(), alice_pk);
+    let alice_ss = ECDHBrainpoolP384r1::generate_shared_secret(&alice_sk, &bob_pk).unwrap();
+    let bob_ss = ECDHBrainpoolP384r1::generate_shared_secret(&bob_sk, &alice_pk).unwrap();
+    assert_eq!(alice_ss, bob_ss);
+    assert_eq!(alice_ss.to_bytes().to_vec(), from_hex(Z).unwrap());
+    println!("brainpoolP384r1 shared secret: {:x}", alice_ss.to_bytes());
+
+    // Seeded keys agree too
+    let sk1 = ECDHBrainpoolP384r1::generate_private_key([12; 32]);
+    let sk2 = ECDHBrainpoolP384r1::generate_private_key([21; 32]);
+    assert_eq!(
+        ECDHBrainpoolP384r1::generate_shared_secret(&sk1, &PkBrainpoolP384r1::from(&sk2)),
+        ECDHBrainpoolP384r1::generate_shared_secret(&sk2, &PkBrainpoolP384r1::from(&sk1))
+    );
+
+    // Malformed public keys
+    let q_a = from_hex(Q_A).unwrap();
+    let mut off_curve = q_a.clone();
+    off_curve[96] ^= 1;
+    assert_eq!(PkBrainpoolP384r1::from_bytes(&off_curve), Err(CryptoError::PointNotOnCurve));
+    let mut compressed_tag = q_a.clone();
+    compressed_tag[0] = 0x02;
+    assert_eq!(PkBrainpoolP384r1::from_bytes(&compressed_tag), Err(CryptoError::InvalidEncoding));
+    let mut x_too_large = [0xff; 97];
+    x_too_large[0] = 0x04;
+    assert_eq!(PkBrainpoolP384r1::from_bytes(&x_too_large), Err(CryptoError::InvalidEncoding));
+    let mut zero = [0; 97];
+    zero[0] = 0x04;
+    assert_eq!(PkBrainpoolP384r1::from_bytes(&zero), Err(CryptoError::PointAtInfinity));
+    assert_eq!(PkBrainpoolP384r1::from_bytes(&q_a[..96]), Err(CryptoError::InvalidLength { expected: 97, got: 96 }));
+    // A P-384 public key isn't on brainpoolP384r1, even if it bypasses `from_bytes`
+    let p384_pk = ECDHNISTP384::<48>::generate_public_key(&ECDHNISTP384::<48>::generate_private_key([1; 32]));
+    let foreign = PkBrainpoolP384r1(p384_pk.to_bytes());
+    assert!(ECDHBrainpoolP384r1::generate_shared_secret(&alice_sk, &foreign).is_err());
+
+    // Private scalars are range-checked
+    assert_eq!(SkBrainpoolP384r1::from_bytes(&[0; 48]).unwrap_err(), CryptoError::ScalarOutOfRange);
+    assert_eq!(SkBrainpoolP384r1::from_bytes(&n).unwrap_err(), CryptoError::ScalarOutOfRange);
+    assert!(matches!(SkBrainpoolP384r1::from_bytes(&[1; 47]), Err(CryptoError::InvalidLength { .. })));
+    assert_eq!(format!("{:?}", alice_sk), "SkBrainpoolP384r1(REDACTED)");
+}
@@ -0,0 +1,59 @@
+
+// #![allow(warnings)]
+use static_dh_ecdh::digest::SHA256Digest;
+use static_dh_ecdh::ecdh::affine_math::{MyAffinePoint, P384Params};
+use static_dh_ecdh::ecdh::ecdh::{
+    keypair, read_public_key, KeyExchange, KeyPair, ToBytes, ECDHNISTK256, ECDHNISTP384, ECDHNISTP521,
+};
+use static_dh_ecdh::encoding::{from_hex, to_hex};
+use static_dh_ecdh::CurveId;
+
+use num_bigint_dig::{BigInt, Sign};
+
+fn main() {
+    for (curve_id, field_size) in [(CurveId::Secp256k1, 32), (CurveId::NistP384, 48), (CurveId::NistP521, 66)] {
+        let keypair = KeyPair::generate(curve_id, [7; 32]);
+        let info = keypair.describe();
+        println!("{:?}: {} ({})", info.curve_id, info.hex, info.fingerprint);
+
+        // The forms are consistent with each other
+        assert_eq!(info.curve_id, curve_id);
+        assert_eq!(keypair.curve_id(), curve_id);
+        assert_eq!(info.uncompressed, keypair.public_key().to_uncompressed_bytes());
+        assert_eq!(info.uncompressed.len(), 1 + 2 * field_size);
+        assert_eq!(info.compressed.len(), 1 + field_size);
+        assert_eq!(from_hex(&info.hex).unwrap(), info.uncompressed);
+        assert_eq!(info.fingerprint, to_hex(&SHA256Digest.digest(&info.uncompressed)));
+
+        // Decompressing the compressed form gives back the uncompressed one
+        let (decompressed, consumed) = read_public_key(curve_id, &info.compressed).unwrap();
+        assert_eq!(consumed, info.compressed.len());
+        assert_eq!(decompressed, keypair.public_key());
+        assert_eq!(decompressed.to_uncompressed_bytes(), info.uncompressed);
+
+        // Describing is deterministic
+        assert_eq!(KeyPair::generate(curve_id, [7; 32]).describe(), info);
+        assert_ne!(KeyPair::generate(curve_id, [8; 32]).describe().fingerprint, info.fingerprint);
+
+        #[cfg(feature = "dangerous-debug")]
+        assert_eq!(from_hex(&info.secret_hex).unwrap().len(), field_size);
+    }
+
+    // `public_key_compressed` is the compressed form, straight from the private key
+    let compressed = |curve_id| KeyPair::generate(curve_id, [7; 32]).describe().compressed;
+    assert_eq!(ECDHNISTK256::public_key_compressed(&keypair::<ECDHNISTK256>([7; 32]).0), compressed(CurveId::Secp256k1));
+    assert_eq!(ECDHNISTP384::<48>::public_key_compressed(&keypair::<ECDHNISTP384<48>>([7; 32]).0), compressed(CurveId::NistP384));
+    assert_eq!(ECDHNISTP521::public_key_compressed(&keypair::<ECDHNISTP521>([7; 32]).0), compressed(CurveId::NistP521));
+    // ... and agrees with the k256 crate's own encoding
+    let sk = keypair::<ECDHNISTK256>([7; 32]).0;
+    let k256_pk = k256::SecretKey::from_bytes(sk.to_bytes()).unwrap().public_key();
+    assert_eq!(ECDHNISTK256::public_key_compressed(&sk), k256::EncodedPoint::encode(k256_pk, true).as_bytes());
+
+    // The compressed encoding agrees with the affine-point math's own for P-384
+    let info = KeyPair::generate(CurveId::NistP384, [9; 32]).describe();
+    let point = MyAffinePoint::<P384Params>::new(
+        BigInt::from_bytes_be(Sign::Plus, &info.uncompressed[1..49]),
+        BigInt::from_bytes_be(Sign::Plus, &info.uncompressed[49..]),
+    );
+    assert_eq!(point.to_compressed_bytes().as_bytes(), &info.compressed[..]);
+}
@@ -1,7 +1,8 @@
 
 #![allow(warnings)]
 
-use static_dh_ecdh::dh::dh::{DH5, DH14, DH15, DH16, DH17, DH18};
+use static_dh_ecdh::dh::dh::{unhexlify_to_bytearray, DH5, DH14, DH15, DH16, DH17, DH18};
+use static_dh_ecdh::CryptoError;
 
 fn main(){
     let mut alice = DH15::new();
@@ -22,6 +23,22 @@ fn main(){
     println!("alice_shared_secret: {}", alice_shared_secret);
     println!("bob_shared_secret:   {}", bob_shared_secret);
     
+    // Every DH group's prime parses (`init_*` would panic otherwise)
+    DH5::new().init_dh5();
+    DH14::new().init_dh14();
+    DH15::new().init_dh15();
+    DH16::new().init_dh16();
+    DH17::new().init_dh17();
+    DH18::new().init_dh18();
+
+    // `unhexlify_to_bytearray` reports malformed input instead of panicking
+    assert_eq!(unhexlify_to_bytearray::<2>("0x0aFf"), Ok([0x0a, 0xff]));
+    assert_eq!(unhexlify_to_bytearray::<2>("0aF"), Err(CryptoError::InvalidEncoding)); // odd length
+    assert_eq!(unhexlify_to_bytearray::<2>("0aFg"), Err(CryptoError::InvalidEncoding)); // non-hex character
+    assert_eq!(unhexlify_to_bytearray::<1>("é0"), Err(CryptoError::InvalidEncoding)); // multi-byte character
+    assert_eq!(unhexlify_to_bytearray::<2>("0aff00"), Err(CryptoError::InvalidLength { expected: 2, got: 3 })); // too long
+    assert_eq!(unhexlify_to_bytearray::<2>("0a"), Err(CryptoError::InvalidLength { expected: 2, got: 1 })); // too short
+
     // println!("alice_private_key: {}", alice.private_key); // if you want to examine this value, mark it public.
     // println!("bob_private_key:   {}", bob.private_key);   // if you want to examine this value, mark it public.
 
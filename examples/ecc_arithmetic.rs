@@ -1,21 +1,78 @@
 // #![allow(warnings)]
 
-use static_dh_ecdh::ecdh::affine_math::{APTypes, MyAffinePoint};
-use static_dh_ecdh::{constants, dh::dh};
+use static_dh_ecdh::ecdh::affine_math::{CurveParams, Field, MyAffinePoint, P256Params, P384Params, P521Params};
+use static_dh_ecdh::{constants, dh::dh, CryptoError};
 
+use elliptic_curve::subtle::Choice;
+use lazy_static::lazy_static;
 use num_bigint_dig::{BigInt, BigUint, Sign};
+use p384::EncodedPoint;
 use rand_chacha::rand_core::{RngCore, SeedableRng};
 use rand_chacha::ChaCha20Rng;
 
+/// A toy curve, `y^2 = x^3 + 2x + 3 (mod 97)`, to exercise the arithmetic on a curve of our own.
+#[derive(Debug, Clone, PartialEq)]
+struct ToyParams;
+
+lazy_static! {
+    static ref TOY_CONSTANTS: (BigInt, BigInt, BigInt) = (BigInt::from(2), BigInt::from(3), BigInt::from(97));
+}
+
+impl CurveParams for ToyParams {
+    fn modp() -> &'static BigInt {
+        &TOY_CONSTANTS.2
+    }
+
+    fn a() -> &'static BigInt {
+        &TOY_CONSTANTS.0
+    }
+
+    fn b() -> &'static BigInt {
+        &TOY_CONSTANTS.1
+    }
+
+    fn generator() -> MyAffinePoint<Self> {
+        MyAffinePoint::new(BigInt::from(3), BigInt::from(6))
+    }
+
+    fn byte_len() -> usize {
+        1
+    }
+}
+
 fn main() {
+    // The hardcoded curve constants are self-consistent
+    assert_eq!(constants::self_test(), Ok(()));
+
+    // Modular square roots: the squares mod 23 (a prime = 3 mod 4) have roots, the other residues don't
+    let p23 = BigInt::from(23);
+    let squares: Vec<i32> = (0..23).map(|x| x * x % 23).collect();
+    for a in 0..23 {
+        let root = MyAffinePoint::<P384Params>::mod_sqrt(&BigInt::from(a), &p23);
+        assert_eq!(root.is_some(), squares.contains(&a));
+        if let Some(root) = root {
+            assert_eq!(&root * &root % &p23, BigInt::from(a));
+        }
+    }
+    // ... and over the p384 prime: 4 = 2^2, while -1 is never a square mod a prime = 3 mod 4
+    let p384 = P384Params::modp();
+    let root = MyAffinePoint::<P384Params>::mod_sqrt(&BigInt::from(4), p384).unwrap();
+    assert!(root == BigInt::from(2) || root == p384 - 2);
+    assert_eq!(MyAffinePoint::<P384Params>::mod_sqrt(&(p384 - 1), p384), None);
+    assert_eq!(MyAffinePoint::<P384Params>::mod_sqrt(&BigInt::from(0), p384), Some(BigInt::from(0)));
+    let y = &MyAffinePoint::<P384Params>::generator().y;
+    let root = MyAffinePoint::<P384Params>::mod_sqrt(&(y * y), p384).unwrap();
+    assert!(&root == y || root == p384 - y);
+
     // Get constants
     let mod_prime =
-        dh::unhexlify_to_bytearray::<48>(&constants::ECDH_NIST_384_MODP.replace("0x", ""));
-    let b_val = dh::unhexlify_to_bytearray::<48>(&constants::ECDH_NIST_384_B_VAL.replace("0x", ""));
+        dh::unhexlify_to_bytearray::<48>(constants::ECDH_NIST_384_MODP).unwrap();
+    let b_val = dh::unhexlify_to_bytearray::<48>(constants::ECDH_NIST_384_B_VAL).unwrap();
 
     let a = BigInt::from(-3);
     let b = BigInt::from_bytes_be(Sign::Plus, &b_val);
     let modp = BigInt::from_bytes_be(Sign::Plus, &mod_prime);
+    assert_eq!((P384Params::a(), P384Params::b(), P384Params::modp()), (&a, &b, &modp));
 
     // Generate Private keys
     let mut rng = ChaCha20Rng::from_seed([13; 32]); // test seed value.
@@ -30,39 +87,28 @@ fn main() {
     let bob_sk = BigUint::from_bytes_be(&dest2);
     println!("bob_sk: {}", &bob_sk);
 
+    // `Field` arithmetic agrees with the equivalent direct `BigInt` computations
+    let field = Field::new(&modp);
+    let u = BigInt::from_bytes_be(Sign::Plus, &dest);
+    let v = BigInt::from_bytes_be(Sign::Plus, &dest2);
+    assert_eq!(field.mul(&u, &v), (&u * &v) % &modp);
+    assert_eq!(field.sqr(&u), u.modpow(&BigInt::from(2), &modp));
+    assert_eq!(field.inv(&u).unwrap(), u.modpow(&(&modp - 2), &modp));
+    assert_eq!(field.sub(&u, &v), field.add(&u, &(&modp - &v)));
+
     // Derive Public keys
-    let gen = MyAffinePoint::<48>::generator();
-    let alice_pk = match gen {
-        APTypes::P384(gen) => {
-            let pub_key = MyAffinePoint::<48>::double_and_add(
-                gen,
-                alice_sk.clone(),
-                &a,
-                &b,
-                &modp,
-            );
-            println!("alice_pkx: {}", pub_key.x);
-            println!("alice_pky: {}", pub_key.y);
-            pub_key
-        }
-        _ => unreachable!(),
-    };
-
-    let gen2 = MyAffinePoint::<48>::generator();
-    let bob_pk = match gen2 {
-        APTypes::P384(gen) => {
-            let pub_key = MyAffinePoint::<48>::double_and_add(gen, bob_sk.clone(), &a, &b, &modp);
-            println!("bob_pkx: {:x}", pub_key.x);
-            println!("bob_pky: {:x}", pub_key.y);
-            pub_key
-        }
+    let alice_pk = MyAffinePoint::<P384Params>::double_and_add(MyAffinePoint::generator(), alice_sk.clone());
+    println!("alice_pkx: {}", alice_pk.x);
+    println!("alice_pky: {}", alice_pk.y);
 
-        _ => unreachable!(),
-    };
+    let bob_pk = MyAffinePoint::<P384Params>::double_and_add(MyAffinePoint::generator(), bob_sk.clone());
+    println!("bob_pkx: {:x}", bob_pk.x);
+    println!("bob_pky: {:x}", bob_pk.y);
+    assert!(alice_pk.is_on_curve() && bob_pk.is_on_curve());
 
     // Evaluate Shared secret keys
-    let alice_ss = MyAffinePoint::<48>::double_and_add(bob_pk, alice_sk.clone(), &a, &b, &modp);
-    let bob_ss = MyAffinePoint::<48>::double_and_add(alice_pk, bob_sk.clone(), &a, &b, &modp);
+    let alice_ss = MyAffinePoint::double_and_add(bob_pk, alice_sk.clone());
+    let bob_ss = MyAffinePoint::double_and_add(alice_pk, bob_sk.clone());
     
     assert_eq!(alice_ss, bob_ss);
 
@@ -72,6 +118,140 @@ fn main() {
     println!("bob_ss: {:x}", &bob_ss.x);
     println!("bob_ss: {:x}", &bob_ss.y);
 
+    // Compressed encoding - the generator's y is odd, so its negation `-G = (x, p - y)` has an even y
+    let gen = MyAffinePoint::<P384Params>::generator();
+    assert_eq!(gen.to_compressed_bytes().as_bytes()[0], 0x03);
+    let neg_gen = MyAffinePoint::<P384Params>::new(gen.x.clone(), &modp - &gen.y);
+    assert_eq!(neg_gen.to_compressed_bytes().as_bytes()[0], 0x02);
+    assert_eq!(
+        &neg_gen.to_compressed_bytes().as_bytes()[1..],
+        &gen.to_compressed_bytes().as_bytes()[1..]
+    );
+
+    // Conditional negation agrees with the plain negation, and with leaving the point alone
+    let mut negated = gen.clone();
+    negated.conditional_negate(Choice::from(1), &modp);
+    assert_eq!(negated, neg_gen);
+    assert!(negated.is_on_curve() && negated.do_the_math(gen.clone()).is_identity());
+    let mut kept = gen.clone();
+    kept.conditional_negate(Choice::from(0), &modp);
+    assert_eq!(kept, gen);
+    negated.conditional_negate(Choice::from(1), &modp);
+    assert_eq!(negated, gen);
+    let mut identity = MyAffinePoint::<P384Params>::identity();
+    identity.conditional_negate(Choice::from(1), &modp);
+    assert!(identity.is_identity());
+    // A y with leading zero bytes (and a tiny modulus)
+    let toy_gen = MyAffinePoint::<ToyParams>::generator();
+    let mut toy_negated = toy_gen.clone();
+    toy_negated.conditional_negate(Choice::from(1), ToyParams::modp());
+    assert_eq!(toy_negated, MyAffinePoint::new(toy_gen.x.clone(), ToyParams::modp() - &toy_gen.y));
+    assert!(toy_negated.is_on_curve());
+
+    // Negative representatives are normalized into `[0, p)` - before comparing and after the arithmetic
+    let negative = MyAffinePoint::<P384Params>::new(&gen.x - &modp, &gen.y - &modp * 2);
+    assert!(negative.x.sign() == Sign::Minus && negative.y.sign() == Sign::Minus);
+    let mut normalized = negative.clone();
+    normalized.normalize(&modp);
+    assert_eq!(normalized, gen);
+    assert_eq!(MyAffinePoint::identity().do_the_math(negative.clone()), gen);
+    assert_eq!(negative.do_the_math(negative.clone()), gen.do_the_math(gen.clone()));
+    assert!(negative.do_the_math(neg_gen.clone()).is_identity());
+    let doubled = MyAffinePoint::double_and_add(negative.clone(), BigUint::from(2u8));
+    assert_eq!(doubled.to_uncompressed_bytes(false), gen.do_the_math(gen.clone()).to_uncompressed_bytes(false));
+    assert_eq!(MyAffinePoint::double_and_add(negative, BigUint::from(1u8)), gen);
+
+    // Validation: on the curve, canonical co-ordinates and not the identity
+    let (a, b) = (P384Params::a(), P384Params::b());
+    assert_eq!(gen.validate(a, b, &modp), Ok(()));
+    assert_eq!(neg_gen.validate(a, b, &modp), Ok(()));
+    let off_curve = MyAffinePoint::<P384Params>::new(gen.x.clone(), &gen.y + 1);
+    assert_eq!(off_curve.validate(a, b, &modp), Err(CryptoError::PointNotOnCurve));
+    // `x + p` and `y - p` satisfy the equation mod p but aren't in `[0, p)`
+    let too_large = MyAffinePoint::<P384Params>::new(&gen.x + &modp, gen.y.clone());
+    assert!(too_large.is_on_curve());
+    assert_eq!(too_large.validate(a, b, &modp), Err(CryptoError::InvalidEncoding));
+    let negative = MyAffinePoint::<P384Params>::new(gen.x.clone(), &gen.y - &modp);
+    assert_eq!(negative.validate(a, b, &modp), Err(CryptoError::InvalidEncoding));
+    assert_eq!(MyAffinePoint::<P384Params>::identity().validate(a, b, &modp), Err(CryptoError::PointAtInfinity));
+    // The curve comes from the arguments, not the type
+    assert_eq!(toy_gen.validate(a, b, &modp), Err(CryptoError::PointNotOnCurve));
+    assert_eq!(toy_gen.validate(ToyParams::a(), ToyParams::b(), ToyParams::modp()), Ok(()));
+
+    // (0, 0) is not a point on P-384 - it is read as the point at infinity
+    let zero_pt = MyAffinePoint::<P384Params>::new(BigInt::from(0), BigInt::from(0));
+    assert!(zero_pt.is_identity());
+    assert_eq!(zero_pt, MyAffinePoint::identity());
+    let sum = zero_pt.do_the_math(alice_ss.clone());
+    assert_eq!(sum, alice_ss);
+
+    // The fixed-base table agrees with `double_and_add`: small scalars, window boundaries, random scalars and `n - 1`
+    let g_ord = BigUint::from_bytes_be(
+        &dh::unhexlify_to_bytearray::<48>(constants::ECDH_NIST_384_GROUP_ORDER).unwrap(),
+    );
+    let mut scalars: Vec<BigUint> = (0u32..=33).map(BigUint::from).collect();
+    for shift in [4, 8, 60, 64, 380, 383] {
+        let power = BigUint::from(1u8) << shift;
+        scalars.push(&power - BigUint::from(1u8));
+        scalars.push(power);
+    }
+    let mut rng = ChaCha20Rng::from_seed([9; 32]);
+    for _ in 0..8 {
+        let mut bytes = [0u8; 48];
+        rng.fill_bytes(&mut bytes);
+        scalars.push(BigUint::from_bytes_be(&bytes) % &g_ord);
+    }
+    scalars.push(&g_ord - BigUint::from(1u8));
+    for k in scalars {
+        let expected = MyAffinePoint::double_and_add(MyAffinePoint::generator(), k.clone());
+        assert_eq!(MyAffinePoint::<P384Params>::mul_generator(k.clone()), expected, "k = {:x}", k);
+    }
+    // Scalars are taken mod `n`
+    assert!(MyAffinePoint::<P384Params>::mul_generator(g_ord.clone()).is_identity());
+    assert_eq!(
+        MyAffinePoint::<P384Params>::mul_generator(&g_ord + BigUint::from(5u8)),
+        MyAffinePoint::<P384Params>::mul_generator(BigUint::from(5u8))
+    );
+
+    // A curve that only implements `CurveParams`: `double_and_add` agrees with repeated addition, all the way
+    // round to the identity
+    let toy_gen = MyAffinePoint::<ToyParams>::generator();
+    assert!(toy_gen.is_on_curve());
+    assert!(!MyAffinePoint::<ToyParams>::new(BigInt::from(3), BigInt::from(7)).is_on_curve());
+    let mut multiple = MyAffinePoint::<ToyParams>::identity();
+    for k in 1u32..=5 {
+        multiple = multiple.do_the_math(toy_gen.clone());
+        assert!(multiple.is_identity() || multiple.is_on_curve());
+        assert_eq!(MyAffinePoint::double_and_add(toy_gen.clone(), BigUint::from(k)), multiple, "k = {}", k);
+    }
+    // (3, 6) has order 5
+    assert!(multiple.is_identity());
+
+    // SEC1 encoded points convert via their co-ordinates: here, the P-384 base point
+    let gx = "aa87ca22be8b05378eb1c71ef320ad746e1d3b628ba79b9859f741e082542a385502f25dbf55296c3a545e3872760ab7";
+    let gy = "3617de4a96262c6f5d9e98bf9292dc29f8f41dbd289a147ce9da3113b5f0b8c00a60b1ce1d7e819d7a431d7c90ea0e5f";
+    let mut encoded = vec![0x04];
+    encoded.extend(dh::unhexlify_to_bytearray::<48>(gx).unwrap());
+    encoded.extend(dh::unhexlify_to_bytearray::<48>(gy).unwrap());
+    let point = MyAffinePoint::<P384Params>::from_encoded_point(&EncodedPoint::from_bytes(&encoded).unwrap()).unwrap();
+    assert_eq!(point.x, BigInt::parse_bytes(gx.as_bytes(), 16).unwrap());
+    assert_eq!(point.y, BigInt::parse_bytes(gy.as_bytes(), 16).unwrap());
+    assert_eq!(point, MyAffinePoint::generator());
+
+    *encoded.last_mut().unwrap() ^= 1;
+    let off_curve = EncodedPoint::from_bytes(&encoded).unwrap();
+    assert_eq!(MyAffinePoint::<P384Params>::from_encoded_point(&off_curve), Err(CryptoError::PointNotOnCurve));
+    assert_eq!(
+        MyAffinePoint::<P384Params>::from_encoded_point(&off_curve.compress()),
+        Err(CryptoError::InvalidEncoding)
+    );
+    assert_eq!(
+        MyAffinePoint::<P384Params>::from_encoded_point(&EncodedPoint::identity()),
+        Err(CryptoError::PointAtInfinity)
+    );
+    // `EncodedPoint` is p384's, other curves are an error rather than a panic
+    assert_eq!(MyAffinePoint::<P256Params>::from_encoded_point(&EncodedPoint::identity()), Err(CryptoError::UnsupportedCurve));
+    assert_eq!(MyAffinePoint::<P521Params>::from_encoded_point(&off_curve), Err(CryptoError::UnsupportedCurve));
 }
 
 //   let bitarray = MyAffinePoint::<48>::to_bit_array(private_key1.clone(), false);
@@ -1,22 +1,216 @@
 
 // #![allow(warnings)]
-use static_dh_ecdh::ecdh::ecdh::{ECDHNISTP256, KeyExchange, ToBytes};
+use core::convert::TryInto;
+
+use static_dh_ecdh::ecdh::ecdh::{
+    ecdh, keypair, read_public_key, CurvePublicKey, ECDHNISTK256, FromBytes, KeyExchange, Pkk256, SharedSecretk256, Skk256,
+    ToBytes,
+};
+use static_dh_ecdh::encoding::{from_hex, to_hex};
+use static_dh_ecdh::signatures::ECDSASHA256Signature;
+use static_dh_ecdh::{CryptoError, CurveId};
 
 
 fn main() {
-    let alice_sk = ECDHNISTP256::generate_private_key([13; 32]);
-    let alice_pk = ECDHNISTP256::generate_public_key(&alice_sk);
+    let alice_sk = ECDHNISTK256::generate_private_key([13; 32]);
+    let alice_pk = ECDHNISTK256::generate_public_key(&alice_sk);
+
+    let bob_sk = ECDHNISTK256::generate_private_key([14; 32]);
+    let bob_pk = ECDHNISTK256::generate_public_key(&bob_sk);
+
+    // The public key can also be derived with `From`
+    let derived: Pkk256 = (&alice_sk).into();
+    assert_eq!(derived, alice_pk);
+    assert_eq!(Pkk256::from(&bob_sk), bob_pk);
+
+    // Secret keys never print their scalar
+    assert_eq!(format!("{:?}", alice_sk), "Skk256(REDACTED)");
+    #[cfg(feature = "dangerous-debug")]
+    assert_eq!(alice_sk.expose_debug(), format!("Skk256({})", alice_sk.to_hex()));
+
+    // Strict mode rejects tiny scalars (and tiny negations i.e. `n - 1`) but accepts real keys
+    let mut one = [0u8; 32];
+    one[31] = 1;
+    assert!(Skk256::from_bytes(&one).is_ok());
+    assert!(matches!(Skk256::from_bytes_strict(&one, Skk256::DEFAULT_MIN_BITS), Err(CryptoError::WeakKey)));
+    let n_minus_one = k256::Scalar::one().negate().to_bytes();
+    assert!(matches!(Skk256::from_bytes_strict(&n_minus_one, Skk256::DEFAULT_MIN_BITS), Err(CryptoError::WeakKey)));
+    let mut small = [0u8; 32];
+    small[24] = 1; // 2^56 i.e. 57 significant bits
+    assert!(Skk256::from_bytes_strict(&small, 58).is_err());
+    assert!(Skk256::from_bytes_strict(&small, 57).is_ok());
+    assert!(Skk256::from_bytes_strict(&alice_sk.to_bytes(), Skk256::DEFAULT_MIN_BITS).is_ok());
+
+    // Public keys round-trip through their hex-string form
+    let bob_pk_hex = bob_pk.to_hex();
+    assert_eq!(Pkk256::from_hex(&bob_pk_hex).unwrap(), bob_pk);
+    println!("bob_pk:   {}", bob_pk_hex);
+
+    // The untagged form used by `ECDSASHA256Signature` interoperates with the ECDH side
+    let signer = ECDSASHA256Signature::generate([9; 32]);
+    let verifying_key = Pkk256::from_untagged_bytes(&signer.1).unwrap();
+    assert_eq!(verifying_key, Pkk256::from(&Skk256::from_bytes(&signer.0).unwrap()));
+    assert_eq!(verifying_key.to_untagged_bytes(), signer.1);
+    assert_eq!(bob_pk.to_untagged_bytes()[..], bob_pk.to_bytes()[1..]);
+    assert_eq!(Pkk256::from_untagged_bytes(&bob_pk.to_untagged_bytes()).unwrap(), bob_pk);
+    let mut off_curve = bob_pk.to_untagged_bytes();
+    off_curve[63] ^= 1;
+    assert!(matches!(Pkk256::from_untagged_bytes(&off_curve), Err(CryptoError::PointNotOnCurve)));
+
+    // x-only (BIP340) keys: the x co-ordinate and the parity of y, and back to the even-y point
+    for seed in 0..8 {
+        let pk = ECDHNISTK256::generate_public_key(&ECDHNISTK256::generate_private_key([seed; 32]));
+        let (x, y_is_odd) = pk.to_xonly();
+        assert_eq!(x[..], pk.to_bytes()[1..33]);
+        let even = Pkk256::from_xonly(&x).unwrap();
+        assert_eq!(even.to_xonly(), (x, false));
+        assert_eq!(even == pk, !y_is_odd);
+    }
+    // BIP340 test vectors 0 (the x-only key of `d = 3`) and 5 (an x that isn't on the curve)
+    let mut three = [0u8; 32];
+    three[31] = 3;
+    let (x, _) = ECDHNISTK256::generate_public_key(&Skk256::from_bytes(&three).unwrap()).to_xonly();
+    assert_eq!(to_hex(&x), "f9308a019258c31049344f85f89d5229b531c845836f99b08601f113bce036f9");
+    let off_curve: [u8; 32] = from_hex("eefdea4cdb677750a420fee807eacf21eb9898ae79b9768766e4faa04a2d4a34").unwrap().try_into().unwrap();
+    assert!(matches!(Pkk256::from_xonly(&off_curve), Err(CryptoError::PointNotOnCurve)));
+    assert!(matches!(Pkk256::from_xonly(&[0xff; 32]), Err(CryptoError::PointNotOnCurve)));
+
+    // `same_as` compares the points, whatever form the key was parsed from
+    let uncompressed = bob_pk.to_bytes();
+    let mut compressed = uncompressed[..33].to_vec();
+    compressed[0] = 0x02 | (uncompressed[64] & 1);
+    match read_public_key(CurveId::Secp256k1, &compressed).unwrap() {
+        (CurvePublicKey::K256(pk), _) => assert!(pk.same_as(&bob_pk)),
+        _ => unreachable!(),
+    }
+    assert!(bob_pk.same_as(&bob_pk) && !bob_pk.same_as(&alice_pk));
 
-    let bob_sk = ECDHNISTP256::generate_private_key([14; 32]);
-    let bob_pk = ECDHNISTP256::generate_public_key(&bob_sk);
+    // Keys round-trip through the bare k256 crate, whether we or k256 produced them
+    bob_pk.assert_rustcrypto_compatible().unwrap();
+    let k256_pk = k256::SecretKey::from_bytes([7; 32]).unwrap().public_key();
+    Pkk256::from_bytes(k256::EncodedPoint::encode(k256_pk, false).as_bytes())
+        .unwrap()
+        .assert_rustcrypto_compatible()
+        .unwrap();
 
-    let alice_ss = ECDHNISTP256::generate_shared_secret(&alice_sk, &bob_pk);
-    let bob_ss = ECDHNISTP256::generate_shared_secret(&bob_sk, &alice_pk);
+    let alice_ss = ECDHNISTK256::generate_shared_secret(&alice_sk, &bob_pk);
+    let bob_ss = ECDHNISTK256::generate_shared_secret(&bob_sk, &alice_pk);
 
     assert_eq!(alice_ss, bob_ss);
+
+    // The shared secret is never the identity: the inputs that could produce it can't be constructed
+    // (a zero scalar, the identity or a point of another order - secp256k1 has prime order)
+    assert!(matches!(Skk256::from_bytes(&[0; 32]), Err(CryptoError::ScalarOutOfRange)));
+    let n = from_hex("fffffffffffffffffffffffffffffffebaaedce6af48a03bbfd25e8cd0364141").unwrap();
+    assert!(matches!(Skk256::from_bytes(&n), Err(CryptoError::ScalarOutOfRange)));
+    assert!(Pkk256::from_bytes(&[0; 65]).is_err());
+    assert!(Pkk256::from_bytes(&[0]).is_err());
+    // SEC1 hybrid keys (`0x06`/`0x07` || x || y) parse when the tag matches the parity of y, and are held as `0x04`
+    let uncompressed = alice_pk.to_bytes();
+    let mut hybrid = uncompressed;
+    hybrid[0] = 0x06 | (uncompressed[64] & 1);
+    let parsed = Pkk256::from_bytes(&hybrid).unwrap();
+    assert_eq!(parsed, alice_pk);
+    assert_eq!(parsed.to_bytes(), uncompressed);
+    assert_eq!(ECDHNISTK256::generate_shared_secret(&bob_sk, &parsed), bob_ss);
+    hybrid[0] ^= 1;
+    assert_eq!(Pkk256::from_bytes(&hybrid), Err(CryptoError::InvalidEncoding));
+    hybrid[0] = 0x05;
+    assert_eq!(Pkk256::from_bytes(&hybrid), Err(CryptoError::InvalidEncoding));
+
+    // The parity of y: the generator's y (`...d4b8`) is even, so that of `-G = (n - 1) * G` is odd
+    let mut one = [0; 32];
+    one[31] = 1;
+    let mut n_minus_one = n.clone();
+    n_minus_one[31] -= 1;
+    for (scalar, y_is_odd) in [(&one[..], false), (&n_minus_one[..], true)] {
+        let pk = ECDHNISTK256::generate_public_key(&Skk256::from_bytes(scalar).unwrap());
+        assert_eq!(pk.y_is_odd(), y_is_odd);
+        assert_eq!(pk.to_xonly().1, y_is_odd);
+        assert_eq!(ECDHNISTK256::public_key_compressed(&Skk256::from_bytes(scalar).unwrap())[0], 0x02 | y_is_odd as u8);
+    }
+    for seed in 0..16 {
+        let sk = ECDHNISTK256::generate_private_key([seed; 32]);
+        assert!(ECDHNISTK256::generate_shared_secret(&sk, &alice_pk).is_ok());
+    }
+    // ... and can't be wrapped by hand either, so `to_bytes` always has an x co-ordinate
+    assert_eq!(SharedSecretk256::new(k256::AffinePoint::identity()), Err(CryptoError::PointAtInfinity));
+    let generator = SharedSecretk256::new(k256::AffinePoint::generator()).unwrap();
+    assert_eq!(generator.to_bytes()[..], from_hex("79be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798").unwrap()[..]);
+    assert_eq!(generator.as_affine(), &k256::AffinePoint::generator());
+
+    // A ready-made cipher key, pinned to HKDF-SHA256(salt = "", ikm = x, info = label)
+    let shared_secret = alice_ss.clone().unwrap();
+    assert_eq!(
+        to_hex(&shared_secret.to_cipher_key(b"chat v1 aes-256-gcm")),
+        "b60e782e542cb746824b5eec91ee9bba88cf8fabb8e10e7252bab007f45d6da2"
+    );
+    let mut okm = [0; 32];
+    shared_secret.derive_key(&[], b"chat v1 aes-256-gcm", &mut okm);
+    assert_eq!(okm, shared_secret.to_cipher_key(b"chat v1 aes-256-gcm"));
+    assert_ne!(shared_secret.to_cipher_key(b"chat v1 chacha20-poly1305"), okm);
+
+    // Several subkeys from one Extract: an encryption key, a MAC key and an IV, pinned to
+    // HKDF-SHA256(salt = "", ikm = x, info = "chat v1" || i) with `i` a 4 byte big-endian counter
+    let subkeys = shared_secret.expand_keys(b"chat v1", &[32, 32, 12]);
+    assert_eq!(subkeys.iter().map(|key| key.len()).collect::<Vec<_>>(), [32, 32, 12]);
+    assert_eq!(to_hex(&subkeys[0]), "ecb3a63e42bea4c07f5c6460417e340d873146da4dc5ff86a1cbf734e76c7231");
+    assert_eq!(to_hex(&subkeys[1]), "a6a6885eac6a85250b60df19e454bbee95e3c64afdc0eaa2da4a16e7d6a92783");
+    assert_eq!(to_hex(&subkeys[2]), "bd14f45f46d779b33c3e876c");
+    assert_eq!(bob_ss.clone().unwrap().expand_keys(b"chat v1", &[32, 32, 12]), subkeys);
+    assert_ne!(subkeys[0], subkeys[1]);
+    assert_ne!(subkeys[0][..12], subkeys[2][..]);
+    // Each subkey only depends on its own index, not on the other lengths
+    assert_eq!(shared_secret.expand_keys(b"chat v1", &[16, 32])[1], subkeys[1]);
+    assert_ne!(shared_secret.expand_keys(b"chat v2", &[32])[0], subkeys[0]);
+
     println!("alice_ss: {:x}", alice_ss.unwrap().to_bytes());
     println!("bob_ss:   {:x}", bob_ss.unwrap().to_bytes());
 
+    // The same exchange through the generic free functions
+    let (carol_sk, carol_pk) = keypair::<ECDHNISTK256>([15; 32]);
+    assert_eq!(
+        ecdh::<ECDHNISTK256>(&carol_sk, &alice_pk),
+        ecdh::<ECDHNISTK256>(&alice_sk, &carol_pk)
+    );
+
+    // Tweaking the private key and deriving its public key is the same as tweaking the public key
+    for seed in 0..8 {
+        let (sk, pk) = keypair::<ECDHNISTK256>([seed; 32]);
+        let tweak = ECDHNISTK256::generate_private_key([seed + 100; 32]).to_bytes().into();
+        assert_eq!(ECDHNISTK256::generate_public_key(&sk.add_tweak(&tweak).unwrap()), pk.add_tweak(&tweak).unwrap());
+    }
+    assert_eq!(alice_pk.add_tweak(&[0; 32]).unwrap(), alice_pk);
+    // BIP32 test vector 2, m -> m/0 (the tweak is the left half of the HMAC-SHA512 output)
+    let master = Skk256::from_bytes(&from_hex("4b03d6fc340455b363f51020ad3ecca4f0850280cf436c70c727923f6db46c3e").unwrap()).unwrap();
+    let tweak = from_hex("60e3739cc2c3950b7c4d7f32cc503e13b996d0f7a45623d0a914e1efa7f811e0").unwrap().try_into().unwrap();
+    let child = master.add_tweak(&tweak).unwrap();
+    assert_eq!(to_hex(&child.to_bytes()), "abe74a98f6c7eabee0428f53798f0ab8aa1bd37873999041703c742f15ac7e1e");
+    assert_eq!(Pkk256::from(&master).add_tweak(&tweak).unwrap(), Pkk256::from(&child));
+    // Tweaks of n or more are rejected rather than reduced, as are results of zero or the identity
+    let n: [u8; 32] = from_hex("fffffffffffffffffffffffffffffffebaaedce6af48a03bbfd25e8cd0364141").unwrap().try_into().unwrap();
+    assert!(matches!(master.add_tweak(&n), Err(CryptoError::ScalarOutOfRange)));
+    assert_eq!(alice_pk.add_tweak(&[0xff; 32]), Err(CryptoError::ScalarOutOfRange));
+    let mut one = [0; 32];
+    one[31] = 1;
+    let one = Skk256::from_bytes(&one).unwrap();
+    let mut minus_one = n;
+    minus_one[31] -= 1;
+    assert!(matches!(one.add_tweak(&minus_one), Err(CryptoError::ScalarOutOfRange)));
+    assert_eq!(Pkk256::from(&one).add_tweak(&minus_one), Err(CryptoError::PointAtInfinity));
+
+    // Deriving a public key never panics - over many seeds and at both ends of the scalar range
+    for i in 0..1000u32 {
+        let mut seed = [0; 32];
+        seed[..4].copy_from_slice(&i.to_le_bytes());
+        let (sk, pk) = ECDHNISTK256::generate_keypair(seed);
+        assert_eq!(Pkk256::from_bytes(&pk.to_bytes()), Ok(ECDHNISTK256::generate_public_key(&sk)));
+    }
+    for sk in [one, Skk256::from_bytes(&minus_one).unwrap()] {
+        let pk = ECDHNISTK256::generate_public_key(&sk);
+        assert_eq!(Pkk256::from_bytes(&pk.to_bytes()), Ok(pk));
+    }
+
     // let bytes = p256::EncodedPoint::from(alice_ss.unwrap().0);
     // let bytes2 = p256::EncodedPoint::from(bob_ss.unwrap().0);
 
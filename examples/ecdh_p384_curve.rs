@@ -1,6 +1,11 @@
 
 // #![allow(warnings)]
-use static_dh_ecdh::ecdh::ecdh::{ECDHNISTP384, KeyExchange, ToBytes};
+use static_dh_ecdh::ecdh::ecdh::{ECDHNISTP384, FromBytes, KeyExchange, PkP384, SharedSecretP384, SkP384, ToBytes};
+use static_dh_ecdh::encoding::from_hex;
+use static_dh_ecdh::ecdh::affine_math::{MyAffinePoint, P384Params};
+use static_dh_ecdh::CryptoError;
+
+use num_bigint_dig::{BigInt, Sign};
 
 fn main () {
     let alice_sk = ECDHNISTP384::<48>::generate_private_key([12; 32]);
@@ -9,13 +14,183 @@ fn main () {
     let bob_sk = ECDHNISTP384::<48>::generate_private_key([21; 32]);
     let bob_pk = ECDHNISTP384::<48>::generate_public_key(&bob_sk);
 
+    // The public key can also be derived with `From`
+    let derived: PkP384 = (&alice_sk).into();
+    assert_eq!(derived, alice_pk);
+    assert_eq!(PkP384::from(&bob_sk), bob_pk);
+
+    // Secret keys never print their scalar
+    assert_eq!(format!("{:?}", alice_sk), "SkP384(REDACTED)");
+    #[cfg(feature = "dangerous-debug")]
+    assert_eq!(alice_sk.expose_debug(), format!("SkP384({})", alice_sk.to_hex()));
+
+    // Keys round-trip through the bare p384 crate
+    alice_pk.assert_rustcrypto_compatible().unwrap();
+    bob_pk.assert_rustcrypto_compatible().unwrap();
+
     let alice_ss = ECDHNISTP384::<48>::generate_shared_secret(&alice_sk, &bob_pk);
     let bob_ss = ECDHNISTP384::<48>::generate_shared_secret(&bob_sk, &alice_pk);
 
     assert_eq!(alice_ss, bob_ss);
 
+    // A ready-made cipher key, pinned to HKDF-SHA256(salt = "", ikm = x, info = label)
+    let cipher_key = alice_ss.clone().unwrap().to_cipher_key(b"session key");
+    assert_eq!(
+        cipher_key,
+        from_hex("e8d21b8ca14b2feb276225c634aee4072df2560d016ff7021d306ba138fd908e").unwrap().as_slice()
+    );
+
+    // Several subkeys from one HKDF-SHA384 Extract, expanded with info = "session keys" || i (4 byte big-endian)
+    let subkeys = alice_ss.clone().unwrap().expand_keys(b"session keys", &[32, 48, 12]);
+    assert_eq!(subkeys[0], from_hex("6ea39a8d88899b3993a91278a14bef015c10e1595ceda176904c9687b030f52d").unwrap());
+    assert_eq!(
+        subkeys[1],
+        from_hex("62da32f988cf19d0e1c8b2739154dc5f57b5f88033ad85e128fd9e637114fe200a8017f4bbe252d1d9b3427d83e1b7ab").unwrap()
+    );
+    assert_eq!(subkeys[2], from_hex("8a107929dfcce300fbed8d97").unwrap());
+    assert_eq!(bob_ss.clone().unwrap().expand_keys(b"session keys", &[32, 48, 12]), subkeys);
+    assert_ne!(subkeys[0], subkeys[1][..32]);
+
     println!("alice_ss: {:x}", &alice_ss.unwrap().to_bytes()); 
-    println!("bob_ss:   {:x}", &bob_ss.unwrap().to_bytes());
+    println!("bob_ss:   {:x}", &bob_ss.clone().unwrap().to_bytes());
+
+    // The peer point is read straight off the `EncodedPoint` - the same point as slicing x and y out of the bytes
+    let bytes = bob_pk.to_bytes();
+    let sliced = MyAffinePoint::<P384Params>::new(
+        BigInt::from_bytes_be(Sign::Plus, &bytes[1..49]),
+        BigInt::from_bytes_be(Sign::Plus, &bytes[49..]),
+    );
+    assert_eq!(MyAffinePoint::<P384Params>::from_encoded_point(&bob_pk.0), Ok(sliced));
+    // ... and a point that can't be sliced (e.g. compressed) is an error rather than a panic
+    let compressed_pk = PkP384(bob_pk.0.compress());
+    assert_eq!(ECDHNISTP384::<48>::generate_shared_secret(&alice_sk, &compressed_pk), Err(CryptoError::InvalidEncoding));
+
+    // The parity of y: the generator's y (`...0e5f`) is odd, so that of `-G = (n - 1) * G` is even
+    let mut one = [0; 48];
+    one[47] = 1;
+    let n_minus_one = from_hex(
+        "ffffffffffffffffffffffffffffffffffffffffffffffffc7634d81f4372ddf581a0db248b0a77aecec196accc52972",
+    )
+    .unwrap();
+    for (scalar, y_is_odd) in [(&one[..], true), (&n_minus_one[..], false)] {
+        let pk = ECDHNISTP384::<48>::generate_public_key(&SkP384::from_bytes(scalar).unwrap());
+        assert_eq!(pk.y_is_odd(), y_is_odd);
+        assert_eq!(pk.to_bytes()[96] & 1 == 1, y_is_odd);
+        // a compressed point wrapped by hand reads its tag
+        assert_eq!(PkP384(pk.0.compress()).y_is_odd(), y_is_odd);
+    }
+    assert!(!PkP384(p384::EncodedPoint::identity()).y_is_odd());
+
+    // SEC1 accepts the compressed form too - both forms of a key give the same shared secret
+    let uncompressed = bob_pk.to_bytes();
+    let mut compressed = uncompressed[..49].to_vec();
+    compressed[0] = 0x02 | (uncompressed[96] & 1);
+    for encoded in [&uncompressed[..], &compressed[..]] {
+        let pk = PkP384::from_sec1_bytes(encoded).unwrap();
+        assert_eq!(pk, bob_pk);
+        assert_eq!(ECDHNISTP384::<48>::generate_shared_secret(&alice_sk, &pk), bob_ss);
+    }
+    // SEC1 hybrid keys (`0x06`/`0x07` || x || y) parse when the tag matches the parity of y, and are held as `0x04`
+    let mut hybrid = uncompressed;
+    hybrid[0] = 0x06 | (uncompressed[96] & 1);
+    for parse in [PkP384::from_bytes, PkP384::from_sec1_bytes] {
+        let pk = parse(&hybrid).unwrap();
+        assert_eq!(pk, bob_pk);
+        assert_eq!(pk.to_bytes(), uncompressed);
+        assert_eq!(ECDHNISTP384::<48>::generate_shared_secret(&alice_sk, &pk), bob_ss);
+    }
+    let mut wrong_parity = hybrid;
+    wrong_parity[0] ^= 1;
+    assert_eq!(PkP384::from_bytes(&wrong_parity), Err(CryptoError::InvalidEncoding));
+    assert_eq!(PkP384::from_sec1_bytes(&wrong_parity), Err(CryptoError::InvalidEncoding));
+    assert_eq!(PkP384::from_sec1_bytes(&hybrid[..49]), Err(CryptoError::InvalidLength { expected: 97, got: 49 }));
+    // ... and still have to be on the curve
+    let mut off_curve = hybrid;
+    off_curve[96] ^= 2;
+    assert_eq!(PkP384::from_bytes(&off_curve), Err(CryptoError::PointNotOnCurve));
+    // The other root of `y^2` is the negated key
+    compressed[0] ^= 1;
+    let negated = PkP384::from_sec1_bytes(&compressed).unwrap();
+    assert_ne!(negated, bob_pk);
+    assert_eq!(negated.to_bytes()[1..49], uncompressed[1..49]);
+    // The identity and lengths that don't match the tag are rejected
+    assert_eq!(PkP384::from_sec1_bytes(&[0x00]), Err(CryptoError::PointAtInfinity));
+    assert_eq!(PkP384::from_sec1_bytes(&[]), Err(CryptoError::InvalidLength { expected: 97, got: 0 }));
+    assert_eq!(PkP384::from_sec1_bytes(&compressed[..48]), Err(CryptoError::InvalidLength { expected: 49, got: 48 }));
+    assert_eq!(PkP384::from_sec1_bytes(&uncompressed[..49]), Err(CryptoError::InvalidLength { expected: 97, got: 49 }));
+    assert_eq!(PkP384::from_sec1_bytes(&[0x05; 49]), Err(CryptoError::InvalidEncoding));
+    assert!(PkP384::from_bytes(&compressed).is_err());
+
+    // The untagged (`x || y`) form
+    let untagged = bob_pk.to_untagged_bytes();
+    assert_eq!(untagged[..], uncompressed[1..]);
+    assert_eq!(PkP384::from_untagged_bytes(&untagged).unwrap(), bob_pk);
+    let mut off_curve = untagged;
+    off_curve[95] ^= 1;
+    assert_eq!(PkP384::from_untagged_bytes(&off_curve), Err(CryptoError::PointNotOnCurve));
+
+    // `same_as` compares the points, whereas `==` compares the encodings
+    compressed[0] ^= 1;
+    let bob_compressed = PkP384(p384::EncodedPoint::from_bytes(&compressed).unwrap());
+    assert_ne!(bob_compressed, bob_pk);
+    assert!(bob_compressed.same_as(&bob_pk) && bob_pk.same_as(&bob_compressed));
+    assert!(bob_pk.same_as(&bob_pk));
+    assert!(!bob_pk.same_as(&alice_pk) && !bob_pk.same_as(&negated));
+    assert!(!PkP384(p384::EncodedPoint::identity()).same_as(&PkP384(p384::EncodedPoint::identity())));
+
+    // Strict parsing rejects the identity and small multiples of the generator, i.e. keys with known private keys
+    let multiple = |k: &[u8]| {
+        let mut scalar = [0u8; 48];
+        scalar[48 - k.len()..].copy_from_slice(k);
+        ECDHNISTP384::<48>::generate_public_key(&SkP384::from_bytes(&scalar).unwrap()).to_bytes()
+    };
+    let n_minus_one = from_hex("ffffffffffffffffffffffffffffffffffffffffffffffffc7634d81f4372ddf581a0db248b0a77aecec196accc52972").unwrap();
+    let generator = multiple(&[1]);
+    assert_eq!(generator[1..5], [0xaa, 0x87, 0xca, 0x22]);
+    for weak in [generator, multiple(&[2]), multiple(&[16]), multiple(&n_minus_one)] {
+        assert!(PkP384::from_bytes(&weak).is_ok());
+        assert_eq!(PkP384::from_bytes_strict(&weak), Err(CryptoError::WeakKey));
+    }
+    assert_eq!(PkP384::from_bytes(&[&[0x04][..], &[0; 96]].concat()), Err(CryptoError::PointAtInfinity));
+    assert_eq!(PkP384::from_bytes_strict(&[&[0x04][..], &[0; 96]].concat()), Err(CryptoError::PointAtInfinity));
+    assert!(PkP384::from_bytes_strict(&multiple(&[17])).is_ok());
+    assert_eq!(PkP384::from_bytes_strict(&bob_pk.to_bytes()), Ok(bob_pk.clone()));
+    assert!(matches!(PkP384::from_bytes_strict(&uncompressed[..96]), Err(CryptoError::InvalidLength { .. })));
+
+    // A peer "public key" of (0, 0) - a common encoding of the point at infinity - is treated as the
+    // identity, so it is rejected by the exchange even if it bypasses `from_bytes`
+    let mut small_order_pk = [0u8; 97];
+    small_order_pk[0] = 0x04;
+    let small_order_pk = PkP384(p384::EncodedPoint::from_bytes(small_order_pk).unwrap());
+    let mut even_sk = [0u8; 48];
+    even_sk[47] = 2;
+    let even_sk = SkP384::from_bytes(&even_sk).unwrap();
+    assert_eq!(
+        ECDHNISTP384::<48>::generate_shared_secret(&even_sk, &small_order_pk),
+        Err(CryptoError::PointAtInfinity)
+    );
+    assert_eq!(
+        ECDHNISTP384::<48>::generate_shared_secret(&alice_sk, &small_order_pk),
+        Err(CryptoError::PointAtInfinity)
+    );
+    // A shared secret can't hold the identity, so `to_bytes` always has an x co-ordinate
+    assert_eq!(SharedSecretP384::new(p384::EncodedPoint::identity()), Err(CryptoError::PointAtInfinity));
+    let wrapped = SharedSecretP384::new(alice_pk.0).unwrap();
+    assert_eq!(wrapped.to_bytes()[..], alice_pk.to_bytes()[1..49]);
+
+    // Co-ordinates with a leading zero byte (x for seed 76, y for seed 25) must still encode to 48 bytes
+    for i in [76u32, 25].iter() {
+        let mut seed = [0u8; 32];
+        seed[..4].copy_from_slice(&i.to_be_bytes());
+        let sk = ECDHNISTP384::<48>::generate_private_key(seed);
+        let pk = ECDHNISTP384::<48>::generate_public_key(&sk);
+        assert!(pk.to_bytes()[1] == 0 || pk.to_bytes()[49] == 0);
+        pk.assert_rustcrypto_compatible().unwrap();
+        assert_eq!(
+            ECDHNISTP384::<48>::generate_shared_secret(&sk, &alice_pk),
+            ECDHNISTP384::<48>::generate_shared_secret(&alice_sk, &pk)
+        );
+    }
 
     // println!("alice_ss: {:x}", &alice_ss.unwrap().0.y().unwrap());  // y co-ord (alice)
     // println!("bob_ss:   {:x}", &bob_ss.unwrap().0.y().unwrap());    // y co-ord (bob)
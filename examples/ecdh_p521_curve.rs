@@ -0,0 +1,70 @@
+
+// #![allow(warnings)]
+use static_dh_ecdh::ecdh::ecdh::{read_public_key, ECDHNISTP521, FromBytes, KeyExchange, PkP521, SkP521, ToBytes};
+use static_dh_ecdh::encoding::from_hex;
+use static_dh_ecdh::{CryptoError, CurveId};
+
+fn main() {
+    let alice_sk = ECDHNISTP521::generate_private_key([12; 32]);
+    let alice_pk = ECDHNISTP521::generate_public_key(&alice_sk);
+
+    let bob_sk = ECDHNISTP521::generate_private_key([21; 32]);
+    let bob_pk = ECDHNISTP521::generate_public_key(&bob_sk);
+
+    let alice_ss = ECDHNISTP521::generate_shared_secret(&alice_sk, &bob_pk);
+    let bob_ss = ECDHNISTP521::generate_shared_secret(&bob_sk, &alice_pk);
+
+    assert_eq!(alice_ss, bob_ss);
+    assert_eq!(PkP521::from(&alice_sk), alice_pk);
+    println!("alice_ss: {:x}", &alice_ss.unwrap().to_bytes());
+    println!("bob_ss:   {:x}", &bob_ss.unwrap().to_bytes());
+
+    // Known answer (computed with OpenSSL's secp521r1). Note the leading zero bytes in `d_a` and `Q_b.x`,
+    // which must survive the fixed 66 byte encoding.
+    let d_a = from_hex(
+        "000221a75dd0c7dc1ebb0b7ddaf795f5ff0649b74b6a1eb9d4aeea1be0e5a0ba41ed90c45fceef0c8e1af928adb00fa944458d08d07c2d55060e8cf1bfbda4356a6f",
+    )
+    .unwrap();
+    let q_a = from_hex(
+        "04\
+         01b58fbb27ae0d8d3d70dc9da8766201036b5aa97e04a635696c10dbb3a9ec8b43dad5658b02f973c99cf89fb6e87051ed7a95659e87fbab884c4e9cc6bab54f626a\
+         019a18d08895918166ba7f70a699cef712621e1a2a601b605e53e7aa5d32411a79749cd2150f048a232a5d4a879842ee837326f59bb7116eed7ab4121d7971647044",
+    )
+    .unwrap();
+    let q_b = from_hex(
+        "04\
+         00aa083ebf1f749109ecf4ca3a14afe3265c8cc6e012dad985d4e59deb6260aed234c58e51061d748a1fd13977881dfa50524a6dd542df931c85842c9bf3cc196868\
+         0192386d8c68184856072c4cfe7c545f41b5433fea2c6a02ae5d9fed279b210fdb274c01f7a60b67f6105f1ea2468ddc246abdeace7580399db9a324880e87574be5",
+    )
+    .unwrap();
+    let z = from_hex(
+        "0063e132b0d8a2e8daf4fd0e91f8a28ca4a87fa322cac481094db61695b7b7c564f7ccf0c5ca5e0e81742e51f2304663492a0b6e1b493e7305f3c9d11456ac0c0791",
+    )
+    .unwrap();
+
+    let sk = SkP521::from_bytes(&d_a).unwrap();
+    assert_eq!(ECDHNISTP521::generate_public_key(&sk).to_bytes()[..], q_a[..]);
+    let peer_pk = PkP521::from_bytes(&q_b).unwrap();
+    let ss = ECDHNISTP521::generate_shared_secret(&sk, &peer_pk).unwrap();
+    assert_eq!(ss.to_bytes()[..], z[..]);
+
+    // Co-ordinates with any of the 7 spare top bits set, or points off the curve, are rejected
+    let mut bad_pk = q_b.clone();
+    bad_pk[1] |= 0x02;
+    assert!(PkP521::from_bytes(&bad_pk).is_err());
+    let mut off_curve = q_b.clone();
+    off_curve[132] ^= 1;
+    assert_eq!(PkP521::from_bytes(&off_curve), Err(CryptoError::PointNotOnCurve));
+    // `(0, 0)` reads as the identity, which isn't a public key
+    let mut identity = vec![0; 133];
+    identity[0] = 0x04;
+    assert_eq!(PkP521::from_bytes(&identity), Err(CryptoError::PointAtInfinity));
+    assert_eq!(read_public_key(CurveId::NistP521, &identity).err(), Some(CryptoError::PointAtInfinity));
+
+    // The field is public, so `generate_shared_secret` re-validates keys that never went through `from_bytes`
+    for bytes in [&off_curve, &identity, &bad_pk] {
+        let unchecked = PkP521(bytes.iter().copied().collect());
+        assert_eq!(ECDHNISTP521::generate_shared_secret(&sk, &unchecked).err(), PkP521::from_bytes(bytes).err());
+    }
+    assert_eq!(ECDHNISTP521::generate_shared_secret(&sk, &PkP521(q_b.iter().copied().collect())).unwrap().to_bytes()[..], z[..]);
+}
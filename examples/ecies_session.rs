@@ -0,0 +1,137 @@
+use static_dh_ecdh::ecdh::ecdh::{keypair, ECDHNISTK256, ECDHNISTP384, ECDHNISTP521, KeyExchange, ToBytes};
+use static_dh_ecdh::ecies::{
+    begin_open, begin_seal, begin_seal_with_rng, decrypt, encrypt, encrypt_with_rng, open, open_record, seal, seal_record,
+    NONCE_LEN, TAG_LEN,
+};
+use static_dh_ecdh::encoding::from_hex;
+use static_dh_ecdh::CryptoError;
+
+use rand_chacha::rand_core::SeedableRng;
+use rand_chacha::ChaCha20Rng;
+
+// The AES-256 test cases 13, 14 and 16 of "The Galois/Counter Mode of Operation (GCM)" (McGrew and Viega):
+// (key, nonce, aad, plaintext, ciphertext || tag)
+const GCM_VECTORS: [(&str, &str, &str, &str, &str); 3] = [
+    (
+        "0000000000000000000000000000000000000000000000000000000000000000",
+        "000000000000000000000000",
+        "",
+        "",
+        "530f8afbc74536b9a963b4f1c4cb738b",
+    ),
+    (
+        "0000000000000000000000000000000000000000000000000000000000000000",
+        "000000000000000000000000",
+        "",
+        "00000000000000000000000000000000",
+        "cea7403d4d606b6e074ec5d3baf39d18d0d1c8a799996bf0265b98b5d48ab919",
+    ),
+    (
+        "feffe9928665731c6d6a8f9467308308feffe9928665731c6d6a8f9467308308",
+        "cafebabefacedbaddecaf888",
+        "feedfacedeadbeeffeedfacedeadbeefabaddad2",
+        "d9313225f88406e5a55909c5aff5269a86a7a9531534f7da2e4c303d8a318a721c3c0c95956809532fcf0e2449a6b525b16aedf5aa0de657ba637b39",
+        "522dc1f099567d07f47f37a32a84427d643a8cdcbfe5c0c97598a2bd2555d1aa8cb08e48590dbb3da7b08b1056828838c5f61e6393ba7a0abcc9f66276fc6ece0f4e1768cddf8853bb2d551b",
+    ),
+];
+
+fn gcm_vectors() {
+    for (key, nonce, aad, plaintext, sealed) in GCM_VECTORS.iter() {
+        let mut key_bytes = [0; 32];
+        key_bytes.copy_from_slice(&from_hex(key).unwrap());
+        let mut nonce_bytes = [0; NONCE_LEN];
+        nonce_bytes.copy_from_slice(&from_hex(nonce).unwrap());
+        let (aad, plaintext, sealed) = (from_hex(aad).unwrap(), from_hex(plaintext).unwrap(), from_hex(sealed).unwrap());
+
+        assert_eq!(seal(&key_bytes, &nonce_bytes, &aad, &plaintext), sealed);
+        assert_eq!(open(&key_bytes, &nonce_bytes, &aad, &sealed).unwrap(), plaintext);
+        // The tag covers the associated data too
+        assert_eq!(open(&key_bytes, &nonce_bytes, b"other aad", &sealed), Err(CryptoError::DecryptionError));
+    }
+
+    // A record is `seal` with the nonce `0u32 || seq`
+    let key = [0x42; 32];
+    let mut nonce = [0; NONCE_LEN];
+    nonce[4..].copy_from_slice(&7u64.to_be_bytes());
+    assert_eq!(seal_record(&key, 7, b"record"), seal(&key, &nonce, &[], b"record"));
+    assert_eq!(open(&key, &nonce, &[], &seal_record(&key, 7, b"record")).unwrap(), b"record");
+}
+
+fn session_round_trip<C: KeyExchange>(seed: [u8; 32]) {
+    let (recipient_sk, recipient_pk) = keypair::<C>(seed);
+    let salt = b"session salt";
+    let records: [&[u8]; 3] = [b"first record", b"", &[0x5a; 200]];
+
+    // One ephemeral agreement, many records
+    let (ephemeral_pk, seal_key) = begin_seal::<C>(&recipient_pk, salt).unwrap();
+    let sealed: Vec<Vec<u8>> = records
+        .iter()
+        .enumerate()
+        .map(|(seq, record)| seal_record(&seal_key, seq as u64, record))
+        .collect();
+
+    let open_key = begin_open::<C>(&recipient_sk, &ephemeral_pk, salt).unwrap();
+    assert_eq!(*seal_key, *open_key);
+    for (seq, (record, sealed)) in records.iter().zip(sealed.iter()).enumerate() {
+        assert_eq!(open_record(&open_key, seq as u64, sealed).unwrap().as_slice(), *record);
+    }
+
+    // Records are bound to their sequence number, key and content
+    assert_eq!(open_record(&open_key, 1, &sealed[0]), Err(CryptoError::DecryptionError));
+    let mut tampered = sealed[2].clone();
+    tampered[7] ^= 1;
+    assert_eq!(open_record(&open_key, 2, &tampered), Err(CryptoError::DecryptionError));
+    let other_key = begin_open::<C>(&recipient_sk, &ephemeral_pk, b"another salt").unwrap();
+    assert_eq!(open_record(&other_key, 0, &sealed[0]), Err(CryptoError::DecryptionError));
+
+    // The ephemeral key can come from any `CryptoRng` - the same rng state gives the same key
+    let (ephemeral_pk, seal_key) = begin_seal_with_rng::<C, _>(&recipient_pk, salt, &mut ChaCha20Rng::from_seed(seed)).unwrap();
+    let (same_pk, _) = begin_seal_with_rng::<C, _>(&recipient_pk, salt, &mut ChaCha20Rng::from_seed(seed)).unwrap();
+    assert_eq!(same_pk, ephemeral_pk);
+    assert_eq!(*begin_open::<C>(&recipient_sk, &ephemeral_pk, salt).unwrap(), *seal_key);
+}
+
+fn one_shot_round_trip<C: KeyExchange>(seed: [u8; 32]) {
+    let (recipient_sk, recipient_pk) = keypair::<C>(seed);
+    let plaintext = b"a one-shot message";
+
+    let message = encrypt::<C>(&recipient_pk, plaintext).unwrap();
+    let pk_len = recipient_pk.to_bytes().len();
+    assert_eq!(message.len(), pk_len + NONCE_LEN + plaintext.len() + TAG_LEN);
+    assert_eq!(decrypt::<C>(&recipient_sk, &message).unwrap(), plaintext);
+    // A fresh ephemeral key and nonce every time
+    assert_ne!(encrypt::<C>(&recipient_pk, plaintext).unwrap(), message);
+    assert_eq!(decrypt::<C>(&recipient_sk, &encrypt::<C>(&recipient_pk, b"").unwrap()).unwrap(), b"");
+
+    // ... or from a caller-supplied rng
+    let mut rng = ChaCha20Rng::from_seed(seed);
+    let from_rng = encrypt_with_rng::<C, _>(&recipient_pk, plaintext, &mut rng).unwrap();
+    assert_eq!(decrypt::<C>(&recipient_sk, &from_rng).unwrap(), plaintext);
+    assert_eq!(encrypt_with_rng::<C, _>(&recipient_pk, plaintext, &mut ChaCha20Rng::from_seed(seed)).unwrap(), from_rng);
+    assert_ne!(encrypt_with_rng::<C, _>(&recipient_pk, plaintext, &mut rng).unwrap(), from_rng);
+
+    // Flipping a ciphertext (or nonce) byte, truncating or using the wrong key all fail
+    let mut tampered = message.clone();
+    tampered[pk_len + NONCE_LEN] ^= 1;
+    assert_eq!(decrypt::<C>(&recipient_sk, &tampered), Err(CryptoError::DecryptionError));
+    let mut tampered = message.clone();
+    tampered[pk_len] ^= 1;
+    assert_eq!(decrypt::<C>(&recipient_sk, &tampered), Err(CryptoError::DecryptionError));
+    assert_eq!(decrypt::<C>(&recipient_sk, &message[..pk_len + NONCE_LEN + TAG_LEN - 1]), Err(CryptoError::DecryptionError));
+    let (other_sk, _) = keypair::<C>([0xee; 32]);
+    assert_eq!(decrypt::<C>(&other_sk, &message), Err(CryptoError::DecryptionError));
+    // An ephemeral key that's off the curve
+    let mut tampered = message;
+    tampered[pk_len - 1] ^= 1;
+    assert!(decrypt::<C>(&recipient_sk, &tampered).is_err());
+}
+
+fn main() {
+    gcm_vectors();
+    session_round_trip::<ECDHNISTK256>([3; 32]);
+    session_round_trip::<ECDHNISTP384<48>>([4; 32]);
+    one_shot_round_trip::<ECDHNISTK256>([5; 32]);
+    one_shot_round_trip::<ECDHNISTP384<48>>([6; 32]);
+    one_shot_round_trip::<ECDHNISTP521>([7; 32]);
+    println!("ecies sessions round-trip");
+}
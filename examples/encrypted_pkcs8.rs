@@ -0,0 +1,65 @@
+
+// #![allow(warnings)]
+use static_dh_ecdh::digest::pbkdf2_hmac_sha256;
+use static_dh_ecdh::ecdh::ecdh::{ECDHNISTK256, KeyExchange, Skk256, ToBytes};
+use static_dh_ecdh::encoding::{from_hex, to_sec1_ec_private_key};
+use static_dh_ecdh::{CryptoError, CurveId};
+
+use rand_chacha::rand_core::SeedableRng;
+use rand_chacha::ChaCha20Rng;
+
+// The `K256_DER` key of `sec1_private_keys`, encrypted with
+// `openssl pkcs8 -topk8 -v2 aes-256-cbc -v2prf hmacWithSHA256 -iter 2048 -passout pass:correct-horse`
+const K256_P8: &str = "3081ec305706092a864886f70d01050d304a302906092a864886f70d01050c301c0408b51eec057e4ba39c02020800300c06082a864886f70d02090500301d060960864801650304012a04108f5e46470146e4e5875f5ef1807ca6520481909e61f5cb7c00cb8905ee64af9ef7abaac0c5a6028d1e9e6a8e7d2d90c7320db6c95b97c9f805fa5992dcec0e95f3cb31563a00cab10ece9d8b2954aedd78e4eecf6b5d35d51ba3d7dbf25dd869b083a2c8d373f71456ec3ad52d24cf5f8abe09db7fe47bb6a4f58f716adc1de0360b69b8b398b8267a8c16ba2bd74db4553924e9bdf4fed4c41c6d70b0b3b517e8f768";
+const PBKDF2_PASSWD_SALT_1: &str = "55ac046e56e3089fec1691c22544b605f94185216dde0465e68b9d57c20dacbc49ca9cccf179b645991664b39d77ef317c71b845b1e30bd509112041d3a19783";
+const K256_D: &str = "238df874870780a1e10baba8cb517767d0e60a7bed186217da5bf279dfc00c0c";
+
+fn main() {
+    // RFC 7914 Section 11
+    let mut okm = [0; 64];
+    pbkdf2_hmac_sha256(b"passwd", b"salt", 1, &mut okm);
+    assert_eq!(okm.to_vec(), from_hex(PBKDF2_PASSWD_SALT_1).unwrap());
+
+    // A blob from another implementation
+    let p8 = from_hex(K256_P8).unwrap();
+    let sk = Skk256::from_encrypted_pkcs8(&p8, b"correct-horse").unwrap();
+    assert_eq!(sk.to_bytes().to_vec(), from_hex(K256_D).unwrap());
+    assert_eq!(Skk256::from_encrypted_pkcs8(&p8, b"correct-horsf").unwrap_err(), CryptoError::InvalidEncoding);
+
+    // Round trip - the salt and the IV are random, so the same key and password give different blobs
+    let sk = ECDHNISTK256::generate_private_key([12; 32]);
+    let p8 = sk.to_encrypted_pkcs8(b"hunter2").unwrap();
+    assert_ne!(p8, sk.to_encrypted_pkcs8(b"hunter2").unwrap());
+    let decrypted = Skk256::from_encrypted_pkcs8(&p8, b"hunter2").unwrap();
+    assert_eq!(decrypted.to_bytes(), sk.to_bytes());
+    assert!(ECDHNISTK256::generate_public_key(&decrypted).same_as(&ECDHNISTK256::generate_public_key(&sk)));
+    // ... unless they come from the same rng state
+    let from_rng = sk.to_encrypted_pkcs8_with_rng(b"hunter2", &mut ChaCha20Rng::from_seed([3; 32])).unwrap();
+    assert_eq!(sk.to_encrypted_pkcs8_with_rng(b"hunter2", &mut ChaCha20Rng::from_seed([3; 32])).unwrap(), from_rng);
+    assert_eq!(Skk256::from_encrypted_pkcs8(&from_rng, b"hunter2").unwrap().to_bytes(), sk.to_bytes());
+    // The empty password works like any other
+    let p8_empty = sk.to_encrypted_pkcs8(b"").unwrap();
+    assert_eq!(Skk256::from_encrypted_pkcs8(&p8_empty, b"").unwrap().to_bytes(), sk.to_bytes());
+
+    // Wrong passwords
+    for password in [&b"hunter3"[..], b"", b"hunter2 "] {
+        assert_eq!(Skk256::from_encrypted_pkcs8(&p8, password).unwrap_err(), CryptoError::InvalidEncoding);
+    }
+
+    // Tampering and truncation
+    let mut flipped = p8.clone();
+    *flipped.last_mut().unwrap() ^= 1;
+    assert_eq!(Skk256::from_encrypted_pkcs8(&flipped, b"hunter2").unwrap_err(), CryptoError::InvalidEncoding);
+    for len in [0, 1, p8.len() / 2, p8.len() - 16, p8.len() - 1] {
+        assert_eq!(Skk256::from_encrypted_pkcs8(&p8[..len], b"hunter2").unwrap_err(), CryptoError::InvalidEncoding);
+    }
+    let mut trailing = p8.clone();
+    trailing.push(0);
+    assert_eq!(Skk256::from_encrypted_pkcs8(&trailing, b"hunter2").unwrap_err(), CryptoError::InvalidEncoding);
+
+    // A plain (unencrypted) SEC1 key isn't an `EncryptedPrivateKeyInfo`
+    let sec1 = to_sec1_ec_private_key(Some(CurveId::Secp256k1), &sk.to_bytes(), None);
+    assert_eq!(Skk256::from_encrypted_pkcs8(&sec1, b"hunter2").unwrap_err(), CryptoError::InvalidEncoding);
+
+    println!("encrypted pkcs8: ok");
+}
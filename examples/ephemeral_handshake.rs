@@ -0,0 +1,52 @@
+
+// #![allow(warnings)]
+use static_dh_ecdh::ecdh::ecdh::{ECDHNISTK256, ECDHNISTP384, ECDHNISTP521, KeyExchange};
+use static_dh_ecdh::handshake::{ephemeral_handshake, ephemeral_handshake_with_rng};
+use static_dh_ecdh::CryptoError;
+
+use rand_chacha::rand_core::SeedableRng;
+use rand_chacha::ChaCha20Rng;
+
+/// Runs a handshake between two simulated parties and returns the key each of them derived.
+fn run<C: KeyExchange>() -> ([u8; 32], [u8; 32]) {
+    let (alice_pk, alice) = ephemeral_handshake::<C>();
+    let (bob_pk, bob) = ephemeral_handshake::<C>();
+    assert_ne!(alice_pk, bob_pk);
+
+    let alice_key = alice.complete(&bob_pk).unwrap();
+    let bob_key = bob.complete(&alice_pk).unwrap();
+    (*alice_key, *bob_key)
+}
+
+fn check<C: KeyExchange>(name: &str) {
+    let (alice_key, bob_key) = run::<C>();
+    assert_eq!(alice_key, bob_key);
+    // Fresh ephemeral keys every time, so no two handshakes share a key
+    let (next_key, _) = run::<C>();
+    assert_ne!(alice_key, next_key);
+    println!("{} handshake key: {:02x?}", name, alice_key);
+
+    // The ephemeral key can come from any `CryptoRng` - the same rng state gives the same key
+    let (alice_pk, alice) = ephemeral_handshake_with_rng::<C, _>(&mut ChaCha20Rng::from_seed([1; 32]));
+    let (same_pk, _) = ephemeral_handshake_with_rng::<C, _>(&mut ChaCha20Rng::from_seed([1; 32]));
+    assert_eq!(alice_pk, same_pk);
+    let (bob_pk, bob) = ephemeral_handshake::<C>();
+    assert_eq!(*alice.complete(&bob_pk).unwrap(), *bob.complete(&alice_pk).unwrap());
+}
+
+fn main() {
+    check::<ECDHNISTK256>("secp256k1");
+    check::<ECDHNISTP384<48>>("P-384");
+    check::<ECDHNISTP521>("P-521");
+
+    // The peer's public key is validated
+    let (mut pk, _) = ephemeral_handshake::<ECDHNISTP384<48>>();
+    let (_, pending) = ephemeral_handshake::<ECDHNISTP384<48>>();
+    pk[96] ^= 1;
+    assert!(matches!(pending.complete(&pk), Err(CryptoError::PointNotOnCurve)));
+    let (_, pending) = ephemeral_handshake::<ECDHNISTK256>();
+    assert!(matches!(
+        pending.complete(&pk[..64]),
+        Err(CryptoError::InvalidLength { expected: 65, got: 64 })
+    ));
+}
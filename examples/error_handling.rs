@@ -0,0 +1,41 @@
+
+// #![allow(warnings)]
+use std::convert::TryFrom;
+use std::error::Error;
+
+use static_dh_ecdh::ecdh::ecdh::{FromBytes, PkP384};
+use static_dh_ecdh::CryptoError;
+
+/// Application code that mixes `CryptoError` with other error types via `?`
+fn parse_peer_key(hex: &str) -> Result<PkP384, Box<dyn Error>> {
+    let bytes = static_dh_ecdh::encoding::from_hex(hex)?;
+    let len = u8::try_from(bytes.len())?; // a foreign error type, through the same `?`
+    println!("parsing a {} byte key", len);
+    Ok(PkP384::from_bytes(&bytes)?)
+}
+
+fn main() {
+    let err: Box<dyn Error> = Box::new(CryptoError::PointNotOnCurve);
+    assert_eq!(err.to_string(), "Point is not on the curve");
+    assert!(err.source().is_none());
+    assert_eq!(err.downcast_ref::<CryptoError>(), Some(&CryptoError::PointNotOnCurve));
+
+    let err = parse_peer_key("04ab").unwrap_err();
+    assert_eq!(err.to_string(), "Invalid length: expected 97 bytes, got 2");
+    assert_eq!(
+        err.downcast_ref::<CryptoError>(),
+        Some(&CryptoError::InvalidLength { expected: 97, got: 2 })
+    );
+    let err = parse_peer_key("0x4g").unwrap_err();
+    assert_eq!(format!("{}", err), "Invalid encoding");
+    println!("boxed error: {} ({:?})", err, err);
+
+    // `CryptoError` is `#[non_exhaustive]`, so downstream matches keep a wildcard arm
+    let describe = |e: &CryptoError| match e {
+        CryptoError::InvalidLength { .. } => "length",
+        CryptoError::PointNotOnCurve | CryptoError::PointAtInfinity => "point",
+        _ => "other",
+    };
+    assert_eq!(describe(&CryptoError::PointAtInfinity), "point");
+    assert_eq!(describe(&CryptoError::WeakKey), "other");
+}
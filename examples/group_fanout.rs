@@ -0,0 +1,106 @@
+
+// #![allow(warnings)]
+use static_dh_ecdh::ecdh::ecdh::{
+    keypair, FromBytes, KeyExchange, PkP384, ToBytes, ECDHNISTK256, ECDHNISTP384, ECDHNISTP521,
+};
+use static_dh_ecdh::CryptoError;
+
+/// One sender key against three recipients must agree with the pairwise exchanges, in both directions.
+fn fan_out<C: KeyExchange>() {
+    let (sender_sk, sender_pk) = keypair::<C>([1; 32]);
+    let recipients: Vec<_> = (2..5).map(|i| keypair::<C>([i; 32])).collect();
+    let pks: Vec<C::PubKey> = recipients.iter().map(|(_, pk)| pk.clone()).collect();
+
+    let secrets = C::shared_secret_many(&sender_sk, &pks);
+    assert_eq!(secrets.len(), 3);
+    for (secret, (sk, pk)) in secrets.iter().zip(recipients.iter()) {
+        let secret = secret.as_ref().unwrap().to_bytes();
+        assert_eq!(secret, C::generate_shared_secret(&sender_sk, pk).unwrap().to_bytes());
+        assert_eq!(secret, C::generate_shared_secret(sk, &sender_pk).unwrap().to_bytes());
+    }
+    assert!(C::shared_secret_many(&sender_sk, &[]).is_empty());
+}
+
+/// Generic code can get at the shared secret's bytes without naming the curve's secret type.
+fn shared_secret_bytes<C: KeyExchange>(expected_len: usize) {
+    let ((alice_sk, alice_pk), (bob_sk, bob_pk)) = (keypair::<C>([1; 32]), keypair::<C>([2; 32]));
+    let secret = C::shared_secret_bytes(&alice_sk, &bob_pk).unwrap();
+    assert_eq!(secret.len(), expected_len);
+    assert_eq!(secret, C::shared_secret_bytes(&bob_sk, &alice_pk).unwrap());
+    assert_eq!(secret, C::generate_shared_secret(&alice_sk, &bob_pk).unwrap().to_bytes().to_vec());
+}
+
+/// `generate_keypair` gives both halves at once, and they agree with another pair's.
+fn generate_keypair<C: KeyExchange>() {
+    let (alice_sk, alice_pk) = C::generate_keypair([7; 32]);
+    let (bob_sk, bob_pk) = C::generate_keypair([8; 32]);
+    assert_eq!(alice_sk.to_bytes(), C::generate_private_key([7; 32]).to_bytes());
+    assert_eq!(alice_pk.to_bytes(), C::generate_public_key(&alice_sk).to_bytes());
+    assert_eq!(
+        C::generate_shared_secret(&alice_sk, &bob_pk).unwrap().to_bytes(),
+        C::generate_shared_secret(&bob_sk, &alice_pk).unwrap().to_bytes()
+    );
+    // The private key's bytes can be stored and restored
+    let restored = C::SKey::from_bytes(&alice_sk.to_bytes()).unwrap();
+    assert_eq!(C::generate_public_key(&restored).to_bytes(), alice_pk.to_bytes());
+}
+
+/// Peer keys straight off the wire - valid ones agree with the two-step path, malformed ones are rejected.
+fn shared_secret_from_bytes<C: KeyExchange>() {
+    let ((alice_sk, alice_pk), (bob_sk, bob_pk)) = (keypair::<C>([1; 32]), keypair::<C>([2; 32]));
+    let bob_bytes = bob_pk.to_bytes();
+    let secret = C::shared_secret_from_bytes(&alice_sk, &bob_bytes).unwrap().to_bytes();
+    assert_eq!(secret, C::generate_shared_secret(&alice_sk, &bob_pk).unwrap().to_bytes());
+    assert_eq!(secret, C::shared_secret_from_bytes(&bob_sk, &alice_pk.to_bytes()).unwrap().to_bytes());
+
+    let len = bob_bytes.len();
+    let mut off_curve = bob_bytes.to_vec();
+    off_curve[len - 1] ^= 1;
+    assert!(matches!(C::shared_secret_from_bytes(&alice_sk, &off_curve), Err(CryptoError::PointNotOnCurve)));
+    assert!(matches!(
+        C::shared_secret_from_bytes(&alice_sk, &bob_bytes[..len - 1]),
+        Err(CryptoError::InvalidLength { expected, got }) if expected == len && got == len - 1
+    ));
+    assert!(matches!(C::shared_secret_from_bytes(&alice_sk, &[]), Err(CryptoError::InvalidLength { .. })));
+    let mut bad_tag = bob_bytes.to_vec();
+    bad_tag[0] = 0x05;
+    assert!(matches!(C::shared_secret_from_bytes(&alice_sk, &bad_tag), Err(CryptoError::InvalidEncoding)));
+}
+
+/// The byte lengths agree with what actually gets serialized.
+fn byte_lengths<C: KeyExchange>(lengths: (usize, usize, usize)) {
+    assert_eq!((C::private_key_len(), C::public_key_len(), C::shared_secret_len()), lengths);
+    let ((alice_sk, _), (_, bob_pk)) = (keypair::<C>([1; 32]), keypair::<C>([2; 32]));
+    assert_eq!(alice_sk.to_bytes().len(), C::private_key_len());
+    assert_eq!(bob_pk.to_bytes().len(), C::public_key_len());
+    assert_eq!(C::shared_secret_bytes(&alice_sk, &bob_pk).unwrap().len(), C::shared_secret_len());
+}
+
+fn main() {
+    fan_out::<ECDHNISTK256>();
+    fan_out::<ECDHNISTP384<48>>();
+    fan_out::<ECDHNISTP521>();
+    shared_secret_bytes::<ECDHNISTK256>(32);
+    shared_secret_bytes::<ECDHNISTP384<48>>(48);
+    shared_secret_bytes::<ECDHNISTP521>(66);
+    generate_keypair::<ECDHNISTK256>();
+    generate_keypair::<ECDHNISTP384<48>>();
+    generate_keypair::<ECDHNISTP521>();
+    shared_secret_from_bytes::<ECDHNISTK256>();
+    shared_secret_from_bytes::<ECDHNISTP384<48>>();
+    shared_secret_from_bytes::<ECDHNISTP521>();
+    byte_lengths::<ECDHNISTK256>((32, 65, 32));
+    byte_lengths::<ECDHNISTP384<48>>((48, 97, 48));
+    byte_lengths::<ECDHNISTP521>((66, 133, 66));
+
+    // A bad key only fails its own entry
+    let (sk, _) = keypair::<ECDHNISTP384<48>>([1; 32]);
+    let (_, pk) = keypair::<ECDHNISTP384<48>>([2; 32]);
+    let identity = PkP384(p384::EncodedPoint::from_bytes([&[0x04][..], &[0; 96]].concat()).unwrap());
+    let secrets = ECDHNISTP384::<48>::shared_secret_many(&sk, &[pk.clone(), identity, pk]);
+    assert!(secrets[0].is_ok());
+    assert_eq!(secrets[1], Err(CryptoError::PointAtInfinity));
+    assert_eq!(secrets[2], secrets[0]);
+
+    println!("fan-out secrets match the pairwise exchanges");
+}
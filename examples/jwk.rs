@@ -0,0 +1,92 @@
+
+// #![allow(warnings)]
+use static_dh_ecdh::ecdh::ecdh::{
+    ECDHNISTK256, ECDHNISTP384, FromBytes, KeyExchange, PkP384, Pkk256, SkP384, Skk256, ToBytes,
+};
+use static_dh_ecdh::encoding::{from_base64url, from_hex, to_base64url};
+use static_dh_ecdh::CryptoError;
+
+// Generated with Python's `joserfc` (`ECKey.import_key(...).as_dict()`)
+const K256_JWK: &str = r#"{"crv": "secp256k1", "x": "dLlb6MNyB0MJlyyS6rUk5OL39cgYusHtiiJq-N1ToNw", "y": "fIfOEmrS5Zh89H-pIeN7v1UtRqwLCHB8IkK3uSZyQUA", "d": "C63A_-4N3wDbrcD_7g3fANutwP_uDd8A263A_-4N3wA", "kty": "EC"}"#;
+const K256_D: &str = "0badc0ffee0ddf00dbadc0ffee0ddf00dbadc0ffee0ddf00dbadc0ffee0ddf00";
+const P384_JWK: &str = r#"{"crv": "P-384", "x": "oUqtlWc9UVE6OFMJFR7le2b4722AoDrlSyaHZ7KMs39y8nKqX7XRHXOVFX2YW18z", "y": "Ip1BNNGmPSoa-hhKLQnlKy1xUn5m-xQnwT5rHLGXjUdKe3tzXXks2qCZYzLbloq0", "d": "EjRWeJCrze8SNFZ4kKvN7xI0VniQq83vEjRWeJCrze8SNFZ4kKvN7xI0VniQq83v", "kty": "EC"}"#;
+const P384_D: &str = "1234567890abcdef1234567890abcdef1234567890abcdef1234567890abcdef1234567890abcdef1234567890abcdef";
+
+fn main() {
+    // base64url (RFC 4648 §10 test vectors, without padding)
+    for (plain, encoded) in [("", ""), ("f", "Zg"), ("fo", "Zm8"), ("foo", "Zm9v"), ("foob", "Zm9vYg"), ("fooba", "Zm9vYmE")] {
+        assert_eq!(to_base64url(plain.as_bytes()), encoded);
+        assert_eq!(from_base64url(encoded).unwrap(), plain.as_bytes());
+    }
+    assert_eq!(to_base64url(&[0xfb, 0xff]), "-_8");
+    assert_eq!(from_base64url("Zm9vYg=="), Err(CryptoError::InvalidEncoding));
+    assert_eq!(from_base64url("Zm9vY"), Err(CryptoError::InvalidEncoding));
+    assert_eq!(from_base64url("Zh"), Err(CryptoError::InvalidEncoding)); // non-zero trailing bits
+    assert_eq!(from_base64url("Zm+v"), Err(CryptoError::InvalidEncoding));
+
+    // Fixtures from another implementation
+    let sk = Skk256::from_jwk(K256_JWK).unwrap();
+    assert_eq!(sk.to_bytes().to_vec(), from_hex(K256_D).unwrap());
+    let pk = Pkk256::from_jwk(K256_JWK).unwrap();
+    assert_eq!(pk, ECDHNISTK256::generate_public_key(&sk));
+
+    let sk = SkP384::from_jwk(P384_JWK).unwrap();
+    assert_eq!(sk.to_bytes().to_vec(), from_hex(P384_D).unwrap());
+    let pk = PkP384::from_jwk(P384_JWK).unwrap();
+    assert_eq!(pk, ECDHNISTP384::<48>::generate_public_key(&sk));
+
+    // Round trips
+    let sk = ECDHNISTK256::generate_private_key([1; 32]);
+    let pk = ECDHNISTK256::generate_public_key(&sk);
+    assert_eq!(Pkk256::from_jwk(&pk.to_jwk()).unwrap(), pk);
+    assert_eq!(Skk256::from_jwk(&sk.to_jwk()).unwrap().to_bytes(), sk.to_bytes());
+    assert_eq!(Pkk256::from_jwk(&sk.to_jwk()).unwrap(), pk);
+    println!("secp256k1 JWK: {}", pk.to_jwk());
+
+    let sk = ECDHNISTP384::<48>::generate_private_key([2; 32]);
+    let pk = ECDHNISTP384::<48>::generate_public_key(&sk);
+    assert_eq!(PkP384::from_jwk(&pk.to_jwk()).unwrap(), pk);
+    assert_eq!(SkP384::from_jwk(&sk.to_jwk()).unwrap().to_bytes(), sk.to_bytes());
+    println!("P-384 JWK: {}", pk.to_jwk());
+
+    // Extra members (and whitespace) are ignored
+    let jwk = pk.to_jwk().replacen('{', "{\n  \"kid\": \"key-1\", \"key_ops\": [\"deriveKey\"], \"ext\": true,\n", 1);
+    assert_eq!(PkP384::from_jwk(&jwk).unwrap(), pk);
+    // ... as long as they don't nest more than 32 deep - which, skipped recursively, could otherwise overflow the stack
+    let nested = |depth: usize| {
+        let ext = format!(r#"{{"ext":{}{},"#, "[".repeat(depth), "]".repeat(depth));
+        pk.to_jwk().replacen('{', &ext, 1)
+    };
+    assert_eq!(PkP384::from_jwk(&nested(32)).unwrap(), pk);
+    assert!(matches!(PkP384::from_jwk(&nested(33)), Err(CryptoError::InvalidEncoding)));
+    assert!(matches!(PkP384::from_jwk(&nested(1_000_000)), Err(CryptoError::InvalidEncoding)));
+
+    // Mismatches are rejected
+    let k256_pk = ECDHNISTK256::generate_public_key(&ECDHNISTK256::generate_private_key([1; 32]));
+    assert!(matches!(PkP384::from_jwk(&k256_pk.to_jwk()), Err(CryptoError::InvalidEncoding)));
+    assert!(matches!(Pkk256::from_jwk(&pk.to_jwk()), Err(CryptoError::InvalidEncoding)));
+    let wrong_kty = pk.to_jwk().replace(r#""kty":"EC""#, r#""kty":"OKP""#);
+    assert!(matches!(PkP384::from_jwk(&wrong_kty), Err(CryptoError::InvalidEncoding)));
+    let no_kty = pk.to_jwk().replace(r#""kty":"EC","#, "");
+    assert!(matches!(PkP384::from_jwk(&no_kty), Err(CryptoError::InvalidEncoding)));
+    assert!(matches!(SkP384::from_jwk(&pk.to_jwk()), Err(CryptoError::InvalidEncoding))); // no `d`
+    assert!(matches!(PkP384::from_jwk(&pk.to_jwk()[1..]), Err(CryptoError::InvalidEncoding)));
+
+    // A `d` that doesn't match `x`/`y`
+    let other_d = to_base64url(&ECDHNISTP384::<48>::generate_private_key([3; 32]).to_bytes());
+    let mismatched = pk.to_jwk().replace('}', &format!(r#","d":"{}"}}"#, other_d));
+    assert!(matches!(SkP384::from_jwk(&mismatched), Err(CryptoError::InvalidEncoding)));
+
+    // A point that's off the curve
+    let mut bytes = pk.to_bytes().to_vec();
+    bytes[96] ^= 1;
+    assert!(PkP384::from_bytes(&bytes).is_err());
+    let off_curve = format!(
+        r#"{{"kty":"EC","crv":"P-384","x":"{}","y":"{}"}}"#,
+        to_base64url(&bytes[1..49]),
+        to_base64url(&bytes[49..])
+    );
+    assert!(matches!(PkP384::from_jwk(&off_curve), Err(CryptoError::PointNotOnCurve)));
+
+    println!("JWK checks passed");
+}
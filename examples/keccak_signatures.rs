@@ -0,0 +1,26 @@
+use static_dh_ecdh::digest::{Keccak256Digest, SHA256Digest};
+use static_dh_ecdh::signatures::{ECDSAKeccak256Signature, ECDSASHA256Signature, ECSignature};
+use static_dh_ecdh::CryptoError;
+
+fn main() {
+    let data = b"ECDSA proves knowledge of a secret number in the context of a single message";
+    let signer = ECDSAKeccak256Signature::generate([12; 32]); // test seed value
+    let signature = signer.sign(data).unwrap();
+    assert!(signer.verify(data, signature.as_ref()).unwrap());
+
+    // A signature over the message's Keccak-256 digest verifies, ...
+    let sha256_signer = ECDSASHA256Signature(signer.0, signer.1);
+    let keccak_digest = Keccak256Digest.digest(data);
+    let over_keccak = sha256_signer.sign_prehashed(&keccak_digest).unwrap();
+    assert_eq!(over_keccak, signature);
+    assert!(signer.verify(data, over_keccak.as_ref()).unwrap());
+
+    // ... one over its SHA-256 digest doesn't
+    let over_sha256 = sha256_signer.sign_prehashed(&SHA256Digest.digest(data)).unwrap();
+    assert!(!signer.verify(data, over_sha256.as_ref()).unwrap());
+    assert_eq!(signer.verify(data, &[]), Err(CryptoError::InvalidLength { expected: 64, got: 0 }));
+    assert_eq!(signer.verify(data, &signature[..32]), Err(CryptoError::InvalidLength { expected: 64, got: 32 }));
+
+    println!("keccak256: {:x?}", keccak_digest);
+    println!("verified_keccak256: {:?}", signer.verify(data, signature.as_ref()));
+}
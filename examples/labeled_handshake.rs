@@ -0,0 +1,41 @@
+
+// #![allow(warnings)]
+//! Two DH operations of a handshake (ephemeral-static and static-static, as in Noise or authenticated ECIES),
+//! kept independent with `generate_shared_secret_labeled`.
+use static_dh_ecdh::ecdh::ecdh::{KeyExchange, ToBytes, ECDHNISTK256, ECDHNISTP384};
+use static_dh_ecdh::encoding::to_hex;
+
+fn main() {
+    let alice_static = ECDHNISTK256::generate_private_key([1; 32]);
+    let alice_ephemeral = ECDHNISTK256::generate_private_key([2; 32]);
+    let bob_static = ECDHNISTK256::generate_private_key([3; 32]);
+    let alice_static_pk = ECDHNISTK256::generate_public_key(&alice_static);
+    let alice_ephemeral_pk = ECDHNISTK256::generate_public_key(&alice_ephemeral);
+    let bob_static_pk = ECDHNISTK256::generate_public_key(&bob_static);
+
+    // Both sides derive the same keys, without agreeing on who goes first
+    let es = ECDHNISTK256::generate_shared_secret_labeled(&alice_ephemeral, &bob_static_pk, b"es").unwrap();
+    let ss = ECDHNISTK256::generate_shared_secret_labeled(&alice_static, &bob_static_pk, b"ss").unwrap();
+    assert_eq!(es, ECDHNISTK256::generate_shared_secret_labeled(&bob_static, &alice_ephemeral_pk, b"es").unwrap());
+    assert_eq!(ss, ECDHNISTK256::generate_shared_secret_labeled(&bob_static, &alice_static_pk, b"ss").unwrap());
+    assert_eq!(es.len(), 32);
+
+    // ... the two DH operations give independent keys, as does the same DH under another label
+    assert_ne!(es, ss);
+    let ss_as_es = ECDHNISTK256::generate_shared_secret_labeled(&alice_static, &bob_static_pk, b"es").unwrap();
+    assert_ne!(ss_as_es, ss);
+    let raw = ECDHNISTK256::generate_shared_secret(&alice_static, &bob_static_pk).unwrap().to_bytes();
+    assert_ne!(ss, raw.to_vec());
+
+    // Pinned: HKDF-SHA256(salt = "", ikm = x, info = "ss" || pk_lo || pk_hi)
+    assert_eq!(to_hex(&ss), "5f282e4d2120bacb58622af60bb11b270011a2c0800bf9a5ca20e11a36b2ee38");
+
+    // It's a default method, so every curve has it
+    type P384 = ECDHNISTP384<48>;
+    let (alice, bob) = (P384::generate_private_key([4; 32]), P384::generate_private_key([5; 32]));
+    assert_eq!(
+        P384::generate_shared_secret_labeled(&alice, &P384::generate_public_key(&bob), b"ss").unwrap(),
+        P384::generate_shared_secret_labeled(&bob, &P384::generate_public_key(&alice), b"ss").unwrap()
+    );
+    println!("labeled secrets agree");
+}
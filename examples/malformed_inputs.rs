@@ -0,0 +1,210 @@
+
+// #![allow(warnings)]
+use static_dh_ecdh::ecdh::ecdh::{
+    ECDHNISTK256, ECDHNISTP384, ECDHNISTP521, FromBytes, KeyExchange, PkP384, PkP521, Pkk256, SkP384, SkP521,
+    Skk256, ToBytes,
+};
+use static_dh_ecdh::ecdh::brainpool::PkBrainpoolP384r1;
+use static_dh_ecdh::ecdh::x25519::SkX25519;
+use static_dh_ecdh::signatures::{
+    sign_with_curve, verify_with_curve, ECDSASHA256Signature, ECDSASHA384Signature, ECSignature, EcdsaSignature,
+};
+use static_dh_ecdh::encoding::from_hex;
+use static_dh_ecdh::{constants, CryptoError, CurveId};
+
+use core::convert::{TryFrom, TryInto};
+
+use generic_array::typenum;
+
+/// The secp256k1 group order `n`.
+const K256_GROUP_ORDER: &str = "fffffffffffffffffffffffffffffffebaaedce6af48a03bbfd25e8cd0364141";
+
+/// A parser that only reports its error.
+type Parser = fn(&[u8]) -> Option<CryptoError>;
+
+/// Feeds `bytes` with one byte dropped and one byte appended to the parser `T`.
+fn assert_off_by_one<T: FromBytes>(bytes: &[u8]) {
+    let mut longer = bytes.to_vec();
+    longer.push(0);
+    let len = bytes.len();
+    assert!(matches!(T::from_bytes(&bytes[..len - 1]), Err(CryptoError::InvalidLength { expected, got }) if expected == len && got == len - 1));
+    assert!(matches!(T::from_bytes(&longer), Err(CryptoError::InvalidLength { expected, got }) if expected == len && got == len + 1));
+    assert!(matches!(T::from_bytes(&[]), Err(CryptoError::InvalidLength { expected, got: 0 }) if expected == len));
+    assert!(T::from_bytes(bytes).is_ok());
+}
+
+fn main() {
+    // ECDH key parsers
+    let sk = ECDHNISTK256::generate_private_key([1; 32]);
+    assert_off_by_one::<Skk256>(&sk.to_bytes());
+    assert_off_by_one::<Pkk256>(&ECDHNISTK256::generate_public_key(&sk).to_bytes());
+
+    let sk = ECDHNISTP384::<48>::generate_private_key([2; 32]);
+    assert_off_by_one::<SkP384>(&sk.to_bytes());
+    assert_off_by_one::<PkP384>(&ECDHNISTP384::<48>::generate_public_key(&sk).to_bytes());
+
+    let sk = ECDHNISTP521::generate_private_key([3; 32]);
+    assert_off_by_one::<SkP521>(&sk.to_bytes());
+    assert_off_by_one::<PkP521>(&ECDHNISTP521::generate_public_key(&sk).to_bytes());
+
+    // The exact lengths, as a caller debugging a wire-format mismatch sees them
+    let wrong_lengths: [(Parser, usize); 8] = [
+        (|b| Skk256::from_bytes(b).err(), 32),
+        (|b| Pkk256::from_bytes(b).err(), 65),
+        (|b| SkP384::from_bytes(b).err(), 48),
+        (|b| PkP384::from_bytes(b).err(), 97),
+        (|b| SkP521::from_bytes(b).err(), 66),
+        (|b| PkP521::from_bytes(b).err(), 133),
+        (|b| SkX25519::from_bytes(b).err(), 32),
+        (|b| PkBrainpoolP384r1::from_bytes(b).err(), 97),
+    ];
+    for (parse, expected) in wrong_lengths.iter() {
+        for got in [0, 1, expected - 1, expected + 1, 2 * expected] {
+            assert_eq!(parse(&vec![0x04; got]), Some(CryptoError::InvalidLength { expected: *expected, got }));
+        }
+    }
+    assert_eq!(
+        Pkk256::from_bytes(&[0x04; 64]).unwrap_err().to_string(),
+        "Invalid length: expected 65 bytes, got 64"
+    );
+
+    // Signature parsers
+    let k256_signer = ECDSASHA256Signature::generate([4; 32]);
+    let data = b"malformed inputs";
+    let signature = k256_signer.sign(data).unwrap();
+    assert_eq!(k256_signer.verify(data, &signature[..63]), Err(CryptoError::InvalidLength { expected: 64, got: 63 }));
+    assert_eq!(k256_signer.verify(data, &[signature.as_ref(), &[0]].concat()), Err(CryptoError::InvalidLength { expected: 64, got: 65 }));
+    let digest = [7; 32];
+    assert_eq!(k256_signer.verify_prehashed(&digest, &signature[1..]), Err(CryptoError::InvalidLength { expected: 64, got: 63 }));
+    let hex = ECDSASHA256Signature::to_hex(&signature);
+    assert_eq!(ECDSASHA256Signature::from_hex(&hex[2..]), Err(CryptoError::InvalidLength { expected: 64, got: 63 }));
+    assert_eq!(ECDSASHA256Signature::from_hex(&(hex.clone() + "00")), Err(CryptoError::InvalidLength { expected: 64, got: 65 }));
+    assert_eq!(
+        EcdsaSignature::<typenum::U32>::from_bytes(&signature[..63]),
+        Err(CryptoError::InvalidLength { expected: 64, got: 63 })
+    );
+    assert!(EcdsaSignature::<typenum::U32>::from_bytes(&signature).is_ok());
+    // Raw signatures off the wire, without a manual `try_into().unwrap()`
+    assert_eq!(ECDSASHA256Signature::from_slice(&signature[..]), Ok(signature));
+    assert_eq!(ECDSASHA256Signature::from_slice(&signature[..63]), Err(CryptoError::InvalidLength { expected: 64, got: 63 }));
+    assert_eq!(ECDSASHA256Signature::from_slice(&[0; 96]), Err(CryptoError::InvalidLength { expected: 64, got: 96 }));
+    let typed: Result<EcdsaSignature<typenum::U32>, _> = signature[..].try_into();
+    assert_eq!(typed.unwrap().to_bytes(), signature.to_vec());
+
+    let p384_signer = ECDSASHA384Signature::generate([5; 32]);
+    let signature = p384_signer.sign(data).unwrap();
+    assert_eq!(p384_signer.verify(data, &signature[..95]), Err(CryptoError::InvalidLength { expected: 96, got: 95 }));
+    assert_eq!(p384_signer.verify(data, &[signature.as_ref(), &[0]].concat()), Err(CryptoError::InvalidLength { expected: 96, got: 97 }));
+    assert_eq!(
+        EcdsaSignature::<typenum::U48>::from_bytes(&signature[1..]),
+        Err(CryptoError::InvalidLength { expected: 96, got: 95 })
+    );
+
+    assert_eq!(ECDSASHA384Signature::from_slice(&signature[..]), Ok(signature));
+    assert_eq!(ECDSASHA384Signature::from_slice(&[0; 64]), Err(CryptoError::InvalidLength { expected: 96, got: 64 }));
+    assert_eq!(ECDSASHA384Signature::from_slice(&[]), Err(CryptoError::InvalidLength { expected: 96, got: 0 }));
+    assert_eq!(
+        EcdsaSignature::<typenum::U48>::try_from(&signature[..]).map(|s| s.to_bytes()),
+        Ok(signature.to_vec())
+    );
+
+    // Empty and half-length signatures are rejected up front, with the expected size
+    for sig in [&[][..], &signature[..48]] {
+        let err = Err(CryptoError::InvalidLength { expected: 96, got: sig.len() });
+        assert_eq!(p384_signer.verify(data, sig), err);
+        assert_eq!(p384_signer.verify_prehashed(&[0; 48], sig), err);
+        assert_eq!(verify_with_curve(CurveId::NistP384, p384_signer.1.as_bytes(), data, sig), err);
+    }
+    let k256_signature = k256_signer.sign(data).unwrap();
+    for sig in [&[][..], &k256_signature[..32]] {
+        let err = Err(CryptoError::InvalidLength { expected: 64, got: sig.len() });
+        assert_eq!(k256_signer.verify(data, sig), err);
+        assert_eq!(k256_signer.verify_prehashed(&digest, sig), err);
+    }
+    // The length is checked before the (here, bogus) verifying key is parsed
+    let no_key = ECDSASHA256Signature([1; 32], [0; 64]);
+    assert!(no_key.verify(data, &k256_signature).is_err());
+    assert_eq!(no_key.verify(data, &[]), Err(CryptoError::InvalidLength { expected: 64, got: 0 }));
+
+    // The runtime-dispatched API
+    assert_eq!(sign_with_curve(CurveId::Secp256k1, &[1; 31], data), Err(CryptoError::InvalidLength { expected: 32, got: 31 }));
+    assert_eq!(sign_with_curve(CurveId::NistP384, &[1; 49], data), Err(CryptoError::InvalidLength { expected: 48, got: 49 }));
+    let pk = p384_signer.1.as_bytes();
+    assert_eq!(verify_with_curve(CurveId::NistP384, pk, data, &signature[..95]), Err(CryptoError::InvalidLength { expected: 96, got: 95 }));
+    assert_eq!(verify_with_curve(CurveId::NistP384, &pk[..96], data, &signature), Err(CryptoError::InvalidLength { expected: 97, got: 96 }));
+    assert_eq!(verify_with_curve(CurveId::NistP384, pk, data, &signature), Ok(true));
+
+    // Well-sized inputs are told apart by what's wrong with them
+    for pk in [
+        ECDHNISTK256::generate_public_key(&ECDHNISTK256::generate_private_key([6; 32])).to_bytes().to_vec(),
+        ECDHNISTP384::<48>::generate_public_key(&ECDHNISTP384::<48>::generate_private_key([6; 32])).to_bytes().to_vec(),
+        ECDHNISTP521::generate_public_key(&ECDHNISTP521::generate_private_key([6; 32])).to_bytes().to_vec(),
+    ] {
+        let mut off_curve = pk.clone();
+        *off_curve.last_mut().unwrap() ^= 1;
+        let mut bad_tag = pk.clone();
+        bad_tag[0] = 0x05;
+        let parse = |bytes: &[u8]| match bytes.len() {
+            65 => Pkk256::from_bytes(bytes).map(|_| ()),
+            97 => PkP384::from_bytes(bytes).map(|_| ()),
+            _ => PkP521::from_bytes(bytes).map(|_| ()),
+        };
+        assert_eq!(parse(&pk), Ok(()));
+        assert_eq!(parse(&off_curve), Err(CryptoError::PointNotOnCurve));
+        assert_eq!(parse(&bad_tag), Err(CryptoError::InvalidEncoding));
+    }
+    assert!(matches!(Skk256::from_bytes(&[0; 32]), Err(CryptoError::ScalarOutOfRange)));
+    assert!(matches!(SkP384::from_bytes(&[0xff; 48]), Err(CryptoError::ScalarOutOfRange)));
+    assert!(matches!(SkP521::from_bytes(&[0; 66]), Err(CryptoError::ScalarOutOfRange)));
+
+    // Pre-validating scalars: `[1, n - 1]` only, with no reduction mod `n`
+    let k256_n = from_hex(K256_GROUP_ORDER).unwrap();
+    let p384_n = from_hex(constants::ECDH_NIST_384_GROUP_ORDER).unwrap();
+    for (n, is_valid) in [(k256_n, Skk256::is_valid_bytes as fn(&[u8]) -> bool), (p384_n, SkP384::is_valid_bytes)] {
+        let (mut n_minus_1, mut n_plus_1) = (n.clone(), n.clone());
+        *n_minus_1.last_mut().unwrap() -= 1;
+        *n_plus_1.last_mut().unwrap() += 1;
+        let mut one = vec![0; n.len()];
+        *one.last_mut().unwrap() = 1;
+        assert!(!is_valid(&vec![0; n.len()]));
+        assert!(is_valid(&one));
+        assert!(is_valid(&n_minus_1));
+        assert!(!is_valid(&n));
+        assert!(!is_valid(&n_plus_1));
+        assert!(!is_valid(&[0xff; 48][..n.len()]));
+        assert!(!is_valid(&n_minus_1[1..]));
+        assert!(!is_valid(&[n_minus_1.as_slice(), &[0]].concat()));
+        assert!(!is_valid(&[]));
+    }
+    assert!(Skk256::is_valid_bytes(&ECDHNISTK256::generate_private_key([7; 32]).to_bytes()));
+
+    // Non-canonical secp256k1 scalars: `from_bytes` reduces them, `from_bytes_exact` doesn't
+    let n = from_hex(K256_GROUP_ORDER).unwrap();
+    let (mut n_minus_1, mut n_plus_1) = (n.clone(), n.clone());
+    n_minus_1[31] -= 1;
+    n_plus_1[31] += 1;
+    let mut one = [0; 32];
+    one[31] = 1;
+    assert_eq!(Skk256::from_bytes(&n_plus_1).unwrap().to_bytes().to_vec(), one.to_vec());
+    assert!(matches!(Skk256::from_bytes_exact(&n), Err(CryptoError::ScalarOutOfRange)));
+    assert!(matches!(Skk256::from_bytes_exact(&n_plus_1), Err(CryptoError::ScalarOutOfRange)));
+    assert!(matches!(Skk256::from_bytes_exact(&[0xff; 32]), Err(CryptoError::ScalarOutOfRange)));
+    assert!(matches!(Skk256::from_bytes_exact(&[0; 32]), Err(CryptoError::ScalarOutOfRange)));
+    assert!(matches!(
+        Skk256::from_bytes_exact(&n_minus_1[1..]),
+        Err(CryptoError::InvalidLength { expected: 32, got: 31 })
+    ));
+    assert_eq!(Skk256::from_bytes_exact(&n_minus_1).unwrap().to_bytes().to_vec(), n_minus_1);
+    assert_eq!(Skk256::from_bytes_exact(&one).unwrap().to_bytes().to_vec(), one.to_vec());
+    assert!(SkP384::is_valid_bytes(&ECDHNISTP384::<48>::generate_private_key([7; 32]).to_bytes()));
+    assert_eq!(
+        ECDSASHA384Signature::verifying_key_from_signing(&[0; 48]),
+        Err(CryptoError::ScalarOutOfRange)
+    );
+    assert_eq!(
+        CryptoError::InvalidLength { expected: 97, got: 96 }.to_string(),
+        "Invalid length: expected 97 bytes, got 96"
+    );
+
+    println!("all malformed inputs rejected");
+}
@@ -0,0 +1,96 @@
+
+// #![allow(warnings)]
+use core::convert::TryInto;
+
+use static_dh_ecdh::digest::SHA256Digest;
+use static_dh_ecdh::encoding::{from_hex, to_hex};
+use static_dh_ecdh::signatures::{ECDSAP256SHA256Signature, ECDSASHA256Signature, ECSignature};
+use static_dh_ecdh::CryptoError;
+
+// RFC 6979 A.2.5 (ECDSA, 256 bits (prime field)) with SHA-256
+const RFC6979_SK: &str = "c9afa9d845ba75166b5c215767b1d6934e50c3db36e89b127b8a622b120f6721";
+const RFC6979_PK: &str = "0460fed4ba255a9d31c961eb74c6356d68c049b8923b61fa6ce669622e60f29fb67903fe1008b8bc99a41ae9e95628bc64f2f1b20c2d7e9f5177a3c294d4462299";
+const RFC6979_SAMPLE_SIG: &str = "efd48b2aacb6a8fd1140dd9cd45e81d69d2c877b56aaf991c34d0ea84eaf3716f7cb1c942d657c41d436c7a1b6e29f65f3e900dbb9aff4064dc4ab2f843acda8";
+const RFC6979_TEST_SIG: &str = "f1abb023518351cd71d881567b1ea663ed3efcf6c5132b354f28d3b0b7d38367019f4113742a2b14bd25926b49c649155f267e60d3814b4c0cc84250e46f0083";
+
+// A (randomized) signature from Python's `cryptography`
+const PYTHON_PK: &str = "04d8cd12ea5c67f2f8a00c1124893edcfa6754c4d6cede6be13bdf2295c810a97fa5a89d2d2a360c0ca9a4d6c7c9ed4b28d3e199d6627f2e696d689c310a5b0f48";
+const PYTHON_DATA: &[u8] = b"randomized P-256 signature";
+const PYTHON_SIG: &str = "da96bd1bfbf8c3783533698477bb2dabee37ca116ccd6b4258b5fcf8166d5462322b2832ef7134157d89f0a0890325ac339f8240e9fea508f0a39355cb9dd5df";
+
+// With the identity as the public key, `r = x(z * G) mod n` and `s = 1` would "verify" for any message (Python, over
+// `IDENTITY_FORGERY_DATA`)
+const IDENTITY_FORGERY_DATA: &[u8] = b"any message at all";
+const IDENTITY_FORGERY_SIG: &str = "f54a7bebeddff03b12b9990f9f2352379bb1cb2c3e83beb66721926f8f3329d70000000000000000000000000000000000000000000000000000000000000001";
+
+const P256_N: &str = "ffffffff00000000ffffffffffffffffbce6faada7179e84f3b9cac2fc632551";
+const P256_P: &str = "ffffffff00000001000000000000000000000000ffffffffffffffffffffffff";
+
+fn main() {
+    // Known answers, signing and verifying
+    let sk: [u8; 32] = from_hex(RFC6979_SK).unwrap().try_into().unwrap();
+    let pk = ECDSAP256SHA256Signature::verifying_key_from_signing(&sk).unwrap();
+    assert_eq!(to_hex(&pk), RFC6979_PK);
+    let signer = ECDSAP256SHA256Signature(sk, pk);
+    for (data, expected) in [(&b"sample"[..], RFC6979_SAMPLE_SIG), (&b"test"[..], RFC6979_TEST_SIG)] {
+        let signature = signer.sign(data).unwrap();
+        assert_eq!(to_hex(&signature), expected);
+        assert_eq!(signer.verify(data, &signature), Ok(true));
+        assert_eq!(signer.sign_prehashed(&SHA256Digest.digest(data)).unwrap(), signature);
+    }
+    assert_eq!(signer.verify(b"sample", &from_hex(RFC6979_TEST_SIG).unwrap()), Ok(false));
+
+    let verifier = ECDSAP256SHA256Signature([0; 32], from_hex(PYTHON_PK).unwrap().try_into().unwrap());
+    let python_sig = from_hex(PYTHON_SIG).unwrap();
+    assert_eq!(verifier.verify(PYTHON_DATA, &python_sig), Ok(true));
+    assert_eq!(verifier.verify(b"sample", &python_sig), Ok(false));
+
+    // Generated keypairs
+    let signer = ECDSAP256SHA256Signature::generate([7; 32]);
+    assert_eq!(ECDSAP256SHA256Signature::verifying_key_from_signing(&signer.0).unwrap(), signer.1);
+    let rebuilt = ECDSAP256SHA256Signature::from_signing_key(&signer.0).unwrap();
+    assert_eq!((rebuilt.0, rebuilt.1), (signer.0, signer.1));
+    let data = b"P-256 is not secp256k1";
+    let signature = signer.sign(data).unwrap();
+    assert_eq!(signer.verify(data, &signature), Ok(true));
+    assert_eq!(ECDSAP256SHA256Signature::r(signature)[..], signature[..32]);
+    assert_eq!(ECDSAP256SHA256Signature::s(signature)[..], signature[32..]);
+    let mut other = ECDSAP256SHA256Signature([0; 32], [0; 65]);
+    other.generate_keypair([8; 32]);
+    assert_eq!(other.verify(data, &signature), Ok(false));
+    // ... and the curves are distinct: the same seed gives unrelated keys and signatures
+    let k256_signer = ECDSASHA256Signature::generate([7; 32]);
+    assert_ne!(k256_signer.1[..], signer.1[1..]);
+    assert_ne!(k256_signer.sign(data).unwrap()[..], signature[..]);
+
+    // Malformed signatures and keys
+    let n = from_hex(P256_N).unwrap();
+    for (r, s) in [(&[0; 32][..], &signature[32..]), (&signature[..32], &[0; 32][..]), (&n[..], &signature[32..]), (&signature[..32], &n[..])] {
+        assert_eq!(signer.verify(data, &[r, s].concat()), Err(CryptoError::InvalidEncoding));
+    }
+    assert_eq!(signer.verify(data, &signature[..63]), Err(CryptoError::InvalidLength { expected: 64, got: 63 }));
+    let mut off_curve = signer.1;
+    off_curve[64] ^= 1;
+    assert_eq!(ECDSAP256SHA256Signature([0; 32], off_curve).verify(data, &signature), Err(CryptoError::PointNotOnCurve));
+    let mut compressed = signer.1;
+    compressed[0] = 0x02;
+    assert_eq!(ECDSAP256SHA256Signature([0; 32], compressed).verify(data, &signature), Err(CryptoError::InvalidEncoding));
+    // `(0, 0)` reads as the identity, which is never a valid key - otherwise the forgery above would verify
+    let mut identity = [0; 65];
+    identity[0] = 0x04;
+    let forged = from_hex(IDENTITY_FORGERY_SIG).unwrap();
+    assert_eq!(
+        ECDSAP256SHA256Signature([0; 32], identity).verify(IDENTITY_FORGERY_DATA, &forged),
+        Err(CryptoError::PointAtInfinity)
+    );
+    // ... and co-ordinates must be below p
+    let mut x_too_large = signer.1;
+    x_too_large[1..33].copy_from_slice(&from_hex(P256_P).unwrap());
+    assert_eq!(ECDSAP256SHA256Signature([0; 32], x_too_large).verify(data, &signature), Err(CryptoError::InvalidEncoding));
+    assert_eq!(ECDSAP256SHA256Signature::verifying_key_from_signing(&[0; 32]), Err(CryptoError::ScalarOutOfRange));
+    assert!(matches!(ECDSAP256SHA256Signature::from_signing_key(&[0; 32]), Err(CryptoError::ScalarOutOfRange)));
+    assert_eq!(ECDSAP256SHA256Signature::verifying_key_from_signing(&n.clone().try_into().unwrap()), Err(CryptoError::ScalarOutOfRange));
+    assert_eq!(ECDSAP256SHA256Signature([0xff; 32], signer.1).sign(data), Err(CryptoError::ScalarOutOfRange));
+
+    println!("P-256 signature: {}", to_hex(&signature));
+}
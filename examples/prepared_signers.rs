@@ -0,0 +1,53 @@
+
+// #![allow(warnings)]
+use std::time::Instant;
+
+use static_dh_ecdh::signatures::{ECDSASHA256Signature, ECSignature, PreparedSigner, PreparedVerifier};
+use static_dh_ecdh::CryptoError;
+
+const ROUNDS: u32 = 200;
+
+fn main() {
+    let signer = ECDSASHA256Signature::generate([12; 32]);
+    let prepared_signer = signer.prepare_signer().unwrap();
+    let prepared_verifier = signer.prepare_verifier().unwrap();
+    let data = b"many messages, one key";
+
+    // Same (deterministic) signatures as the stateless API, and they verify either way
+    let signature = prepared_signer.sign(data).unwrap();
+    assert_eq!(signature, signer.sign(data).unwrap());
+    assert_eq!(prepared_verifier.verify(data, &signature), Ok(true));
+    assert_eq!(prepared_signer.verifier().verify(data, &signature), Ok(true));
+    assert_eq!(prepared_verifier.verify(b"another message", &signature), Ok(false));
+    assert_eq!(prepared_verifier.verify(data, &signature[1..]), Err(CryptoError::InvalidLength { expected: 64, got: 63 }));
+
+    // Invalid keys are caught once, up front
+    assert!(matches!(PreparedSigner::new(&[0; 32]), Err(CryptoError::SignatureError)));
+    assert!(matches!(PreparedVerifier::new(&[1; 64]), Err(CryptoError::SignatureError)));
+    assert!(ECDSASHA256Signature([0; 32], [1; 64]).prepare_signer().is_err());
+
+    // A rough comparison (run with `--release` for meaningful numbers)
+    let start = Instant::now();
+    for _ in 0..ROUNDS {
+        signer.verify(data, &signature).unwrap();
+    }
+    let stateless = start.elapsed();
+    let start = Instant::now();
+    for _ in 0..ROUNDS {
+        prepared_verifier.verify(data, &signature).unwrap();
+    }
+    let prepared = start.elapsed();
+    println!("{} verifications: stateless {:?}, prepared {:?}", ROUNDS, stateless, prepared);
+
+    let start = Instant::now();
+    for _ in 0..ROUNDS {
+        signer.sign(data).unwrap();
+    }
+    let stateless = start.elapsed();
+    let start = Instant::now();
+    for _ in 0..ROUNDS {
+        prepared_signer.sign(data).unwrap();
+    }
+    let prepared = start.elapsed();
+    println!("{} signatures:    stateless {:?}, prepared {:?}", ROUNDS, stateless, prepared);
+}
@@ -0,0 +1,35 @@
+
+// #![allow(warnings)]
+use static_dh_ecdh::ecdh::ecdh::{keypair, ECDHNISTK256, KeyExchange};
+use static_dh_ecdh::ratchet::Ratchet;
+
+fn main() {
+    let (alice_sk, alice_pk) = keypair::<ECDHNISTK256>([12; 32]);
+    let (bob_sk, bob_pk) = keypair::<ECDHNISTK256>([21; 32]);
+
+    let alice_ss = ECDHNISTK256::generate_shared_secret(&alice_sk, &bob_pk).unwrap();
+    let bob_ss = ECDHNISTK256::generate_shared_secret(&bob_sk, &alice_pk).unwrap();
+
+    // Both sides derive the same sequence of keys
+    let mut alice_ratchet = Ratchet::new(&alice_ss);
+    let mut bob_ratchet = Ratchet::new(&bob_ss);
+    let alice_keys: Vec<[u8; 32]> = (0..16).map(|_| *alice_ratchet.next_key()).collect();
+    let bob_keys: Vec<[u8; 32]> = (0..16).map(|_| *bob_ratchet.next_key()).collect();
+    assert_eq!(alice_keys, bob_keys);
+
+    // Deterministic for a fixed shared secret, and every key is distinct
+    let mut again = Ratchet::new(&alice_ss);
+    assert_eq!(*again.next_key(), alice_keys[0]);
+    for (i, key) in alice_keys.iter().enumerate() {
+        assert!(alice_keys[i + 1..].iter().all(|other| other != key));
+    }
+
+    // A different shared secret gives an unrelated sequence
+    let (carol_sk, _) = keypair::<ECDHNISTK256>([33; 32]);
+    let carol_ss = ECDHNISTK256::generate_shared_secret(&carol_sk, &bob_pk).unwrap();
+    assert_ne!(*Ratchet::new(&carol_ss).next_key(), alice_keys[0]);
+
+    for (i, key) in alice_keys.iter().take(3).enumerate() {
+        println!("message_key_{}: {:02x?}", i, key);
+    }
+}
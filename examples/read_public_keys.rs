@@ -0,0 +1,94 @@
+
+// #![allow(warnings)]
+use static_dh_ecdh::ecdh::ecdh::{
+    keypair, read_public_key, CurvePublicKey, KeyExchange, ToBytes, ToSec1Bytes, ECDHNISTK256, ECDHNISTP384,
+    ECDHNISTP521,
+};
+use static_dh_ecdh::{CryptoError, CurveId};
+
+/// Compresses an uncompressed SEC1 point by hand i.e. `0x02 | parity(y) || x`
+fn compress(uncompressed: &[u8]) -> Vec<u8> {
+    let field_size = (uncompressed.len() - 1) / 2;
+    let mut compressed = vec![0x02 | (uncompressed[uncompressed.len() - 1] & 1)];
+    compressed.extend_from_slice(&uncompressed[1..1 + field_size]);
+    compressed
+}
+
+/// Generic code serializes the key with a runtime compression flag, and both forms read back as the same key.
+fn sec1_forms<C: KeyExchange>(curve_id: CurveId, field_size: usize)
+where
+    C::PubKey: ToSec1Bytes,
+{
+    for seed in 1..5 {
+        let (_, pk) = keypair::<C>([seed; 32]);
+        let uncompressed = pk.to_sec1_bytes(false);
+        let compressed = pk.to_sec1_bytes(true);
+        assert_eq!(uncompressed, pk.to_bytes().to_vec());
+        assert_eq!(compressed, compress(&uncompressed));
+        assert_eq!((uncompressed.len(), compressed.len()), (1 + 2 * field_size, 1 + field_size));
+
+        let (from_uncompressed, _) = read_public_key(curve_id, &uncompressed).unwrap();
+        let (from_compressed, consumed) = read_public_key(curve_id, &compressed).unwrap();
+        assert_eq!(consumed, compressed.len());
+        assert_eq!(from_uncompressed, from_compressed);
+        for compress_point in [false, true] {
+            assert_eq!(from_compressed.to_sec1_bytes(compress_point), pk.to_sec1_bytes(compress_point));
+        }
+    }
+}
+
+fn main() {
+    sec1_forms::<ECDHNISTK256>(CurveId::Secp256k1, 32);
+    sec1_forms::<ECDHNISTP384<48>>(CurveId::NistP384, 48);
+    sec1_forms::<ECDHNISTP521>(CurveId::NistP521, 66);
+
+    let (_, k256_pk) = keypair::<ECDHNISTK256>([1; 32]);
+    let (_, p384_pk) = keypair::<ECDHNISTP384<48>>([2; 32]);
+    let (_, p521_pk) = keypair::<ECDHNISTP521>([3; 32]);
+
+    // Two concatenated keys (one uncompressed, one compressed), followed by some unrelated trailing bytes
+    let mut stream = k256_pk.to_bytes().to_vec();
+    stream.extend_from_slice(&compress(&k256_pk.to_bytes()));
+    stream.extend_from_slice(b"trailer");
+
+    let (first, consumed) = read_public_key(CurveId::Secp256k1, &stream).unwrap();
+    assert_eq!(consumed, 65);
+    assert_eq!(first, CurvePublicKey::K256(k256_pk.clone()));
+    let (second, consumed_2) = read_public_key(CurveId::Secp256k1, &stream[consumed..]).unwrap();
+    assert_eq!(consumed_2, 33);
+    assert_eq!(second, CurvePublicKey::K256(k256_pk));
+    assert_eq!(&stream[consumed + consumed_2..], b"trailer");
+
+    // Heterogeneous objects - the caller knows the curve of each
+    let mut stream = compress(&p384_pk.to_bytes());
+    stream.extend_from_slice(&p521_pk.to_bytes());
+    stream.extend_from_slice(&compress(&p521_pk.to_bytes()));
+    let (key, offset) = read_public_key(CurveId::NistP384, &stream).unwrap();
+    assert_eq!((offset, key.curve_id()), (49, CurveId::NistP384));
+    assert_eq!(key, CurvePublicKey::P384(p384_pk.clone()));
+    let (key, consumed) = read_public_key(CurveId::NistP521, &stream[offset..]).unwrap();
+    assert_eq!(consumed, 133);
+    assert_eq!(key, CurvePublicKey::P521(p521_pk.clone()));
+    let (key, consumed) = read_public_key(CurveId::NistP521, &stream[offset + 133..]).unwrap();
+    assert_eq!(consumed, 67);
+    assert_eq!(key, CurvePublicKey::P521(p521_pk));
+
+    // Both parities decompress correctly
+    for seed in 4..8 {
+        let (_, pk) = keypair::<ECDHNISTP384<48>>([seed; 32]);
+        let (key, _) = read_public_key(CurveId::NistP384, &compress(&pk.to_bytes())).unwrap();
+        assert_eq!(key, CurvePublicKey::P384(pk));
+    }
+
+    // Truncated input, an unknown tag and an x co-ordinate with no point on the curve
+    let p384_compressed = compress(&p384_pk.to_bytes());
+    assert_eq!(read_public_key(CurveId::NistP384, &p384_compressed[..48]), Err(CryptoError::InvalidLength { expected: 49, got: 48 }));
+    assert_eq!(read_public_key(CurveId::NistP384, &[]), Err(CryptoError::InvalidLength { expected: 49, got: 0 }));
+    assert_eq!(read_public_key(CurveId::NistP384, &[0x05; 97]), Err(CryptoError::InvalidEncoding));
+    let mut not_on_curve = [0u8; 49];
+    not_on_curve[0] = 0x02;
+    not_on_curve[48] = 5; // x = 5 - x^3 - 3x + b is not a square mod p
+    assert_eq!(read_public_key(CurveId::NistP384, &not_on_curve), Err(CryptoError::PointNotOnCurve));
+
+    println!("read every key from the stream");
+}
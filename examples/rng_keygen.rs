@@ -0,0 +1,35 @@
+
+// #![allow(warnings)]
+use rand::rngs::OsRng;
+use rand::RngCore;
+use rand_chacha::rand_core::SeedableRng;
+use rand_chacha::ChaCha20Rng;
+use static_dh_ecdh::ecdh::ecdh::{KeyExchange, ToBytes, ECDHNISTK256, ECDHNISTP384, ECDHNISTP521};
+
+/// Keys from an rng are the keys of the seed drawn from it, so any `CryptoRng` (e.g. a wasm one) can be
+/// plugged in.
+fn rng_keygen<C: KeyExchange>() {
+    let mut rng = ChaCha20Rng::from_seed([42; 32]);
+    let sk = C::generate_private_key_with_rng(&mut rng);
+
+    let mut replay = ChaCha20Rng::from_seed([42; 32]);
+    let mut seed = [0; 32];
+    replay.fill_bytes(&mut seed);
+    assert_eq!(sk.to_bytes(), C::generate_private_key(seed).to_bytes());
+
+    // The rng advances, so consecutive keys differ
+    assert_ne!(C::generate_private_key_with_rng(&mut rng).to_bytes(), sk.to_bytes());
+
+    let (alice_sk, bob_sk) = (C::generate_private_key_with_rng(&mut OsRng), C::generate_private_key_with_rng(&mut OsRng));
+    assert_eq!(
+        C::generate_shared_secret(&alice_sk, &C::generate_public_key(&bob_sk)).unwrap().to_bytes(),
+        C::generate_shared_secret(&bob_sk, &C::generate_public_key(&alice_sk)).unwrap().to_bytes()
+    );
+}
+
+fn main() {
+    rng_keygen::<ECDHNISTK256>();
+    rng_keygen::<ECDHNISTP384<48>>();
+    rng_keygen::<ECDHNISTP521>();
+    println!("rng-based keys match their seeds");
+}
@@ -0,0 +1,67 @@
+
+// #![allow(warnings)]
+use static_dh_ecdh::ecdh::ecdh::{ECDHNISTK256, KeyExchange, SkP384, Skk256, ToBytes};
+use static_dh_ecdh::encoding::{from_hex, parse_sec1_ec_private_key, to_sec1_ec_private_key};
+use static_dh_ecdh::{CryptoError, CurveId};
+
+// Generated with `openssl ecparam -name <curve> -genkey -noout -outform DER` (the `NO_PUBLIC` forms with
+// `openssl ec -no_public`, the others with `-conv_form compressed` and `-param_enc explicit`)
+const K256_DER: &str = "30740201010420238df874870780a1e10baba8cb517767d0e60a7bed186217da5bf279dfc00c0ca00706052b8104000aa14403420004107b570eb8e0b59367e040c1ae21973e8cf8ba88a00b968338a024b1256687e38e03b4eb5b299106d10ff94f84e30c16859d51fe47d6e6d83cb2622d14dba4ab";
+const K256_D: &str = "238df874870780a1e10baba8cb517767d0e60a7bed186217da5bf279dfc00c0c";
+const K256_DER_NO_PUBLIC: &str = "302e0201010420238df874870780a1e10baba8cb517767d0e60a7bed186217da5bf279dfc00c0ca00706052b8104000a";
+const K256_DER_COMPRESSED: &str = "30540201010420238df874870780a1e10baba8cb517767d0e60a7bed186217da5bf279dfc00c0ca00706052b8104000aa12403220003107b570eb8e0b59367e040c1ae21973e8cf8ba88a00b968338a024b1256687e3";
+const K256_DER_EXPLICIT: &str = "308201510201010420238df874870780a1e10baba8cb517767d0e60a7bed186217da5bf279dfc00c0ca081e33081e0020101302c06072a8648ce3d0101022100fffffffffffffffffffffffffffffffffffffffffffffffffffffffefffffc2f3044042000000000000000000000000000000000000000000000000000000000000000000420000000000000000000000000000000000000000000000000000000000000000704410479be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798483ada7726a3c4655da4fbfc0e1108a8fd17b448a68554199c47d08ffb10d4b8022100fffffffffffffffffffffffffffffffebaaedce6af48a03bbfd25e8cd0364141020101a14403420004107b570eb8e0b59367e040c1ae21973e8cf8ba88a00b968338a024b1256687e38e03b4eb5b299106d10ff94f84e30c16859d51fe47d6e6d83cb2622d14dba4ab";
+const P384_DER: &str = "3081a40201010430283db8df1b3fabf40ba7dd3f93025f4b9516427113a211ce39e477271d168d2442328e2c530ff8198c2305bee4277cfda00706052b81040022a164036200041f8faa81ce4718ea8f8ae4cd22901658b24b355e17b5863e4582a945f60189d45de809b33d95e63a3bbfb4a023067914ab0479b589baedf6099f3a731ef6f86e74852624c50b3fc05be6e66931f501c334eaa06be741f46d837de135637feb46";
+const P384_D: &str = "283db8df1b3fabf40ba7dd3f93025f4b9516427113a211ce39e477271d168d2442328e2c530ff8198c2305bee4277cfd";
+const P384_DER_NO_PUBLIC: &str = "303e0201010430283db8df1b3fabf40ba7dd3f93025f4b9516427113a211ce39e477271d168d2442328e2c530ff8198c2305bee4277cfda00706052b81040022";
+
+fn main() {
+    let der_k256 = from_hex(K256_DER).unwrap();
+    // Fixtures from another implementation
+    let der = from_hex(K256_DER).unwrap();
+    assert_eq!(Skk256::from_sec1_der(&der).unwrap().to_bytes().to_vec(), from_hex(K256_D).unwrap());
+    for der in [K256_DER_NO_PUBLIC, K256_DER_COMPRESSED] {
+        let sk = Skk256::from_sec1_der(&from_hex(der).unwrap()).unwrap();
+        assert_eq!(sk.to_bytes().to_vec(), from_hex(K256_D).unwrap());
+    }
+    let der = from_hex(P384_DER).unwrap();
+    assert_eq!(SkP384::from_sec1_der(&der).unwrap().to_bytes().to_vec(), from_hex(P384_D).unwrap());
+    let sk = SkP384::from_sec1_der(&from_hex(P384_DER_NO_PUBLIC).unwrap()).unwrap();
+    assert_eq!(sk.to_bytes().to_vec(), from_hex(P384_D).unwrap());
+
+    let (curve_id, scalar, point) = parse_sec1_ec_private_key(&der).unwrap();
+    assert_eq!((curve_id, scalar.len(), point.map(|p| p.len())), (Some(CurveId::NistP384), 48, Some(97)));
+
+    // Encoding is the inverse of parsing, byte for byte
+    let d = from_hex(K256_D).unwrap();
+    let pk = ECDHNISTK256::generate_public_key(&Skk256::from_sec1_der(&der_k256).unwrap()).to_bytes();
+    assert_eq!(to_sec1_ec_private_key(Some(CurveId::Secp256k1), &d, Some(&pk)), der_k256);
+    assert_eq!(to_sec1_ec_private_key(Some(CurveId::Secp256k1), &d, None), from_hex(K256_DER_NO_PUBLIC).unwrap());
+
+    // Malformed keys
+    let der = der_k256;
+    assert!(matches!(Skk256::from_sec1_der(&der[..der.len() - 1]), Err(CryptoError::InvalidEncoding))); // truncated
+    assert!(matches!(Skk256::from_sec1_der(&[der.as_slice(), &[0]].concat()), Err(CryptoError::InvalidEncoding)));
+    assert!(matches!(Skk256::from_sec1_der(&[]), Err(CryptoError::InvalidEncoding)));
+    let mut wrong_version = der.clone();
+    wrong_version[4] = 0x02;
+    assert!(matches!(Skk256::from_sec1_der(&wrong_version), Err(CryptoError::InvalidEncoding)));
+    let mut off_curve = der.clone();
+    *off_curve.last_mut().unwrap() ^= 1;
+    assert!(matches!(Skk256::from_sec1_der(&off_curve), Err(CryptoError::PointNotOnCurve)));
+    let explicit = from_hex(K256_DER_EXPLICIT).unwrap();
+    assert!(matches!(Skk256::from_sec1_der(&explicit), Err(CryptoError::UnsupportedCurve)));
+
+    // A valid public key, but not the private scalar's
+    let other_pk = ECDHNISTK256::generate_public_key(&ECDHNISTK256::generate_private_key([1; 32])).to_bytes();
+    let mut mismatched = der.clone();
+    let len = mismatched.len();
+    mismatched[len - 65..].copy_from_slice(&other_pk);
+    assert!(matches!(Skk256::from_sec1_der(&mismatched), Err(CryptoError::InvalidEncoding)));
+
+    // A valid key, but for another curve
+    assert!(matches!(SkP384::from_sec1_der(&der), Err(CryptoError::InvalidEncoding)));
+    assert!(matches!(Skk256::from_sec1_der(&from_hex(P384_DER).unwrap()), Err(CryptoError::InvalidEncoding)));
+
+    println!("SEC1 private keys parsed");
+}
@@ -0,0 +1,70 @@
+
+// #![allow(warnings)]
+use static_dh_ecdh::ecdh::ecdh::{keypair, FromBytes, KeyExchange, Skk256, ToBytes, ECDHNISTK256};
+use static_dh_ecdh::encoding::from_hex;
+use static_dh_ecdh::CryptoError;
+
+use rand_chacha::rand_core::SeedableRng;
+use rand_chacha::ChaCha20Rng;
+
+fn main() {
+    let (sk, pk) = keypair::<ECDHNISTK256>([12; 32]);
+    let shares = sk.split(3, 5).unwrap();
+    assert_eq!(shares.len(), 5);
+    for (i, share) in shares.iter().enumerate() {
+        assert_eq!(share.len(), 33);
+        assert_eq!(share[0] as usize, i + 1);
+    }
+
+    // Any `threshold` shares give back the key ...
+    for picked in [[0, 1, 2], [0, 2, 4], [4, 3, 1], [1, 2, 3]] {
+        let subset: Vec<Vec<u8>> = picked.iter().map(|&i| shares[i].clone()).collect();
+        let combined = Skk256::combine(&subset).unwrap();
+        assert_eq!(combined.to_bytes(), sk.to_bytes());
+        assert_eq!(ECDHNISTK256::generate_public_key(&combined), pk);
+    }
+    // ... as do more than `threshold`
+    assert_eq!(Skk256::combine(&shares[1..]).unwrap().to_bytes(), sk.to_bytes());
+    assert_eq!(Skk256::combine(&shares).unwrap().to_bytes(), sk.to_bytes());
+    // ... but fewer give another key
+    for subset in [&shares[..2], &shares[3..], &shares[..1]] {
+        assert_ne!(Skk256::combine(subset).map(|key| key.to_bytes()), Ok(sk.to_bytes()));
+    }
+    // Fresh randomness every time, so two splits don't share anything beyond the key
+    let other_shares = sk.split(3, 5).unwrap();
+    assert_ne!(other_shares, shares);
+    assert_eq!(Skk256::combine(&other_shares[2..]).unwrap().to_bytes(), sk.to_bytes());
+    // ... unless they come from the same rng state
+    let from_rng = sk.split_with_rng(3, 5, &mut ChaCha20Rng::from_seed([7; 32])).unwrap();
+    assert_eq!(sk.split_with_rng(3, 5, &mut ChaCha20Rng::from_seed([7; 32])).unwrap(), from_rng);
+    assert_eq!(Skk256::combine(&from_rng[..3]).unwrap().to_bytes(), sk.to_bytes());
+    // A threshold of 1 puts the key itself in every share
+    for share in sk.split(1, 3).unwrap() {
+        assert_eq!(share[1..], sk.to_bytes()[..]);
+    }
+
+    // Hand-made shares of `f(x) = (n - 1) + 5x`, so the interpolation has to wrap around mod n
+    let n_minus_one = from_hex("fffffffffffffffffffffffffffffffebaaedce6af48a03bbfd25e8cd0364140").unwrap();
+    let share = |x: u8, y: u8| {
+        let mut share = vec![0; 33];
+        share[0] = x;
+        share[32] = y;
+        share
+    };
+    let expected = Skk256::from_bytes(&n_minus_one).unwrap().to_bytes();
+    assert_eq!(Skk256::combine(&[share(1, 4), share(2, 9)]).unwrap().to_bytes(), expected);
+    assert_eq!(Skk256::combine(&[share(3, 14), share(1, 4), share(2, 9)]).unwrap().to_bytes(), expected);
+
+    // Invalid parameters and malformed shares
+    assert_eq!(sk.split(0, 5).err(), Some(CryptoError::ECCError));
+    assert_eq!(sk.split(4, 3).err(), Some(CryptoError::ECCError));
+    assert_eq!(Skk256::combine(&[]).err(), Some(CryptoError::InvalidEncoding));
+    assert_eq!(Skk256::combine(&[shares[0][..32].to_vec()]).err(), Some(CryptoError::InvalidLength { expected: 33, got: 32 }));
+    assert_eq!(Skk256::combine(&[shares[0].clone(), shares[0].clone()]).err(), Some(CryptoError::InvalidEncoding));
+    assert_eq!(Skk256::combine(&[share(0, 4)]).err(), Some(CryptoError::InvalidEncoding));
+    let mut out_of_range = vec![1; 33];
+    out_of_range[1..].copy_from_slice(&[0xff; 32]);
+    assert_eq!(Skk256::combine(&[out_of_range]).err(), Some(CryptoError::InvalidEncoding));
+    // `f(x) = x` has `f(0) = 0`, which isn't a valid key
+    assert_eq!(Skk256::combine(&[share(1, 1), share(2, 2)]).err(), Some(CryptoError::ScalarOutOfRange));
+}
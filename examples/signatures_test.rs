@@ -2,28 +2,550 @@
 // #![allow(warnings)]
 
 use p384::{EncodedPoint};
-use static_dh_ecdh::signatures::{ECDSASHA256Signature, ECDSASHA384Signature, ECSignature};
+use core::convert::{TryFrom, TryInto};
+use elliptic_curve::sec1::ToEncodedPoint;
+use static_dh_ecdh::digest::{SHA256Digest, SHA384Digest};
+use static_dh_ecdh::encoding::from_hex;
+use static_dh_ecdh::signatures::{
+    sign_with_curve, verify_with_curve, ECDSASHA256Signature, ECDSASHA384Signature, ECSignature,
+    EcdsaSignature,
+};
+use static_dh_ecdh::ecdh::ecdh::{KeyExchange, ToBytes, ECDHNISTK256};
+use static_dh_ecdh::{CryptoError, CurveId};
 
+use num_bigint_dig::{BigInt, BigUint, Sign};
+use rand_chacha::rand_core::SeedableRng;
+use rand_chacha::ChaCha20Rng;
+use sha2::{Sha256, Sha384, Sha512};
+use static_dh_ecdh::ecdh::affine_math::{ECSignerType, MyAffinePoint, P384Params};
+
+const SECP256K1_N: &str = "fffffffffffffffffffffffffffffffebaaedce6af48a03bbfd25e8cd0364141";
+const P384_N: &str = "ffffffffffffffffffffffffffffffffffffffffffffffffc7634d81f4372ddf581a0db248b0a77aecec196accc52973";
+
+// A P-384 signature whose `R` has `x(R) = n + 2`, so `r = 2`. Built in Python by picking `R`, `u1` and `u2` and
+// solving for the public key `Q = (R - u1 * G) / u2` (and checked with `cryptography`).
+const X_GE_N_PK: &str = "04e7a24544cbb257c91d0a622abdfd22fcf373d6decdee06c4453da5ffb1dd912f59fc7abea1c02afaebf765915fe5b1b3dba63faf7c684d3bb9dcc0a46a51ee6ed85f870d0e0343747d46a437b6d539037755ea3c4e0c5e0c271f15026e6a48ab";
+const X_GE_N_DIGEST: &str = "05088b8420298529fcfa37116de5d752bb57c8ddd899325019ae72388dd2ae902efbdec6fb312c6d88e0e392a9bd79ad";
+const X_GE_N_SIG: &str = "000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000002dafbeeaebf7a7681fafd0d4268c708495312cda26fa626a1d03ddc60b2b67aa59919c70e2a4d48dd674378853812fd74";
+
+// With the identity as the public key, `u1 * G + u2 * Q = u1 * G`, so `r = x(z * G) mod n` and `s = 1` would
+// "verify" for any message (Python, over `IDENTITY_FORGERY_DATA`)
+const IDENTITY_FORGERY_DATA: &[u8] = b"any message at all";
+const IDENTITY_FORGERY_SIG: &str = "559196a483a50fc2cd3910adf0f2ff152c6d62409181797e6500dec995da7502c04fb1cecc30955b3d0b89a9f4b88be0000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000001";
+
+// P-384 signed with SHA-256 and SHA-512 instead of SHA-384, by Python's `cryptography` (the key is the one in
+// `jwk.rs`, `d = 1234567890abcdef...`).
+const OTHER_HASH_PK: &str = "04a14aad95673d51513a385309151ee57b66f8ef6d80a03ae54b268767b28cb37f72f272aa5fb5d11d7395157d985b5f33229d4134d1a63d2a1afa184a2d09e52b2d71527e66fb1427c13e6b1cb1978d474a7b7b735d792cdaa0996332db968ab4";
+const OTHER_HASH_DATA: &[u8] = b"legacy systems pair P-384 with other hashes";
+const OTHER_HASH_SHA256_SIG: &str = "8c3b976718d61a2a4c6731b27c09841cbe0c235f2644bd0b775a190b9893620844d385d31d917fa35d2603e1997cebddaf69c286d4402bd5417b1e390382f5c0053b596962cd7d6f650c80261c31208af51ca249362e1e5572a50bfb1aa68522";
+// A third party's secp256k1 key and SHA-256 signature (Python's `cryptography`, low-s normalized)
+const THIRD_PARTY_PK: &str = "048f9c14b84e6ee89efcaeb0129be1e5557002d1603c7f9688792803c48888364b92b2837a852428dec3aa436e65a7c19d5cacafe4eb47b5298ce48427c988d66c";
+const THIRD_PARTY_COMPRESSED_PK: &str = "028f9c14b84e6ee89efcaeb0129be1e5557002d1603c7f9688792803c48888364b";
+const THIRD_PARTY_DATA: &[u8] = b"signed by someone else";
+const THIRD_PARTY_SIG: &str = "894c560e73a988d666eb3b5322cab486ec6f4486c9a1493daa133eaaacc14ac6379812cec1df077adb9f71328d51b9d8a4f107d8f28825eb23b5ce284548a8ce";
+// `k = 0x0abc...0abc` with the `OTHER_HASH` key, over SHA-384("nonce retries") (Python, textbook ECDSA)
+const OTHER_HASH_SK: &str = "1234567890abcdef1234567890abcdef1234567890abcdef1234567890abcdef1234567890abcdef1234567890abcdef";
+const FIXED_K_SIG: &str = "1226d5c5d1861b36497d2660b38adaaa2ddb51b733c6213b959cea2ae5aefc76f15ad3a2b3286c8c4f39a67d0a700dcb8b2b9e9bcbf385e2c0a64dee906a03b4b3bc8f80b60db87f862f7b7204a53dbedb508cadcaafe57ade097f96054d146e";
+const OTHER_HASH_SHA512_SIG: &str = "d5603bf90fd9a08bbbaa069fb8151320550efba9b1d5e1c8957889b6265744dfeb8ad5bc0d3e0d4af54719a67b73fa00da54fbe7b0ab15d2387a847ccc6a4f7c0ceadff6af253871fa85af480301a2f70a6c9d3fc838a7fde6b3a3d52a2ae389";
+// RFC 6979 A.2.6 - P-384, SHA-384, message "sample"
+const RFC6979_P384_SK: &str = "6b9d3dad2e1b8c1c05b19875b6659f4de23c3b667bf297ba9aa47740787137d896d5724e4c70a825f872c9ea60d2edf5";
+const RFC6979_P384_SAMPLE_SIG: &str = "94edbb92a5ecb8aad4736e56c691916b3f88140666ce9fa73d64c4ea95ad133c81a648152e44acf96e36dd1e80fabe4699ef4aeb15f178cea1fe40db2603138f130e740a19624526203b6351d0a3a94fa329c145786e679e7b82c71a38628ac8";
+// The same, with `[7; 32]` as the additional data of RFC 6979 Section 3.6 (computed with a Python implementation)
+const HEDGED_P384_SAMPLE_SIG: &str = "91ef35542edc08c407c1ac2b52dac83578ccc2fc6967105b7a901b64ab35d92553a459ace1491a9db04ce5e8531a853cc2df8d78dd0d4293cdf89366629fcd24372e0e8a04db8e594953e6d3dfaeb7f72807cac06cce84172a5dc3299c399995";
+
+/// Returns `r || s'` with `s'` replaced by `f(s, n)`, left-padded to the field size.
+fn with_s(sig: &[u8], n_hex: &str, f: impl Fn(BigUint, BigUint) -> BigUint) -> Vec<u8> {
+    let half = sig.len() / 2;
+    let n = BigUint::from_bytes_be(&from_hex(n_hex).unwrap());
+    let s = f(BigUint::from_bytes_be(&sig[half..]), n).to_bytes_be();
+    let mut out = sig[..half].to_vec();
+    out.resize(2 * half - s.len(), 0);
+    out.extend_from_slice(&s);
+    out
+}
 
 fn main () {
     let data = b"ECDSA proves knowledge of a secret number in the context of a single message";
-    let mut signer = ECDSASHA256Signature([0; 32], [0; 64]);
-    let _keys = signer.generate_keypair([12; 32]);  // test seed value
+    let signer = ECDSASHA256Signature::generate([12; 32]);  // test seed value
     let signature = signer.sign(data).unwrap();
-    let v = signer.verify(data, &signature.as_ref());
+    let v = signer.verify(data, signature.as_ref());
 
     println!("verified_256: {:?}", v);
     println!("r256: {:?}", ECDSASHA256Signature::r(signature));
     println!("s256: {:?}", ECDSASHA256Signature::s(signature));
 
+    // The structured form splits at the right offsets and round-trips
+    let typed = signer.sign_typed(data).unwrap();
+    assert_eq!(typed.r()[..], ECDSASHA256Signature::r(signature)[..]);
+    assert_eq!(typed.s()[..], ECDSASHA256Signature::s(signature)[..]);
+    assert_eq!(typed.to_bytes(), signature.to_vec());
+    assert_eq!(EcdsaSignature::try_from(&signature[..]).unwrap(), typed);
+    assert!(signer.verify_typed(data, &typed).unwrap());
+    assert!(EcdsaSignature::<generic_array::typenum::U32>::from_bytes(&signature[..63]).is_err());
+
+    // Someone else's signature, against their public key in any of its encodings
+    let pk = from_hex(THIRD_PARTY_PK).unwrap();
+    let third_party_sig = from_hex(THIRD_PARTY_SIG).unwrap();
+    for pk in [&pk[..], &pk[1..], &from_hex(THIRD_PARTY_COMPRESSED_PK).unwrap()[..]] {
+        assert_eq!(ECDSASHA256Signature::verify_with_key(pk, THIRD_PARTY_DATA, &third_party_sig), Ok(true));
+        assert_eq!(ECDSASHA256Signature::verify_with_key(pk, data, &third_party_sig), Ok(false));
+    }
+    assert_eq!(ECDSASHA256Signature::verify_with_key(&signer.1, data, &signature), Ok(true));
+    assert_eq!(ECDSASHA256Signature::verify_with_key(&signer.1, THIRD_PARTY_DATA, &third_party_sig), Ok(false));
+    assert_eq!(ECDSASHA256Signature::verify_with_key(&pk[..64], THIRD_PARTY_DATA, &third_party_sig), Err(CryptoError::InvalidEncoding));
+    assert_eq!(ECDSASHA256Signature::verify_with_key(&[], THIRD_PARTY_DATA, &third_party_sig), Err(CryptoError::InvalidEncoding));
+    assert_eq!(
+        ECDSASHA256Signature::verify_with_key(&pk, THIRD_PARTY_DATA, &third_party_sig[..63]),
+        Err(CryptoError::InvalidLength { expected: 64, got: 63 })
+    );
+
+    // Several signers over one message: the result says exactly which signatures are valid
+    let multisig_message = b"spend output 0 of the shared wallet";
+    let signers: Vec<ECDSASHA256Signature> = (1..=3).map(|seed| ECDSASHA256Signature::generate([seed; 32])).collect();
+    let mut sigs_and_keys: Vec<([u8; 64], [u8; 65])> = signers
+        .iter()
+        .map(|signer| {
+            let mut pubkey = [0x04; 65];
+            pubkey[1..].copy_from_slice(&signer.1);
+            (signer.sign(multisig_message).unwrap(), pubkey)
+        })
+        .collect();
+    assert_eq!(ECDSASHA256Signature::verify_multi(multisig_message, &sigs_and_keys), Ok(vec![true, true, true]));
+    sigs_and_keys[1].0[5] ^= 1;
+    assert_eq!(ECDSASHA256Signature::verify_multi(multisig_message, &sigs_and_keys), Ok(vec![true, false, true]));
+    // ... each entry agrees with `verify_with_key`
+    for (sig, pubkey) in &sigs_and_keys {
+        let expected = ECDSASHA256Signature::verify_with_key(pubkey, multisig_message, sig).unwrap();
+        assert_eq!(ECDSASHA256Signature::verify_multi(multisig_message, &[(*sig, *pubkey)]), Ok(vec![expected]));
+    }
+    // ... a signature paired with another signer's key, or one that doesn't parse, is just invalid
+    let swapped = [(sigs_and_keys[0].0, sigs_and_keys[2].1), ([0; 64], sigs_and_keys[0].1), sigs_and_keys[2]];
+    assert_eq!(ECDSASHA256Signature::verify_multi(multisig_message, &swapped), Ok(vec![false, false, true]));
+    assert_eq!(ECDSASHA256Signature::verify_multi(b"another message", &sigs_and_keys), Ok(vec![false, false, false]));
+    assert_eq!(ECDSASHA256Signature::verify_multi(multisig_message, &[]), Ok(vec![]));
+    // ... while a key that isn't a point is an error
+    sigs_and_keys[2].1[64] ^= 1;
+    assert_eq!(ECDSASHA256Signature::verify_multi(multisig_message, &sigs_and_keys), Err(CryptoError::InvalidEncoding));
+
+    // The same keypair reused for ECDH
+    let (ecdh_sk, ecdh_pk) = signer.as_ecdh_keys().unwrap();
+    assert_eq!(ecdh_pk, ECDHNISTK256::generate_public_key(&ecdh_sk));
+    assert_eq!(ecdh_sk.to_bytes()[..], signer.0[..]);
+    assert_eq!(ecdh_pk.to_untagged_bytes(), signer.1);
+    let (peer_sk, peer_pk) = ECDSASHA256Signature::generate([13; 32]).as_ecdh_keys().unwrap();
+    assert_eq!(
+        ECDHNISTK256::generate_shared_secret(&ecdh_sk, &peer_pk).unwrap(),
+        ECDHNISTK256::generate_shared_secret(&peer_sk, &ecdh_pk).unwrap()
+    );
+    assert!(ECDSASHA256Signature([0; 32], signer.1).as_ecdh_keys().is_err());
+    assert!(ECDSASHA256Signature(signer.0, [1; 64]).as_ecdh_keys().is_err());
+
+    // DER encoding matches k256's and also works into a fixed-size (no-alloc) buffer
+    let der = ECDSASHA256Signature::to_der(&signature);
+    let k256_signature = k256::ecdsa::Signature::try_from(signature.as_ref()).unwrap();
+    assert_eq!(der.as_slice(), k256_signature.to_asn1().as_bytes());
+    let mut der_buf = [0u8; 72];
+    let der_len = ECDSASHA256Signature::to_der_into(&signature, &mut der_buf).unwrap();
+    assert_eq!(&der_buf[..der_len], der.as_slice());
+    assert!(ECDSASHA256Signature::to_der_into(&signature, &mut [0u8; 8]).is_err());
+
+    // signing a manually computed digest must give the exact same (deterministic) signature
+    let digest = SHA256Digest.digest(data);
+    let prehashed_signature = signer.sign_prehashed(&digest).unwrap();
+    assert_eq!(signature, prehashed_signature);
+    assert!(signer.verify_prehashed(&digest, prehashed_signature.as_ref()).unwrap());
+
+    // ecrecover - exactly one of the recovery-ids 0 and 1 gives back the signer's `VerifyingKey`
+    let signer_vk = k256::ecdsa::VerifyingKey::from(&k256::ecdsa::SigningKey::from_bytes(&signer.0).unwrap());
+    let signer_vk = signer_vk.to_encoded_point(false);
+    let recovered: Vec<bool> = (0..2)
+        .map(|id| match ECDSASHA256Signature::recover(data, &signature, id) {
+            Ok(pk) => pk[..] == *signer_vk.as_bytes(),
+            Err(_) => false,
+        })
+        .collect();
+    assert_eq!(recovered.iter().filter(|ok| **ok).count(), 1);
+    assert_eq!(ECDSASHA256Signature::recover(data, &signature, 4), Err(CryptoError::SignatureError));
+    // ids 2 and 3 need `r + n < p`, which this signature's `r` doesn't satisfy
+    assert_eq!(ECDSASHA256Signature::recover(data, &signature, 2), Err(CryptoError::SignatureError));
+    assert_eq!(ECDSASHA256Signature::recover(data, &[0; 64], 0), Err(CryptoError::SignatureError));
+    assert_eq!(ECDSASHA256Signature::recover(data, &signature[..63], 0), Err(CryptoError::SignatureError));
+
+    // Bitcoin `signmessage` with the private key `1` - whose compressed P2PKH address is well known
+    let mut sk = [0; 32];
+    sk[31] = 1;
+    let vk = k256::EncodedPoint::encode(k256::SecretKey::from_bytes(sk).unwrap().public_key(), false);
+    let btc_signer = ECDSASHA256Signature(sk, vk.as_bytes()[1..].try_into().unwrap());
+    let address = btc_signer.bitcoin_address();
+    assert_eq!(address, "1BgGZ9tcN4rm9KBzDn7KprQz87SZ26SAMH");
+
+    let message = "Hello, Bitcoin!";
+    let btc_signature = btc_signer.sign_bitcoin_message(message).unwrap();
+    let expected: [u8; 65] = from_hex(
+        "208fc1998e9830c655641c4e55de201a60f6de5a31658df8973694ea8dfad690\
+         327927347d20b480a099c1ac191b87e742dc2afe9744f0bcb8f0eca5e8157a4ff2",
+    )
+    .unwrap()
+    .as_slice()
+    .try_into()
+    .unwrap();
+    assert_eq!(btc_signature, expected);
+    assert!(ECDSASHA256Signature::verify_bitcoin_message(message, &btc_signature, &address).unwrap());
+    assert!(!ECDSASHA256Signature::verify_bitcoin_message("Hello, Bitcoin?", &btc_signature, &address).unwrap());
+    // the same signature flagged as `uncompressed` recovers a key with a different address
+    let mut uncompressed = btc_signature;
+    uncompressed[0] -= 4;
+    assert!(!ECDSASHA256Signature::verify_bitcoin_message(message, &uncompressed, &address).unwrap());
+    assert!(ECDSASHA256Signature::verify_bitcoin_message(
+        message,
+        &uncompressed,
+        "1EHNa6Q4Jz2uvNExL497mE43ikXhwF6kZm"
+    )
+    .unwrap());
+
     let data = b"ECDSA proves knowledge of a secret number in the context of a single message";
-    let mut signer = ECDSASHA384Signature([0; 48], EncodedPoint::identity());
-    let _keys = signer.generate_keypair([12; 32]); // test seed value
+    let signer = ECDSASHA384Signature::generate([12; 32]); // test seed value
+    // `generate` builds the same keypair as the `generate_keypair` method
+    let mut legacy = ECDSASHA384Signature([0; 48], EncodedPoint::identity());
+    legacy.generate_keypair([12; 32]);
+    assert_eq!((legacy.0, legacy.1), (signer.0, signer.1));
+    // the verifying key can be recomputed from the stored signing key alone
+    assert_eq!(ECDSASHA384Signature::verifying_key_from_signing(&legacy.0).unwrap(), legacy.1);
+    assert_eq!(ECDSASHA384Signature::verifying_key_from_signing(&[0; 48]), Err(CryptoError::ScalarOutOfRange));
+    assert_eq!(ECDSASHA384Signature::verifying_key_from_signing(&[0xff; 48]), Err(CryptoError::ScalarOutOfRange));
+    // a fixed signing key rebuilds the same signer, without the RNG
+    let rebuilt = ECDSASHA384Signature::from_signing_key(&signer.0).unwrap();
+    assert_eq!((rebuilt.0, rebuilt.1), (signer.0, signer.1));
+    assert!(ECDSASHA384Signature::from_signing_key(&[0; 48]).is_err());
+    let k256_signer = ECDSASHA256Signature::generate([12; 32]);
+    let rebuilt = ECDSASHA256Signature::from_signing_key(&k256_signer.0).unwrap();
+    assert_eq!((rebuilt.0, rebuilt.1), (k256_signer.0, k256_signer.1));
+    assert!(matches!(ECDSASHA256Signature::from_signing_key(&[0; 32]), Err(CryptoError::ScalarOutOfRange)));
     let signature = signer.sign(data).unwrap();
-    let v = signer.verify(data, &signature.as_ref());
+    let v = signer.verify(data, signature.as_ref());
     
     println!("verified_384: {:?}", v);
     println!("r384: {:?}", ECDSASHA384Signature::r(signature));
     println!("s384: {:?}", ECDSASHA384Signature::s(signature));
 
-}
\ No newline at end of file
+    let typed = signer.sign_typed(data).unwrap();
+    assert_eq!(typed.r().len(), 48);
+    assert!(signer.verify_typed(data, &typed).unwrap());
+
+    // Hedged signing: zero entropy is plain RFC 6979, any other entropy gives a different valid signature
+    let rfc6979_sk: [u8; 48] = from_hex(RFC6979_P384_SK).unwrap().try_into().unwrap();
+    let rfc6979_signer = ECDSASHA384Signature::from_signing_key(&rfc6979_sk).unwrap();
+    let deterministic = rfc6979_signer.sign_hedged(b"sample", &[0; 32]).unwrap();
+    assert_eq!(deterministic.to_vec(), from_hex(RFC6979_P384_SAMPLE_SIG).unwrap());
+    assert_eq!(rfc6979_signer.sign_hedged(b"sample", &[0; 32]).unwrap(), deterministic);
+    let hedged = rfc6979_signer.sign_hedged(b"sample", &[7; 32]).unwrap();
+    assert_eq!(hedged.to_vec(), from_hex(HEDGED_P384_SAMPLE_SIG).unwrap());
+    assert_ne!(rfc6979_signer.sign_hedged(b"sample", &[8; 32]).unwrap(), hedged);
+    for signature in [deterministic, hedged] {
+        assert!(rfc6979_signer.verify(b"sample", &signature).unwrap());
+        assert!(!rfc6979_signer.verify(b"other", &signature).unwrap());
+    }
+    let hedged = signer.sign_hedged(data, &[1; 32]).unwrap();
+    assert!(signer.verify(data, &hedged).unwrap());
+
+    // `k` is random for p384, so signatures differ - check that they verify either way
+    let digest = SHA384Digest.digest(data);
+    let prehashed_signature = signer.sign_prehashed(&digest).unwrap();
+    assert!(signer.verify(data, prehashed_signature.as_ref()).unwrap());
+    assert!(signer.verify_prehashed(&digest, signature.as_ref()).unwrap());
+    // ... for many nonces (each inverted with Fermat's little theorem) and digests
+    for i in 0..32u8 {
+        let digest = SHA384Digest.digest(&[i]);
+        assert!(signer.verify_prehashed(&digest, &signer.sign_prehashed(&digest).unwrap()).unwrap());
+    }
+
+    // Runtime dispatch on a `CurveId`
+    let k256_signer = ECDSASHA256Signature::generate([5; 32]);
+    let mut k256_pk = vec![0x04];
+    k256_pk.extend_from_slice(&k256_signer.1);
+    let k256_sig = sign_with_curve(CurveId::Secp256k1, &k256_signer.0, data).unwrap();
+    assert!(verify_with_curve(CurveId::Secp256k1, &k256_pk, data, &k256_sig).unwrap());
+    assert!(!verify_with_curve(CurveId::Secp256k1, &k256_pk, b"another message", &k256_sig).unwrap());
+
+    let p384_sig = sign_with_curve(CurveId::NistP384, &signer.0, data).unwrap();
+    assert!(verify_with_curve(CurveId::NistP384, signer.1.as_bytes(), data, &p384_sig).unwrap());
+    assert!(!verify_with_curve(CurveId::NistP384, signer.1.as_bytes(), b"another message", &p384_sig).unwrap());
+    // A key that's off the curve is an error, not a failed verification
+    let mut off_curve = signer.1.as_bytes().to_vec();
+    off_curve[96] ^= 1;
+    assert_eq!(verify_with_curve(CurveId::NistP384, &off_curve, data, &p384_sig), Err(CryptoError::PointNotOnCurve));
+
+    // keys of the wrong size and curves without a signature scheme are rejected
+    assert_eq!(sign_with_curve(CurveId::NistP384, &k256_signer.0, data), Err(CryptoError::InvalidLength { expected: 48, got: 32 }));
+    // ... as are P-384 scalars outside `[1, n - 1]`, whichever entry point they're signed with
+    let n = from_hex(P384_N).unwrap();
+    let mut n_plus_one = n.clone();
+    n_plus_one[47] += 1;
+    let digest = SHA384Digest.digest(data);
+    for sk in [vec![0; 48], n.clone(), n_plus_one] {
+        assert_eq!(sign_with_curve(CurveId::NistP384, &sk, data), Err(CryptoError::ScalarOutOfRange));
+        let sk: [u8; 48] = sk.try_into().unwrap();
+        assert_eq!(ECDSASHA384Signature(sk, signer.1).sign(data), Err(CryptoError::ScalarOutOfRange));
+        assert_eq!(ECSignerType::<48>::sign_prehashed(&digest, &sk), Err(CryptoError::ScalarOutOfRange));
+        assert_eq!(ECSignerType::<48>::sign_prehashed_hedged(&digest, &sk, &[7; 32]), Err(CryptoError::ScalarOutOfRange));
+        assert_eq!(
+            ECSignerType::<48>::sign_prehashed_with_nonces(&digest, &sk, || BigUint::from(1u8)),
+            Err(CryptoError::ScalarOutOfRange)
+        );
+    }
+    assert_eq!(sign_with_curve(CurveId::NistP521, &[1; 66], data), Err(CryptoError::UnsupportedCurve));
+    assert_eq!(
+        verify_with_curve(CurveId::NistP521, &k256_pk, data, &k256_sig),
+        Err(CryptoError::UnsupportedCurve)
+    );
+
+    // Low-S checks: of `s` and `n - s` exactly one is low, and nothing gets normalized along the way
+    for (sig, n, is_low_s) in [
+        (k256_sig.clone(), SECP256K1_N, ECDSASHA256Signature::is_low_s as fn(&[u8]) -> bool),
+        (p384_sig.clone(), P384_N, ECDSASHA384Signature::is_low_s),
+    ] {
+        let flipped = with_s(&sig, n, |s, n| n - s);
+        let (low, high) = if is_low_s(&sig) { (sig.clone(), flipped) } else { (flipped, sig.clone()) };
+        assert!(is_low_s(&low));
+        assert!(!is_low_s(&high));
+        assert_eq!(with_s(&high, n, |s, n| n - s), low);
+        // the boundary: `n / 2` is low, `n / 2 + 1` isn't
+        assert!(is_low_s(&with_s(&sig, n, |_, n| n >> 1)));
+        assert!(!is_low_s(&with_s(&sig, n, |_, n| (n >> 1) + 1u8)));
+        assert!(!is_low_s(&with_s(&sig, n, |_, _| BigUint::from(0u8))));
+        assert!(!is_low_s(&low[1..]));
+    }
+    // k256 signatures come out low-S, p384 ones as they fall
+    assert!(ECDSASHA256Signature::is_low_s(&k256_sig));
+    let high_s_p384 = with_s(&p384_sig, P384_N, |s, n| n - s);
+    assert!(ECDSASHA384Signature::is_low_s(&p384_sig) != ECDSASHA384Signature::is_low_s(&high_s_p384));
+    // both forms verify for p384, which is why protocols that want to reject one need `is_low_s`
+    assert!(verify_with_curve(CurveId::NistP384, signer.1.as_bytes(), data, &high_s_p384).unwrap());
+
+    // Explicit normalization: a high `s` becomes `n - s`, anything else is left alone
+    let high_s_k256: [u8; 64] = with_s(&k256_sig, SECP256K1_N, |s, n| n - s).try_into().unwrap();
+    assert!(ECDSASHA256Signature::is_high_s(&high_s_k256));
+    assert!(!ECDSASHA256Signature::is_high_s(k256_sig.as_slice().try_into().unwrap()));
+    let mut normalized = high_s_k256;
+    ECDSASHA256Signature::normalize_s(&mut normalized);
+    assert_eq!(normalized[..], k256_sig[..]);
+    ECDSASHA256Signature::normalize_s(&mut normalized);
+    assert_eq!(normalized[..], k256_sig[..]);
+    let (low_s_p384, high_s_p384): ([u8; 96], [u8; 96]) = if ECDSASHA384Signature::is_low_s(&p384_sig) {
+        (p384_sig.as_slice().try_into().unwrap(), high_s_p384.as_slice().try_into().unwrap())
+    } else {
+        (high_s_p384.as_slice().try_into().unwrap(), p384_sig.as_slice().try_into().unwrap())
+    };
+    assert!(ECDSASHA384Signature::is_high_s(&high_s_p384));
+    assert!(!ECDSASHA384Signature::is_high_s(&low_s_p384));
+    let mut normalized = high_s_p384;
+    ECDSASHA384Signature::normalize_s(&mut normalized);
+    assert_eq!(normalized, low_s_p384);
+    assert!(ECDSASHA384Signature::is_low_s(&normalized));
+    assert!(verify_with_curve(CurveId::NistP384, signer.1.as_bytes(), data, &normalized).unwrap());
+    ECDSASHA384Signature::normalize_s(&mut normalized);
+    assert_eq!(normalized, low_s_p384);
+    // the boundary matches `is_low_s`: `n / 2` stays, `n / 2 + 1` flips to `n / 2`, and `0` or `n` aren't high
+    for (sig, n) in [(k256_sig.clone(), SECP256K1_N), (p384_sig.clone(), P384_N)] {
+        let half = with_s(&sig, n, |_, n| n >> 1);
+        let above_half = with_s(&sig, n, |_, n| (n >> 1) + 1u8);
+        let zero = with_s(&sig, n, |_, _| BigUint::from(0u8));
+        let order = with_s(&sig, n, |_, n| n);
+        let normalize = |sig: &[u8]| -> (bool, Vec<u8>) {
+            if let Ok(mut sig) = <[u8; 64]>::try_from(sig) {
+                (ECDSASHA256Signature::is_high_s(&sig), { ECDSASHA256Signature::normalize_s(&mut sig); sig.to_vec() })
+            } else {
+                let mut sig: [u8; 96] = sig.try_into().unwrap();
+                (ECDSASHA384Signature::is_high_s(&sig), { ECDSASHA384Signature::normalize_s(&mut sig); sig.to_vec() })
+            }
+        };
+        assert_eq!(normalize(&half), (false, half.clone()));
+        assert_eq!(normalize(&above_half), (true, half));
+        assert_eq!(normalize(&zero), (false, zero));
+        assert_eq!(normalize(&order), (false, order));
+    }
+
+    // `r` and `s` as integers: reassembling them gives back the signature ...
+    let p384_sig: [u8; 96] = p384_sig.as_slice().try_into().unwrap();
+    let (r, s) = ECDSASHA384Signature::r_s_bigint(&p384_sig);
+    let mut reassembled = [0u8; 96];
+    for (half, value) in reassembled.chunks_mut(48).zip([&r, &s]) {
+        let be = value.to_bytes_be();
+        half[48 - be.len()..].copy_from_slice(&be);
+    }
+    assert_eq!(reassembled, p384_sig);
+    // ... and they satisfy the verification equation `x(z/s * G + r/s * Q) = r (mod n)`, recomputed by hand
+    let n = BigUint::from_bytes_be(&from_hex(P384_N).unwrap());
+    let s_inverse = s.modpow(&(&n - 2u8), &n);
+    let z = BigUint::from_bytes_be(&SHA384Digest.digest(data));
+    let q = MyAffinePoint::<P384Params>::from_encoded_point(&signer.1).unwrap();
+    let point = MyAffinePoint::<P384Params>::mul_generator(&z * &s_inverse % &n)
+        .do_the_math(MyAffinePoint::double_and_add(q, &r * &s_inverse % &n));
+    assert_eq!(point.x.to_biguint().unwrap() % &n, r);
+    // out of range components come back reduced mod n
+    let out_of_range: [u8; 96] = with_s(&p384_sig, P384_N, |_, n| n + 5u8).try_into().unwrap();
+    assert_eq!(ECDSASHA384Signature::r_s_bigint(&out_of_range), (r, BigUint::from(5u8)));
+
+    // `x(R)` is reduced mod n before it is compared with `r`
+    // The identity isn't a public key: `(0, 0)` (SEC1 can't encode the identity any other way uncompressed) must not
+    // let a forged signature through, whichever way it's passed in
+    let identity_pk = [&[0x04][..], &[0; 96][..]].concat();
+    let forged = from_hex(IDENTITY_FORGERY_SIG).unwrap();
+    let identity_verifier = ECDSASHA384Signature([0; 48], EncodedPoint::from_bytes(&identity_pk).unwrap());
+    assert_eq!(identity_verifier.verify(IDENTITY_FORGERY_DATA, &forged), Err(CryptoError::PointAtInfinity));
+    assert_eq!(
+        identity_verifier.verify_prehashed(&SHA384Digest.digest(IDENTITY_FORGERY_DATA), &forged),
+        Err(CryptoError::PointAtInfinity)
+    );
+    assert_eq!(
+        verify_with_curve(CurveId::NistP384, &identity_pk, IDENTITY_FORGERY_DATA, &forged),
+        Err(CryptoError::PointAtInfinity)
+    );
+
+    let verifier = ECDSASHA384Signature([0; 48], EncodedPoint::from_bytes(from_hex(X_GE_N_PK).unwrap()).unwrap());
+    let digest: [u8; 48] = from_hex(X_GE_N_DIGEST).unwrap().try_into().unwrap();
+    let sig = from_hex(X_GE_N_SIG).unwrap();
+    assert_eq!(verifier.verify_prehashed(&digest, &sig), Ok(true));
+    // ... and the unreduced `x(R) = n + 2` isn't a valid `r`
+    let unreduced = with_s(&sig, P384_N, |_, n| n + 2u8);
+    let unreduced = [&unreduced[48..], &sig[48..]].concat();
+    assert_eq!(verifier.verify_prehashed(&digest, &unreduced), Err(CryptoError::InvalidEncoding));
+    // ... nor is any other small `r`, the comparison is on the whole (fixed-width) value
+    let mut off_by_one = sig.clone();
+    off_by_one[47] = 3;
+    assert_eq!(verifier.verify_prehashed(&digest, &off_by_one), Ok(false));
+
+    // The nonce (and any `hedged-nonces` entropy or `blinding` factors) can come from any `CryptoRng` - the same rng
+    // state gives the same signature
+    let from_rng = signer.sign_with_rng(data, &mut ChaCha20Rng::from_seed([4; 32])).unwrap();
+    assert_eq!(signer.verify(data, &from_rng), Ok(true));
+    assert_eq!(signer.sign_with_rng(data, &mut ChaCha20Rng::from_seed([4; 32])).unwrap(), from_rng);
+    assert_ne!(signer.sign_with_rng(data, &mut ChaCha20Rng::from_seed([5; 32])).unwrap(), from_rng);
+    let mut rng = ChaCha20Rng::from_seed([4; 32]);
+    let (r, s) = ECSignerType::<48>::sign_prehashed_with_rng(&SHA384Digest.digest(data), &signer.0, &mut rng).unwrap();
+    assert_eq!((r.to_biguint().unwrap(), s.to_biguint().unwrap()), ECDSASHA384Signature::r_s_bigint(&from_rng));
+    // Only P-384 (`N = 48`) is implemented, other sizes are an error rather than a panic
+    let mut rng = ChaCha20Rng::from_seed([4; 32]);
+    assert_eq!(ECSignerType::<32>::sign_prehashed_with_rng(&[1; 32], &[1; 32], &mut rng), Err(CryptoError::UnsupportedCurve));
+    assert_eq!(
+        ECSignerType::<66>::sign_prehashed_with_nonces(&[1; 66], &[1; 66], || BigUint::from(1u8)),
+        Err(CryptoError::UnsupportedCurve)
+    );
+    assert_eq!(ECSignerType::<32>::sign_prehashed_hedged(&[1; 32], &[1; 32], &[7; 32]), Err(CryptoError::UnsupportedCurve));
+
+    // A well-formed signature that doesn't verify is `Ok(false)`, a malformed one an Error (and never a panic)
+    let p384_sig = signer.sign(data).unwrap();
+    assert_eq!(signer.verify(b"another message", &p384_sig), Ok(false));
+    let mut flipped = p384_sig;
+    flipped[95] ^= 1;
+    assert_eq!(signer.verify(data, &flipped), Ok(false));
+    let n = from_hex(P384_N).unwrap();
+    let mut n_minus_one = n.clone();
+    n_minus_one[47] -= 1;
+    for (r, s) in [
+        (&[0; 48][..], &p384_sig[48..]),
+        (&p384_sig[..48], &[0; 48][..]),
+        (&n[..], &p384_sig[48..]),
+        (&p384_sig[..48], &n[..]),
+        (&[0xff; 48][..], &p384_sig[48..]),
+        (&p384_sig[..48], &[0xff; 48][..]),
+    ] {
+        let malformed = [r, s].concat();
+        assert_eq!(signer.verify(data, &malformed), Err(CryptoError::InvalidEncoding));
+        assert_eq!(signer.verify_prehashed(&digest, &malformed), Err(CryptoError::InvalidEncoding));
+        // ... and the runtime-dispatched API passes that on rather than calling it a failed verification
+        assert_eq!(
+            verify_with_curve(CurveId::NistP384, signer.1.as_bytes(), data, &malformed),
+            Err(CryptoError::InvalidEncoding)
+        );
+    }
+    for (r, s) in [(&n_minus_one[..], &p384_sig[48..]), (&p384_sig[..48], &n_minus_one[..])] {
+        assert_eq!(signer.verify(data, &[r, s].concat()), Ok(false));
+    }
+    for len in [0, 1, 48, 95, 97, 192] {
+        assert_eq!(signer.verify(data, &vec![1; len]), Err(CryptoError::InvalidLength { expected: 96, got: len }));
+    }
+
+    // Streaming: the chunks hash to the same digest as the whole message
+    let large = vec![0x5au8; 1 << 20];
+    let k256_signer = ECDSASHA256Signature::generate([5; 32]);
+    let mut stream = k256_signer.sign_stream();
+    for chunk in large.chunks(4096 + 7) {
+        stream.update(chunk);
+    }
+    assert_eq!(stream.finalize().unwrap(), k256_signer.sign(&large).unwrap());
+    let mut stream = signer.sign_stream();
+    let (head, tail) = large.split_at(1000);
+    stream.update(head);
+    stream.update(&[]);
+    stream.update(tail);
+    let streamed = stream.finalize().unwrap();
+    assert_eq!(signer.verify(&large, &streamed), Ok(true));
+    assert_eq!(signer.verify(&large[1..], &streamed), Ok(false));
+    assert_eq!(signer.sign_stream().finalize().map(|sig| signer.verify(b"", &sig)), Ok(Ok(true)));
+    // ... and streamed verification agrees with `verify`, for good and bad signatures alike
+    let k256_sig = k256_signer.sign(&large).unwrap();
+    let mut k256_bad = k256_sig;
+    k256_bad[10] ^= 1;
+    for sig in [&k256_sig[..], &k256_bad[..], &k256_sig[..63]] {
+        let mut stream = k256_signer.verify_stream(sig);
+        for chunk in large.chunks(4096 + 7) {
+            stream.update(chunk);
+        }
+        assert_eq!(stream.finalize(), k256_signer.verify(&large, sig));
+    }
+    let mut p384_bad = streamed;
+    p384_bad[60] ^= 1;
+    for (message, sig) in [(&large[..], &streamed[..]), (&large[1..], &streamed[..]), (&large[..], &p384_bad[..])] {
+        let mut stream = signer.verify_stream(sig);
+        for chunk in message.chunks(1 << 16) {
+            stream.update(chunk);
+        }
+        assert_eq!(stream.finalize(), signer.verify(message, sig));
+    }
+    assert_eq!(signer.verify_stream(&streamed).finalize(), Ok(false));
+    assert_eq!(signer.verify_stream(&streamed[1..]).finalize(), Err(CryptoError::InvalidLength { expected: 96, got: 95 }));
+
+    // Other message hashes: SHA-256 is left-padded and SHA-512 truncated to its leftmost 384 bits
+    let verifier = ECDSASHA384Signature([0; 48], EncodedPoint::from_bytes(from_hex(OTHER_HASH_PK).unwrap()).unwrap());
+    let sha256_sig = from_hex(OTHER_HASH_SHA256_SIG).unwrap();
+    let sha512_sig = from_hex(OTHER_HASH_SHA512_SIG).unwrap();
+    assert_eq!(verifier.verify_with_digest::<Sha256>(OTHER_HASH_DATA, &sha256_sig), Ok(true));
+    assert_eq!(verifier.verify_with_digest::<Sha512>(OTHER_HASH_DATA, &sha512_sig), Ok(true));
+    assert_eq!(verifier.verify_with_digest::<Sha512>(OTHER_HASH_DATA, &sha256_sig), Ok(false));
+    assert_eq!(verifier.verify(OTHER_HASH_DATA, &sha512_sig), Ok(false));
+    for sig in [
+        signer.sign_with_digest::<Sha256>(data).unwrap(),
+        signer.sign_with_digest::<Sha512>(data).unwrap(),
+    ] {
+        assert!(signer.verify_with_digest::<Sha256>(data, &sig).unwrap() != signer.verify_with_digest::<Sha512>(data, &sig).unwrap());
+    }
+    let sha384_sig = signer.sign_with_digest::<Sha384>(data).unwrap();
+    assert_eq!(signer.verify(data, &sha384_sig), Ok(true));
+    assert_eq!(ECSignerType::<48>::digest_to_field(&[1; 32])[..16], [0; 16]);
+    assert_eq!(ECSignerType::<48>::digest_to_field(&[1; 64]), [1; 48]);
+
+    // Roughly 1 in 128 signatures has an `r` or `s` with a leading zero byte - they must come out left-padded
+    // to 48 bytes (rather than panic) and still verify
+    let short = (0..2048)
+        .map(|_| signer.sign_prehashed(&digest).unwrap())
+        .find(|sig| sig[0] == 0 || sig[48] == 0)
+        .expect("no short `r` or `s` in 2048 signatures");
+    assert_eq!(short.len(), 96);
+    assert!(signer.verify_prehashed(&digest, &short).unwrap());
+
+    // Nonces outside `[1, n - 1]` are skipped rather than used (or panicked on)
+    let sk = from_hex(OTHER_HASH_SK).unwrap();
+    let fixed_digest = SHA384Digest.digest(b"nonce retries");
+    let k = BigUint::from_bytes_be(&[0x0a, 0xbc].repeat(24));
+    let n = BigUint::from_bytes_be(&from_hex(P384_N).unwrap());
+    let mut candidates = vec![BigUint::from(0u8), n.clone(), n + 1u8, k].into_iter();
+    let (r, s) = ECSignerType::<48>::sign_prehashed_with_nonces(&fixed_digest, &sk, || candidates.next().unwrap()).unwrap();
+    assert_eq!(candidates.next(), None);
+    let expected = from_hex(FIXED_K_SIG).unwrap();
+    assert_eq!(r, BigInt::from_bytes_be(Sign::Plus, &expected[..48]));
+    assert_eq!(s, BigInt::from_bytes_be(Sign::Plus, &expected[48..]));
+    assert_eq!(verifier.verify_prehashed(&fixed_digest, &expected), Ok(true));
+    // Signing the same digest twice uses fresh nonces, with or without `hedged-nonces`
+    assert_ne!(signer.sign_prehashed(&digest).unwrap(), signer.sign_prehashed(&digest).unwrap());
+}
@@ -0,0 +1,69 @@
+
+// #![allow(warnings)]
+use static_dh_ecdh::ecdh::ecdh::{
+    FromBytes, SkP384, SkP521, Skk256, ToBytes, ECDHNISTK256, ECDHNISTP384, ECDHNISTP521,
+};
+use static_dh_ecdh::encoding::{from_hex, parse_spki_ec_public_key};
+use static_dh_ecdh::{CryptoError, CurveId};
+
+// Peer keys exported with Python's `cryptography` (`PublicFormat.SubjectPublicKeyInfo`), for private scalars of
+// `0x2222...` (P-521: `0x01 || 0x2222...`). The shared secrets were computed there too, against our `0x1111...` keys.
+const K256_SPKI: &str = "3056301006072a8648ce3d020106052b8104000a03420004466d7fcae563e5cb09a0d1870bb580344804617879a14949cf22285f1bae3f276728176c3c6431f8eeda4538dc37c865e2784f3a9e77d044f33e407797e1278a";
+const K256_SS: &str = "77e0510d5042e2f5e9e59c977b81eeed590cf7d20c1c51da451a8eaa9fdc45ff";
+const P384_SPKI: &str = "3076301006072a8648ce3d020106052b81040022036200044f2bda7fd2105f8467e21f45223ad58863ffa4c084832d9f6c64ffc47fdd519727ab53cb71f9c40de24b64acde61f02fc7dce130b612fa5dbcac94573a2354fd005d8e9caefdc5fde48304474708bbd82f77e1fd2c630bea236f6f8dccc1678e";
+const P384_SS: &str = "2ac3da23c114b5b1f3aa200cf3c57bebd1b3b880a0e68066ab5d00dda50dcfe6cd03410292346187a84b1f12d53569c0";
+// The same P-384 key, with a compressed point
+const P384_SPKI_COMPRESSED: &str = "3046301006072a8648ce3d020106052b81040022033200024f2bda7fd2105f8467e21f45223ad58863ffa4c084832d9f6c64ffc47fdd519727ab53cb71f9c40de24b64acde61f02f";
+const P521_SPKI: &str = "30819b301006072a8648ce3d020106052b81040023038186000400731edbc438b1e6e4147bef27209a7639f411fcf594a2f07f452964bcf00dd0dda8df2dfa00945f987825012703d6aceb3dc9ae50699ee43abfa3206a96b1467bef00db3038fc22ce4978d1e270eb64963badc896efffe75fdcbfa8acc8414dde2cee6a80937a117a53e1a644c65ebeaf35cfb296eb4916a324c216ed40e91076f4e4ff";
+const P521_SS: &str = "011feb6c7d6b1bbe1d4cb373e65b17993e4f1fe7f8b6ff3ee6f16279efb54de8c8ed08e4832de4c5c6d0f7067729164beb87ed72711d5a7ee08a0da98a3c3206d693";
+
+fn main() {
+    let k256_sk = Skk256::from_bytes(&[0x11; 32]).unwrap();
+    let p384_sk = SkP384::from_bytes(&[0x11; 48]).unwrap();
+    let mut p521_scalar = [0x11; 66];
+    p521_scalar[0] = 0x01;
+    let p521_sk = SkP521::from_bytes(&p521_scalar).unwrap();
+
+    // Valid peer keys
+    let spki = from_hex(K256_SPKI).unwrap();
+    let shared = ECDHNISTK256::agree_with_spki_der(&k256_sk, &spki).unwrap();
+    assert_eq!(shared.to_hex(), K256_SS);
+    let spki = from_hex(P384_SPKI).unwrap();
+    let shared = ECDHNISTP384::<48>::agree_with_spki_der(&p384_sk, &spki).unwrap();
+    assert_eq!(shared.to_hex(), P384_SS);
+    let compressed = from_hex(P384_SPKI_COMPRESSED).unwrap();
+    assert_eq!(ECDHNISTP384::<48>::agree_with_spki_der(&p384_sk, &compressed).unwrap().to_hex(), P384_SS);
+    let spki = from_hex(P521_SPKI).unwrap(); // long-form (`0x81`) DER lengths
+    let shared = ECDHNISTP521::agree_with_spki_der(&p521_sk, &spki).unwrap();
+    assert_eq!(shared.to_hex(), P521_SS);
+
+    let spki = from_hex(P384_SPKI).unwrap();
+    let (curve_id, point) = parse_spki_ec_public_key(&spki).unwrap();
+    assert_eq!((curve_id, point.len()), (CurveId::NistP384, 97));
+
+    // Malformed peer keys
+    let spki = from_hex(P384_SPKI).unwrap();
+    let agree = |der: &[u8]| ECDHNISTP384::<48>::agree_with_spki_der(&p384_sk, der).map(|ss| ss.to_bytes());
+    assert_eq!(agree(&spki[..spki.len() - 1]), Err(CryptoError::InvalidEncoding)); // truncated
+    assert_eq!(agree(&[spki.as_slice(), &[0]].concat()), Err(CryptoError::InvalidEncoding)); // trailing data
+    assert_eq!(agree(&[]), Err(CryptoError::InvalidEncoding));
+    let mut wrong_tag = spki.clone();
+    wrong_tag[0] = 0x31;
+    assert_eq!(agree(&wrong_tag), Err(CryptoError::InvalidEncoding));
+    let mut unused_bits = spki.clone();
+    unused_bits[22] = 0x01; // the BIT STRING's unused-bits byte
+    assert_eq!(agree(&unused_bits), Err(CryptoError::InvalidEncoding));
+    let mut off_curve = spki.clone();
+    *off_curve.last_mut().unwrap() ^= 1;
+    assert_eq!(agree(&off_curve), Err(CryptoError::PointNotOnCurve));
+    let mut unknown_curve = spki.clone();
+    unknown_curve[19] = 0x21; // P-224
+    assert_eq!(agree(&unknown_curve), Err(CryptoError::UnsupportedCurve));
+    // a valid key, but for another curve
+    assert_eq!(agree(&from_hex(K256_SPKI).unwrap()), Err(CryptoError::InvalidEncoding));
+    assert!(matches!(
+        ECDHNISTK256::agree_with_spki_der(&k256_sk, &from_hex(P384_SPKI).unwrap()),
+        Err(CryptoError::InvalidEncoding)
+    ));
+    println!("SPKI peer keys agreed");
+}
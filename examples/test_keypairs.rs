@@ -0,0 +1,56 @@
+
+// #![allow(warnings)]
+use static_dh_ecdh::ecdh::ecdh::{KeyExchange, ToBytes, ECDHNISTK256, ECDHNISTP384, ECDHNISTP521};
+use static_dh_ecdh::testing::{deterministic_keypair, test_keypair, test_keypair_for, test_keypair_p384, test_keypair_p521};
+
+fn main() {
+    let (alice_sk, alice_pk) = test_keypair(0);
+    let (bob_sk, bob_pk) = test_keypair(1);
+
+    // Index 0 and 1 differ ...
+    assert_ne!(alice_pk, bob_pk);
+    assert_ne!(alice_sk.to_bytes(), bob_sk.to_bytes());
+    assert_ne!(test_keypair_p384(0).1, test_keypair_p384(1).1);
+    assert_ne!(test_keypair_p521(0).1, test_keypair_p521(1).1);
+
+    // ... and are stable, from call to call and from release to release
+    assert_eq!(test_keypair(0).1, alice_pk);
+    assert_eq!(test_keypair_for::<ECDHNISTK256>(1).1, bob_pk);
+    assert_eq!(
+        alice_pk.to_hex(),
+        "04c5e2ea3657b05a70ae7832e31a13d54836d5f0e33172503ab084de20b77cefe69f4d3efcf36c9bfda2d1aa0b45dbaa88dc561731ca62efe7227513b43dd547fe"
+    );
+    assert_eq!(
+        bob_pk.to_hex(),
+        "0443b9d13c8c4ae9cb396134011fcb0d144cb0e0e1bf8fa1b34e509d5e925b489e24360bb875f3a17948088e451403bf23623632790d4fbb235f9b871e8a3ac0e7"
+    );
+    assert!(test_keypair_p384(0).1.to_hex().starts_with("048d8b98013768f75f651f15b253141391d11f85dd9ba7db2d"));
+    assert!(test_keypair_p521(0).1.to_hex().starts_with("0400e8b8a6c7dd14a5fd2ecd6e5519ba0f2c1cf5603b5353ce"));
+
+    // The fixtures work like any other keys
+    let alice_ss = ECDHNISTK256::generate_shared_secret(&alice_sk, &bob_pk).unwrap();
+    let bob_ss = ECDHNISTK256::generate_shared_secret(&bob_sk, &alice_pk).unwrap();
+    assert_eq!(alice_ss, bob_ss);
+    let ((alice_sk, alice_pk), (bob_sk, bob_pk)) = (test_keypair_p384(0), test_keypair_p384(1));
+    assert_eq!(
+        ECDHNISTP384::<48>::generate_shared_secret(&alice_sk, &bob_pk),
+        ECDHNISTP384::<48>::generate_shared_secret(&bob_sk, &alice_pk)
+    );
+    let ((alice_sk, alice_pk), (bob_sk, bob_pk)) = (test_keypair_p521(0), test_keypair_p521(1));
+    assert_eq!(
+        ECDHNISTP521::generate_shared_secret(&alice_sk, &bob_pk),
+        ECDHNISTP521::generate_shared_secret(&bob_sk, &alice_pk)
+    );
+
+    // Counter-based keypairs, for any number of keys
+    let (sk, pk) = deterministic_keypair::<ECDHNISTK256>(0);
+    assert_eq!(pk.to_hex(), "04ed7645744b155cf7245617803ca4c98ee0e7023615b4c06eae04b50ec6ed08eef066e493957721071efacb35ca44b9fe9ceda65273eb40d353a56284dd2e591b");
+    assert_eq!(ECDHNISTK256::generate_public_key(&sk), pk);
+    assert_eq!(deterministic_keypair::<ECDHNISTK256>(0).1, pk);
+    assert_ne!(deterministic_keypair::<ECDHNISTK256>(1).1, pk);
+    assert_ne!(deterministic_keypair::<ECDHNISTK256>(1 << 32).1, pk);
+    assert!(deterministic_keypair::<ECDHNISTP384<48>>(0).1.to_hex().starts_with("04e9698c62907e2e29f9df370a85bd341e743876f7fa9ab947b0"));
+    assert!(deterministic_keypair::<ECDHNISTP521>(0).1.to_hex().starts_with("0401892c0151b8ae046956bf5c67023c53f16c6463810264b5b5"));
+
+    println!("test keypairs are distinct and stable");
+}
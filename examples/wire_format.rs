@@ -0,0 +1,67 @@
+
+// #![allow(warnings)]
+use static_dh_ecdh::ecdh::ecdh::{ECDHNISTK256, ECDHNISTP384, KeyExchange, ToBytes};
+use static_dh_ecdh::signatures::ECDSASHA256Signature;
+use static_dh_ecdh::wire::HandshakeMessage;
+use static_dh_ecdh::CryptoError;
+
+/// Signs a fresh ephemeral key for `C` and checks the message round-trips, returning its serialized form.
+fn round_trip<C: KeyExchange>(signer: &ECDSASHA256Signature, seed: [u8; 32]) -> Vec<u8> {
+    let ephemeral_pk = C::generate_public_key(&C::generate_private_key(seed));
+    let message = HandshakeMessage::<C>::sign(ephemeral_pk.clone(), signer).unwrap();
+    let bytes = message.to_bytes();
+    let pk_len = ephemeral_pk.to_bytes().len();
+    assert_eq!(bytes.len(), 2 + pk_len + 2 + 64);
+    assert_eq!(bytes[..2], (pk_len as u16).to_be_bytes());
+    assert_eq!(bytes[2..2 + pk_len], ephemeral_pk.to_bytes()[..]);
+    assert_eq!(bytes[2 + pk_len..4 + pk_len], [0, 64]);
+
+    let parsed = HandshakeMessage::<C>::from_bytes(&bytes).unwrap();
+    assert_eq!(parsed.ephemeral_pk.to_bytes(), ephemeral_pk.to_bytes());
+    assert_eq!(parsed.signature, message.signature);
+    assert_eq!(parsed.verify(&signer.1), Ok(true));
+    bytes
+}
+
+fn main() {
+    let signer = ECDSASHA256Signature::generate([21; 32]);
+    let other_signer = ECDSASHA256Signature::generate([22; 32]);
+
+    let bytes = round_trip::<ECDHNISTK256>(&signer, [1; 32]);
+    round_trip::<ECDHNISTP384<48>>(&signer, [2; 32]);
+
+    // The signature binds the key to the signer
+    let message = HandshakeMessage::<ECDHNISTK256>::from_bytes(&bytes).unwrap();
+    assert_eq!(message.verify(&other_signer.1), Ok(false));
+    let mut swapped = HandshakeMessage::<ECDHNISTK256>::from_bytes(&bytes).unwrap();
+    swapped.ephemeral_pk = ECDHNISTK256::generate_public_key(&ECDHNISTK256::generate_private_key([3; 32]));
+    assert_eq!(swapped.verify(&signer.1), Ok(false));
+
+    // Truncated buffers (at every length) fail cleanly, as do trailing bytes
+    for len in 0..bytes.len() {
+        assert!(matches!(
+            HandshakeMessage::<ECDHNISTK256>::from_bytes(&bytes[..len]),
+            Err(CryptoError::InvalidEncoding)
+        ));
+    }
+    let mut longer = bytes.clone();
+    longer.push(0);
+    assert!(matches!(HandshakeMessage::<ECDHNISTK256>::from_bytes(&longer), Err(CryptoError::InvalidEncoding)));
+
+    // A message for another curve has the wrong key length
+    assert!(matches!(HandshakeMessage::<ECDHNISTP384<48>>::from_bytes(&bytes), Err(CryptoError::InvalidEncoding)));
+
+    // Both components are validated
+    let mut off_curve = bytes.clone();
+    off_curve[2 + 64] ^= 1;
+    assert!(matches!(HandshakeMessage::<ECDHNISTK256>::from_bytes(&off_curve), Err(CryptoError::PointNotOnCurve)));
+    let mut zero_r = bytes.clone();
+    let sig_start = bytes.len() - 64;
+    zero_r[sig_start..sig_start + 32].copy_from_slice(&[0; 32]);
+    assert!(matches!(HandshakeMessage::<ECDHNISTK256>::from_bytes(&zero_r), Err(CryptoError::InvalidEncoding)));
+    let mut bad_sig_len = bytes.clone();
+    bad_sig_len[sig_start - 1] = 63;
+    assert!(matches!(HandshakeMessage::<ECDHNISTK256>::from_bytes(&bad_sig_len), Err(CryptoError::InvalidEncoding)));
+
+    println!("secp256k1 handshake message: {} bytes", bytes.len());
+}
@@ -0,0 +1,304 @@
+
+// #![allow(warnings)]
+//! Runs Wycheproof-format test vectors against `ECDHNISTP384` and `ECDSASHA384Signature`.
+//!
+//! With no arguments the vectors in `testdata/wycheproof` are used. Upstream Wycheproof files
+//! (`ecdh_secp384r1_ecpoint_test.json`, `ecdsa_secp384r1_sha384_p1363_test.json`) can be passed as arguments instead.
+//!
+//! `valid` cases must pass, `invalid` cases must fail (at parse time or during the operation) and `acceptable`
+//! cases may go either way - but if they pass, the result must match.
+use std::{env, fs};
+
+use static_dh_ecdh::ecdh::ecdh::{ECDHNISTP384, FromBytes, KeyExchange, PkP384, SkP384, ToBytes};
+use static_dh_ecdh::encoding::from_hex;
+use static_dh_ecdh::signatures::{ECDSASHA384Signature, ECSignature};
+
+use p384::EncodedPoint;
+
+const DEFAULT_VECTORS: [&str; 2] = [
+    "testdata/wycheproof/ecdh_secp384r1_ecpoint_test.json",
+    "testdata/wycheproof/ecdsa_secp384r1_sha384_p1363_test.json",
+];
+
+/// Just enough JSON to read Wycheproof files - numbers are kept as their source text.
+#[derive(Debug)]
+#[allow(dead_code)] // the `Bool` payload is only ever printed
+enum Json {
+    Null,
+    Bool(bool),
+    Number(String),
+    Str(String),
+    Array(Vec<Json>),
+    Object(Vec<(String, Json)>),
+}
+
+impl Json {
+    fn get(&self, key: &str) -> Option<&Json> {
+        match self {
+            Json::Object(members) => members.iter().find(|(k, _)| k == key).map(|(_, v)| v),
+            _ => None,
+        }
+    }
+
+    fn str(&self, key: &str) -> &str {
+        match self.get(key) {
+            Some(Json::Str(s)) => s,
+            other => panic!("expected a string for {:?}, got {:?}", key, other),
+        }
+    }
+
+    fn array(&self, key: &str) -> &[Json] {
+        match self.get(key) {
+            Some(Json::Array(items)) => items,
+            other => panic!("expected an array for {:?}, got {:?}", key, other),
+        }
+    }
+
+    fn tc_id(&self) -> String {
+        match self.get("tcId") {
+            Some(Json::Number(n)) => n.clone(),
+            other => panic!("expected a tcId, got {:?}", other),
+        }
+    }
+}
+
+struct Parser<'a> {
+    src: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn parse(src: &'a str) -> Json {
+        let mut parser = Parser { src: src.as_bytes(), pos: 0 };
+        let value = parser.value();
+        parser.skip_whitespace();
+        assert_eq!(parser.pos, parser.src.len(), "trailing characters after the JSON value");
+        value
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.pos < self.src.len() && self.src[self.pos].is_ascii_whitespace() {
+            self.pos += 1;
+        }
+    }
+
+    fn expect(&mut self, c: u8) {
+        self.skip_whitespace();
+        assert_eq!(self.src.get(self.pos), Some(&c), "expected {:?} at offset {}", c as char, self.pos);
+        self.pos += 1;
+    }
+
+    fn eat(&mut self, word: &str) -> bool {
+        if self.src[self.pos..].starts_with(word.as_bytes()) {
+            self.pos += word.len();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn value(&mut self) -> Json {
+        self.skip_whitespace();
+        match self.src.get(self.pos) {
+            Some(b'{') => {
+                self.pos += 1;
+                let mut members = Vec::new();
+                self.skip_whitespace();
+                if !self.eat("}") {
+                    loop {
+                        self.skip_whitespace();
+                        let key = self.string();
+                        self.expect(b':');
+                        members.push((key, self.value()));
+                        self.skip_whitespace();
+                        if self.eat("}") {
+                            break;
+                        }
+                        self.expect(b',');
+                    }
+                }
+                Json::Object(members)
+            }
+            Some(b'[') => {
+                self.pos += 1;
+                let mut items = Vec::new();
+                self.skip_whitespace();
+                if !self.eat("]") {
+                    loop {
+                        items.push(self.value());
+                        self.skip_whitespace();
+                        if self.eat("]") {
+                            break;
+                        }
+                        self.expect(b',');
+                    }
+                }
+                Json::Array(items)
+            }
+            Some(b'"') => Json::Str(self.string()),
+            Some(_) if self.eat("true") => Json::Bool(true),
+            Some(_) if self.eat("false") => Json::Bool(false),
+            Some(_) if self.eat("null") => Json::Null,
+            Some(_) => {
+                let start = self.pos;
+                while self.pos < self.src.len() && b"+-.eE0123456789".contains(&self.src[self.pos]) {
+                    self.pos += 1;
+                }
+                assert!(self.pos > start, "unexpected character at offset {}", start);
+                Json::Number(String::from_utf8(self.src[start..self.pos].to_vec()).unwrap())
+            }
+            None => panic!("unexpected end of input"),
+        }
+    }
+
+    fn string(&mut self) -> String {
+        self.expect(b'"');
+        let mut out = String::new();
+        loop {
+            let c = self.src[self.pos];
+            self.pos += 1;
+            match c {
+                b'"' => return out,
+                b'\\' => {
+                    let escaped = self.src[self.pos];
+                    self.pos += 1;
+                    match escaped {
+                        b'n' => out.push('\n'),
+                        b't' => out.push('\t'),
+                        b'r' => out.push('\r'),
+                        b'b' => out.push('\u{8}'),
+                        b'f' => out.push('\u{c}'),
+                        b'u' => {
+                            let hex = std::str::from_utf8(&self.src[self.pos..self.pos + 4]).unwrap();
+                            let code = u32::from_str_radix(hex, 16).unwrap();
+                            out.push(std::char::from_u32(code).unwrap_or('\u{fffd}'));
+                            self.pos += 4;
+                        }
+                        other => out.push(other as char),
+                    }
+                }
+                _ => {
+                    // Copy a whole (possibly multi-byte) UTF-8 sequence
+                    let start = self.pos - 1;
+                    while self.pos < self.src.len() && self.src[self.pos] & 0xc0 == 0x80 {
+                        self.pos += 1;
+                    }
+                    out.push_str(std::str::from_utf8(&self.src[start..self.pos]).unwrap());
+                }
+            }
+        }
+    }
+}
+
+/// Wycheproof private keys are ASN.1 style integers i.e. they may carry a leading `00` (or be shorter than 48 bytes).
+fn private_key_bytes(hex: &str) -> Option<[u8; 48]> {
+    let bytes = from_hex(hex).ok()?;
+    let leading_zeros = bytes.iter().take_while(|b| **b == 0).count();
+    let bytes = &bytes[leading_zeros..];
+    if bytes.len() > 48 {
+        return None;
+    }
+    let mut sk = [0; 48];
+    sk[48 - bytes.len()..].copy_from_slice(bytes);
+    Some(sk)
+}
+
+/// Returns the computed shared secret, or `None` if any step rejected the inputs.
+fn ecdh_case(test: &Json) -> Option<Vec<u8>> {
+    let sk = SkP384::from_bytes(&private_key_bytes(test.str("private"))?).ok()?;
+    let pk = PkP384::from_bytes(&from_hex(test.str("public")).ok()?).ok()?;
+    let shared = ECDHNISTP384::<48>::generate_shared_secret(&sk, &pk).ok()?;
+    Some(shared.to_bytes().to_vec())
+}
+
+/// Returns `true` if the signature verified.
+fn ecdsa_case(verifier: &Option<ECDSASHA384Signature>, test: &Json) -> bool {
+    let verifier = match verifier {
+        Some(verifier) => verifier,
+        None => return false,
+    };
+    let (msg, sig) = match (from_hex(test.str("msg")), from_hex(test.str("sig"))) {
+        (Ok(msg), Ok(sig)) => (msg, sig),
+        _ => return false,
+    };
+    matches!(verifier.verify(&msg, &sig), Ok(true))
+}
+
+/// Checks an outcome against the expected `result` and returns a description of any mismatch.
+fn check(test: &Json, passed: bool, matches_expected: bool) -> Option<String> {
+    let ok = match test.str("result") {
+        "valid" => passed && matches_expected,
+        "invalid" => !passed,
+        "acceptable" => !passed || matches_expected,
+        other => panic!("unknown result {:?}", other),
+    };
+    if ok {
+        None
+    } else {
+        Some(format!(
+            "tcId {} ({}): expected {}, got {}",
+            test.tc_id(),
+            test.str("comment"),
+            test.str("result"),
+            if passed { "accepted" } else { "rejected" }
+        ))
+    }
+}
+
+fn run(path: &str) -> (usize, Vec<String>) {
+    let src = fs::read_to_string(path).unwrap_or_else(|e| panic!("failed to read {}: {}", path, e));
+    let vectors = Parser::parse(&src);
+    let mut count = 0;
+    let mut failures = Vec::new();
+
+    for group in vectors.array("testGroups") {
+        match group.str("type") {
+            "EcdhEcpointTest" => {
+                assert_eq!(group.str("curve"), "secp384r1");
+                for test in group.array("tests") {
+                    let shared = ecdh_case(test);
+                    let expected = from_hex(test.str("shared")).unwrap();
+                    failures.extend(check(test, shared.is_some(), shared.as_ref() == Some(&expected)));
+                    count += 1;
+                }
+            }
+            "EcdsaP1363Verify" => {
+                // Older files name the key `key`, newer ones `publicKey`
+                let key = group.get("publicKey").or_else(|| group.get("key")).expect("missing public key");
+                assert_eq!(key.str("curve"), "secp384r1");
+                assert_eq!(group.str("sha"), "SHA-384");
+                // Not through `PkP384::from_bytes`, so that `verify` has to reject invalid keys by itself
+                let verifier = from_hex(key.str("uncompressed"))
+                    .ok()
+                    .and_then(|pk| EncodedPoint::from_bytes(&pk).ok())
+                    .map(|pk| ECDSASHA384Signature([0; 48], pk));
+                for test in group.array("tests") {
+                    failures.extend(check(test, ecdsa_case(&verifier, test), true));
+                    count += 1;
+                }
+            }
+            other => panic!("unsupported test group type {:?}", other),
+        }
+    }
+    (count, failures)
+}
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let paths: Vec<&str> = if args.is_empty() {
+        DEFAULT_VECTORS.to_vec()
+    } else {
+        args.iter().map(|s| s.as_str()).collect()
+    };
+
+    let mut all_failures = Vec::new();
+    for path in paths {
+        let (count, failures) = run(path);
+        println!("{}: {} cases, {} failed", path, count, failures.len());
+        all_failures.extend(failures);
+    }
+    for failure in &all_failures {
+        println!("  {}", failure);
+    }
+    assert!(all_failures.is_empty(), "{} test vectors failed", all_failures.len());
+}
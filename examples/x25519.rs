@@ -0,0 +1,82 @@
+
+// #![allow(warnings)]
+use static_dh_ecdh::ecdh::ecdh::{FromBytes, KeyExchange, ToBytes};
+use static_dh_ecdh::ecdh::x25519::{clamp, PkX25519, SkX25519, ECDHX25519};
+use static_dh_ecdh::encoding::from_hex;
+use static_dh_ecdh::CryptoError;
+
+// RFC 7748 Section 5.2 - `(scalar, u, X25519(scalar, u))`
+const RFC7748_VECTORS: [(&str, &str, &str); 2] = [
+    (
+        "a546e36bf0527c9d3b16154b82465edd62144c0ac1fc5a18506a2244ba449ac4",
+        "e6db6867583030db3594c1a424b15f7c726624ec26b3353b10a903a6d0ab1c4c",
+        "c3da55379de9c6908e94ea4df28d084f32eccf03491c71f754b4075577a28552",
+    ),
+    (
+        "4b66e9d4d1b4673c5ad22691957d6af5c11b6421e0ea01d42ca4169e7918ba0d",
+        "e5210f12786811d3f4b7959d0538ae2c31dbe7106fc03c3efc4cd549c715a493",
+        "95cbde9476e8907d7aade45cb4b873f88b595a68799fa152e6f8f7647aac7957",
+    ),
+];
+// RFC 7748 Section 6.1
+const ALICE_SK: &str = "77076d0a7318a57d3c16c17251b26645df4c2f87ebc0992ab177fba51db92c2a";
+const ALICE_PK: &str = "8520f0098930a754748b7ddcb43ef75a0dbf3a0d26381af4eba4a98eaa9b4e6a";
+const BOB_SK: &str = "5dab087e624a8a4b79e17f8b83800ee66f3bb1292618b6fd1c2f8b27ff88e0eb";
+const BOB_PK: &str = "de9edb7d7b7dc1b4d35b61c2ece435373f8343c85b78674dadfc7e146f882b4f";
+const SHARED_SECRET: &str = "4a5d9d5ba4ce2de1728e3bf480350f25e07e21c947d19e3376f09b3c1e161742";
+// Low-order points (and non-canonical encodings of them): 0, 1, the two points of order 8, p - 1, p and p + 1
+const LOW_ORDER_POINTS: [&str; 7] = [
+    "0000000000000000000000000000000000000000000000000000000000000000",
+    "0100000000000000000000000000000000000000000000000000000000000000",
+    "e0eb7a7c3b41b8ae1656e3faf19fc46ada098deb9c32b1fd866205165f49b800",
+    "5f9c95bca3508c24b1d0b1559c83ef5b04445cc4581c8e86d8224eddd09f1157",
+    "ecffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff7f",
+    "edffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff7f",
+    "eeffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff7f",
+];
+
+fn main() {
+    for (scalar, u, expected) in RFC7748_VECTORS {
+        let sk = SkX25519::from_hex(scalar).unwrap();
+        let shared = ECDHX25519::generate_shared_secret(&sk, &PkX25519::from_hex(u).unwrap()).unwrap();
+        assert_eq!(shared.to_hex(), expected);
+    }
+
+    let alice_sk = SkX25519::from_hex(ALICE_SK).unwrap();
+    let bob_sk = SkX25519::from_hex(BOB_SK).unwrap();
+    assert_eq!(ECDHX25519::generate_public_key(&alice_sk).to_hex(), ALICE_PK);
+    assert_eq!(ECDHX25519::generate_public_key(&bob_sk).to_hex(), BOB_PK);
+    let alice_ss = ECDHX25519::shared_secret_from_bytes(&alice_sk, &from_hex(BOB_PK).unwrap()).unwrap();
+    let bob_ss = ECDHX25519::shared_secret_from_bytes(&bob_sk, &from_hex(ALICE_PK).unwrap()).unwrap();
+    assert_eq!(alice_ss.to_hex(), SHARED_SECRET);
+    assert_eq!(alice_ss, bob_ss);
+    // the private key is stored as given and only clamped when used
+    assert_eq!(alice_sk.to_hex(), ALICE_SK);
+    let clamped = SkX25519::from_bytes(&clamp(alice_sk.to_bytes().into())).unwrap();
+    assert_eq!(ECDHX25519::generate_public_key(&clamped).to_hex(), ALICE_PK);
+
+    // Low-order public keys give the all-zero output, which is rejected
+    for point in LOW_ORDER_POINTS {
+        let pk = PkX25519::from_hex(point).unwrap();
+        assert_eq!(ECDHX25519::generate_shared_secret(&alice_sk, &pk), Err(CryptoError::PointAtInfinity));
+    }
+    // ... whatever the (clamped) scalar - it's a multiple of the cofactor 8, which kills the small-order part
+    let low_order = PkX25519::from_hex(LOW_ORDER_POINTS[2]).unwrap();
+    for seed in 0..4 {
+        let sk = ECDHX25519::generate_private_key([seed; 32]);
+        assert_eq!(ECDHX25519::generate_shared_secret(&sk, &low_order), Err(CryptoError::PointAtInfinity));
+    }
+    assert_eq!(clamp([0xff; 32])[0] & 7, 0);
+
+    // Generated keys agree, and the top bit of a u co-ordinate is ignored
+    let (sk_1, pk_1) = ECDHX25519::generate_keypair([1; 32]);
+    let (sk_2, pk_2) = ECDHX25519::generate_keypair([2; 32]);
+    let shared = ECDHX25519::generate_shared_secret(&sk_1, &pk_2).unwrap();
+    assert_eq!(shared, ECDHX25519::generate_shared_secret(&sk_2, &pk_1).unwrap());
+    let mut high_bit = pk_2.clone();
+    high_bit.0[31] |= 0x80;
+    assert_eq!(ECDHX25519::generate_shared_secret(&sk_1, &high_bit).unwrap(), shared);
+    assert_eq!(PkX25519::from_bytes(&[9; 31]), Err(CryptoError::InvalidLength { expected: 32, got: 31 }));
+
+    println!("X25519 vectors passed");
+}
@@ -0,0 +1,229 @@
+//! AES-256 (FIPS 197) and AES-256-GCM (NIST SP 800-38D), for `pkcs8` and `ecies`.
+//!
+//! Note: this is a table-based textbook implementation and isn't constant time. GHASH is branch-free.
+
+use alloc::vec::Vec;
+
+use elliptic_curve::subtle::ConstantTimeEq;
+use lazy_static::lazy_static;
+use zeroize::{Zeroize, Zeroizing};
+
+use crate::{CryptoError, Result};
+
+pub(crate) const BLOCK_LEN: usize = 16;
+pub(crate) const KEY_LEN: usize = 32;
+/// GCM nonces are 96 bits, so that the initial counter block is `nonce || 1`.
+pub(crate) const GCM_NONCE_LEN: usize = 12;
+pub(crate) const GCM_TAG_LEN: usize = 16;
+
+/// AES-256-GCM.
+///
+/// - Returns `ciphertext || tag`
+pub(crate) fn gcm_seal(key: &[u8; KEY_LEN], nonce: &[u8; GCM_NONCE_LEN], aad: &[u8], plaintext: &[u8]) -> Vec<u8> {
+    let round_keys = Zeroizing::new(expand_key(key));
+    let mut out = Vec::with_capacity(plaintext.len() + GCM_TAG_LEN);
+    out.extend_from_slice(plaintext);
+    gcm_ctr(&round_keys, nonce, &mut out);
+    let tag = gcm_tag(&round_keys, nonce, aad, &out);
+    out.extend_from_slice(&tag);
+    out
+}
+
+/// The inverse of `gcm_seal`.
+///
+/// - Returns `CryptoError::DecryptionError` if `sealed` is shorter than a tag or the tag doesn't match.
+pub(crate) fn gcm_open(key: &[u8; KEY_LEN], nonce: &[u8; GCM_NONCE_LEN], aad: &[u8], sealed: &[u8]) -> Result<Vec<u8>> {
+    if sealed.len() < GCM_TAG_LEN {
+        return Err(CryptoError::DecryptionError);
+    }
+    let (ciphertext, tag) = sealed.split_at(sealed.len() - GCM_TAG_LEN);
+    let round_keys = Zeroizing::new(expand_key(key));
+    if !bool::from(gcm_tag(&round_keys, nonce, aad, ciphertext).as_slice().ct_eq(tag)) {
+        return Err(CryptoError::DecryptionError);
+    }
+    let mut plaintext = ciphertext.to_vec();
+    gcm_ctr(&round_keys, nonce, &mut plaintext);
+    Ok(plaintext)
+}
+
+/// XORs `buf` with the keystream `E(nonce || 2) || E(nonce || 3) || ...`.
+fn gcm_ctr(round_keys: &RoundKeys, nonce: &[u8; GCM_NONCE_LEN], buf: &mut [u8]) {
+    // The 32-bit counter mustn't wrap around to `nonce || 1`
+    assert!(buf.len() as u64 <= ((1 << 32) - 2) * BLOCK_LEN as u64, "GCM messages are limited to 64 GiB");
+    let mut counter = [0; BLOCK_LEN];
+    counter[..GCM_NONCE_LEN].copy_from_slice(nonce);
+    let mut keystream = Zeroizing::new([0; BLOCK_LEN]);
+    for (i, chunk) in buf.chunks_mut(BLOCK_LEN).enumerate() {
+        counter[GCM_NONCE_LEN..].copy_from_slice(&(i as u32 + 2).to_be_bytes());
+        keystream.copy_from_slice(&counter);
+        encrypt_block(round_keys, &mut *keystream);
+        chunk.iter_mut().zip(keystream.iter()).for_each(|(b, k)| *b ^= k);
+    }
+}
+
+/// `E(nonce || 1) ^ GHASH(H, aad, ciphertext)` with `H = E(0)`.
+fn gcm_tag(round_keys: &RoundKeys, nonce: &[u8; GCM_NONCE_LEN], aad: &[u8], ciphertext: &[u8]) -> [u8; GCM_TAG_LEN] {
+    let mut h = [0; BLOCK_LEN];
+    encrypt_block(round_keys, &mut h);
+    let h = u128::from_be_bytes(h);
+
+    // Both inputs are zero-padded to whole blocks and followed by their lengths in bits
+    let mut y = 0;
+    for data in [aad, ciphertext].iter() {
+        for chunk in data.chunks(BLOCK_LEN) {
+            let mut block = [0; BLOCK_LEN];
+            block[..chunk.len()].copy_from_slice(chunk);
+            y = gf_mul(y ^ u128::from_be_bytes(block), h);
+        }
+    }
+    let lengths = ((aad.len() as u128 * 8) << 64) | (ciphertext.len() as u128 * 8);
+    y = gf_mul(y ^ lengths, h);
+
+    let mut tag = [0; GCM_TAG_LEN];
+    tag[..GCM_NONCE_LEN].copy_from_slice(nonce);
+    tag[GCM_TAG_LEN - 1] = 1;
+    encrypt_block(round_keys, &mut tag);
+    tag.iter_mut().zip(y.to_be_bytes().iter()).for_each(|(t, y)| *t ^= y);
+    tag
+}
+
+/// Multiplication in GCM's `GF(2^128)` (SP 800-38D Algorithm 1), where the first bit of a block is the `x^0`
+/// coefficient - i.e. the most significant bit of the big-endian `u128`.
+fn gf_mul(x: u128, y: u128) -> u128 {
+    let mut z = 0;
+    let mut v = y;
+    for i in (0..128).rev() {
+        z ^= v & 0u128.wrapping_sub((x >> i) & 1);
+        v = (v >> 1) ^ ((0xe1 << 120) & 0u128.wrapping_sub(v & 1));
+    }
+    z
+}
+
+/// The 15 AES-256 round keys (FIPS 197 Section 5.2, `Nk = 8`, `Nr = 14`).
+pub(crate) type RoundKeys = [[u8; BLOCK_LEN]; 15];
+
+lazy_static! {
+    /// The AES S-box and its inverse: `S(x) = A * x^-1 + 0x63` in `GF(2^8)` (FIPS 197 Section 5.1.1).
+    static ref SBOXES: ([u8; 256], [u8; 256]) = {
+        let mut sbox = [0; 256];
+        let mut inv_sbox = [0; 256];
+        for x in 0..=255u8 {
+            // x^254 = x^-1 (and 0 for 0)
+            let mut inverse = 1;
+            for _ in 0..254 {
+                inverse = gmul(inverse, x);
+            }
+            let s = inverse
+                ^ inverse.rotate_left(1)
+                ^ inverse.rotate_left(2)
+                ^ inverse.rotate_left(3)
+                ^ inverse.rotate_left(4)
+                ^ 0x63;
+            sbox[x as usize] = s;
+            inv_sbox[s as usize] = x;
+        }
+        (sbox, inv_sbox)
+    };
+}
+
+/// Multiplication in `GF(2^8)` modulo `x^8 + x^4 + x^3 + x + 1`.
+fn gmul(mut a: u8, mut b: u8) -> u8 {
+    let mut product = 0;
+    while b != 0 {
+        if b & 1 == 1 {
+            product ^= a;
+        }
+        a = (a << 1) ^ if a & 0x80 != 0 { 0x1b } else { 0 };
+        b >>= 1;
+    }
+    product
+}
+
+pub(crate) fn expand_key(key: &[u8; KEY_LEN]) -> RoundKeys {
+    let sbox = &SBOXES.0;
+    let mut words = [[0u8; 4]; 60];
+    for (i, word) in key.chunks(4).enumerate() {
+        words[i].copy_from_slice(word);
+    }
+    let mut rcon = 1;
+    for i in 8..60 {
+        let mut temp = words[i - 1];
+        if i % 8 == 0 {
+            temp.rotate_left(1);
+            temp.iter_mut().for_each(|b| *b = sbox[*b as usize]);
+            temp[0] ^= rcon;
+            rcon = gmul(rcon, 2);
+        } else if i % 8 == 4 {
+            temp.iter_mut().for_each(|b| *b = sbox[*b as usize]);
+        }
+        for j in 0..4 {
+            words[i][j] = words[i - 8][j] ^ temp[j];
+        }
+    }
+    let mut round_keys = [[0; BLOCK_LEN]; 15];
+    for (round_key, quad) in round_keys.iter_mut().zip(words.chunks(4)) {
+        for (c, word) in quad.iter().enumerate() {
+            round_key[4 * c..4 * c + 4].copy_from_slice(word);
+        }
+    }
+    words.iter_mut().for_each(|word| word.zeroize());
+    round_keys
+}
+
+/// Encrypts one block in place. The state is column-major i.e. byte `r + 4c` is row `r` of column `c`.
+pub(crate) fn encrypt_block(round_keys: &RoundKeys, block: &mut [u8]) {
+    let sbox = &SBOXES.0;
+    add_round_key(block, &round_keys[0]);
+    for (round, round_key) in round_keys.iter().enumerate().skip(1) {
+        block.iter_mut().for_each(|b| *b = sbox[*b as usize]);
+        shift_rows(block, false);
+        if round != 14 {
+            for column in block.chunks_mut(4) {
+                let a = [column[0], column[1], column[2], column[3]];
+                for (r, b) in column.iter_mut().enumerate() {
+                    *b = gmul(a[r], 2) ^ gmul(a[(r + 1) % 4], 3) ^ a[(r + 2) % 4] ^ a[(r + 3) % 4];
+                }
+            }
+        }
+        add_round_key(block, round_key);
+    }
+}
+
+/// The inverse of `encrypt_block` (FIPS 197 Section 5.3).
+pub(crate) fn decrypt_block(round_keys: &RoundKeys, block: &mut [u8]) {
+    let inv_sbox = &SBOXES.1;
+    add_round_key(block, &round_keys[14]);
+    for round in (0..14).rev() {
+        shift_rows(block, true);
+        block.iter_mut().for_each(|b| *b = inv_sbox[*b as usize]);
+        add_round_key(block, &round_keys[round]);
+        if round != 0 {
+            for column in block.chunks_mut(4) {
+                let a = [column[0], column[1], column[2], column[3]];
+                for (r, b) in column.iter_mut().enumerate() {
+                    *b = gmul(a[r], 14) ^ gmul(a[(r + 1) % 4], 11) ^ gmul(a[(r + 2) % 4], 13) ^ gmul(a[(r + 3) % 4], 9);
+                }
+            }
+        }
+    }
+}
+
+/// Row `r` is rotated left by `r` (or right, for the inverse).
+fn shift_rows(block: &mut [u8], inverse: bool) {
+    let mut state = [0; BLOCK_LEN];
+    state.copy_from_slice(block);
+    for r in 1..4 {
+        for c in 0..4 {
+            let shifted = r + 4 * ((c + r) % 4);
+            if inverse {
+                block[shifted] = state[r + 4 * c];
+            } else {
+                block[r + 4 * c] = state[shifted];
+            }
+        }
+    }
+}
+
+fn add_round_key(block: &mut [u8], round_key: &[u8; BLOCK_LEN]) {
+    block.iter_mut().zip(round_key.iter()).for_each(|(b, k)| *b ^= k);
+}
@@ -1,5 +1,12 @@
 #![allow(missing_docs)]
 
+use num_bigint_dig::BigInt;
+
+use crate::ecdh::affine_math::{
+    p256_constants, p384_constants, p521_constants, CurveParams, MyAffinePoint, P256Params, P384Params, P521Params,
+};
+use crate::{CryptoError, Result};
+
 // Constants are self explanatory
 
 pub const DH_GROUP_5_PRIME: &str = "
@@ -166,8 +173,82 @@ pub const SUPPORTED_DH_GROUPS: [u8; 2] = [0x3, 0x4];
 
 // Elliptic curve constants
 
+// NIST P256 (secp256r1) constants - used by `ECDSAP256SHA256Signature`
+pub const ECDH_NIST_256_MODP: &str = "0xffffffff00000001000000000000000000000000ffffffffffffffffffffffff";
+pub const ECDH_NIST_256_GROUP_ORDER: &str = "0xffffffff00000000ffffffffffffffffbce6faada7179e84f3b9cac2fc632551";
+pub const ECDH_NIST_256_B_VAL: &str = "0x5ac635d8aa3a93e7b3ebbd55769886bc651d06b0cc53b0f63bce3c3e27d2604b";
+pub const ECDH_NIST_256_GX: &str = "0x6b17d1f2e12c4247f8bce6e563a440f277037d812deb33a0f4a13945d898c296";
+pub const ECDH_NIST_256_GY: &str = "0x4fe342e2fe1a7f9b8ee7eb4a7c0f9e162bce33576b315ececbb6406837bf51f5";
+
 // ECDH NIST P384 constants
 pub const ECDH_NIST_384_PVT_KEY_SIZE: usize = 384/8;
 pub const ECDH_NIST_384_MODP: &str = "0xfffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffeffffffff0000000000000000ffffffff";
 pub const ECDH_NIST_384_GROUP_ORDER: &str = "0xffffffffffffffffffffffffffffffffffffffffffffffffc7634d81f4372ddf581a0db248b0a77aecec196accc52973";
 pub const ECDH_NIST_384_B_VAL:  &str = "0xb3312fa7e23ee7e4988e056be3f82d19181d9c6efe8141120314088f5013875ac656398d8a2ed19d2a85c8edd3ec2aef";
+
+// ECDH NIST P521 constants (66 bytes - only the low bit of the first byte is ever used)
+pub const ECDH_NIST_521_PVT_KEY_SIZE: usize = 66;
+pub const ECDH_NIST_521_MODP: &str = "0x01ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff";
+pub const ECDH_NIST_521_GROUP_ORDER: &str = "0x01fffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffa51868783bf2f966b7fcc0148f709a5d03bb5c9b8899c47aebb6fb71e91386409";
+pub const ECDH_NIST_521_B_VAL: &str = "0x0051953eb9618e1c9a1f929a21a0b68540eea2da725b99b315f3b8b489918ef109e156193951ec7e937b1652c0bd3bb1bf073573df883d2c34f1ef451fd46b503f00";
+pub const ECDH_NIST_521_GX: &str = "0x00c6858e06b70404e9cd9e3ecb662395b4429c648139053fb521f828af606b4d3dbaa14b5e77efe75928fe1dc127a2ffa8de3348b3c1856a429bf97e7e31c2e5bd66";
+pub const ECDH_NIST_521_GY: &str = "0x011839296a789a3bc0045c8a5fb42c7d1bd998f54449579b446817afbd17273e662c97ee72995ef42640c550b9013fad0761353c7086a272c24088be94769fd16650";
+
+// brainpoolP384r1 constants (RFC 5639 Section 3.6) - unlike the NIST curves, `a` isn't `-3`
+pub const ECDH_BRAINPOOL_P384R1_MODP: &str = "0x8cb91e82a3386d280f5d6f7e50e641df152f7109ed5456b412b1da197fb71123acd3a729901d1a71874700133107ec53";
+pub const ECDH_BRAINPOOL_P384R1_GROUP_ORDER: &str = "0x8cb91e82a3386d280f5d6f7e50e641df152f7109ed5456b31f166e6cac0425a7cf3ab6af6b7fc3103b883202e9046565";
+pub const ECDH_BRAINPOOL_P384R1_A_VAL: &str = "0x7bc382c63d8c150c3c72080ace05afa0c2bea28e4fb22787139165efba91f90f8aa5814a503ad4eb04a8c7dd22ce2826";
+pub const ECDH_BRAINPOOL_P384R1_B_VAL: &str = "0x04a8c7dd22ce28268b39b55416f0447c2fb77de107dcd2a62e880ea53eeb62d57cb4390295dbc9943ab78696fa504c11";
+pub const ECDH_BRAINPOOL_P384R1_GX: &str = "0x1d1c64f068cf45ffa2a63a81b7c13f6b8847a3e77ef14fe3db7fcafe0cbd10e8e826e03436d646aaef87b2e247d4af1e";
+pub const ECDH_BRAINPOOL_P384R1_GY: &str = "0x8abe1d7520f9c2a45cb1eb8e95cfd55262b70b29feec5864e19c054ff99129280e4646217791811142820341263c5315";
+
+/// Checks the hardcoded P-256, P-384 and P-521 constants against properties that a typo would break, before trusting
+/// the hand-rolled affine math with them:
+///
+/// - each prime `p` matches its (independently written) form i.e. `2^256 - 2^224 + 2^192 + 2^96 - 1`,
+///   `2^384 - 2^128 - 2^96 + 2^32 - 1` and `2^521 - 1`
+/// - `p` and the group order `n` pass a base-2 Fermat test and `n` is within the Hasse bound `|p + 1 - n| <= 2 sqrt(p)`
+/// - the generator is on the curve (which also pins down `b`), `n * G` is the identity and `(n - 1) * G = -G`
+///
+/// - Returns `CryptoError::ECCError` if any check fails.
+///
+/// Note: this does a few full-size scalar multiplications, so call it once (e.g. at startup) rather than per operation.
+pub fn self_test() -> Result<()> {
+    let one = BigInt::from(1);
+    let p256 = (&one << 256usize) - (&one << 224usize) + (&one << 192usize) + (&one << 96usize) - 1;
+    let p384 = (&one << 384usize) - (&one << 128usize) - (&one << 96usize) + (&one << 32usize) - 1;
+    let p521 = (&one << 521usize) - 1;
+    let (_, _, modp, g_ord) = p256_constants();
+    check_curve::<P256Params>(&p256, modp, g_ord)?;
+    let (_, _, modp, g_ord) = p384_constants();
+    check_curve::<P384Params>(&p384, modp, g_ord)?;
+    let (_, _, modp, g_ord) = p521_constants();
+    check_curve::<P521Params>(&p521, modp, g_ord)
+}
+
+fn check_curve<C: CurveParams>(expected_p: &BigInt, p: &BigInt, n: &BigInt) -> Result<()> {
+    let one = BigInt::from(1);
+    let is_probable_prime = |x: &BigInt| BigInt::from(2).modpow(&(x - &one), x) == one;
+    // `(p + 1 - n)^2 <= 4p`
+    let trace = p + &one - n;
+    if p != expected_p
+        || C::modp() != p
+        || !is_probable_prime(p)
+        || !is_probable_prime(n)
+        || &trace * &trace > p * BigInt::from(4)
+    {
+        return Err(CryptoError::ECCError);
+    }
+
+    let g = MyAffinePoint::<C>::generator();
+    let minus_g = MyAffinePoint::<C>::new(g.x.clone(), p - &g.y);
+    let n = n.to_biguint().ok_or(CryptoError::ECCError)?;
+    if !g.is_on_curve()
+        || g.is_identity()
+        || !MyAffinePoint::double_and_add(g.clone(), n.clone()).is_identity()
+        || MyAffinePoint::double_and_add(g, n - 1u8) != minus_g
+    {
+        return Err(CryptoError::ECCError);
+    }
+    Ok(())
+}
@@ -1,37 +1,33 @@
 use num_bigint_dig::{BigUint, RandBigInt};
+use rand_chacha::rand_core::{CryptoRng, RngCore};
 // use rand;
 
 use core::convert::TryInto;
 
 use crate::constants;
+use crate::{encoding, CryptoError, Result};
 
 /// A function to convert (i.e. unhexlify) a hex-string to a byte array. (Didnt want to use a full-blown crate 
 /// for this.)
 ///
 /// Note: this function uses a generic constant `N` via `const-generics`. At the time of this writing,
 /// `c-g` is not yet stable but will be in 2 weeks from now.
-pub fn unhexlify_to_bytearray<const N: usize>(prime: &str) -> [u8; N] {
-    let mut bytearray = [0; N];
-    let hex_string = prime;
-    for i in (0..hex_string.len()).step_by(2) {
-        if i > (2 * N - 2) {
-            break;
-        }
-        let substring = &hex_string[i..i + 2];
-        let z = (u8::from_str_radix(substring, 16)).unwrap();
-        bytearray[(i - (i / 2))] = z;
-    }
-    return bytearray;
+///
+/// - Returns `CryptoError::InvalidEncoding` for an odd-length string or a non-hex character and
+///   `CryptoError::InvalidLength` if the string doesn't hold exactly `N` bytes.
+pub fn unhexlify_to_bytearray<const N: usize>(hex_string: &str) -> Result<[u8; N]> {
+    let bytes = encoding::from_hex(hex_string)?;
+    bytes.as_slice().try_into().map_err(|_| CryptoError::InvalidLength { expected: N, got: bytes.len() })
 }
 
 /// Returns supported DH_GROUPS or an 'Unsupported' error string. 
 pub fn get_dh(group: u8) -> DH {
     if group == constants::SUPPORTED_DH_GROUPS[0] {
-        return DH::Dh5(DH5::new());
+        DH::Dh5(DH5::new())
     } else if group == constants::SUPPORTED_DH_GROUPS[1] {
-        return DH::Dh15(DH15::new());
+        DH::Dh15(DH15::new())
     } else {
-        return DH::UnSupported("UnSupported DH_GROUP");
+        DH::UnSupported("UnSupported DH_GROUP")
     }
 }
 
@@ -54,7 +50,7 @@ pub enum DH {
     UnSupported(&'static str),
 }
 /// A data struct to hold state for DH_GROUP_ID 5 as per RFC - https://tools.ietf.org/html/rfc3526
-#[derive(Debug, PartialEq, Clone)]
+#[derive(Debug, Default, PartialEq, Clone)]
 pub struct DH5 {
     prime_num: BigUint,
     generator: usize,
@@ -82,26 +78,34 @@ impl DH5 {
         let prime_byte_arr = unhexlify_to_bytearray::<192>(
             &constants::DH_GROUP_5_PRIME
                 .replace(" ", "")
-                .replace("\n\t", ""),
-        );
+                .replace("\n", "")
+                .replace("\t", ""),
+        )
+        .expect("`DH_GROUP_5_PRIME` is well-formed hex");
         self.prime_num = BigUint::from_bytes_be(&prime_byte_arr);
         self.generator = constants::DH_GROUP_5_GENERATOR;
         self.exp_size = constants::DH_GROUP_5_EXPONENT_LENGTH;
     }
 
-    /// Generate the private key
+    /// Generate the private key, from `rand::thread_rng()` - so this needs the `std` feature, see
+    /// `generate_private_key_with_rng` otherwise.
+    #[cfg(feature = "std")]
     pub fn generate_private_key(&mut self) -> BigUint {
-        let mut rng = rand::thread_rng();
-        self.private_key = rng.gen_biguint((self.exp_size * 8 as usize) as usize);
+        self.generate_private_key_with_rng(&mut rand::thread_rng())
+    }
+
+    /// Generate the private key, from `rng`
+    pub fn generate_private_key_with_rng<R: RngCore + CryptoRng>(&mut self, rng: &mut R) -> BigUint {
+        self.private_key = rng.gen_biguint(self.exp_size * 8);
         // let bytes = Math::bigint_to_bytes(unsigned);
         // self.private_key = Math::bytes_to_bigint(&bytes)
-        return self.private_key.clone(); // Need to change the return type to () after testing
+        self.private_key.clone() // Need to change the return type to () after testing
     }
 
     /// Generate the public key
     pub fn generate_pubic_key(&mut self) -> BigUint {
         self.public_key = BigUint::from(self.generator).modpow(&self.private_key, &self.prime_num);
-        return self.public_key.clone(); // Need to change the return type to () after testing
+        self.public_key.clone() // Need to change the return type to () after testing
     }
 
     /// Compute the shared secret
@@ -123,7 +127,7 @@ impl DH5 {
 }
 
 /// A data struct to hold state for DH_GROUP_ID 14 as per RFC - https://tools.ietf.org/html/rfc3526
-#[derive(Debug, PartialEq, Clone)]
+#[derive(Debug, Default, PartialEq, Clone)]
 pub struct DH14 {
     prime_num: BigUint,
     generator: usize,
@@ -153,25 +157,32 @@ impl DH14 {
                 .replace(" ", "")
                 .replace("\n", "")
                 .replace("\t", ""),
-        );
+        )
+        .expect("`DH_GROUP_14_PRIME` is well-formed hex");
         self.prime_num = BigUint::from_bytes_le(&prime_byte_arr);
         self.generator = constants::DH_GROUP_14_GENERATOR;
         self.exp_size = constants::DH_GROUP_14_EXPONENT_LENGTH;
     }
 
-    /// Generate the private key
+    /// Generate the private key, from `rand::thread_rng()` - so this needs the `std` feature, see
+    /// `generate_private_key_with_rng` otherwise.
+    #[cfg(feature = "std")]
     pub fn generate_private_key(&mut self) -> BigUint {
-        let mut rng = rand::thread_rng();
-        self.private_key = rng.gen_biguint((self.exp_size * 8 as usize) as usize);
+        self.generate_private_key_with_rng(&mut rand::thread_rng())
+    }
+
+    /// Generate the private key, from `rng`
+    pub fn generate_private_key_with_rng<R: RngCore + CryptoRng>(&mut self, rng: &mut R) -> BigUint {
+        self.private_key = rng.gen_biguint(self.exp_size * 8);
         // let bytes = Math::bigint_to_bytes(unsigned);
         // self.private_key = Math::bytes_to_bigint(&bytes)
-        return self.private_key.clone(); // Need to change the return type to () after testing
+        self.private_key.clone() // Need to change the return type to () after testing
     }
 
     /// Generate the public key
     pub fn generate_pubic_key(&mut self) -> BigUint {
         self.public_key = BigUint::from(self.generator).modpow(&self.private_key, &self.prime_num);
-        return self.public_key.clone(); // Need to change the return type to () after testing
+        self.public_key.clone() // Need to change the return type to () after testing
     }
 
     /// Compute the shared secret
@@ -193,7 +204,7 @@ impl DH14 {
 }
 
 /// A data struct to hold state for DH_GROUP_ID 15 as per RFC - https://tools.ietf.org/html/rfc3526
-#[derive(Debug, PartialEq, Clone)]
+#[derive(Debug, Default, PartialEq, Clone)]
 pub struct DH15 {
     prime_num: BigUint,
     generator: usize,
@@ -223,25 +234,32 @@ impl DH15 {
                 .replace(" ", "")
                 .replace("\n", "")
                 .replace("\t", ""),
-        );
+        )
+        .expect("`DH_GROUP_15_PRIME` is well-formed hex");
         self.prime_num = BigUint::from_bytes_le(&prime_byte_arr);
         self.generator = constants::DH_GROUP_15_GENERATOR;
         self.exp_size = constants::DH_GROUP_15_EXPONENT_LENGTH;
     }
 
-    /// Generate the private key
+    /// Generate the private key, from `rand::thread_rng()` - so this needs the `std` feature, see
+    /// `generate_private_key_with_rng` otherwise.
+    #[cfg(feature = "std")]
     pub fn generate_private_key(&mut self) -> BigUint {
-        let mut rng = rand::thread_rng();
-        self.private_key = rng.gen_biguint((self.exp_size * 8 as usize) as usize);
+        self.generate_private_key_with_rng(&mut rand::thread_rng())
+    }
+
+    /// Generate the private key, from `rng`
+    pub fn generate_private_key_with_rng<R: RngCore + CryptoRng>(&mut self, rng: &mut R) -> BigUint {
+        self.private_key = rng.gen_biguint(self.exp_size * 8);
         // let bytes = Math::bigint_to_bytes(unsigned);
         // self.private_key = Math::bytes_to_bigint(&bytes)
-        return self.private_key.clone(); // Need to change the return type to () after testing
+        self.private_key.clone() // Need to change the return type to () after testing
     }
 
     /// Generate the public key
     pub fn generate_pubic_key(&mut self) -> BigUint {
         self.public_key = BigUint::from(self.generator).modpow(&self.private_key, &self.prime_num);
-        return self.public_key.clone(); // Need to change the return type to () after testing
+        self.public_key.clone() // Need to change the return type to () after testing
     }
 
     /// Compute the shared secret
@@ -263,7 +281,7 @@ impl DH15 {
 }
 
 /// A data struct to hold state for DH_GROUP_ID 16 as per RFC - https://tools.ietf.org/html/rfc3526
-#[derive(Debug, PartialEq, Clone)]
+#[derive(Debug, Default, PartialEq, Clone)]
 pub struct DH16 {
     prime_num: BigUint,
     generator: usize,
@@ -293,25 +311,32 @@ impl DH16 {
                 .replace(" ", "")
                 .replace("\n", "")
                 .replace("\t", ""),
-        );
+        )
+        .expect("`DH_GROUP_16_PRIME` is well-formed hex");
         self.prime_num = BigUint::from_bytes_le(&prime_byte_arr);
         self.generator = constants::DH_GROUP_16_GENERATOR;
         self.exp_size = constants::DH_GROUP_16_EXPONENT_LENGTH;
     }
 
-    /// Generate the private key
+    /// Generate the private key, from `rand::thread_rng()` - so this needs the `std` feature, see
+    /// `generate_private_key_with_rng` otherwise.
+    #[cfg(feature = "std")]
     pub fn generate_private_key(&mut self) -> BigUint {
-        let mut rng = rand::thread_rng();
-        self.private_key = rng.gen_biguint((self.exp_size * 8 as usize) as usize);
+        self.generate_private_key_with_rng(&mut rand::thread_rng())
+    }
+
+    /// Generate the private key, from `rng`
+    pub fn generate_private_key_with_rng<R: RngCore + CryptoRng>(&mut self, rng: &mut R) -> BigUint {
+        self.private_key = rng.gen_biguint(self.exp_size * 8);
         // let bytes = Math::bigint_to_bytes(unsigned);
         // self.private_key = Math::bytes_to_bigint(&bytes)
-        return self.private_key.clone(); // Need to change the return type to () after testing
+        self.private_key.clone() // Need to change the return type to () after testing
     }
 
     /// Generate the public key
     pub fn generate_pubic_key(&mut self) -> BigUint {
         self.public_key = BigUint::from(self.generator).modpow(&self.private_key, &self.prime_num);
-        return self.public_key.clone(); // Need to change the return type to () after testing
+        self.public_key.clone() // Need to change the return type to () after testing
     }
 
     /// Compute the shared secret
@@ -333,7 +358,7 @@ impl DH16 {
 }
 
 /// A data struct to hold state for DH_GROUP_ID 17 as per RFC - https://tools.ietf.org/html/rfc3526
-#[derive(Debug, PartialEq, Clone)]
+#[derive(Debug, Default, PartialEq, Clone)]
 pub struct DH17 {
     prime_num: BigUint,
     generator: usize,
@@ -363,25 +388,32 @@ impl DH17 {
                 .replace(" ", "")
                 .replace("\n", "")
                 .replace("\t", ""),
-        );
+        )
+        .expect("`DH_GROUP_17_PRIME` is well-formed hex");
         self.prime_num = BigUint::from_bytes_le(&prime_byte_arr);
         self.generator = constants::DH_GROUP_17_GENERATOR;
         self.exp_size = constants::DH_GROUP_17_EXPONENT_LENGTH;
     }
 
-    /// Generate the private key
+    /// Generate the private key, from `rand::thread_rng()` - so this needs the `std` feature, see
+    /// `generate_private_key_with_rng` otherwise.
+    #[cfg(feature = "std")]
     pub fn generate_private_key(&mut self) -> BigUint {
-        let mut rng = rand::thread_rng();
-        self.private_key = rng.gen_biguint((self.exp_size * 8 as usize) as usize);
+        self.generate_private_key_with_rng(&mut rand::thread_rng())
+    }
+
+    /// Generate the private key, from `rng`
+    pub fn generate_private_key_with_rng<R: RngCore + CryptoRng>(&mut self, rng: &mut R) -> BigUint {
+        self.private_key = rng.gen_biguint(self.exp_size * 8);
         // let bytes = Math::bigint_to_bytes(unsigned);
         // self.private_key = Math::bytes_to_bigint(&bytes)
-        return self.private_key.clone(); // Need to change the return type to () after testing
+        self.private_key.clone() // Need to change the return type to () after testing
     }
 
     /// Generate the public key
     pub fn generate_pubic_key(&mut self) -> BigUint {
         self.public_key = BigUint::from(self.generator).modpow(&self.private_key, &self.prime_num);
-        return self.public_key.clone(); // Need to change the return type to () after testing
+        self.public_key.clone() // Need to change the return type to () after testing
     }
 
     /// Compute the shared secret
@@ -403,7 +435,7 @@ impl DH17 {
 }
 
 /// A data struct to hold state for DH_GROUP_ID 18 as per RFC - https://tools.ietf.org/html/rfc3526
-#[derive(Debug, PartialEq, Clone)]
+#[derive(Debug, Default, PartialEq, Clone)]
 pub struct DH18 {
     prime_num: BigUint,
     generator: usize,
@@ -433,25 +465,32 @@ impl DH18 {
                 .replace(" ", "")
                 .replace("\n", "")
                 .replace("\t", ""),
-        );
+        )
+        .expect("`DH_GROUP_18_PRIME` is well-formed hex");
         self.prime_num = BigUint::from_bytes_le(&prime_byte_arr);
         self.generator = constants::DH_GROUP_18_GENERATOR;
         self.exp_size = constants::DH_GROUP_18_EXPONENT_LENGTH;
     }
 
-    /// Generate the private key
+    /// Generate the private key, from `rand::thread_rng()` - so this needs the `std` feature, see
+    /// `generate_private_key_with_rng` otherwise.
+    #[cfg(feature = "std")]
     pub fn generate_private_key(&mut self) -> BigUint {
-        let mut rng = rand::thread_rng();
-        self.private_key = rng.gen_biguint((self.exp_size * 8 as usize) as usize);
+        self.generate_private_key_with_rng(&mut rand::thread_rng())
+    }
+
+    /// Generate the private key, from `rng`
+    pub fn generate_private_key_with_rng<R: RngCore + CryptoRng>(&mut self, rng: &mut R) -> BigUint {
+        self.private_key = rng.gen_biguint(self.exp_size * 8);
         // let bytes = Math::bigint_to_bytes(unsigned);
         // self.private_key = Math::bytes_to_bigint(&bytes)
-        return self.private_key.clone(); // Need to change the return type to () after testing
+        self.private_key.clone() // Need to change the return type to () after testing
     }
 
     /// Generate the public key
     pub fn generate_pubic_key(&mut self) -> BigUint {
         self.public_key = BigUint::from(self.generator).modpow(&self.private_key, &self.prime_num);
-        return self.public_key.clone(); // Need to change the return type to () after testing
+        self.public_key.clone() // Need to change the return type to () after testing
     }
 
     /// Compute the shared secret
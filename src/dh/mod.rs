@@ -1,2 +1,3 @@
 /// A module for a static DH implementation 
+#[allow(clippy::module_inception)]
 pub mod dh;
\ No newline at end of file
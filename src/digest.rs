@@ -1,6 +1,14 @@
 // #![allow(warnings)]
 
 use sha2::{Sha256, Sha384, Digest};
+use sha2::digest::{BlockInput, FixedOutput, Reset, Update};
+use hmac::{Hmac, Mac, NewMac};
+use generic_array::typenum::Unsigned;
+use generic_array::GenericArray;
+use zeroize::Zeroize;
+
+use alloc::vec;
+use alloc::vec::Vec;
 
 use core::convert::TryInto;
 
@@ -46,3 +54,350 @@ impl SHA384Digest {
         0x2
     }
 }
+
+/// HKDF-SHA256 (RFC 5869) - derives `okm.len()` bytes of keying material from the input keying material `ikm`.
+///
+/// An empty `salt` is treated as `HashLen` zero bytes. Panics if more than `255 * 32` bytes are requested.
+pub fn hkdf_sha256(salt: &[u8], ikm: &[u8], info: &[u8], okm: &mut [u8]) {
+    assert!(okm.len() <= 255 * 32, "HKDF-SHA256 can output at most 8160 bytes");
+    let prk = hkdf_extract::<Sha256>(salt, ikm);
+    hkdf_expand::<Sha256>(&prk, info, okm);
+}
+
+/// HKDF (RFC 5869) with one Extract and an Expand per entry of `outputs`, each giving a subkey of that many bytes.
+///
+/// Subkey `i` is expanded with `info || i` (`i` as a 4 byte big-endian counter), so the subkeys are independent of
+/// one another - unlike consecutive slices of one long output, changing a length doesn't shift the other keys.
+/// An empty `salt` is treated as `HashLen` zero bytes. Panics if a subkey is longer than `255 * HashLen` bytes.
+pub(crate) fn hkdf_expand_keys<D>(salt: &[u8], ikm: &[u8], info: &[u8], outputs: &[usize]) -> Vec<Vec<u8>>
+where
+    D: BlockInput + FixedOutput + Clone + Default + Reset + Update,
+{
+    let hash_len = D::OutputSize::USIZE;
+    assert!(outputs.iter().all(|&len| len <= 255 * hash_len), "HKDF can output at most 255 * HashLen bytes");
+    let mut prk = hkdf_extract::<D>(salt, ikm);
+    let keys = outputs
+        .iter()
+        .enumerate()
+        .map(|(i, &len)| {
+            let mut labelled_info = info.to_vec();
+            labelled_info.extend_from_slice(&(i as u32).to_be_bytes());
+            let mut okm = vec![0; len];
+            hkdf_expand::<D>(&prk, &labelled_info, &mut okm);
+            okm
+        })
+        .collect();
+    prk.as_mut_slice().zeroize();
+    keys
+}
+
+/// HKDF-Extract: `PRK = HMAC(salt, IKM)`
+fn hkdf_extract<D>(salt: &[u8], ikm: &[u8]) -> GenericArray<u8, D::OutputSize>
+where
+    D: BlockInput + FixedOutput + Clone + Default + Reset + Update,
+{
+    let mut extract = Hmac::<D>::new_varkey(salt).unwrap();
+    extract.update(ikm);
+    extract.finalize().into_bytes()
+}
+
+/// HKDF-Expand: `T(i) = HMAC(PRK, T(i-1) || info || i)`, filling `okm`. The caller checks the length limit.
+fn hkdf_expand<D>(prk: &[u8], info: &[u8], okm: &mut [u8])
+where
+    D: BlockInput + FixedOutput + Clone + Default + Reset + Update,
+{
+    let mut t: GenericArray<u8, D::OutputSize> = Default::default();
+    for (i, chunk) in okm.chunks_mut(D::OutputSize::USIZE).enumerate() {
+        let mut expand = Hmac::<D>::new_varkey(prk).unwrap();
+        if i != 0 {
+            expand.update(&t);
+        }
+        expand.update(info);
+        expand.update(&[i as u8 + 1]);
+        t = expand.finalize().into_bytes();
+        chunk.copy_from_slice(&t[..chunk.len()]);
+    }
+}
+
+/// PBKDF2 with HMAC-SHA256 (RFC 8018 Section 5.2) - stretches a password into `okm.len()` bytes of key, with
+/// `iterations` rounds per 32 byte block.
+pub fn pbkdf2_hmac_sha256(password: &[u8], salt: &[u8], iterations: u32, okm: &mut [u8]) {
+    let prf = Hmac::<Sha256>::new_varkey(password).unwrap();
+    for (i, chunk) in okm.chunks_mut(32).enumerate() {
+        // U_1 = PRF(P, S || INT(i)), U_j = PRF(P, U_{j-1}) and T_i = U_1 ^ ... ^ U_c
+        let mut mac = prf.clone();
+        mac.update(salt);
+        mac.update(&(i as u32 + 1).to_be_bytes());
+        let mut u = mac.finalize().into_bytes();
+        let mut t = u;
+        for _ in 1..iterations {
+            let mut mac = prf.clone();
+            mac.update(&u);
+            u = mac.finalize().into_bytes();
+            t.iter_mut().zip(u.iter()).for_each(|(t, u)| *t ^= u);
+        }
+        chunk.copy_from_slice(&t[..chunk.len()]);
+    }
+}
+
+/// A struct representing a RIPEMD-160 Digest instance
+///
+/// This is only here to derive Bitcoin (P2PKH) addresses i.e. `RIPEMD160(SHA256(pubkey))`.
+pub struct RIPEMD160Digest;
+
+const RIPEMD160_R: [usize; 80] = [
+    0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15,
+    7, 4, 13, 1, 10, 6, 15, 3, 12, 0, 9, 5, 2, 14, 11, 8,
+    3, 10, 14, 4, 9, 15, 8, 1, 2, 7, 0, 6, 13, 11, 5, 12,
+    1, 9, 11, 10, 0, 8, 12, 4, 13, 3, 7, 15, 14, 5, 6, 2,
+    4, 0, 5, 9, 7, 12, 2, 10, 14, 1, 3, 8, 11, 6, 15, 13,
+];
+const RIPEMD160_R_PRIME: [usize; 80] = [
+    5, 14, 7, 0, 9, 2, 11, 4, 13, 6, 15, 8, 1, 10, 3, 12,
+    6, 11, 3, 7, 0, 13, 5, 10, 14, 15, 8, 12, 4, 9, 1, 2,
+    15, 5, 1, 3, 7, 14, 6, 9, 11, 8, 12, 2, 10, 0, 4, 13,
+    8, 6, 4, 1, 3, 11, 15, 0, 5, 12, 2, 13, 9, 7, 10, 14,
+    12, 15, 10, 4, 1, 5, 8, 7, 6, 2, 13, 14, 0, 3, 9, 11,
+];
+const RIPEMD160_S: [u32; 80] = [
+    11, 14, 15, 12, 5, 8, 7, 9, 11, 13, 14, 15, 6, 7, 9, 8,
+    7, 6, 8, 13, 11, 9, 7, 15, 7, 12, 15, 9, 11, 7, 13, 12,
+    11, 13, 6, 7, 14, 9, 13, 15, 14, 8, 13, 6, 5, 12, 7, 5,
+    11, 12, 14, 15, 14, 15, 9, 8, 9, 14, 5, 6, 8, 6, 5, 12,
+    9, 15, 5, 11, 6, 8, 13, 12, 5, 12, 13, 14, 11, 8, 5, 6,
+];
+const RIPEMD160_S_PRIME: [u32; 80] = [
+    8, 9, 9, 11, 13, 15, 15, 5, 7, 7, 8, 11, 14, 14, 12, 6,
+    9, 13, 15, 7, 12, 8, 9, 11, 7, 7, 12, 7, 6, 15, 13, 11,
+    9, 7, 15, 11, 8, 6, 6, 14, 12, 13, 5, 14, 13, 13, 7, 5,
+    15, 5, 8, 11, 14, 14, 6, 14, 6, 9, 12, 9, 12, 5, 15, 8,
+    8, 5, 12, 9, 12, 5, 14, 6, 8, 13, 6, 5, 15, 13, 11, 11,
+];
+const RIPEMD160_K: [u32; 5] = [0x0000_0000, 0x5a82_7999, 0x6ed9_eba1, 0x8f1b_bcdc, 0xa953_fd4e];
+const RIPEMD160_K_PRIME: [u32; 5] = [0x50a2_8be6, 0x5c4d_d124, 0x6d70_3ef3, 0x7a6d_76e9, 0x0000_0000];
+
+impl RIPEMD160Digest {
+    /// Computes the RIPEMD-160 digest of a slice of bytes.
+    pub fn digest(&self, data: &[u8]) -> [u8; 20] {
+        let mut h: [u32; 5] = [0x6745_2301, 0xefcd_ab89, 0x98ba_dcfe, 0x1032_5476, 0xc3d2_e1f0];
+
+        // MD4-style padding: 0x80, zeroes, then the bit-length as a little-endian u64
+        let bit_len = (data.len() as u64).wrapping_mul(8);
+        let mut tail = [0u8; 128];
+        let rem = data.len() % 64;
+        tail[..rem].copy_from_slice(&data[data.len() - rem..]);
+        tail[rem] = 0x80;
+        let tail_len = if rem < 56 { 64 } else { 128 };
+        tail[tail_len - 8..tail_len].copy_from_slice(&bit_len.to_le_bytes());
+
+        for block in data[..data.len() - rem].chunks(64).chain(tail[..tail_len].chunks(64)) {
+            Self::compress(&mut h, block);
+        }
+
+        let mut out = [0u8; 20];
+        for (chunk, word) in out.chunks_mut(4).zip(h.iter()) {
+            chunk.copy_from_slice(&word.to_le_bytes());
+        }
+        out
+    }
+
+    fn f(j: usize, x: u32, y: u32, z: u32) -> u32 {
+        match j / 16 {
+            0 => x ^ y ^ z,
+            1 => (x & y) | (!x & z),
+            2 => (x | !y) ^ z,
+            3 => (x & z) | (y & !z),
+            _ => x ^ (y | !z),
+        }
+    }
+
+    fn compress(h: &mut [u32; 5], block: &[u8]) {
+        let mut x = [0u32; 16];
+        for (word, bytes) in x.iter_mut().zip(block.chunks(4)) {
+            *word = u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+        }
+
+        let (mut al, mut bl, mut cl, mut dl, mut el) = (h[0], h[1], h[2], h[3], h[4]);
+        let (mut ar, mut br, mut cr, mut dr, mut er) = (h[0], h[1], h[2], h[3], h[4]);
+        for j in 0..80 {
+            let t = al
+                .wrapping_add(Self::f(j, bl, cl, dl))
+                .wrapping_add(x[RIPEMD160_R[j]])
+                .wrapping_add(RIPEMD160_K[j / 16])
+                .rotate_left(RIPEMD160_S[j])
+                .wrapping_add(el);
+            al = el;
+            el = dl;
+            dl = cl.rotate_left(10);
+            cl = bl;
+            bl = t;
+
+            let t = ar
+                .wrapping_add(Self::f(79 - j, br, cr, dr))
+                .wrapping_add(x[RIPEMD160_R_PRIME[j]])
+                .wrapping_add(RIPEMD160_K_PRIME[j / 16])
+                .rotate_left(RIPEMD160_S_PRIME[j])
+                .wrapping_add(er);
+            ar = er;
+            er = dr;
+            dr = cr.rotate_left(10);
+            cr = br;
+            br = t;
+        }
+
+        let t = h[1].wrapping_add(cl).wrapping_add(dr);
+        h[1] = h[2].wrapping_add(dl).wrapping_add(er);
+        h[2] = h[3].wrapping_add(el).wrapping_add(ar);
+        h[3] = h[4].wrapping_add(al).wrapping_add(br);
+        h[4] = h[0].wrapping_add(bl).wrapping_add(cr);
+        h[0] = t;
+    }
+}
+
+/// A struct representing a Keccak-256 Digest instance
+///
+/// This is the original Keccak submission (as used by Ethereum) and not NIST's SHA3-256 - the two only
+/// differ in the padding byte.
+#[cfg(feature = "sha3")]
+pub struct Keccak256Digest;
+
+#[cfg(feature = "sha3")]
+const KECCAK_RC: [u64; 24] = [
+    0x0000_0000_0000_0001, 0x0000_0000_0000_8082, 0x8000_0000_0000_808a, 0x8000_0000_8000_8000,
+    0x0000_0000_0000_808b, 0x0000_0000_8000_0001, 0x8000_0000_8000_8081, 0x8000_0000_0000_8009,
+    0x0000_0000_0000_008a, 0x0000_0000_0000_0088, 0x0000_0000_8000_8009, 0x0000_0000_8000_000a,
+    0x0000_0000_8000_808b, 0x8000_0000_0000_008b, 0x8000_0000_0000_8089, 0x8000_0000_0000_8003,
+    0x8000_0000_0000_8002, 0x8000_0000_0000_0080, 0x0000_0000_0000_800a, 0x8000_0000_8000_000a,
+    0x8000_0000_8000_8081, 0x8000_0000_0000_8080, 0x0000_0000_8000_0001, 0x8000_0000_8000_8008,
+];
+#[cfg(feature = "sha3")]
+const KECCAK_RHO: [u32; 24] = [
+    1, 3, 6, 10, 15, 21, 28, 36, 45, 55, 2, 14, 27, 41, 56, 8, 25, 43, 62, 18, 39, 61, 20, 44,
+];
+#[cfg(feature = "sha3")]
+const KECCAK_PI: [usize; 24] = [
+    10, 7, 11, 17, 18, 3, 5, 16, 8, 21, 24, 4, 15, 23, 19, 13, 12, 2, 20, 14, 22, 9, 6, 1,
+];
+
+#[cfg(feature = "sha3")]
+impl Keccak256Digest {
+    /// The sponge's rate in bytes i.e. `(1600 - 2 * 256) / 8`
+    const RATE: usize = 136;
+
+    /// Computes the Keccak-256 digest of a slice of bytes.
+    pub fn digest(&self, data: &[u8]) -> [u8; 32] {
+        Self::sponge(data, 0x01)
+    }
+
+    /// Absorbs `data` into a Keccak-f[1600] sponge with the given domain/padding byte and squeezes 32 bytes.
+    fn sponge(data: &[u8], pad: u8) -> [u8; 32] {
+        let mut state = [0u64; 25];
+        let mut blocks = data.chunks_exact(Self::RATE);
+        for block in &mut blocks {
+            Self::absorb(&mut state, block);
+            Self::keccak_f(&mut state);
+        }
+        let rem = blocks.remainder();
+        let mut last = [0u8; Self::RATE];
+        last[..rem.len()].copy_from_slice(rem);
+        last[rem.len()] ^= pad;
+        last[Self::RATE - 1] ^= 0x80;
+        Self::absorb(&mut state, &last);
+        Self::keccak_f(&mut state);
+
+        let mut out = [0u8; 32];
+        for (chunk, lane) in out.chunks_mut(8).zip(state.iter()) {
+            chunk.copy_from_slice(&lane.to_le_bytes());
+        }
+        out
+    }
+
+    fn absorb(state: &mut [u64; 25], block: &[u8]) {
+        for (lane, bytes) in state.iter_mut().zip(block.chunks(8)) {
+            let mut word = [0u8; 8];
+            word.copy_from_slice(bytes);
+            *lane ^= u64::from_le_bytes(word);
+        }
+    }
+
+    fn keccak_f(a: &mut [u64; 25]) {
+        for rc in KECCAK_RC.iter() {
+            // theta
+            let mut c = [0u64; 5];
+            for x in 0..5 {
+                c[x] = a[x] ^ a[x + 5] ^ a[x + 10] ^ a[x + 15] ^ a[x + 20];
+            }
+            for x in 0..5 {
+                let d = c[(x + 4) % 5] ^ c[(x + 1) % 5].rotate_left(1);
+                for y in 0..5 {
+                    a[5 * y + x] ^= d;
+                }
+            }
+            // rho and pi
+            let mut last = a[1];
+            for (rho, pi) in KECCAK_RHO.iter().zip(KECCAK_PI.iter()) {
+                let tmp = a[*pi];
+                a[*pi] = last.rotate_left(*rho);
+                last = tmp;
+            }
+            // chi
+            for y in 0..5 {
+                let row = [a[5 * y], a[5 * y + 1], a[5 * y + 2], a[5 * y + 3], a[5 * y + 4]];
+                for x in 0..5 {
+                    a[5 * y + x] = row[x] ^ (!row[(x + 1) % 5] & row[(x + 2) % 5]);
+                }
+            }
+            // iota
+            a[0] ^= rc;
+        }
+    }
+}
+
+/// `HMAC_DRBG` as described in RFC 6979 Section 3.2 - yields the candidate values for a
+/// deterministic ECDSA ephemeral scalar `k`.
+///
+/// `entropy_input` is the serialized private key `x` and `nonce` is the reduced message digest `h1`.
+/// Callers keep drawing from `next()` until they get a value in `[1, n-1]`.
+pub(crate) struct HmacDrbg<D>
+where
+    D: BlockInput + FixedOutput + Clone + Default + Reset + Update,
+{
+    k: Hmac<D>,
+    v: GenericArray<u8, D::OutputSize>,
+}
+
+impl<D> HmacDrbg<D>
+where
+    D: BlockInput + FixedOutput + Clone + Default + Reset + Update,
+{
+    /// Initializes the DRBG (RFC 6979 Section 3.2 steps b - g)
+    pub(crate) fn new(entropy_input: &[u8], nonce: &[u8]) -> Self {
+        let mut k = Hmac::<D>::new(&Default::default());
+        let mut v = GenericArray::<u8, D::OutputSize>::default();
+        v.fill(0x01);
+
+        for i in 0..=1 {
+            k.update(&v);
+            k.update(&[i]);
+            k.update(entropy_input);
+            k.update(nonce);
+            k = Hmac::new_varkey(&k.finalize().into_bytes()).unwrap();
+            k.update(&v);
+            v = k.finalize_reset().into_bytes();
+        }
+        Self { k, v }
+    }
+
+    /// Returns the next candidate for `k` (RFC 6979 Section 3.2 step h)
+    pub(crate) fn next(&mut self) -> GenericArray<u8, D::OutputSize> {
+        self.k.update(&self.v);
+        let t = self.k.finalize_reset().into_bytes();
+
+        self.k.update(&t);
+        self.k.update(&[0x00]);
+        self.k = Hmac::new_varkey(&self.k.finalize_reset().into_bytes()).unwrap();
+        self.k.update(&t);
+        self.v = self.k.finalize_reset().into_bytes();
+        t
+    }
+}
@@ -1,25 +1,35 @@
 #![allow(warnings)]
 
+use alloc::vec::Vec;
 use core::convert::TryInto;
+use core::fmt::Debug;
+use core::marker::PhantomData;
 // use libc_print::libc_println;
 use num_bigint_dig::{BigInt, BigUint, RandBigInt, Sign, ModInverse};
 use num_traits::{Zero};
+use lazy_static::lazy_static;
 use generic_array::GenericArray;
+use elliptic_curve::subtle::{Choice, ConditionallySelectable, ConstantTimeEq};
 use p384::EncodedPoint;
 
+use rand_chacha::rand_core::{CryptoRng, RngCore};
+#[cfg(not(feature = "std"))]
+use rand_chacha::rand_core::SeedableRng;
+use sha2::Digest;
+
 use crate::digest::SHA384Digest;
 use crate::{constants, dh};
 use crate::{Result, CryptoError};
 
-use super::ecdh::{PkP384, SharedSecretP384};
+use super::ecdh::{PkP384, PkP521, SharedSecretP384, SharedSecretP521};
 
 /// An enum for the various types of AffinePoint(s)
 #[derive(Debug, Clone, PartialEq)]
 pub enum APTypes {
     /// Affine-Point Type for a point curve NIST-p384
-    P384(MyAffinePoint<48>),
+    P384(MyAffinePoint<P384Params>),
     /// Affine-Point Type for a point curve NIST-p521
-    P521(MyAffinePoint<66>),
+    P521(MyAffinePoint<P521Params>),
     /// Placeholder for more Affine-Point Types
     __Nonexhaustive,
 }
@@ -41,67 +51,229 @@ pub enum BitArrayTypes {
 pub enum EncodedTypes {
     /// An EncodedPoint type for p384
     EncodedTypeP384(PkP384),
-    /// An EncodedPoint type for p521
+    /// An EncodedPoint type for a p384 shared secret
     EncodedTypeP384_SS(SharedSecretP384),
+    /// An EncodedPoint type for p521
+    EncodedTypeP521(PkP521),
+    /// An EncodedPoint type for a p521 shared secret
+    EncodedTypeP521_SS(SharedSecretP521),
     /// Placeholder variant to hold EncodedPoint types
     __Nonexhaustive,
 }
 
+/// The domain parameters of a short-Weierstrass curve `y^2 = x^3 + ax + b (mod p)`. `MyAffinePoint` is generic
+/// over this, so adding a curve only takes a new marker type and an impl.
+pub trait CurveParams: Debug + Clone + PartialEq {
+    /// The prime modulus `p` of the field the co-ordinates live in
+    fn modp() -> &'static BigInt;
+    /// The curve co-efficient `a`
+    fn a() -> &'static BigInt;
+    /// The curve co-efficient `b`
+    fn b() -> &'static BigInt;
+    /// The base point of the curve
+    fn generator() -> MyAffinePoint<Self>;
+    /// The size, in bytes, of a field element (i.e. of each co-ordinate)
+    fn byte_len() -> usize;
+}
+
+/// The NIST P-256 (secp256r1) curve parameters
+#[derive(Debug, Clone, PartialEq)]
+pub struct P256Params;
+
+/// The NIST P-384 curve parameters
+#[derive(Debug, Clone, PartialEq)]
+pub struct P384Params;
+
+/// The NIST P-521 curve parameters
+#[derive(Debug, Clone, PartialEq)]
+pub struct P521Params;
+
+/// The brainpoolP384r1 curve parameters (RFC 5639). Its `a` is a random-looking field element rather than `-3`,
+/// which the formulas here don't assume anyway.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BrainpoolP384r1Params;
+
+lazy_static! {
+    static ref P384_GENERATOR: MyAffinePoint<P384Params> = {
+        // NIST P-384 basepoint in affine coordinates:
+        // x = aa87ca22 be8b0537 8eb1c71ef 320ad74 6e1d3b62 8ba79b98 59f741e0 82542a38 5502f25d bf55296c 3a545e38 72760ab7
+        // y = 3617de4a 96262c6f 5d9e98bf9 292dc29 f8f41dbd 289a147c e9da3113 b5f0b8c0 0a60b1ce 1d7e819d 7a431d7c 90ea0e5f
+        let x: [u8; 48] = [
+            0xaa, 0x87, 0xca, 0x22, 0xbe, 0x8b, 0x05, 0x37, 0x8e, 0xb1, 0xc7, 0x1e, 0xf3,
+            0x20, 0xad, 0x74, 0x6e, 0x1d, 0x3b, 0x62, 0x8b, 0xa7, 0x9b, 0x98, 0x59, 0xf7,
+            0x41, 0xe0, 0x82, 0x54, 0x2a, 0x38, 0x55, 0x02, 0xf2, 0x5d, 0xbf, 0x55, 0x29,
+            0x6c, 0x3a, 0x54, 0x5e, 0x38, 0x72, 0x76, 0x0a, 0xb7,
+        ];
+        let y: [u8; 48] = [
+            0x36, 0x17, 0xde, 0x4a, 0x96, 0x26, 0x2c, 0x6f, 0x5d, 0x9e, 0x98, 0xbf, 0x92,
+            0x92, 0xdc, 0x29, 0xf8, 0xf4, 0x1d, 0xbd, 0x28, 0x9a, 0x14, 0x7c, 0xe9, 0xda,
+            0x31, 0x13, 0xb5, 0xf0, 0xb8, 0xc0, 0x0a, 0x60, 0xb1, 0xce, 0x1d, 0x7e, 0x81,
+            0x9d, 0x7a, 0x43, 0x1d, 0x7c, 0x90, 0xea, 0x0e, 0x5f,
+        ];
+        MyAffinePoint::new(BigInt::from_bytes_be(Sign::Plus, &x), BigInt::from_bytes_be(Sign::Plus, &y))
+    };
+
+    // NIST P-256 basepoint - see `constants::ECDH_NIST_256_GX` and `ECDH_NIST_256_GY`
+    static ref P256_GENERATOR: MyAffinePoint<P256Params> = {
+        let x = dh::dh::unhexlify_to_bytearray::<32>(constants::ECDH_NIST_256_GX)
+            .expect("`ECDH_NIST_256_GX` is well-formed hex");
+        let y = dh::dh::unhexlify_to_bytearray::<32>(constants::ECDH_NIST_256_GY)
+            .expect("`ECDH_NIST_256_GY` is well-formed hex");
+        MyAffinePoint::new(BigInt::from_bytes_be(Sign::Plus, &x), BigInt::from_bytes_be(Sign::Plus, &y))
+    };
+
+    // brainpoolP384r1 basepoint - see `constants::ECDH_BRAINPOOL_P384R1_GX` and `ECDH_BRAINPOOL_P384R1_GY`
+    static ref BRAINPOOL_P384R1_GENERATOR: MyAffinePoint<BrainpoolP384r1Params> = {
+        let x = dh::dh::unhexlify_to_bytearray::<48>(constants::ECDH_BRAINPOOL_P384R1_GX)
+            .expect("`ECDH_BRAINPOOL_P384R1_GX` is well-formed hex");
+        let y = dh::dh::unhexlify_to_bytearray::<48>(constants::ECDH_BRAINPOOL_P384R1_GY)
+            .expect("`ECDH_BRAINPOOL_P384R1_GY` is well-formed hex");
+        MyAffinePoint::new(BigInt::from_bytes_be(Sign::Plus, &x), BigInt::from_bytes_be(Sign::Plus, &y))
+    };
+
+    // NIST P-521 basepoint - see `constants::ECDH_NIST_521_GX` and `ECDH_NIST_521_GY`
+    static ref P521_GENERATOR: MyAffinePoint<P521Params> = {
+        let x = dh::dh::unhexlify_to_bytearray::<66>(constants::ECDH_NIST_521_GX)
+            .expect("`ECDH_NIST_521_GX` is well-formed hex");
+        let y = dh::dh::unhexlify_to_bytearray::<66>(constants::ECDH_NIST_521_GY)
+            .expect("`ECDH_NIST_521_GY` is well-formed hex");
+        MyAffinePoint::new(BigInt::from_bytes_be(Sign::Plus, &x), BigInt::from_bytes_be(Sign::Plus, &y))
+    };
+}
+
+impl CurveParams for P256Params {
+    fn modp() -> &'static BigInt {
+        &p256_constants().2
+    }
+
+    fn a() -> &'static BigInt {
+        &p256_constants().0
+    }
+
+    fn b() -> &'static BigInt {
+        &p256_constants().1
+    }
+
+    fn generator() -> MyAffinePoint<Self> {
+        P256_GENERATOR.clone()
+    }
+
+    fn byte_len() -> usize {
+        32
+    }
+}
+
+impl CurveParams for P384Params {
+    fn modp() -> &'static BigInt {
+        &p384_constants().2
+    }
+
+    fn a() -> &'static BigInt {
+        &p384_constants().0
+    }
+
+    fn b() -> &'static BigInt {
+        &p384_constants().1
+    }
+
+    fn generator() -> MyAffinePoint<Self> {
+        P384_GENERATOR.clone()
+    }
+
+    fn byte_len() -> usize {
+        48
+    }
+}
+
+impl CurveParams for P521Params {
+    fn modp() -> &'static BigInt {
+        &p521_constants().2
+    }
+
+    fn a() -> &'static BigInt {
+        &p521_constants().0
+    }
+
+    fn b() -> &'static BigInt {
+        &p521_constants().1
+    }
+
+    fn generator() -> MyAffinePoint<Self> {
+        P521_GENERATOR.clone()
+    }
+
+    fn byte_len() -> usize {
+        66
+    }
+}
+
+impl CurveParams for BrainpoolP384r1Params {
+    fn modp() -> &'static BigInt {
+        &brainpool_p384r1_constants().2
+    }
+
+    fn a() -> &'static BigInt {
+        &brainpool_p384r1_constants().0
+    }
+
+    fn b() -> &'static BigInt {
+        &brainpool_p384r1_constants().1
+    }
+
+    fn generator() -> MyAffinePoint<Self> {
+        BRAINPOOL_P384R1_GENERATOR.clone()
+    }
+
+    fn byte_len() -> usize {
+        48
+    }
+}
+
 /// Affine coordinates are the conventional way of expressing elliptic curve points in two dimensional space i.e. (x, y)
 /// Typically, `x and y` are 2 very large integers (in the order of say 256 or 384 bits, hence the name). In ECC, points on 
 /// the curve are represented as some integer modulo a prime number. 
 ///
 /// Infinity - is just a special point usually named `O`. Its also referred to as the identity element of a prime field.
 #[derive(Debug, Clone, PartialEq)]
-pub struct MyAffinePoint<const N: usize> {
+pub struct MyAffinePoint<C: CurveParams> {
     /// The x co-ordinate of a elliptic curve point modulo a prime 
     pub x: BigInt,
     /// The y co-ordinate of a elliptic curve point modulo a prime 
     pub y: BigInt,
     /// Is just a special point usually named `O`. In our case that's - (0,0, true) 
     pub infinity: bool,
+    _curve: PhantomData<C>,
 }
 
-impl<const N: usize> MyAffinePoint<N> {
-    /// Returns the base point of a NIST p-cURVE.
-    pub fn generator() -> APTypes {
-        match N {
-            // NIST P-384 basepoint in affine coordinates:
-            // x = aa87ca22 be8b0537 8eb1c71ef 320ad74 6e1d3b62 8ba79b98 59f741e0 82542a38 5502f25d bf55296c 3a545e38 72760ab7
-            // y = 3617de4a 96262c6f 5d9e98bf9 292dc29 f8f41dbd 289a147c e9da3113 b5f0b8c0 0a60b1ce 1d7e819d 7a431d7c 90ea0e5f
-            48 => { // Is this expected? The compiler cant seem to tell that the generic constant `N` equals `48`in a `matched` arm. I'm
-                    // assuming the compiler has access to this information at compile time.  
-                let x: [u8; 48] = [
-                    0xaa, 0x87, 0xca, 0x22, 0xbe, 0x8b, 0x05, 0x37, 0x8e, 0xb1, 0xc7, 0x1e, 0xf3,
-                    0x20, 0xad, 0x74, 0x6e, 0x1d, 0x3b, 0x62, 0x8b, 0xa7, 0x9b, 0x98, 0x59, 0xf7,
-                    0x41, 0xe0, 0x82, 0x54, 0x2a, 0x38, 0x55, 0x02, 0xf2, 0x5d, 0xbf, 0x55, 0x29,
-                    0x6c, 0x3a, 0x54, 0x5e, 0x38, 0x72, 0x76, 0x0a, 0xb7,
-                ];
-                let y: [u8; 48] = [
-                    0x36, 0x17, 0xde, 0x4a, 0x96, 0x26, 0x2c, 0x6f, 0x5d, 0x9e, 0x98, 0xbf, 0x92,
-                    0x92, 0xdc, 0x29, 0xf8, 0xf4, 0x1d, 0xbd, 0x28, 0x9a, 0x14, 0x7c, 0xe9, 0xda,
-                    0x31, 0x13, 0xb5, 0xf0, 0xb8, 0xc0, 0x0a, 0x60, 0xb1, 0xce, 0x1d, 0x7e, 0x81,
-                    0x9d, 0x7a, 0x43, 0x1d, 0x7c, 0x90, 0xea, 0x0e, 0x5f,
-                ];
-
-                APTypes::P384(MyAffinePoint {
-                    x: BigInt::from_bytes_be(Sign::Plus, &x),
-                    y: BigInt::from_bytes_be(Sign::Plus, &y),
-                    infinity: false,
-                })
-            }
-
-            66 => APTypes::__Nonexhaustive,
-            _ => APTypes::__Nonexhaustive,
-        }
+impl<C: CurveParams> MyAffinePoint<C> {
+    /// Returns the base point of the curve `C`.
+    pub fn generator() -> Self {
+        C::generator()
     }
 
     /// Returns the identity of the group: the point at infinity.
-    pub fn identity() -> MyAffinePoint<N> {
+    pub fn identity() -> Self {
         Self {
             x: Zero::zero(),
             y: Zero::zero(),
             infinity: true,
+            _curve: PhantomData,
+        }
+    }
+
+    /// Builds a point from its affine co-ordinates.
+    ///
+    /// `(0, 0)` isn't on any of the supported curves (`b != 0`), but some encodings use it as a sentinel for
+    /// the point at infinity - so it is mapped to the canonical identity rather than treated as an ordinary point.
+    pub fn new(x: BigInt, y: BigInt) -> Self {
+        if x.is_zero() && y.is_zero() {
+            return Self::identity();
+        }
+        Self {
+            x,
+            y,
+            infinity: false,
+            _curve: PhantomData,
         }
     }
 
@@ -110,61 +282,141 @@ impl<const N: usize> MyAffinePoint<N> {
         self.infinity
     }
 
+    /// Reduces the co-ordinates into the canonical range `[0, modp)`, adding `modp` to a negative remainder.
+    ///
+    /// The `Field` formulas only ever produce canonical co-ordinates, but a point built with `new` can hold any
+    /// representative (e.g. `(x, -y)`), which would compare unequal to the same point and encode as the wrong
+    /// bytes (`to_bytes_be` drops the sign). The identity is left as is.
+    pub fn normalize(&mut self, modp: &BigInt) {
+        if self.infinity {
+            return;
+        }
+        let field = Field::new(modp);
+        self.x = field.reduce(&self.x);
+        self.y = field.reduce(&self.y);
+    }
+
+    /// Replaces the point with its negation `(x, p - y)` if `choice` is set, and leaves it as is otherwise - a
+    /// building block for ladders and signed-digit windows, where the choice is derived from the secret scalar.
+    ///
+    /// Both candidates for y are computed and one is picked with a byte-wise `ConditionallySelectable` over their
+    /// fixed-width encodings, so which one is kept doesn't show in the branches taken. The `BigInt` arithmetic
+    /// around it isn't constant time itself (see `invert_mod_prime`). The identity is its own negation.
+    pub fn conditional_negate(&mut self, choice: Choice, modp: &BigInt) {
+        if self.infinity {
+            return;
+        }
+        let field = Field::new(modp);
+        let y = field.reduce(&self.y);
+        let negated = field.sub(&BigInt::zero(), &y);
+
+        let len = ((modp.bits() + 7) / 8) as usize;
+        let (mut y_bytes, mut negated_bytes) = (Vec::with_capacity(len), Vec::with_capacity(len));
+        for (bytes, value) in [(&mut y_bytes, &y), (&mut negated_bytes, &negated)] {
+            let be = value.to_bytes_be().1;
+            bytes.resize(len - be.len(), 0);
+            bytes.extend_from_slice(&be);
+        }
+        let selected: Vec<u8> = y_bytes
+            .iter()
+            .zip(negated_bytes.iter())
+            .map(|(y, negated)| u8::conditional_select(y, negated, choice))
+            .collect();
+        self.y = BigInt::from_bytes_be(Sign::Plus, &selected);
+    }
+
+    /// Returns a square root of `a mod modp` or `None` if `a` is a quadratic non-residue - the primitive behind
+    /// point decompression, see `Field::sqrt`.
+    ///
+    /// Note - this computes `a^((p + 1) / 4)`, so `modp` must be a prime `= 3 mod 4` (as the p384 and p521
+    /// primes are). For other primes it can return `None` for a square, but a returned value always squares to `a`.
+    pub fn mod_sqrt(a: &BigInt, modp: &BigInt) -> Option<BigInt> {
+        Field::new(modp).sqrt(a)
+    }
+
+    /// Returns `true` if the point satisfies the curve equation `y^2 = x^3 + ax + b (mod p)`. The identity
+    /// has no affine co-ordinates and is always on the curve.
+    pub fn is_on_curve(&self) -> bool {
+        self.infinity || satisfies_curve_equation(&self.x, &self.y, C::a(), C::b(), C::modp())
+    }
+
+    /// Checks that the point is usable as a public key on the curve `y^2 = x^3 + ax + b (mod modp)` - the one
+    /// place where that invariant is enforced for the affine-point curves.
+    ///
+    /// - Returns `CryptoError::PointAtInfinity` for the identity, `CryptoError::InvalidEncoding` if a co-ordinate
+    ///   isn't in `[0, modp)` and `CryptoError::PointNotOnCurve` if the curve equation doesn't hold.
+    pub fn validate(&self, a: &BigInt, b: &BigInt, modp: &BigInt) -> Result<()> {
+        if self.infinity {
+            return Err(CryptoError::PointAtInfinity);
+        }
+        // A non-canonical representative (e.g. `x + p`) would satisfy the equation too
+        let in_range = |c: &BigInt| c.sign() != Sign::Minus && c < modp;
+        if !in_range(&self.x) || !in_range(&self.y) {
+            return Err(CryptoError::InvalidEncoding);
+        }
+        if !satisfies_curve_equation(&self.x, &self.y, a, b, modp) {
+            return Err(CryptoError::PointNotOnCurve);
+        }
+        Ok(())
+    }
+
     /// This method performs the actual math i.e. `POINT doubling` and `addition` operations. In very simple terms, 
     /// this method calculates the result of multiplying (which in ECC arithmetic doubling or adding to itself) the generator point
     /// with that of a private scalar value. (PS - if you're wondering, the scalar itself is huge number - 32 bytes for P256 or 48 for P384)
     ///
     /// Note - This is a texbook implementation taken from RFC https://tools.ietf.org/html/rfc6090#section-3
-    pub fn do_the_math(
-        &self,
-        pointP: MyAffinePoint<N>,
-        a: &BigInt,
-        _b: &BigInt,
-        modp: &BigInt,
-    ) -> MyAffinePoint<N> {
+    ///
+    /// Both operands are normalized first, so the comparisons below (and the result) only ever see canonical
+    /// co-ordinates.
+    pub fn do_the_math(&self, pointP: MyAffinePoint<C>) -> MyAffinePoint<C> {
+        let mut lhs = self.clone();
+        lhs.normalize(C::modp());
+        let mut rhs = pointP;
+        rhs.normalize(C::modp());
+        lhs.add_normalized(rhs)
+    }
+
+    /// The group law on normalized points, see `do_the_math`.
+    fn add_normalized(&self, pointP: MyAffinePoint<C>) -> MyAffinePoint<C> {
+        let field = Field::new(C::modp());
+        let a = C::a();
         if bool::from(self.is_identity()) && bool::from(pointP.infinity) {
             Self::identity()
         } else if bool::from(self.is_identity()) {
             pointP
         } else if bool::from(pointP.infinity) {
-            MyAffinePoint {
-                x: self.x.clone(),
-                y: self.y.clone(),
-                infinity: false,
-            }
+            self.clone()
         } else {
             // Point doubling when bitarray[i] == 0
             if pointP.x == self.x && pointP.y == self.y {
-                let y1 = self.y.clone();
-                let x1 = self.x.clone();
-                let t = ((2u8 * &y1) % modp).mod_inverse(modp).unwrap();
-                let slope = (((3u8 * &x1 * &x1) + a) * t) % modp;
-                let x3 = ((&slope * &slope) - (2u8 * &x1)) % modp;
-                let y3 = (&slope * (&x1 - &x3) - &y1) % modp;
-
-                MyAffinePoint {
-                    x: x3,
-                    y: y3,
-                    infinity: false,
+                // A point with `y = 0` is its own negation (i.e. it has order 2), so doubling it
+                // yields the identity. This also keeps us from inverting zero below.
+                if field.reduce(&self.y).is_zero() {
+                    return Self::identity();
                 }
-            } else if (pointP.x == self.x) && pointP.y == -self.y.clone() {
+                let y1 = &self.y;
+                let x1 = &self.x;
+                let t = field.inv(&field.add(y1, y1)).unwrap();
+                let three_x_sqr = field.mul(&BigInt::from(3u8), &field.sqr(x1));
+                let slope = field.mul(&field.add(&three_x_sqr, a), &t);
+                let x3 = field.sub(&field.sqr(&slope), &field.add(x1, x1));
+                let y3 = field.sub(&field.mul(&slope, &field.sub(x1, &x3)), y1);
+
+                MyAffinePoint::new(x3, y3)
+            } else if (pointP.x == self.x) && field.add(&pointP.y, &self.y).is_zero() {
                 Self::identity()
             } else if pointP.x != self.x || pointP.y != self.y {
                 // Point addition when bitarray[i] == 1
-                let y1 = self.y.clone();
-                let x1 = self.x.clone();
-                let y2 = pointP.y;
-                let x2 = pointP.x;
-                let t1 = (&x2 - &x1).mod_inverse(modp).unwrap();
-                let slope = ((&y2 - &y1) * t1) % modp;
-                let x3 = (&slope * &slope - &x1 - &x2) % modp;
-                let y3 = (&slope * (&x1 - &x3) - &y1) % modp;
-
-                MyAffinePoint {
-                    x: x3,
-                    y: y3,
-                    infinity: false,
-                }
+                let y1 = &self.y;
+                let x1 = &self.x;
+                let y2 = &pointP.y;
+                let x2 = &pointP.x;
+                let t1 = field.inv(&field.sub(x2, x1)).unwrap();
+                let slope = field.mul(&field.sub(y2, y1), &t1);
+                let x3 = field.sub(&field.sub(&field.sqr(&slope), x1), x2);
+                let y3 = field.sub(&field.mul(&slope, &field.sub(x1, &x3)), y1);
+
+                MyAffinePoint::new(x3, y3)
             } else {
                 unreachable!()
             }
@@ -178,48 +430,33 @@ impl<const N: usize> MyAffinePoint<N> {
     /// This function performs the point doubling and addition operations, given a nonzero scalar value (i.e. private key) and a
     /// generator point or a public key value (which is just another point). It is used to do 2 things - generate a public key or
     /// a shared secret/key.
-    pub fn double_and_add(
-        g: MyAffinePoint<N>,
-        k: BigUint,
-        a: &BigInt,
-        b: &BigInt,
-        modp: &BigInt,
-    ) -> MyAffinePoint<N> {
-        let bits = Self::to_bit_array(k, false);
+    pub fn double_and_add(g: MyAffinePoint<C>, k: BigUint) -> MyAffinePoint<C> {
         let mut p = Self::identity();
         let mut q = g;
         // let mut counter: u16 = 0;
-        match bits {
-            BitArrayTypes::P384(bitarray) => {
-                for i in 0..bitarray.len() {
-                    if bitarray[i] == 1 {
-                        if q == Self::identity() {
-                            return Self::identity();
-                        } else {
-                            // counter += 1;
-                            // libc_println!("counter: {:?}", &counter);
-                            p = p.do_the_math(q.clone(), a, b, modp);
-                        }
+        // Walk the bits of `k` from the least significant one up
+        for byte in k.to_bytes_le() {
+            for i in 0..8 {
+                if (byte >> i) & 1 == 1 {
+                    if q == Self::identity() {
+                        return Self::identity();
+                    } else {
+                        // counter += 1;
+                        // libc_println!("counter: {:?}", &counter);
+                        p = p.do_the_math(q.clone());
                     }
-                    q = q.do_the_math(q.clone(), a, b, modp);
-                }
-                if p.y.sign() == Sign::Minus {
-                    num_bigint_dig::negate_sign(&mut p.y);
-                    p.y = modp - p.y; 
-                    // libc_println!("p.y:  {:x}", modp - &p.y);
-                    p
-                } else { 
-                    p
                 }
+                q = q.do_the_math(q.clone());
             }
-            _ => Self::identity(),
         }
+        p.normalize(C::modp());
+        p
     }
 
     /// Returns an array of bits i.e. its elements represent a `scalar` bit pattern.
     /// Note - this function takes a +ve scalar value.
     pub fn to_bit_array(mut scalar: BigUint, reverse: bool) -> BitArrayTypes {
-        match N {
+        match C::byte_len() {
             48 => {
                 let mut bit_array = [0u8; 48 * 8]; // Need full featured `const_generics` to make this array generic
                 let mut i = 0;
@@ -235,6 +472,19 @@ impl<const N: usize> MyAffinePoint<N> {
                 }
                 BitArrayTypes::P384(bit_array)
             }
+            66 => {
+                let mut bit_array = [0u8; 66 * 8];
+                let mut i = 0;
+                while &scalar > &BigUint::from(0u8) {
+                    bit_array[i] = (scalar.clone() & BigUint::from(1u8) == BigUint::from(1u8)) as u8;
+                    scalar >>= 1;
+                    i += 1;
+                }
+                if reverse {
+                    bit_array.reverse();
+                }
+                BitArrayTypes::P521(bit_array)
+            }
             _ => BitArrayTypes::__Nonexhaustive,
         }
     }
@@ -242,62 +492,162 @@ impl<const N: usize> MyAffinePoint<N> {
     /// A method to transform `MyAffinePoint` types into RustCrypto's `EncodedPoint`. Encoded points
     /// are the uncompressed form of a point on the curve 
     pub fn to_uncompressed_bytes(&self, ss: bool) -> EncodedTypes {
-        match N {
+        match C::byte_len() {
             48 => {
+                // Co-ordinates with leading zero bytes serialize to fewer than 48 bytes - left-pad them.
                 let mut bytes = GenericArray::default();
-                let pub_key_x: [u8; N] = self
-                    .x
-                    .to_bytes_be()
-                    .1
-                    .try_into()
-                    .expect("failed to serialize pub_x to bytearray");
-                let pub_key_y: [u8; N] = self
-                    .y
-                    .to_bytes_be()
-                    .1
-                    .try_into()
-                    .expect("failed to serialize pub_y to bytearray");
-                bytes[..pub_key_x.len()].copy_from_slice(&pub_key_x);
-                bytes[pub_key_x.len()..].copy_from_slice(&pub_key_y);
+                let pub_key_x = self.x.to_bytes_be().1;
+                let pub_key_y = self.y.to_bytes_be().1;
+                bytes[48 - pub_key_x.len()..48].copy_from_slice(&pub_key_x);
+                bytes[96 - pub_key_y.len()..].copy_from_slice(&pub_key_y);
                 if ss {
                     EncodedTypes::EncodedTypeP384_SS(SharedSecretP384(EncodedPoint::from_untagged_bytes(&bytes)))
                 } else {
                     EncodedTypes::EncodedTypeP384(PkP384(EncodedPoint::from_untagged_bytes(&bytes)))
                 }
             }
+            66 => {
+                // P-521 co-ordinates are 521 bits, so the top byte is mostly zero - always left-pad to 66 bytes.
+                let mut bytes = [0u8; 133];
+                bytes[0] = 0x04;
+                let pub_key_x = self.x.to_bytes_be().1;
+                let pub_key_y = self.y.to_bytes_be().1;
+                bytes[67 - pub_key_x.len()..67].copy_from_slice(&pub_key_x);
+                bytes[133 - pub_key_y.len()..].copy_from_slice(&pub_key_y);
+                if ss {
+                    EncodedTypes::EncodedTypeP521_SS(SharedSecretP521(GenericArray::clone_from_slice(&bytes[1..67])))
+                } else {
+                    EncodedTypes::EncodedTypeP521(PkP521(GenericArray::clone_from_slice(&bytes)))
+                }
+            }
             _ =>  EncodedTypes::__Nonexhaustive,
 
         }
     }
 
-    ///  A method to transform `EncodedPoint` types into `MyAffinePoint` types.
+    /// Returns `true` if the y co-ordinate is odd i.e. `y mod 2 == 1`.
+    ///
+    /// Note - `BigInt` is signed, so the parity is read off the lowest bit of y's magnitude and never
+    /// its sign. This assumes y has been reduced into `[0, p)`, which `normalize` guarantees.
+    pub fn y_is_odd(&self) -> bool {
+        self.y.to_bytes_le().1[0] & 1 == 1
+    }
+
+    /// A method to transform an uncompressed `EncodedPoint` into a `MyAffinePoint`, using its `x()`/`y()`
+    /// accessors rather than slicing the SEC1 bytes.
+    ///
+    /// The co-ordinates must be `< p` (else `CryptoError::InvalidEncoding`) and satisfy the curve equation
+    /// (else `CryptoError::PointNotOnCurve`). The SEC1 identity and `(0, 0)` are `CryptoError::PointAtInfinity`
+    /// and a compressed point is `CryptoError::InvalidEncoding`. `EncodedPoint` is p384's, so a curve with other
+    /// than 48 byte co-ordinates is `CryptoError::UnsupportedCurve`.
     ///
     /// TODO - `EncodedPoint` type needs to be generic here.
-    pub fn from_encoded_point(point: EncodedPoint) -> Self {
-        match N {
+    pub fn from_encoded_point(point: &EncodedPoint) -> Result<Self> {
+        match C::byte_len() {
             48 => {
-                let pubkey_x =
-                    BigInt::from_bytes_be(Sign::Plus, point.x().map(|x| x.as_slice()).unwrap());
-                let pubkey_y =
-                    BigInt::from_bytes_be(Sign::Plus, point.y().map(|y| y.as_slice()).unwrap());
-                MyAffinePoint {
-                    x: pubkey_x,
-                    y: pubkey_y,
-                    infinity: false,
+                if point.is_identity() {
+                    return Err(CryptoError::PointAtInfinity);
                 }
+                let (x, y) = match (point.x(), point.y()) {
+                    (Some(x), Some(y)) => (x, y),
+                    _ => return Err(CryptoError::InvalidEncoding),
+                };
+                // `(0, 0)` is read as the identity by `new`, which `validate` rejects - it must never be used as a
+                // public key, e.g. it would verify `r = x(z * G), s = 1` for any message
+                let x = BigInt::from_bytes_be(Sign::Plus, x);
+                let y = BigInt::from_bytes_be(Sign::Plus, y);
+                let point = MyAffinePoint::new(x, y);
+                point.validate(C::a(), C::b(), C::modp())?;
+                Ok(point)
             }
-            _ => unimplemented!(),
+            _ => Err(CryptoError::UnsupportedCurve),
         }
     }
 }
 
-impl<const N: usize> Default for MyAffinePoint<N> {
+/// `y^2 = x^3 + ax + b (mod modp)`
+fn satisfies_curve_equation(x: &BigInt, y: &BigInt, a: &BigInt, b: &BigInt, modp: &BigInt) -> bool {
+    let field = Field::new(modp);
+    let rhs = field.add(&field.add(&field.mul(&field.sqr(x), x), &field.mul(a, x)), b);
+    field.sqr(y) == rhs
+}
+
+impl<C: CurveParams> Default for MyAffinePoint<C> {
     /// Default impl for `MyAffinePoint` point. Returns the identity element.
     fn default() -> Self {
         Self::identity()
     }
 }
 
+/// Arithmetic in the prime field `GF(p)` that the curve's co-ordinates live in.
+///
+/// Every operation reduces its result into the canonical range `[0, p)`. This keeps the modulus
+/// (and all of the reduction logic) in one place rather than threading `% modp` through each formula.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Field<'a> {
+    p: &'a BigInt,
+}
+
+impl<'a> Field<'a> {
+    /// Creates a field context for the prime modulus `p`.
+    pub fn new(p: &'a BigInt) -> Self {
+        Field { p }
+    }
+
+    /// Returns the prime modulus
+    pub fn modulus(&self) -> &BigInt {
+        self.p
+    }
+
+    /// Reduces `a` into `[0, p)`. Unlike `%`, the result is never negative.
+    pub fn reduce(&self, a: &BigInt) -> BigInt {
+        let r = a % self.p;
+        if r.sign() == Sign::Minus {
+            r + self.p
+        } else {
+            r
+        }
+    }
+
+    /// Returns `a + b mod p`
+    pub fn add(&self, a: &BigInt, b: &BigInt) -> BigInt {
+        self.reduce(&(a + b))
+    }
+
+    /// Returns `a - b mod p`
+    pub fn sub(&self, a: &BigInt, b: &BigInt) -> BigInt {
+        self.reduce(&(a - b))
+    }
+
+    /// Returns `a * b mod p`
+    pub fn mul(&self, a: &BigInt, b: &BigInt) -> BigInt {
+        self.reduce(&(a * b))
+    }
+
+    /// Returns `a^2 mod p`
+    pub fn sqr(&self, a: &BigInt) -> BigInt {
+        self.mul(a, a)
+    }
+
+    /// Returns the multiplicative inverse `a^-1 mod p` or `None` if `a` is zero mod p.
+    pub fn inv(&self, a: &BigInt) -> Option<BigInt> {
+        self.reduce(a).mod_inverse(self.p).map(|inv| self.reduce(&inv))
+    }
+
+    /// Returns a square root of `a mod p` or `None` if `a` is not a square.
+    ///
+    /// Note - this uses `a^((p + 1) / 4)`, so it only works for `p = 3 mod 4` (true for both p384 and p521).
+    pub fn sqrt(&self, a: &BigInt) -> Option<BigInt> {
+        let a = self.reduce(a);
+        let root = a.modpow(&((self.p + BigInt::from(1)) >> 2), self.p);
+        if self.sqr(&root) == a {
+            Some(root)
+        } else {
+            None
+        }
+    }
+}
+
 // use libc_print::libc_println;
 /// A `SignerType` struct to sign messages and verify signatures.
 #[derive(Debug, Clone, PartialEq)]
@@ -310,72 +660,188 @@ impl<const N: usize> ECSignerType<N> {
     /// As k is a random integer, signatures produced by this func are non-determinstic
     ///
     /// Note: `RNG` used here is `NOT` cryptographically secure.
-    // pub fn sign(data: &[u8], sk: &[u8]) -> (BigInt, BigInt) {
-    //     let hash_type = match N {
-    //         48 => SHA384Digest,
-    //         _ => unimplemented!(),
-    //     };
-
-    //     let (a, b, modp, g_ord) = match N {
-    //         48 => get_p384_constants(),
-    //         _ => unimplemented!(),
-    //     };
-    //     let digest = hash_type.digest(data);
-    //     let e = BigInt::from_bytes_be(Sign::Plus, &digest); // what is `z's` bit-length,
-    //     let z = e; // do we need this - if e.bits() != 8 * N
-    //                // {panic!("Ln must be equal to {:?} not {:?}", N * 8, e.bits())};
-    //     let mut r: BigInt = Zero::zero();
-    //     let mut s: BigInt = Zero::zero();
-    //     while &r == &BigInt::from(0) || &s == &BigInt::from(0) {
-    //         let mut rng = rand::thread_rng();
-    //         let k = rng.gen_biguint((N * 8 as usize) as usize) % &g_ord.to_biguint().unwrap();
-    //         if k < BigUint::from(1u8) || k > &g_ord.to_biguint().unwrap() - BigUint::from(1u8) {
-    //             panic!("k has to be within group order")
-    //         };
-    //         let gen = MyAffinePoint::<N>::generator();
-    //         let k_mul = match gen {
-    //             APTypes::P384(gen) => MyAffinePoint::<48>::double_and_add(
-    //                 // Scalar multiplication of k with Generator point for the curve
-    //                 gen,
-    //                 k.clone(),
-    //                 &a,
-    //                 &b,
-    //                 &modp,
-    //             ),
-    //             _ => unimplemented!(),
-    //         };
-
-    //         // Calculate `r` and  `s` components which together constitute an ECDSA signature.
-    //         r = k_mul.x % &g_ord;
-    //         if r != BigInt::from(0) {
-    //             let k_inverse = k.mod_inverse(&g_ord).unwrap();
-    //             let sk_bigint = BigInt::from_bytes_be(Sign::Plus, &sk);
-    //             s = (k_inverse * (&z + (&r * sk_bigint) % &g_ord)) % &g_ord;
-    //             if s != BigInt::from(0) {
-    //                 break;
-    //             }
-    //         }
-    //     }
-    //     (r, s)
-    // }
+    ///
+    /// Panics for a key outside `[1, n - 1]` - see `sign_prehashed` for a `Result`.
+    pub fn sign(data: &[u8], sk: &[u8]) -> (BigInt, BigInt) {
+        let hash_type = match N {
+            48 => SHA384Digest,
+            _ => unimplemented!(),
+        };
+        let digest = hash_type.digest(data);
+        Self::sign_prehashed(&digest, sk).expect("the signing key must be in [1, n - 1]")
+    }
 
-    /// Given a `message`, `signature` and the `corresponding public key` of the private key used to generate the signature,
-    /// returns a `Ok(true)` value if verification suceeds or an Error. 
-    pub fn verify(data: &[u8], signature: &[u8], pk: EncodedPoint) -> Result<bool> { // pk here is specific to p384 curve
-        if signature.len() != 2 * N {                                                // type needs fixing if we want to make this
-            panic!("invalid signature: {:?}", signature.len())                       // generic
+    /// Same as `sign` but hashes `data` with `D` instead of the curve's default hash (e.g. SHA-256 or SHA-512
+    /// with p384, for legacy systems). The digest is fitted to the field size with `digest_to_field`.
+    pub fn sign_with_digest<D: Digest>(data: &[u8], sk: &[u8]) -> Result<(BigInt, BigInt)> {
+        Self::sign_prehashed(&Self::digest_to_field(&D::digest(data)), sk)
+    }
+
+    /// Same as `verify` but hashes `data` with `D` - the counterpart of `sign_with_digest`.
+    pub fn verify_with_digest<D: Digest>(data: &[u8], signature: &[u8], pk: EncodedPoint) -> Result<bool> {
+        Self::verify_prehashed(&Self::digest_to_field(&D::digest(data)), signature, pk)
+    }
+
+    /// Converts a digest of any length to the `N` byte integer `z` that ECDSA signs, as per FIPS 186-4 6.4:
+    /// a longer digest is truncated to its leftmost bits and a shorter one is taken as is (i.e. left-padded
+    /// with zeros). As the group orders of the supported curves are a whole number of bytes, the leftmost
+    /// bits are simply the leftmost `N` bytes.
+    pub fn digest_to_field(digest: &[u8]) -> [u8; N] {
+        let mut z = [0; N];
+        if digest.len() >= N {
+            z.copy_from_slice(&digest[..N]);
+        } else {
+            z[N - digest.len()..].copy_from_slice(digest);
+        }
+        z
+    }
+
+    /// Given an already computed message digest and a signing key, returns the signature.
+    ///
+    /// The digest must be as long as the curve's field size (i.e. SHA-384 for p384), else
+    /// this returns a `CryptoError::InvalidLength`. A key outside `[1, n - 1]` is a `CryptoError::ScalarOutOfRange`
+    /// and an `N` other than 48 (p384) a `CryptoError::UnsupportedCurve`.
+    ///
+    /// The nonces are drawn from `rand::thread_rng()` or, with the `hedged-nonces` feature, derived from the key,
+    /// the digest and fresh randomness (RFC 6979 with additional data). Without the `std` feature there's no
+    /// `thread_rng()`, so the nonce is the deterministic RFC 6979 one (see `sign_prehashed_hedged`) - use
+    /// `sign_prehashed_with_rng` for randomized signatures there.
+    pub fn sign_prehashed(digest: &[u8], sk: &[u8]) -> Result<(BigInt, BigInt)> {
+        #[cfg(feature = "std")]
+        return Self::sign_prehashed_with_rng(digest, sk, &mut rand::thread_rng());
+        #[cfg(not(feature = "std"))]
+        return Self::sign_prehashed_hedged(digest, sk, &[0; 32]);
+    }
+
+    /// Same as `sign_prehashed` but with all the randomness (the nonces, the `hedged-nonces` entropy and the
+    /// `blinding` factors) drawn from `rng`.
+    pub fn sign_prehashed_with_rng<R: RngCore + CryptoRng>(digest: &[u8], sk: &[u8], rng: &mut R) -> Result<(BigInt, BigInt)> {
+        if digest.len() != N {
+            return Err(CryptoError::InvalidLength { expected: N, got: digest.len() });
+        }
+        let g_ord = match N {
+            48 => &p384_constants().3,
+            _ => return Err(CryptoError::UnsupportedCurve),
+        };
+        let next_nonce = nonces(digest, sk, g_ord, rng);
+        Self::sign_with_nonce_source(digest, sk, next_nonce, rng)
+    }
+
+    /// Same as `sign_prehashed` but "hedged": the nonces are derived deterministically from the key and the digest
+    /// (RFC 6979, with SHA-384 as the HMAC) and `extra_entropy` is mixed into the HMAC-DRBG as the additional data
+    /// of Section 3.6. A bad RNG then can't repeat a nonce across messages, and a good one defeats fault attacks
+    /// that rely on re-signing a message with the same nonce.
+    ///
+    /// An all-zero `extra_entropy` adds nothing, i.e. gives the plain (deterministic) RFC 6979 signature. With the
+    /// `blinding` feature, the blinding factors come from `rand::thread_rng()` or, without the `std` feature, from a
+    /// ChaCha20 rng seeded with `extra_entropy`.
+    pub fn sign_prehashed_hedged(digest: &[u8], sk: &[u8], extra_entropy: &[u8; 32]) -> Result<(BigInt, BigInt)> {
+        if digest.len() != N {
+            return Err(CryptoError::InvalidLength { expected: N, got: digest.len() });
+        }
+        let g_ord = match N {
+            48 => &p384_constants().3,
+            _ => return Err(CryptoError::UnsupportedCurve),
+        };
+        let additional_data: &[u8] = if extra_entropy == &[0; 32] { &[] } else { extra_entropy };
+        let mut next_nonce = rfc6979_nonces(digest, sk, g_ord, additional_data);
+        // Only the `blinding` factors are drawn from this rng
+        #[cfg(feature = "std")]
+        let mut rng = rand::thread_rng();
+        #[cfg(not(feature = "std"))]
+        let mut rng = rand_chacha::ChaCha20Rng::from_seed(*extra_entropy);
+        Self::sign_with_nonce_source(digest, sk, |_| next_nonce(), &mut rng)
+    }
+
+    /// Same as `sign_prehashed` but takes the nonces `k` from `next_nonce`. Candidates outside `[1, n - 1]`
+    /// (e.g. `k = 0`) and those that give `r = 0` or `s = 0` are skipped and the next one is drawn, so a
+    /// signature is never computed with an invalid nonce.
+    ///
+    /// Warning: this is a low-level API. Signing two different digests with the same `k` (or with related
+    /// nonces) reveals the private key - `next_nonce` must return unique, secret and uniformly random values.
+    ///
+    /// With the `blinding` feature, the blinding factors come from `rand::thread_rng()`, so this needs the `std`
+    /// feature.
+    #[cfg(feature = "std")]
+    pub fn sign_prehashed_with_nonces<F>(digest: &[u8], sk: &[u8], mut next_nonce: F) -> Result<(BigInt, BigInt)>
+    where
+        F: FnMut() -> BigUint,
+    {
+        Self::sign_with_nonce_source(digest, sk, |_| next_nonce(), &mut rand::thread_rng())
+    }
+
+    /// The signing loop behind the `sign_prehashed*` functions: `next_nonce` gets `rng` too, and with the `blinding`
+    /// feature the blinding factors are drawn from `rng`.
+    fn sign_with_nonce_source<F, R>(digest: &[u8], sk: &[u8], mut next_nonce: F, rng: &mut R) -> Result<(BigInt, BigInt)>
+    where
+        F: FnMut(&mut R) -> BigUint,
+        R: RngCore + CryptoRng,
+    {
+        if digest.len() != N {
+            return Err(CryptoError::InvalidLength { expected: N, got: digest.len() });
+        }
+        let g_ord = match N {
+            48 => &p384_constants().3,
+            _ => return Err(CryptoError::UnsupportedCurve),
         };
+        let e = BigInt::from_bytes_be(Sign::Plus, &digest); // what is `z's` bit-length,
+        let z = e; // do we need this - if e.bits() != 8 * N
+                   // {panic!("Ln must be equal to {:?} not {:?}", N * 8, e.bits())};
+        let n = g_ord.to_biguint().unwrap();
+        let sk_bigint = BigInt::from_bytes_be(Sign::Plus, &sk);
+        // Signing with `d = 0` (or any `d = 0 mod n`) would leak the digest-only `s = k^-1 * z`
+        if sk_bigint.is_zero() || &sk_bigint >= g_ord {
+            return Err(CryptoError::ScalarOutOfRange);
+        }
+        loop {
+            let k = next_nonce(rng);
+            if k.is_zero() || k >= n {
+                continue;
+            }
+            // Scalar multiplication of k with Generator point for the curve
+            let k_mul = nonce_point(&k, g_ord, rng);
+
+            // Calculate `r` and  `s` components which together constitute an ECDSA signature.
+            let r = k_mul.x % g_ord;
+            if r != BigInt::from(0) {
+                let s = signature_s(&k, &z, &r, &sk_bigint, g_ord, rng);
+                if s != BigInt::from(0) {
+                    return Ok((r, s));
+                }
+            }
+        }
+    }
 
+    /// Given a `message`, `signature` and the `corresponding public key` of the private key used to generate the signature,
+    /// returns `Ok(true)` if verification suceeds, `Ok(false)` if it doesn't or an Error for a malformed signature
+    /// (see `verify_prehashed`).
+    pub fn verify(data: &[u8], signature: &[u8], pk: EncodedPoint) -> Result<bool> { // pk here is specific to p384 curve
         let hash_type = match N {
             48 => SHA384Digest,
             _ => unimplemented!(),
         };
         let digest = hash_type.digest(data);
+        Self::verify_prehashed(&digest, signature, pk)
+    }
+
+    /// Same as `verify` but takes an already computed message digest. The digest must be as long as
+    /// the curve's field size (i.e. SHA-384 for p384), else this returns a `CryptoError::InvalidLength`.
+    ///
+    /// - Returns `Ok(false)` for a well-formed signature that doesn't match the digest and key, a
+    /// `CryptoError::InvalidLength` if the signature isn't `2 * N` bytes and a `CryptoError::InvalidEncoding`
+    /// if `r` or `s` is outside `[1, n - 1]`.
+    pub fn verify_prehashed(digest: &[u8], signature: &[u8], pk: EncodedPoint) -> Result<bool> {
+        if signature.len() != 2 * N {
+            return Err(CryptoError::InvalidLength { expected: 2 * N, got: signature.len() });
+        }
+        if digest.len() != N {
+            return Err(CryptoError::InvalidLength { expected: N, got: digest.len() });
+        }
         let e = BigInt::from_bytes_be(Sign::Plus, &digest);
         let z = e;
 
-        let (a, b, modp, g_ord) = match N {
-            48 => get_p384_constants(),
+        let g_ord = match N {
+            48 => &p384_constants().3,
             _ => unimplemented!(),
         };
         let r_bytes: [u8; N] = signature[..N].try_into().unwrap();
@@ -384,58 +850,269 @@ impl<const N: usize> ECSignerType<N> {
         let r = BigInt::from_bytes_be(Sign::Plus, &r_bytes);
         let s = BigInt::from_bytes_be(Sign::Plus, &s_bytes);
 
-        if r < BigInt::from(1) || r > &g_ord - BigInt::from(1) {
-            return Err(CryptoError::SignatureError);
-        } else if s < BigInt::from(1) || s > &g_ord - BigInt::from(1) {
-            return Err(CryptoError::SignatureError);
+        // These aren't signatures at all (as opposed to signatures that fail to verify)
+        if r < BigInt::from(1) || r > g_ord - BigInt::from(1) {
+            return Err(CryptoError::InvalidEncoding);
+        } else if s < BigInt::from(1) || s > g_ord - BigInt::from(1) {
+            return Err(CryptoError::InvalidEncoding);
         }
 
         // Calculate u1 and u2
-        let s_inverse = s.mod_inverse(&g_ord).unwrap();
-        let u1 = (z * &s_inverse) % &g_ord;
-        let u2 = (&r * &s_inverse) % &g_ord;
+        let s_inverse = s.mod_inverse(g_ord).unwrap();
+        let u1 = (z * &s_inverse) % g_ord;
+        let u2 = (&r * &s_inverse) % g_ord;
 
         // Calculate curve point (x1, y1) = u1 * G + u2 * P, where G - generator and P - PublicKey
-        let gen = MyAffinePoint::<N>::generator();
-
         // u1 * G - operation
-        let u1_mul_result = match gen {
-            APTypes::P384(gen) => {
-                MyAffinePoint::<48>::double_and_add(gen, u1.to_biguint().unwrap(), &a, &b, &modp)
-            }
-            _ => unimplemented!(),
-        };
+        let u1_mul_result =
+            MyAffinePoint::<P384Params>::double_and_add(MyAffinePoint::generator(), u1.to_biguint().unwrap());
 
         // u2 * P - operation
-        let u2_mul_result = match N {
-            48 => { //Get P - PublicKey in affine-form.
-                let affine_pubkey = MyAffinePoint::<48>::from_encoded_point(pk);  
-                MyAffinePoint::<48>::double_and_add(                                         
-                    affine_pubkey,
-                    u2.to_biguint().unwrap(),
-                    &a,
-                    &b,
-                    &modp,
-                )
-            }
-            _ => unimplemented!(),
-        };
-        let result = u1_mul_result.do_the_math(u2_mul_result, &a, &b, &modp); // does point adddition
-        if r == (result.x % &g_ord) {
-            Ok(true)
-        } else {
-            Err(CryptoError::SignatureError)
+        let affine_pubkey = MyAffinePoint::<P384Params>::from_encoded_point(&pk)?; //Get P - PublicKey in affine-form.
+        let u2_mul_result = MyAffinePoint::double_and_add(affine_pubkey, u2.to_biguint().unwrap());
+        let result = u1_mul_result.do_the_math(u2_mul_result); // does point adddition
+        if result.is_identity() {
+            return Ok(false);
         }
+        // `r` was reduced mod n when signing, so `x(R)` (which is mod p, and p > n) must be reduced mod n too
+        // before comparing - else the rare signatures with `x(R) >= n` wouldn't verify. Both sides are compared
+        // as fixed-width encodings, in constant time.
+        let expected_r = to_fixed_bytes::<N>(&(result.x % g_ord));
+        Ok(bool::from(r_bytes.ct_eq(&expected_r)))
     }
 }
 
+/// Left-pads a non-negative integer `< 2^(8 * N)` to exactly `N` big-endian bytes.
+fn to_fixed_bytes<const N: usize>(x: &BigInt) -> [u8; N] {
+    let mut bytes = [0; N];
+    let be = x.to_bytes_be().1;
+    bytes[N - be.len()..].copy_from_slice(&be);
+    bytes
+}
+
+/// Draws the nonces for `sign_prehashed_with_rng` from its rng.
+#[cfg(not(feature = "hedged-nonces"))]
+fn nonces<R: RngCore + CryptoRng>(_digest: &[u8], _sk: &[u8], g_ord: &BigInt, _rng: &mut R) -> impl FnMut(&mut R) -> BigUint {
+    let n = g_ord.to_biguint().unwrap();
+    move |rng| rng.gen_biguint(n.bits()) % &n
+}
+
+/// Derives the nonces for `sign_prehashed` with the HMAC-DRBG of RFC 6979, adding fresh randomness as the
+/// "additional data" of Section 3.6: `K = HMAC_K(V || 0x00 || int2octets(d) || bits2octets(z) || random)`.
+///
+/// The nonce stays unique per (key, digest) even if the RNG repeats or is predictable, and a fault or a
+/// side-channel attack doesn't get to replay the same nonce on a repeated message, as it would for plain RFC 6979.
+#[cfg(feature = "hedged-nonces")]
+fn nonces<R: RngCore + CryptoRng>(digest: &[u8], sk: &[u8], g_ord: &BigInt, rng: &mut R) -> impl FnMut(&mut R) -> BigUint {
+    let mut entropy = [0; 48];
+    rng.fill_bytes(&mut entropy);
+    let mut next_nonce = rfc6979_nonces(digest, sk, g_ord, &entropy);
+    move |_| next_nonce()
+}
+
+/// The candidate nonces of RFC 6979 Section 3.2 (HMAC-SHA384) for the p384 key `sk` and `digest`, with
+/// `additional_data` appended to `int2octets(d) || bits2octets(z)` as in Section 3.6 - empty for plain RFC 6979.
+fn rfc6979_nonces(digest: &[u8], sk: &[u8], g_ord: &BigInt, additional_data: &[u8]) -> impl FnMut() -> BigUint {
+    use sha2::Sha384;
+
+    use crate::digest::HmacDrbg;
+
+    let z = BigUint::from_bytes_be(digest) % g_ord.to_biguint().unwrap();
+    let mut seed_data = [0; 48].to_vec();
+    seed_data[48 - z.to_bytes_be().len()..].copy_from_slice(&z.to_bytes_be());
+    seed_data.extend_from_slice(additional_data);
+    let mut drbg = HmacDrbg::<Sha384>::new(sk, &seed_data);
+    move || BigUint::from_bytes_be(&drbg.next())
+}
+
+/// Computes `k * G`.
+#[cfg(not(feature = "blinding"))]
+fn nonce_point<R: RngCore + CryptoRng>(k: &BigUint, g_ord: &BigInt, _rng: &mut R) -> MyAffinePoint<P384Params> {
+    MyAffinePoint::double_and_add(MyAffinePoint::generator(), k.clone())
+}
+
+/// Computes `k * G` as `(k + m * n) * G` for a random 64 bit `m`. As `G` has order `n` the result is the same, but
+/// the bits that drive the double-and-add loop are no longer those of the nonce.
+#[cfg(feature = "blinding")]
+fn nonce_point<R: RngCore + CryptoRng>(k: &BigUint, g_ord: &BigInt, rng: &mut R) -> MyAffinePoint<P384Params> {
+    let m = rng.gen_biguint(64);
+    MyAffinePoint::double_and_add(MyAffinePoint::generator(), k + m * g_ord.to_biguint().unwrap())
+}
+
+/// Computes `s = k^-1 * (z + r * d) mod n`.
+#[cfg(not(feature = "blinding"))]
+fn signature_s<R: RngCore + CryptoRng>(k: &BigUint, z: &BigInt, r: &BigInt, d: &BigInt, g_ord: &BigInt, _rng: &mut R) -> BigInt {
+    let k_inverse = invert_mod_prime(&BigInt::from_biguint(Sign::Plus, k.clone()), g_ord);
+    (k_inverse * (z + (r * d) % g_ord)) % g_ord
+}
+
+/// Computes `s = k^-1 * (z + r * d) mod n` as `(k * b)^-1 * (b * z + r * (b * d)) mod n` for a random `b` in
+/// `[1, n - 1]`, so neither the inversion nor the multiplications see `k` or `d` themselves.
+#[cfg(feature = "blinding")]
+fn signature_s<R: RngCore + CryptoRng>(k: &BigUint, z: &BigInt, r: &BigInt, d: &BigInt, g_ord: &BigInt, rng: &mut R) -> BigInt {
+    let one = BigInt::from(1);
+    let b = rng.gen_bigint_range(&one, g_ord);
+    let k = BigInt::from_biguint(Sign::Plus, k.clone());
+    let kb_inverse = invert_mod_prime(&((k * &b) % g_ord), g_ord);
+    let bd = (&b * d) % g_ord;
+    (kb_inverse * ((&b * z + r * bd) % g_ord)) % g_ord
+}
+
+/// Inverts `x` modulo the prime `n` as `x^(n - 2) mod n` (Fermat's little theorem), for the secret nonce. Unlike
+/// the extended Euclidean algorithm behind `mod_inverse`, the sequence of steps depends only on `n` and not on `x`.
+///
+/// Note: `num_bigint_dig`'s `modpow` isn't constant-time at the limb level either, so this narrows rather than
+/// closes the timing channel (see the `blinding` feature). The inversion of the public `s` when verifying is left
+/// to `mod_inverse`.
+pub(crate) fn invert_mod_prime(x: &BigInt, n: &BigInt) -> BigInt {
+    x.modpow(&(n - BigInt::from(2)), n)
+}
+
+lazy_static! {
+    /// The p384 constants - parsed once, on first use.
+    static ref P384_CONSTANTS: (BigInt, BigInt, BigInt, BigInt) = parse_p384_constants();
+}
+
+/// Returns the cached p384 constants `(a, b, modp, group order)` as `BigInts`
+pub fn p384_constants() -> &'static (BigInt, BigInt, BigInt, BigInt) {
+    &P384_CONSTANTS
+}
+
+lazy_static! {
+    /// The p384 group order as (big-endian) bytes, for range checks that don't need a `BigInt`.
+    static ref P384_GROUP_ORDER_BYTES: [u8; 48] = dh::dh::unhexlify_to_bytearray::<48>(constants::ECDH_NIST_384_GROUP_ORDER)
+        .expect("`ECDH_NIST_384_GROUP_ORDER` is well-formed hex");
+}
+
+/// Returns the cached p384 group order as 48 big-endian bytes.
+pub(crate) fn p384_group_order_bytes() -> &'static [u8; 48] {
+    &P384_GROUP_ORDER_BYTES
+}
+
 /// Returns p384 constants as `BigInts`
+///
+/// This clones the cached values, prefer `p384_constants()` to borrow them.
 pub fn get_p384_constants() -> (BigInt, BigInt, BigInt, BigInt) {
-    let mod_prime =
-        dh::dh::unhexlify_to_bytearray::<48>(&constants::ECDH_NIST_384_MODP.replace("0x", ""));
-    let b_val = dh::dh::unhexlify_to_bytearray::<48>(&constants::ECDH_NIST_384_B_VAL.replace("0x", ""));
-    let group_order =
-        dh::dh::unhexlify_to_bytearray::<48>(&constants::ECDH_NIST_384_GROUP_ORDER.replace("0x", ""));
+    p384_constants().clone()
+}
+
+fn parse_p384_constants() -> (BigInt, BigInt, BigInt, BigInt) {
+    let mod_prime = dh::dh::unhexlify_to_bytearray::<48>(constants::ECDH_NIST_384_MODP)
+        .expect("`ECDH_NIST_384_MODP` is well-formed hex");
+    let b_val = dh::dh::unhexlify_to_bytearray::<48>(constants::ECDH_NIST_384_B_VAL)
+        .expect("`ECDH_NIST_384_B_VAL` is well-formed hex");
+    let group_order = dh::dh::unhexlify_to_bytearray::<48>(constants::ECDH_NIST_384_GROUP_ORDER)
+        .expect("`ECDH_NIST_384_GROUP_ORDER` is well-formed hex");
+
+    let a = BigInt::from(-3);
+    let b = BigInt::from_bytes_be(Sign::Plus, &b_val);
+    let modp = BigInt::from_bytes_be(Sign::Plus, &mod_prime);
+    let g_ord = BigInt::from_bytes_be(Sign::Plus, &group_order);
+    (a, b, modp, g_ord)
+}
+
+/// The width, in bits, of each window of the P-384 fixed-base table.
+const P384_WINDOW_BITS: usize = 4;
+/// The number of windows needed to cover a 384 bit scalar.
+const P384_WINDOWS: usize = 48 * 8 / P384_WINDOW_BITS;
+
+lazy_static! {
+    /// Multiples of the P-384 generator - `P384_GENERATOR_TABLE[i][d - 1] = d * 16^i * G` for `d` in `1..16`.
+    /// Built once, on first use.
+    static ref P384_GENERATOR_TABLE: Vec<Vec<MyAffinePoint<P384Params>>> = build_p384_generator_table();
+}
+
+fn build_p384_generator_table() -> Vec<Vec<MyAffinePoint<P384Params>>> {
+    let mut base = MyAffinePoint::<P384Params>::generator();
+    let mut table = Vec::with_capacity(P384_WINDOWS);
+    for _ in 0..P384_WINDOWS {
+        let mut row: Vec<MyAffinePoint<P384Params>> = Vec::with_capacity((1 << P384_WINDOW_BITS) - 1);
+        row.push(base.clone());
+        for d in 1..(1 << P384_WINDOW_BITS) - 1 {
+            let next = row[d - 1].do_the_math(base.clone());
+            row.push(next);
+        }
+        // 16 * base = 15 * base + base
+        base = row[row.len() - 1].do_the_math(base);
+        table.push(row);
+    }
+    table
+}
+
+impl MyAffinePoint<P384Params> {
+    /// Multiplies the P-384 generator by `k`, using a precomputed table of generator multiples.
+    ///
+    /// Gives the same result as `double_and_add(generator, k)` but, as the doublings are done once (when the
+    /// table is built), each call only costs one point addition per non-zero 4 bit window of `k`.
+    pub fn mul_generator(k: BigUint) -> Self {
+        let g_ord = &p384_constants().3;
+        // `G` has order `n`, so reducing `k` doesn't change the result - and it keeps `k` within the table.
+        let k = k % g_ord.to_biguint().unwrap();
+        let mut result = Self::identity();
+        for (i, byte) in k.to_bytes_le().iter().enumerate() {
+            for (j, digit) in [byte & 0x0f, byte >> 4].iter().enumerate() {
+                if *digit != 0 {
+                    let multiple = &P384_GENERATOR_TABLE[2 * i + j][*digit as usize - 1];
+                    result = result.do_the_math(multiple.clone());
+                }
+            }
+        }
+        result
+    }
+
+    /// A method to transform a P-384 `MyAffinePoint` into RustCrypto's compressed `EncodedPoint`. The
+    /// SEC1 tag is `0x02` for an even y co-ordinate and `0x03` for an odd one.
+    pub fn to_compressed_bytes(&self) -> EncodedPoint {
+        let mut bytes = [0u8; 49];
+        bytes[0] = 0x02 | self.y_is_odd() as u8;
+        let pub_key_x = self.x.to_bytes_be().1;
+        bytes[49 - pub_key_x.len()..].copy_from_slice(&pub_key_x);
+        EncodedPoint::from_bytes(&bytes[..]).expect("failed to encode compressed point")
+    }
+}
+
+lazy_static! {
+    /// The p256 constants - parsed once, on first use.
+    static ref P256_CONSTANTS: (BigInt, BigInt, BigInt, BigInt) = parse_p256_constants();
+}
+
+/// Returns the cached p256 constants `(a, b, modp, group order)` as `BigInts`
+pub fn p256_constants() -> &'static (BigInt, BigInt, BigInt, BigInt) {
+    &P256_CONSTANTS
+}
+
+fn parse_p256_constants() -> (BigInt, BigInt, BigInt, BigInt) {
+    let mod_prime = dh::dh::unhexlify_to_bytearray::<32>(constants::ECDH_NIST_256_MODP)
+        .expect("`ECDH_NIST_256_MODP` is well-formed hex");
+    let b_val = dh::dh::unhexlify_to_bytearray::<32>(constants::ECDH_NIST_256_B_VAL)
+        .expect("`ECDH_NIST_256_B_VAL` is well-formed hex");
+    let group_order = dh::dh::unhexlify_to_bytearray::<32>(constants::ECDH_NIST_256_GROUP_ORDER)
+        .expect("`ECDH_NIST_256_GROUP_ORDER` is well-formed hex");
+
+    let a = BigInt::from(-3);
+    let b = BigInt::from_bytes_be(Sign::Plus, &b_val);
+    let modp = BigInt::from_bytes_be(Sign::Plus, &mod_prime);
+    let g_ord = BigInt::from_bytes_be(Sign::Plus, &group_order);
+    (a, b, modp, g_ord)
+}
+
+lazy_static! {
+    /// The p521 constants - parsed once, on first use.
+    static ref P521_CONSTANTS: (BigInt, BigInt, BigInt, BigInt) = parse_p521_constants();
+}
+
+/// Returns the cached p521 constants `(a, b, modp, group order)` as `BigInts`
+pub fn p521_constants() -> &'static (BigInt, BigInt, BigInt, BigInt) {
+    &P521_CONSTANTS
+}
+
+fn parse_p521_constants() -> (BigInt, BigInt, BigInt, BigInt) {
+    let mod_prime = dh::dh::unhexlify_to_bytearray::<66>(constants::ECDH_NIST_521_MODP)
+        .expect("`ECDH_NIST_521_MODP` is well-formed hex");
+    let b_val = dh::dh::unhexlify_to_bytearray::<66>(constants::ECDH_NIST_521_B_VAL)
+        .expect("`ECDH_NIST_521_B_VAL` is well-formed hex");
+    let group_order = dh::dh::unhexlify_to_bytearray::<66>(constants::ECDH_NIST_521_GROUP_ORDER)
+        .expect("`ECDH_NIST_521_GROUP_ORDER` is well-formed hex");
 
     let a = BigInt::from(-3);
     let b = BigInt::from_bytes_be(Sign::Plus, &b_val);
@@ -443,3 +1120,30 @@ pub fn get_p384_constants() -> (BigInt, BigInt, BigInt, BigInt) {
     let g_ord = BigInt::from_bytes_be(Sign::Plus, &group_order);
     (a, b, modp, g_ord)
 }
+
+lazy_static! {
+    /// The brainpoolP384r1 constants - parsed once, on first use.
+    static ref BRAINPOOL_P384R1_CONSTANTS: (BigInt, BigInt, BigInt, BigInt) = parse_brainpool_p384r1_constants();
+}
+
+/// Returns the cached brainpoolP384r1 constants `(a, b, modp, group order)` as `BigInts`
+pub fn brainpool_p384r1_constants() -> &'static (BigInt, BigInt, BigInt, BigInt) {
+    &BRAINPOOL_P384R1_CONSTANTS
+}
+
+fn parse_brainpool_p384r1_constants() -> (BigInt, BigInt, BigInt, BigInt) {
+    let mod_prime = dh::dh::unhexlify_to_bytearray::<48>(constants::ECDH_BRAINPOOL_P384R1_MODP)
+        .expect("`ECDH_BRAINPOOL_P384R1_MODP` is well-formed hex");
+    let a_val = dh::dh::unhexlify_to_bytearray::<48>(constants::ECDH_BRAINPOOL_P384R1_A_VAL)
+        .expect("`ECDH_BRAINPOOL_P384R1_A_VAL` is well-formed hex");
+    let b_val = dh::dh::unhexlify_to_bytearray::<48>(constants::ECDH_BRAINPOOL_P384R1_B_VAL)
+        .expect("`ECDH_BRAINPOOL_P384R1_B_VAL` is well-formed hex");
+    let group_order = dh::dh::unhexlify_to_bytearray::<48>(constants::ECDH_BRAINPOOL_P384R1_GROUP_ORDER)
+        .expect("`ECDH_BRAINPOOL_P384R1_GROUP_ORDER` is well-formed hex");
+
+    let a = BigInt::from_bytes_be(Sign::Plus, &a_val);
+    let b = BigInt::from_bytes_be(Sign::Plus, &b_val);
+    let modp = BigInt::from_bytes_be(Sign::Plus, &mod_prime);
+    let g_ord = BigInt::from_bytes_be(Sign::Plus, &group_order);
+    (a, b, modp, g_ord)
+}
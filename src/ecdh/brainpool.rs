@@ -0,0 +1,175 @@
+//! ECDH over brainpoolP384r1 (RFC 5639), as required by some European (e.g. eIDAS) systems.
+//!
+//! RustCrypto has no brainpool curves, so this runs on the same `MyAffinePoint` arithmetic as P-384 and P-521. The
+//! formulas there take `a` from the curve's `CurveParams` rather than assuming the NIST `a = -3`, which brainpool
+//! curves don't satisfy. Keys are serialized like the NIST ones: a 48 byte big-endian scalar, an uncompressed SEC1
+//! public key and the x co-ordinate of the shared point.
+//!
+//! Note - like the other affine-point curves, none of this is constant time.
+
+use generic_array::{typenum, GenericArray};
+use num_bigint_dig::{BigInt, BigUint, Sign};
+use num_traits::Zero;
+use rand_chacha::rand_core::{RngCore, SeedableRng};
+use rand_chacha::ChaCha20Rng;
+use zeroize::Zeroize;
+
+use core::fmt;
+
+use super::affine_math::{brainpool_p384r1_constants, BrainpoolP384r1Params, MyAffinePoint};
+use super::ecdh::{FromBytes, KeyExchange, ToBytes};
+use crate::{CryptoError, Result};
+
+/// A brainpoolP384r1 private key: a scalar in `[1, n - 1]` (48 bytes, big-endian).
+#[derive(Clone)]
+pub struct SkBrainpoolP384r1(GenericArray<u8, typenum::U48>);
+
+impl Drop for SkBrainpoolP384r1 {
+    fn drop(&mut self) {
+        self.0.as_mut_slice().zeroize();
+    }
+}
+
+/// Prints `SkBrainpoolP384r1(REDACTED)` so that the private scalar never ends up in logs.
+impl fmt::Debug for SkBrainpoolP384r1 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("SkBrainpoolP384r1(REDACTED)")
+    }
+}
+
+/// A brainpoolP384r1 public key, held in its uncompressed SEC1 form i.e. `0x04 || x || y` (97 bytes).
+#[derive(Debug, Clone, PartialEq)]
+pub struct PkBrainpoolP384r1(pub GenericArray<u8, typenum::U97>);
+
+/// A struct to hold the computed brainpoolP384r1 shared secret (i.e. the x co-ordinate)
+#[derive(Debug, Clone, PartialEq)]
+pub struct SharedSecretBrainpoolP384r1(pub GenericArray<u8, typenum::U48>);
+
+impl ToBytes for SkBrainpoolP384r1 {
+    type OutputSize = typenum::U48;
+
+    fn to_bytes(&self) -> GenericArray<u8, Self::OutputSize> {
+        self.0
+    }
+}
+
+impl FromBytes for SkBrainpoolP384r1 {
+    fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        if bytes.len() != Self::size() {
+            return Err(CryptoError::InvalidLength { expected: Self::size(), got: bytes.len() });
+        }
+        // The scalar must be in `[1, n - 1]`
+        let (_, _, _, g_ord) = brainpool_p384r1_constants();
+        let scalar = BigInt::from_bytes_be(Sign::Plus, bytes);
+        if scalar.is_zero() || &scalar >= g_ord {
+            return Err(CryptoError::ScalarOutOfRange);
+        }
+        Ok(SkBrainpoolP384r1(GenericArray::clone_from_slice(bytes)))
+    }
+}
+
+impl ToBytes for PkBrainpoolP384r1 {
+    // 1 + 2 * 48 bytes
+    type OutputSize = typenum::U97;
+
+    fn to_bytes(&self) -> GenericArray<u8, Self::OutputSize> {
+        self.0
+    }
+}
+
+impl FromBytes for PkBrainpoolP384r1 {
+    /// Only the uncompressed form `0x04 || x || y` is accepted.
+    ///
+    /// - Returns `CryptoError::InvalidLength` for a wrong length, `CryptoError::InvalidEncoding` for another tag or
+    ///   a co-ordinate `>= p`, `CryptoError::PointNotOnCurve` for a point off the curve and
+    ///   `CryptoError::PointAtInfinity` for `(0, 0)`.
+    fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        if bytes.len() != Self::size() {
+            return Err(CryptoError::InvalidLength { expected: Self::size(), got: bytes.len() });
+        }
+        if bytes[0] != 0x04 {
+            return Err(CryptoError::InvalidEncoding);
+        }
+        to_affine(bytes)?;
+        Ok(PkBrainpoolP384r1(GenericArray::clone_from_slice(bytes)))
+    }
+}
+
+/// Derives the public key, i.e. `ECDHBrainpoolP384r1::generate_public_key`.
+impl From<&SkBrainpoolP384r1> for PkBrainpoolP384r1 {
+    fn from(sk: &SkBrainpoolP384r1) -> Self {
+        ECDHBrainpoolP384r1::generate_public_key(sk)
+    }
+}
+
+impl ToBytes for SharedSecretBrainpoolP384r1 {
+    type OutputSize = typenum::U48;
+
+    fn to_bytes(&self) -> GenericArray<u8, Self::OutputSize> {
+        // §4.1: Representation of the KEX result is the serialization of the x-coordinate
+        self.0
+    }
+}
+
+/// A struct that represents the ECDH implementation for the brainpoolP384r1 curve.
+pub struct ECDHBrainpoolP384r1;
+
+impl KeyExchange for ECDHBrainpoolP384r1 {
+    type SKey = SkBrainpoolP384r1;
+    type PubKey = PkBrainpoolP384r1;
+    type CompSecret = SharedSecretBrainpoolP384r1;
+
+    fn generate_private_key(seed: [u8; 32]) -> Self::SKey {
+        let mut rng = ChaCha20Rng::from_seed(seed); // test seed value.
+        let mut dest = [0; 48];
+        // `n` is a little over `2^383`, so resample until we land in `[1, n - 1]`
+        loop {
+            rng.fill_bytes(&mut dest);
+            if let Ok(sk) = SkBrainpoolP384r1::from_bytes(&dest) {
+                dest.zeroize();
+                return sk;
+            }
+        }
+    }
+
+    fn generate_public_key(sk: &Self::SKey) -> Self::PubKey {
+        let point = MyAffinePoint::<BrainpoolP384r1Params>::double_and_add(
+            MyAffinePoint::generator(),
+            BigUint::from_bytes_be(sk.0.as_slice()),
+        );
+        let mut bytes = GenericArray::default();
+        bytes[0] = 0x04;
+        bytes[1..49].copy_from_slice(&to_field_bytes(&point.x));
+        bytes[49..].copy_from_slice(&to_field_bytes(&point.y));
+        PkBrainpoolP384r1(bytes)
+    }
+
+    fn generate_shared_secret(sk: &Self::SKey, others_pk: &Self::PubKey) -> Result<Self::CompSecret> {
+        // The field is public, so a key that never went through `from_bytes` is re-validated here
+        let affine_pt = to_affine(&others_pk.0)?;
+        let shared_secret = MyAffinePoint::double_and_add(affine_pt, BigUint::from_bytes_be(sk.0.as_slice()));
+        if shared_secret.is_identity() {
+            return Err(CryptoError::PointAtInfinity);
+        }
+        Ok(SharedSecretBrainpoolP384r1(GenericArray::clone_from_slice(&to_field_bytes(&shared_secret.x))))
+    }
+}
+
+/// Parses and validates (see `MyAffinePoint::validate`) the co-ordinates of an uncompressed point.
+fn to_affine(bytes: &[u8]) -> Result<MyAffinePoint<BrainpoolP384r1Params>> {
+    let point = MyAffinePoint::new(
+        BigInt::from_bytes_be(Sign::Plus, &bytes[1..49]),
+        BigInt::from_bytes_be(Sign::Plus, &bytes[49..]),
+    );
+    let (a, b, modp, _) = brainpool_p384r1_constants();
+    point.validate(a, b, modp)?;
+    Ok(point)
+}
+
+/// Left-pads a co-ordinate to 48 bytes, as it can have leading zero bytes.
+fn to_field_bytes(x: &BigInt) -> [u8; 48] {
+    let mut bytes = [0; 48];
+    let be = x.to_bytes_be().1;
+    bytes[48 - be.len()..].copy_from_slice(&be);
+    bytes
+}
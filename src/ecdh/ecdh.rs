@@ -1,10 +1,11 @@
 // #![allow(warnings)]
 
 use core::ops::{Mul};
-use core::{convert::TryInto};
+use core::fmt;
 
 use num_bigint_dig::{BigInt, BigUint, Sign};
-use rand_chacha::rand_core::{RngCore, SeedableRng};
+use num_traits::Zero;
+use rand_chacha::rand_core::{CryptoRng, RngCore, SeedableRng};
 use rand_chacha::ChaCha20Rng;
 
 use generic_array::{
@@ -13,14 +14,25 @@ use generic_array::{
 };
 
 use elliptic_curve::sec1::EncodedPoint as PubKey;
+use elliptic_curve::subtle::ConstantTimeEq;
 use elliptic_curve::{sec1::UncompressedPointSize, Curve};
-use k256::{AffinePoint, NonZeroScalar, PublicKey, Scalar, Secp256k1};
+use elliptic_curve::ff::PrimeField;
+use k256::{AffinePoint, NonZeroScalar, ProjectivePoint, PublicKey, Scalar, Secp256k1};
 use p384::{NistP384, SecretKey as P384Secret};
 
-use super::affine_math::{APTypes, EncodedTypes, MyAffinePoint};
+use super::affine_math::{
+    p384_constants, p384_group_order_bytes, p521_constants, CurveParams, EncodedTypes, Field, MyAffinePoint, P384Params, P521Params,
+};
+
+use alloc::string::String;
+use alloc::vec::Vec;
 
-use crate::{constants, dh::dh};
-use crate::{CryptoError, Result};
+use sha2::{Sha256, Sha384};
+use zeroize::Zeroize;
+
+use crate::digest::{hkdf_expand_keys, hkdf_sha256, SHA256Digest};
+use crate::encoding;
+use crate::{CryptoError, CurveId, Result};
 
 /// Implemented by types that have a fixed-length byte representation
 pub trait ToBytes {
@@ -34,16 +46,70 @@ pub trait ToBytes {
     fn size() -> usize {
         Self::OutputSize::to_usize()
     }
+
+    /// Returns the serialized form of this type as a lower-case hex-string.
+    ///
+    /// Caution: for the secret key types (`Skk256`, `SkP384`) this is the raw private scalar.
+    /// Treat the returned string with the same care as the key itself.
+    fn to_hex(&self) -> String {
+        encoding::to_hex(&self.to_bytes())
+    }
+
+    /// Returns the serialized form of this type as unpadded base64url (RFC 4648 §5), e.g. for URLs and JWTs.
+    ///
+    /// Caution: as with `to_hex`, for the secret key types this is the raw private scalar.
+    #[cfg(feature = "base64")]
+    fn to_base64url(&self) -> String {
+        encoding::to_base64url(&self.to_bytes())
+    }
 }
 /// Implemented by types that can be deserialized from byte representation
 pub trait FromBytes: ToBytes + Sized {
     /// Types implementing this method are de-serializable
+    ///
+    /// - Returns `CryptoError::InvalidLength` if `bytes` isn't exactly `Self::size()` bytes long.
     fn from_bytes(bytes: &[u8]) -> Result<Self>;
+
+    /// De-serializes a type from its hex-string form. The string may carry a `0x` prefix and
+    /// use mixed case.
+    ///
+    /// Caution: for the secret key types (`Skk256`, `SkP384`) the input is the raw private scalar.
+    fn from_hex(hex_string: &str) -> Result<Self> {
+        Self::from_bytes(&encoding::from_hex(hex_string)?)
+    }
+
+    /// De-serializes a type from its unpadded base64url form.
+    ///
+    /// - Returns `CryptoError::InvalidEncoding` for padding or characters outside the base64url alphabet (see
+    ///   `encoding::from_base64url`), else the errors of `from_bytes`.
+    #[cfg(feature = "base64")]
+    fn from_base64url(encoded: &str) -> Result<Self> {
+        Self::from_bytes(&encoding::from_base64url(encoded)?)
+    }
+}
+/// Implemented by the public key types, for a SEC1 encoding whose form is picked at runtime.
+///
+/// `ToBytes` stays the fixed-size uncompressed form; this is for generic code that serializes any supported key
+/// with a compression flag.
+pub trait ToSec1Bytes {
+    /// The compressed (`0x02`/`0x03 || x`) SEC1 encoding if `compress` is set, else the uncompressed one
+    /// (`0x04 || x || y`).
+    fn to_sec1_bytes(&self, compress: bool) -> Vec<u8>;
 }
+
 /// An ECDH-k256 private key is simply a scalar in the NIST P-256 field.
 #[derive(Clone)]
 pub struct Skk256(NonZeroScalar);
+
+impl Drop for Skk256 {
+    fn drop(&mut self) {
+        self.0.zeroize();
+    }
+}
 /// An ECDH-k256 public key. This is derived from the private key using scalar point multiplication.
+///
+/// Note: the derived `PartialEq` compares the wrapped `k256::PublicKey` and isn't constant-time - use `same_as`
+/// for comparisons against a secret or pinned key.
 #[derive(Debug, Clone, PartialEq)]
 pub struct Pkk256(k256::PublicKey);
 
@@ -61,21 +127,129 @@ impl ToBytes for Pkk256 {
 
 // Everything is serialized and deserialized in uncompressed form
 impl FromBytes for Pkk256 {
+    /// Parses the uncompressed form `0x04 || x || y`, or a SEC1 hybrid form (`0x06`/`0x07 || x || y`) whose tag
+    /// matches the parity of y - which is then held as `0x04`, so it re-encodes uncompressed.
     fn from_bytes(bytes: &[u8]) -> Result<Self> {
         // In order to parse as an uncompressed curve point, we first make sure the input length is
         // correct. This also ensures we're receiving the uncompressed representation.
         if bytes.len() != Self::OutputSize::to_usize() {
-            return Err(CryptoError::InvalidEncoding);
+            return Err(CryptoError::InvalidLength { expected: Self::OutputSize::to_usize(), got: bytes.len() });
         }
+        let mut uncompressed = GenericArray::<u8, Self::OutputSize>::clone_from_slice(bytes);
+        from_hybrid(&mut uncompressed)?;
         // Now just call the routine exposed by the k256 crate. This preserves the
         // invariant that public keys can't be the point at infinity, since the point at infinity
         // has no representation as a SEC1 bytestring.
         let parsed =
-            k256::PublicKey::from_sec1_bytes(bytes).map_err(|_| CryptoError::InvalidEncoding)?;
+            k256::PublicKey::from_sec1_bytes(&uncompressed).map_err(|_| CryptoError::PointNotOnCurve)?;
         Ok(Pkk256(parsed))
     }
 }
 
+/// Derives the public key, i.e. `ECDHNISTK256::generate_public_key`.
+impl From<&Skk256> for Pkk256 {
+    fn from(sk: &Skk256) -> Self {
+        ECDHNISTK256::generate_public_key(sk)
+    }
+}
+
+impl Pkk256 {
+    /// Parses the untagged (`x || y`, 64 bytes) form, as stored in `ECDSASHA256Signature`'s verifying key.
+    ///
+    /// - Returns the errors of `from_bytes` e.g. `CryptoError::PointNotOnCurve`.
+    pub fn from_untagged_bytes(bytes: &[u8; 64]) -> Result<Self> {
+        let mut tagged = [0x04; 65];
+        tagged[1..].copy_from_slice(bytes);
+        Pkk256::from_bytes(&tagged)
+    }
+
+    /// The untagged form i.e. the uncompressed encoding without its `0x04` tag.
+    pub fn to_untagged_bytes(&self) -> [u8; 64] {
+        let mut untagged = [0; 64];
+        untagged.copy_from_slice(&self.to_bytes()[1..]);
+        untagged
+    }
+
+    /// The BIP340 x-only form of the key i.e. its 32 byte x co-ordinate, along with whether y is odd (the
+    /// parity that the x-only form drops).
+    pub fn to_xonly(&self) -> ([u8; 32], bool) {
+        let bytes = self.to_bytes();
+        let mut x = [0; 32];
+        x.copy_from_slice(&bytes[1..33]);
+        (x, bytes[64] & 1 == 1)
+    }
+
+    /// Whether the key's y co-ordinate is odd, i.e. the parity that the compressed form's `0x02`/`0x03` tag
+    /// encodes.
+    pub fn y_is_odd(&self) -> bool {
+        k256::EncodedPoint::encode(self.0, true).as_bytes()[0] == 0x03
+    }
+
+    /// Reconstructs the key with the given x co-ordinate and an even y, as BIP340 does for x-only keys.
+    ///
+    /// - Returns `CryptoError::PointNotOnCurve` if no point has this x co-ordinate (or `x >= p`).
+    pub fn from_xonly(x: &[u8; 32]) -> Result<Self> {
+        let mut compressed = [0x02; 33];
+        compressed[1..].copy_from_slice(x);
+        let parsed =
+            k256::PublicKey::from_sec1_bytes(&compressed).map_err(|_| CryptoError::PointNotOnCurve)?;
+        Ok(Pkk256(parsed))
+    }
+
+    /// Returns the key `P + tweak * G`, the public counterpart of `Skk256::add_tweak` (e.g. for BIP32
+    /// non-hardened child key derivation).
+    ///
+    /// - Returns `CryptoError::ScalarOutOfRange` if `tweak >= n` or `CryptoError::PointAtInfinity` if the result
+    ///   is the identity.
+    pub fn add_tweak(&self, tweak: &[u8; 32]) -> Result<Pkk256> {
+        let tweak = parse_tweak(tweak)?;
+        let tweaked = (ProjectivePoint::generator() * tweak + *self.0.as_affine()).to_affine();
+        PublicKey::from_affine(tweaked)
+            .map(Pkk256)
+            .map_err(|_| CryptoError::PointAtInfinity)
+    }
+
+    /// Checks whether `other` is the same point as this key, independently of how either was encoded, in
+    /// constant time (for keys on the curve, the uncompressed encoding is unique).
+    pub fn same_as(&self, other: &Pkk256) -> bool {
+        bool::from(self.to_bytes().as_slice().ct_eq(other.to_bytes().as_slice()))
+    }
+
+    /// Checks that this key's serialized form interoperates with the `k256` crate, in both directions:
+    ///
+    /// - our encoding parses with a bare `k256::PublicKey::from_sec1_bytes` and re-encodes to the same bytes
+    /// - `k256`'s own encoding of the key parses back (via `from_bytes`) to this key
+    ///
+    /// Returns `CryptoError::InvalidEncoding` if the two ever diverge.
+    pub fn assert_rustcrypto_compatible(&self) -> Result<()> {
+        let ours = self.to_bytes();
+        let parsed =
+            k256::PublicKey::from_sec1_bytes(&ours).map_err(|_| CryptoError::InvalidEncoding)?;
+        let theirs = k256::EncodedPoint::encode(parsed, false);
+        if theirs.as_bytes() != ours.as_slice() || Pkk256::from_bytes(theirs.as_bytes())? != *self {
+            return Err(CryptoError::InvalidEncoding);
+        }
+        Ok(())
+    }
+}
+
+/// Prints `Skk256(REDACTED)` so that the private scalar never ends up in logs.
+impl fmt::Debug for Skk256 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Skk256(REDACTED)")
+    }
+}
+
+#[cfg(feature = "dangerous-debug")]
+impl Skk256 {
+    /// Returns a `Debug` representation that includes the raw private scalar (as hex).
+    ///
+    /// Caution: only meant for debugging - never log the output of this function.
+    pub fn expose_debug(&self) -> String {
+        alloc::format!("Skk256({})", self.to_hex())
+    }
+}
+
 impl ToBytes for Skk256 {
     // A fancy way of saying "32 bytes"
     type OutputSize = <Secp256k1 as Curve>::FieldSize;
@@ -89,26 +263,198 @@ impl ToBytes for Skk256 {
     }
 }
 
+// Note: this reduces the scalar mod `n`, so e.g. `n + 1` imports as the key `1` - see `Skk256::from_bytes_exact`
+// for imports that must be canonical.
 impl FromBytes for Skk256 {
     fn from_bytes(bytes: &[u8]) -> Result<Self> {
         // Check the length
         if bytes.len() != Self::OutputSize::to_usize() {
-            return Err(CryptoError::InvalidEncoding);
+            return Err(CryptoError::InvalidLength { expected: Self::OutputSize::to_usize(), got: bytes.len() });
         }
         // Copy the bytes into a fixed-size array
         let arr = GenericArray::<u8, Self::OutputSize>::clone_from_slice(bytes);
         // We do not allow private keys to be 0. This is so that we can avoid checking the output
         // of the k256::kex() function (see docs there for more detail)
         let scalar = Scalar::from_bytes_reduced(&arr);
-        let nonzero_scalar = NonZeroScalar::new(scalar).ok_or(CryptoError::InvalidEncoding)?;
+        let nonzero_scalar = NonZeroScalar::new(scalar).ok_or(CryptoError::ScalarOutOfRange)?;
+        debug_assert!(!bool::from(nonzero_scalar.is_zero()), "NonZeroScalar must never be zero");
 
         Ok(Skk256(nonzero_scalar))
     }
 }
 
+impl Skk256 {
+    /// Returns `true` if `bytes` is a valid private key i.e. 32 bytes encoding a scalar in `[1, n - 1]`.
+    ///
+    /// Unlike `from_bytes` (which reduces the scalar mod `n`), scalars `>= n` are rejected - so this is the stricter
+    /// check to run over imported keys before constructing them.
+    pub fn is_valid_bytes(bytes: &[u8]) -> bool {
+        if bytes.len() != Self::size() {
+            return false;
+        }
+        let scalar: Option<Scalar> = Scalar::from_repr(GenericArray::clone_from_slice(bytes));
+        scalar.is_some_and(|scalar| !bool::from(scalar.is_zero()))
+    }
+
+    /// Same as `from_bytes` but without the reduction mod `n`: only the canonical encoding of a scalar in
+    /// `[1, n - 1]` is accepted, so every key has exactly one byte string that imports as it.
+    ///
+    /// - Returns `CryptoError::InvalidLength` for a wrong length and `CryptoError::ScalarOutOfRange` for `0` or a
+    ///   scalar `>= n`.
+    pub fn from_bytes_exact(bytes: &[u8]) -> Result<Self> {
+        if bytes.len() != Self::size() {
+            return Err(CryptoError::InvalidLength { expected: Self::size(), got: bytes.len() });
+        }
+        let scalar: Option<NonZeroScalar> = NonZeroScalar::from_repr(GenericArray::clone_from_slice(bytes));
+        scalar.map(Skk256).ok_or(CryptoError::ScalarOutOfRange)
+    }
+
+    /// A reasonable threshold for `from_bytes_strict` - a uniformly random scalar has fewer than 64 significant
+    /// bits with probability ~2^-192.
+    pub const DEFAULT_MIN_BITS: u32 = 64;
+
+    /// Same as `from_bytes` but additionally rejects scalars `k` where `k` or `n - k` has fewer than `min_bits`
+    /// significant bits, returning `CryptoError::WeakKey`. Both are equally easy to brute-force.
+    ///
+    /// This only catches obviously broken inputs (e.g. a key of `1` or an all-zero buffer with a stray byte);
+    /// it is not a substitute for generating keys from a proper source of entropy.
+    pub fn from_bytes_strict(bytes: &[u8], min_bits: u32) -> Result<Self> {
+        let sk = Skk256::from_bytes(bytes)?;
+        let scalar: Scalar = *sk.0;
+        if significant_bits(&scalar.to_bytes()) < min_bits
+            || significant_bits(&(-scalar).to_bytes()) < min_bits
+        {
+            return Err(CryptoError::WeakKey);
+        }
+        Ok(sk)
+    }
+}
+
+impl Skk256 {
+    /// Returns the key `(k + tweak) mod n`, where `tweak` is a big-endian integer (e.g. for BIP32 child key
+    /// derivation). Its public key is `Pkk256::add_tweak` of this key's public key.
+    ///
+    /// - Returns `CryptoError::ScalarOutOfRange` if `tweak >= n` or if the result is zero.
+    pub fn add_tweak(&self, tweak: &[u8; 32]) -> Result<Skk256> {
+        let tweaked = *self.0 + parse_tweak(tweak)?;
+        NonZeroScalar::new(tweaked).map(Skk256).ok_or(CryptoError::ScalarOutOfRange)
+    }
+}
+
+/// Parses a big-endian tweak, rejecting (rather than reducing) values `>= n` as BIP32 does.
+fn parse_tweak(tweak: &[u8; 32]) -> Result<Scalar> {
+    Scalar::from_repr(GenericArray::clone_from_slice(tweak)).ok_or(CryptoError::ScalarOutOfRange)
+}
+
+/// The length of a `Skk256::split` share: a 1 byte index and a 32 byte scalar.
+const SHARE_LEN: usize = 33;
+
+impl Skk256 {
+    /// Splits the key into `shares` Shamir shares over the scalar field GF(n), any `threshold` of which `combine`
+    /// back into this key. Fewer shares reveal nothing about it.
+    ///
+    /// Each share is `i || f(i)` (33 bytes): its index `i` in `1..=shares` and the big-endian value at `i` of a
+    /// random polynomial `f` of degree `threshold - 1` with `f(0)` = the private scalar.
+    ///
+    /// - Returns `CryptoError::ECCError` unless `1 <= threshold <= shares`.
+    ///
+    /// Note: the coefficients of `f` are generated from `rand::thread_rng()`, so this needs the `std` feature - see
+    /// `split_with_rng` otherwise.
+    #[cfg(feature = "std")]
+    pub fn split(&self, threshold: u8, shares: u8) -> Result<Vec<Vec<u8>>> {
+        self.split_with_rng(threshold, shares, &mut rand::thread_rng())
+    }
+
+    /// `split`, with the coefficients of `f` drawn from `rng`.
+    pub fn split_with_rng<R: RngCore + CryptoRng>(&self, threshold: u8, shares: u8, rng: &mut R) -> Result<Vec<Vec<u8>>> {
+        if threshold == 0 || threshold > shares {
+            return Err(CryptoError::ECCError);
+        }
+        let mut coefficients = Vec::with_capacity(threshold as usize);
+        coefficients.push(*self.0);
+        let mut bytes = GenericArray::default();
+        while coefficients.len() < threshold as usize {
+            rng.fill_bytes(&mut bytes);
+            if let Some(coefficient) = Scalar::from_repr(bytes) {
+                coefficients.push(coefficient);
+            }
+        }
+        bytes.as_mut_slice().zeroize();
+
+        let shares = (1..=shares)
+            .map(|i| {
+                // Horner's rule
+                let x = Scalar::from(i as u64);
+                let y = coefficients.iter().rev().fold(Scalar::zero(), |acc, coefficient| acc * x + coefficient);
+                let mut share = Vec::with_capacity(SHARE_LEN);
+                share.push(i);
+                share.extend_from_slice(&y.to_bytes());
+                share
+            })
+            .collect();
+        coefficients.zeroize();
+        Ok(shares)
+    }
+
+    /// Reconstructs a key from shares made by `split`, by Lagrange interpolation of `f(0)`.
+    ///
+    /// At least `threshold` shares of the same split give back the key (any extra ones are consistent with it).
+    /// Shares don't record the threshold, so fewer give an unrelated key rather than an error.
+    ///
+    /// - Returns `CryptoError::InvalidLength` for a share that isn't 33 bytes, `CryptoError::InvalidEncoding` for no
+    ///   shares, an index of 0, a repeated index or a value `>= n`, and `CryptoError::ScalarOutOfRange` if the
+    ///   result is zero.
+    pub fn combine(shares: &[Vec<u8>]) -> Result<Self> {
+        if shares.is_empty() {
+            return Err(CryptoError::InvalidEncoding);
+        }
+        let mut points: Vec<(Scalar, Scalar)> = Vec::with_capacity(shares.len());
+        for share in shares {
+            if share.len() != SHARE_LEN {
+                return Err(CryptoError::InvalidLength { expected: SHARE_LEN, got: share.len() });
+            }
+            let x = Scalar::from(share[0] as u64);
+            if share[0] == 0 || points.iter().any(|(x_j, _)| *x_j == x) {
+                return Err(CryptoError::InvalidEncoding);
+            }
+            let y = Scalar::from_repr(GenericArray::clone_from_slice(&share[1..])).ok_or(CryptoError::InvalidEncoding)?;
+            points.push((x, y));
+        }
+
+        // f(0) = sum of y_i * l_i(0), where l_i(0) = prod_{j != i} x_j / (x_j - x_i)
+        let mut secret = Scalar::zero();
+        for (i, (x_i, y_i)) in points.iter().enumerate() {
+            let (mut numerator, mut denominator) = (Scalar::one(), Scalar::one());
+            for (j, (x_j, _)) in points.iter().enumerate() {
+                if i != j {
+                    numerator *= x_j;
+                    denominator *= x_j - x_i;
+                }
+            }
+            // The indices are distinct and below n, so no denominator is zero
+            let inverse = Option::<Scalar>::from(denominator.invert()).ok_or(CryptoError::InvalidEncoding)?;
+            secret += y_i * &(numerator * inverse);
+        }
+        for (_, y) in points.iter_mut() {
+            y.zeroize();
+        }
+        NonZeroScalar::new(secret).map(Skk256).ok_or(CryptoError::ScalarOutOfRange)
+    }
+}
+
+/// The bit-length of a big-endian integer.
+fn significant_bits(bytes: &[u8]) -> u32 {
+    match bytes.iter().position(|b| *b != 0) {
+        Some(i) => (bytes.len() - i) as u32 * 8 - bytes[i].leading_zeros(),
+        None => 0,
+    }
+}
+
 /// A struct to hold the computed p-256 shared secret
+///
+/// It's never the point at infinity (see `new`), so it always has an x co-ordinate to serialize.
 #[derive(Debug, Clone, PartialEq)]
-pub struct SharedSecretk256(pub AffinePoint);
+pub struct SharedSecretk256(pub(crate) AffinePoint);
 
 /// We only need the x co-ordinate from the result (i.e. 32 bytes of a coordinate from an Affine Point.)
 impl ToBytes for SharedSecretk256 {
@@ -117,7 +463,53 @@ impl ToBytes for SharedSecretk256 {
     fn to_bytes(&self) -> GenericArray<u8, Self::OutputSize> {
         // §4.1: Representation of the KEX result is the serialization of the x-coordinate
         let bytes = k256::EncodedPoint::from(self.0);
-        GenericArray::<u8, Self::OutputSize>::clone_from_slice(bytes.x().unwrap())
+        GenericArray::<u8, Self::OutputSize>::clone_from_slice(bytes.x().expect("the identity is rejected by `new`"))
+    }
+}
+
+impl SharedSecretk256 {
+    /// Wraps a computed shared secret point.
+    ///
+    /// - Returns a `CryptoError::PointAtInfinity` for the identity, which has no x co-ordinate.
+    pub fn new(point: AffinePoint) -> Result<Self> {
+        if bool::from(point.is_identity()) {
+            return Err(CryptoError::PointAtInfinity);
+        }
+        Ok(SharedSecretk256(point))
+    }
+
+    /// Returns the shared secret point.
+    pub fn as_affine(&self) -> &AffinePoint {
+        &self.0
+    }
+
+    /// Runs the shared secret (i.e. its x co-ordinate) through HKDF-SHA256, filling `okm`.
+    pub fn derive_key(&self, salt: &[u8], info: &[u8], okm: &mut [u8]) {
+        let mut ikm = self.to_bytes();
+        hkdf_sha256(salt, &ikm, info, okm);
+        ikm.as_mut_slice().zeroize();
+    }
+
+    /// Derives a 32 byte key for a cipher such as AES-256-GCM or ChaCha20-Poly1305, as
+    /// `HKDF-SHA256(salt = "", ikm = shared secret, info = label)`.
+    ///
+    /// This is a convenience for the common case - call `derive_key` directly for a salt or a different key length.
+    pub fn to_cipher_key(&self, label: &[u8]) -> [u8; 32] {
+        let mut key = [0; 32];
+        self.derive_key(&[], label, &mut key);
+        key
+    }
+
+    /// Derives one subkey per entry of `outputs` (e.g. an encryption key, a MAC key and an IV), each of that many
+    /// bytes, with a single HKDF-SHA256 Extract over the shared secret (empty salt) and an Expand per subkey with
+    /// `info || i` (a 4 byte big-endian counter).
+    ///
+    /// Panics if a subkey is longer than `255 * 32` bytes.
+    pub fn expand_keys(&self, info: &[u8], outputs: &[usize]) -> Vec<Vec<u8>> {
+        let mut ikm = self.to_bytes();
+        let keys = hkdf_expand_keys::<Sha256>(&[], &ikm, info, outputs);
+        ikm.as_mut_slice().zeroize();
+        keys
     }
 }
 
@@ -130,12 +522,116 @@ pub trait KeyExchange {
     /// Shared Secret type
     type CompSecret: ToBytes;
 
+    /// The serialized length of `Self::SKey` in bytes i.e. its `ToBytes::size()`.
+    fn private_key_len() -> usize {
+        Self::SKey::size()
+    }
+    /// The serialized (uncompressed) length of `Self::PubKey` in bytes, for sizing buffers and checking incoming
+    /// lengths without naming the curve's types.
+    fn public_key_len() -> usize {
+        Self::PubKey::size()
+    }
+    /// The serialized length of `Self::CompSecret` in bytes.
+    fn shared_secret_len() -> usize {
+        Self::CompSecret::size()
+    }
+
     /// A function to generate a random private key, given a 32 byte seed value. 
+    ///
+    /// Caution: the key is only as unpredictable as the seed - any 32 bytes are accepted, including constant or
+    /// low-entropy ones. This is meant for reproducible tests; prefer `generate_private_key_with_rng` otherwise.
     fn generate_private_key(seed: [u8; 32]) -> Self::SKey;
+    /// Generates a private key from a seed drawn from `rng` - any `CryptoRng` works, so this doesn't depend on
+    /// `std` or `getrandom` (e.g. a `getrandom`-backed rng with the `js` feature on `wasm32-unknown-unknown`).
+    ///
+    /// The `CryptoRng` bound keeps non-cryptographic generators (e.g. `rand::rngs::mock::StepRng`) out.
+    fn generate_private_key_with_rng<R: RngCore + CryptoRng>(rng: &mut R) -> Self::SKey {
+        let mut seed = [0; 32];
+        rng.fill_bytes(&mut seed);
+        let sk = Self::generate_private_key(seed);
+        seed.zeroize();
+        sk
+    }
     /// A method to generate the public key, given a private key. 
     fn generate_public_key(sk: &Self::SKey) -> Self::PubKey;
+    /// Generates a keypair i.e. `generate_private_key` followed by `generate_public_key` - see the caution
+    /// there about the seed. The private key's bytes are one `to_bytes` away, for storage.
+    fn generate_keypair(seed: [u8; 32]) -> (Self::SKey, Self::PubKey) {
+        let sk = Self::generate_private_key(seed);
+        let pk = Self::generate_public_key(&sk);
+        (sk, pk)
+    }
     /// A method to compute the shared secret, given a private key and public key.
     fn generate_shared_secret(sk: &Self::SKey, pk: &Self::PubKey) -> Result<Self::CompSecret>;
+
+    /// Parses the peer's serialized public key (with the full validation of `Self::PubKey::from_bytes`) and computes
+    /// the shared secret - the common case of a key straight off the wire.
+    ///
+    /// - Returns the errors of `from_bytes` (e.g. `CryptoError::InvalidLength` or `CryptoError::PointNotOnCurve`)
+    ///   for a malformed key, else those of `generate_shared_secret`.
+    fn shared_secret_from_bytes(sk: &Self::SKey, peer_pk_bytes: &[u8]) -> Result<Self::CompSecret> {
+        let peer_pk = Self::PubKey::from_bytes(peer_pk_bytes)?;
+        Self::generate_shared_secret(sk, &peer_pk)
+    }
+
+    /// Computes the shared secret and serializes it in one step (32 bytes for secp256k1, 48 for P-384 and 66 for
+    /// P-521), for code that is generic over the curve.
+    fn shared_secret_bytes(sk: &Self::SKey, pk: &Self::PubKey) -> Result<Vec<u8>> {
+        Ok(Self::generate_shared_secret(sk, pk)?.to_bytes().to_vec())
+    }
+
+    /// Computes the shared secret and derives a 32 byte key from it that is bound to `label` and to the two
+    /// public keys involved, as `HKDF-SHA256(salt = "", ikm = shared secret, info = label || pk_lo || pk_hi)`.
+    ///
+    /// `pk_lo`/`pk_hi` are the serialized public keys in lexicographic order, so both parties get the same key
+    /// without agreeing on roles. Give every DH in a handshake its own label (e.g. `"es"` for ephemeral-static and
+    /// `"ss"` for static-static): the keys are then independent, even though the same static key is involved.
+    fn generate_shared_secret_labeled(sk: &Self::SKey, pk: &Self::PubKey, label: &[u8]) -> Result<Vec<u8>> {
+        let mut shared_secret = Self::generate_shared_secret(sk, pk)?.to_bytes();
+        let our_pk = Self::generate_public_key(sk).to_bytes();
+        let their_pk = pk.to_bytes();
+        let (lo, hi) = if our_pk.as_slice() <= their_pk.as_slice() {
+            (&our_pk, &their_pk)
+        } else {
+            (&their_pk, &our_pk)
+        };
+
+        let mut info = Vec::with_capacity(label.len() + 2 * lo.len());
+        info.extend_from_slice(label);
+        info.extend_from_slice(lo);
+        info.extend_from_slice(hi);
+        let mut key = alloc::vec![0; 32];
+        hkdf_sha256(&[], &shared_secret, &info, &mut key);
+        shared_secret.as_mut_slice().zeroize();
+        Ok(key)
+    }
+
+    /// Derives the compressed SEC1 encoding of `sk`'s public key (`0x02`/`0x03 || x` - 33 bytes for secp256k1,
+    /// 49 for P-384 and 67 for P-521), e.g. as a compact identity for the key's owner.
+    fn public_key_compressed(sk: &Self::SKey) -> Vec<u8> {
+        compress(&Self::generate_public_key(sk).to_bytes())
+    }
+
+    /// Computes the shared secret between one private key and each of `pks` (e.g. one ephemeral key and the
+    /// members of a group), in order. A bad public key only fails its own entry.
+    fn shared_secret_many(sk: &Self::SKey, pks: &[Self::PubKey]) -> Vec<Result<Self::CompSecret>> {
+        pks.iter().map(|pk| Self::generate_shared_secret(sk, pk)).collect()
+    }
+}
+
+/// Generates a keypair for the curve `C`, given a 32 byte seed value.
+///
+/// This is shorthand for `C::generate_keypair` i.e. `C::generate_private_key` followed by `C::generate_public_key` -
+/// see the caution there about the seed.
+pub fn keypair<C: KeyExchange>(seed: [u8; 32]) -> (C::SKey, C::PubKey) {
+    C::generate_keypair(seed)
+}
+
+/// Computes the shared secret for the curve `C`, given our private key and the other party's public key.
+///
+/// This is shorthand for `C::generate_shared_secret`.
+pub fn ecdh<C: KeyExchange>(sk: &C::SKey, pk: &C::PubKey) -> Result<C::CompSecret> {
+    C::generate_shared_secret(sk, pk)
 }
 /// A struct that represents the ECDH implementation for the p-256 curve 
 pub struct ECDHNISTK256;
@@ -155,7 +651,11 @@ impl KeyExchange for ECDHNISTK256 {
 
     fn generate_public_key(sk: &Self::SKey) -> Self::PubKey {
         let affine_pub_key = AffinePoint::generator().mul(sk.0);
-        Pkk256(PublicKey::from_affine(affine_pub_key).expect("Failed to derive public key"))
+        // `from_affine` only rejects the identity, which `k * G` can't be: `Skk256` wraps a `NonZeroScalar` i.e.
+        // `k` is in `[1, n - 1]`, and `G` has prime order `n`, so `k * G` is the identity only for `k = 0 (mod n)`.
+        Pkk256(PublicKey::from_affine(affine_pub_key).unwrap_or_else(|_| {
+            unreachable!("a non-zero scalar times the generator is never the identity")
+        }))
     }
 
     fn generate_shared_secret(
@@ -163,19 +663,27 @@ impl KeyExchange for ECDHNISTK256 {
         others_pk: &Self::PubKey,
     ) -> Result<Self::CompSecret> {
         let shared_secret = others_pk.0.as_affine().mul(sk.0);
-        Ok(SharedSecretk256(shared_secret))
+        // `new` rejects the identity, though it can't currently come up: `sk` is a non-zero scalar (mod n) and
+        // `others_pk` is never the identity (`k256::PublicKey` can't hold it and SEC1 has no uncompressed encoding
+        // for it). As secp256k1 has prime order, every other point has order n, so `sk * pk` is never the identity.
+        SharedSecretk256::new(shared_secret)
     }
 }
 
 /// An ECDH-P384 private key is simply a scalar in the NIST P-384 field.
-#[derive(Debug, Clone)]
+#[derive(Clone)]
 pub struct SkP384(P384Secret);
 /// An ECDH-P384 public key. This is derived from the private key using scalar point multiplication.
+///
+/// Note: the derived `PartialEq` compares the wrapped SEC1 bytes, so the same key in compressed and uncompressed
+/// form compares unequal (and it isn't constant-time) - use `same_as` to compare the points themselves.
 #[derive(Debug, Clone, PartialEq)]
 pub struct PkP384(pub PubKey<NistP384>);
 /// A struct to hold the computed p-384 shared secret
+///
+/// It's never the point at infinity (see `new`), so it always has an x co-ordinate to serialize.
 #[derive(Debug, Clone, PartialEq)]
-pub struct SharedSecretP384(pub PubKey<NistP384>);
+pub struct SharedSecretP384(pub(crate) PubKey<NistP384>);
 
 // Everything is serialized and deserialized in uncompressed form
 impl ToBytes for PkP384 {
@@ -190,20 +698,161 @@ impl ToBytes for PkP384 {
 
 // Everything is serialized and deserialized in uncompressed form
 impl FromBytes for PkP384 {
+    /// Parses the uncompressed form `0x04 || x || y`, or a SEC1 hybrid form (`0x06`/`0x07 || x || y`) whose tag
+    /// matches the parity of y - which is then held as `0x04`, so it re-encodes uncompressed.
     fn from_bytes(bytes: &[u8]) -> Result<Self> {
         // In order to parse as an uncompressed curve point, we first make sure the input length is
         // correct. This also ensures we're receiving the uncompressed representation.
         if bytes.len() != Self::OutputSize::to_usize() {
-            return Err(CryptoError::InvalidEncoding);
+            return Err(CryptoError::InvalidLength { expected: Self::OutputSize::to_usize(), got: bytes.len() });
         }
+        let mut uncompressed = GenericArray::<u8, Self::OutputSize>::clone_from_slice(bytes);
+        from_hybrid(&mut uncompressed)?;
         // Now just call the routine exposed by the k256 crate. This preserves the
         // invariant that public keys can't be the point at infinity, since the point at infinity
         // has no representation as a SEC1 bytestring.
-        let parsed = PubKey::from_bytes(bytes).map_err(|_| CryptoError::InvalidEncoding)?;
+        let parsed = PubKey::from_bytes(uncompressed).map_err(|_| CryptoError::InvalidEncoding)?;
+        // `p384` doesn't validate the co-ordinates, so we check `x, y < p` and that the point is on the curve
+        // ourselves - otherwise a point on a weaker curve could leak the private key (invalid-curve attack).
+        // `(0, 0)` reads as the identity, which is rejected too.
+        MyAffinePoint::<P384Params>::from_encoded_point(&parsed)?;
         Ok(PkP384(parsed))
     }
 }
 
+/// Derives the public key, i.e. `ECDHNISTP384::generate_public_key`.
+impl From<&SkP384> for PkP384 {
+    fn from(sk: &SkP384) -> Self {
+        ECDHNISTP384::<48>::generate_public_key(sk)
+    }
+}
+
+impl PkP384 {
+    /// Parses the untagged (`x || y`, 96 bytes) form.
+    ///
+    /// - Returns the errors of `from_bytes` e.g. `CryptoError::PointNotOnCurve`.
+    pub fn from_untagged_bytes(bytes: &[u8; 96]) -> Result<Self> {
+        let mut tagged = [0x04; 97];
+        tagged[1..].copy_from_slice(bytes);
+        PkP384::from_bytes(&tagged)
+    }
+
+    /// The untagged form i.e. the uncompressed encoding without its `0x04` tag.
+    pub fn to_untagged_bytes(&self) -> [u8; 96] {
+        let mut untagged = [0; 96];
+        untagged.copy_from_slice(&self.to_bytes()[1..]);
+        untagged
+    }
+
+    /// Whether the key's y co-ordinate is odd, i.e. the parity that the compressed form's `0x02`/`0x03` tag
+    /// encodes.
+    ///
+    /// A key that was wrapped by hand in compressed form gives the parity of its tag, and the identity (which
+    /// `from_bytes` never produces) gives `false`.
+    pub fn y_is_odd(&self) -> bool {
+        match self.0.y() {
+            Some(y) => y[y.len() - 1] & 1 == 1,
+            None => self.0.as_bytes()[0] == 0x03,
+        }
+    }
+
+    /// How many multiples of the generator (`±G, ±2G, ...`) `from_bytes_strict` rejects.
+    pub const WEAK_MULTIPLES: u32 = 16;
+
+    /// Same as `from_bytes` but additionally rejects "suspicious" points, returning `CryptoError::WeakKey`:
+    ///
+    /// - `±kG` for `1 <= k <= WEAK_MULTIPLES`, whose private keys are trivially known
+    ///
+    /// P-384 has a cofactor of 1, so there are no other small-order points to reject.
+    pub fn from_bytes_strict(bytes: &[u8]) -> Result<Self> {
+        let pk = PkP384::from_bytes(bytes)?;
+        let point = MyAffinePoint::<P384Params>::from_encoded_point(&pk.0)?;
+        // `kG` and `-kG` share an x co-ordinate
+        let generator = MyAffinePoint::<P384Params>::generator();
+        let mut multiple = generator.clone();
+        for _ in 0..Self::WEAK_MULTIPLES {
+            if multiple.x == point.x {
+                return Err(CryptoError::WeakKey);
+            }
+            multiple = multiple.do_the_math(generator.clone());
+        }
+        Ok(pk)
+    }
+
+    /// Checks whether `other` is the same point as this key, independently of how either was encoded (i.e.
+    /// a compressed key is decompressed first), in constant time.
+    ///
+    /// Returns `false` if either key isn't a valid SEC1 encoding of a point on the curve.
+    pub fn same_as(&self, other: &PkP384) -> bool {
+        match (PkP384::from_sec1_bytes(self.0.as_bytes()), PkP384::from_sec1_bytes(other.0.as_bytes())) {
+            (Ok(ours), Ok(theirs)) => bool::from(ours.0.as_bytes().ct_eq(theirs.0.as_bytes())),
+            _ => false,
+        }
+    }
+
+    /// Parses a SEC1 encoded public key in any form: uncompressed (`0x04 || x || y`, 97 bytes), hybrid
+    /// (`0x06`/`0x07 || x || y`, see `from_bytes`) or compressed (`0x02`/`0x03 || x`, 49 bytes). Compressed keys are
+    /// decompressed with the curve equation, so the key behaves exactly as if it had been received uncompressed.
+    ///
+    /// - Returns `CryptoError::PointAtInfinity` for the 1 byte identity encoding, `CryptoError::InvalidLength`
+    ///   if the length doesn't match the tag and the errors of `from_bytes` otherwise.
+    pub fn from_sec1_bytes(bytes: &[u8]) -> Result<Self> {
+        let uncompressed_len = <Self as ToBytes>::OutputSize::to_usize();
+        let expected = match bytes.first() {
+            Some(0x04) | Some(0x06) | Some(0x07) => uncompressed_len,
+            Some(0x02) | Some(0x03) => 1 + P384Params::byte_len(),
+            Some(0x00) if bytes.len() == 1 => return Err(CryptoError::PointAtInfinity),
+            Some(_) => return Err(CryptoError::InvalidEncoding),
+            None => return Err(CryptoError::InvalidLength { expected: uncompressed_len, got: 0 }),
+        };
+        if bytes.len() != expected {
+            return Err(CryptoError::InvalidLength { expected, got: bytes.len() });
+        }
+        PkP384::from_bytes(&decompress::<P384Params>(bytes)?)
+    }
+
+    /// Checks that this key's serialized form interoperates with the `p384` crate, in both directions:
+    ///
+    /// - our encoding parses with a bare `p384::EncodedPoint::from_bytes` and re-encodes to the same bytes
+    /// - `p384`'s own encoding of the key's affine co-ordinates parses back (via `from_bytes`) to this key
+    ///
+    /// Returns `CryptoError::InvalidEncoding` if the two ever diverge.
+    pub fn assert_rustcrypto_compatible(&self) -> Result<()> {
+        let ours = self.to_bytes();
+        let parsed =
+            p384::EncodedPoint::from_bytes(ours.as_slice()).map_err(|_| CryptoError::InvalidEncoding)?;
+        let (x, y) = match (parsed.x(), parsed.y()) {
+            (Some(x), Some(y)) => (x, y),
+            _ => return Err(CryptoError::InvalidEncoding),
+        };
+        let theirs = p384::EncodedPoint::from_affine_coordinates(x, y, false);
+        if parsed.as_bytes() != ours.as_slice()
+            || theirs.as_bytes() != ours.as_slice()
+            || PkP384::from_bytes(theirs.as_bytes())? != *self
+        {
+            return Err(CryptoError::InvalidEncoding);
+        }
+        Ok(())
+    }
+}
+
+/// Prints `SkP384(REDACTED)` so that the private scalar never ends up in logs.
+impl fmt::Debug for SkP384 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("SkP384(REDACTED)")
+    }
+}
+
+#[cfg(feature = "dangerous-debug")]
+impl SkP384 {
+    /// Returns a `Debug` representation that includes the raw private scalar (as hex).
+    ///
+    /// Caution: only meant for debugging - never log the output of this function.
+    pub fn expose_debug(&self) -> String {
+        alloc::format!("SkP384({})", self.to_hex())
+    }
+}
+
 impl ToBytes for SkP384 {
     // A fancy way of saying "48 bytes"
     type OutputSize = <NistP384 as Curve>::FieldSize;
@@ -221,12 +870,28 @@ impl FromBytes for SkP384 {
     fn from_bytes(bytes: &[u8]) -> Result<Self> {
         // Check the length
         if bytes.len() != Self::OutputSize::to_usize() {
-            return Err(CryptoError::InvalidEncoding);
+            return Err(CryptoError::InvalidLength { expected: Self::OutputSize::to_usize(), got: bytes.len() });
+        }
+
+        // `p384` doesn't range-check the scalar, so we make sure it is in `[1, n - 1]` ourselves
+        let (_, _, _, g_ord) = p384_constants();
+        let scalar = BigInt::from_bytes_be(Sign::Plus, bytes);
+        if scalar.is_zero() || &scalar >= g_ord {
+            return Err(CryptoError::ScalarOutOfRange);
         }
+        let secret = P384Secret::from_bytes(bytes).map_err(|_| CryptoError::ScalarOutOfRange)?;
+        Ok(SkP384(secret))
+    }
+}
 
-        Ok(SkP384(
-            P384Secret::from_bytes(bytes).expect("Failed to deserialize raw private scalar"),
-        ))
+impl SkP384 {
+    /// Returns `true` if `bytes` is a valid private key i.e. 48 bytes encoding a scalar in `[1, n - 1]` - the same
+    /// check as `from_bytes`, without building the key (or a `BigInt`).
+    pub fn is_valid_bytes(bytes: &[u8]) -> bool {
+        // Big-endian and of equal length, so comparing the bytes compares the integers
+        bytes.len() == Self::size()
+            && bytes.iter().any(|byte| *byte != 0)
+            && bytes < &p384_group_order_bytes()[..]
     }
 }
 
@@ -237,7 +902,53 @@ impl ToBytes for SharedSecretP384 {
     fn to_bytes(&self) -> GenericArray<u8, Self::OutputSize> {
         // §4.1: Representation of the KEX result is the serialization of the x-coordinate
         let bytes = p384::EncodedPoint::from(self.0);
-        GenericArray::<u8, Self::OutputSize>::clone_from_slice(bytes.x().unwrap())
+        GenericArray::<u8, Self::OutputSize>::clone_from_slice(bytes.x().expect("the identity is rejected by `new`"))
+    }
+}
+
+impl SharedSecretP384 {
+    /// Wraps a computed shared secret point, in SEC1 form.
+    ///
+    /// - Returns a `CryptoError::PointAtInfinity` for the identity, which has no x co-ordinate.
+    pub fn new(point: PubKey<NistP384>) -> Result<Self> {
+        if point.is_identity() {
+            return Err(CryptoError::PointAtInfinity);
+        }
+        Ok(SharedSecretP384(point))
+    }
+
+    /// Returns the shared secret point, in SEC1 form.
+    pub fn as_encoded_point(&self) -> &PubKey<NistP384> {
+        &self.0
+    }
+
+    /// Runs the shared secret (i.e. its x co-ordinate) through HKDF-SHA256, filling `okm`.
+    pub fn derive_key(&self, salt: &[u8], info: &[u8], okm: &mut [u8]) {
+        let mut ikm = self.to_bytes();
+        hkdf_sha256(salt, &ikm, info, okm);
+        ikm.as_mut_slice().zeroize();
+    }
+
+    /// Derives a 32 byte key for a cipher such as AES-256-GCM or ChaCha20-Poly1305, as
+    /// `HKDF-SHA256(salt = "", ikm = shared secret, info = label)`.
+    ///
+    /// This is a convenience for the common case - call `derive_key` directly for a salt or a different key length.
+    pub fn to_cipher_key(&self, label: &[u8]) -> [u8; 32] {
+        let mut key = [0; 32];
+        self.derive_key(&[], label, &mut key);
+        key
+    }
+
+    /// Derives one subkey per entry of `outputs` (e.g. an encryption key, a MAC key and an IV), each of that many
+    /// bytes, with a single HKDF-SHA384 Extract over the shared secret (empty salt) and an Expand per subkey with
+    /// `info || i` (a 4 byte big-endian counter).
+    ///
+    /// Panics if a subkey is longer than `255 * 48` bytes.
+    pub fn expand_keys(&self, info: &[u8], outputs: &[usize]) -> Vec<Vec<u8>> {
+        let mut ikm = self.to_bytes();
+        let keys = hkdf_expand_keys::<Sha384>(&[], &ikm, info, outputs);
+        ikm.as_mut_slice().zeroize();
+        keys
     }
 }
 
@@ -253,75 +964,196 @@ impl<const N: usize> KeyExchange for ECDHNISTP384<N> {
         let mut rng = ChaCha20Rng::from_seed(seed); // test seed value.
         let mut dest = [0; N];
         rng.fill_bytes(&mut dest);
-        SkP384(P384Secret::from_bytes(&dest).expect("Failed to generate a `P384` private key"))
+        SkP384(P384Secret::from_bytes(dest).expect("Failed to generate a `P384` private key"))
     }
 
     fn generate_public_key(sk: &Self::SKey) -> Self::PubKey {
-        let mod_prime =
-            dh::unhexlify_to_bytearray::<N>(&constants::ECDH_NIST_384_MODP.replace("0x", ""));
-        let b_val =
-            dh::unhexlify_to_bytearray::<N>(&constants::ECDH_NIST_384_B_VAL.replace("0x", ""));
-
-        let a = BigInt::from(-3);
-        let b = BigInt::from_bytes_be(Sign::Plus, &b_val);
-        let modp = BigInt::from_bytes_be(Sign::Plus, &mod_prime);
-
-        let gen = MyAffinePoint::<N>::generator();
-        let pk = match gen {
-            APTypes::P384(gen) => {
-                let pub_key = MyAffinePoint::<48>::double_and_add(
-                    gen,
-                    BigUint::from_bytes_be(sk.clone().to_bytes().as_slice()),
-                    &a,
-                    &b,
-                    &modp,
-                );
-                if let EncodedTypes::EncodedTypeP384(pubkey) = pub_key.to_uncompressed_bytes(false) {
-                    pubkey
-                } else {
-                    unreachable!() // technically, should be unreachable
-                }
-            }
-            _ => unreachable!(),
-        };
-        pk
+        let pub_key = MyAffinePoint::<P384Params>::mul_generator(BigUint::from_bytes_be(sk.clone().to_bytes().as_slice()));
+        if let EncodedTypes::EncodedTypeP384(pubkey) = pub_key.to_uncompressed_bytes(false) {
+            pubkey
+        } else {
+            unreachable!() // technically, should be unreachable
+        }
     }
 
     fn generate_shared_secret(
         sk: &Self::SKey,
         others_pk: &Self::PubKey,
     ) -> Result<Self::CompSecret> {
-        let mod_prime =
-            dh::unhexlify_to_bytearray::<N>(&constants::ECDH_NIST_384_MODP.replace("0x", ""));
-        let b_val =
-            dh::unhexlify_to_bytearray::<N>(&constants::ECDH_NIST_384_B_VAL.replace("0x", ""));
+        p384_shared_secret(&BigUint::from_bytes_be(sk.clone().to_bytes().as_slice()), others_pk)
+    }
 
-        let a = BigInt::from(-3);
-        let b = BigInt::from_bytes_be(Sign::Plus, &b_val);
-        let modp = BigInt::from_bytes_be(Sign::Plus, &mod_prime);
+    // The scalar is only converted to a `BigUint` once (the curve constants are cached anyway)
+    fn shared_secret_many(sk: &Self::SKey, pks: &[Self::PubKey]) -> Vec<Result<Self::CompSecret>> {
+        let k = BigUint::from_bytes_be(sk.clone().to_bytes().as_slice());
+        pks.iter().map(|pk| p384_shared_secret(&k, pk)).collect()
+    }
+}
 
-        if others_pk.0.as_bytes().len() != 97 {
-            panic!()
-        };
-        let pk: [u8; 97] = others_pk
-            .0
-            .as_bytes()
-            .try_into()
-            .expect("failed to serialize `EncodedPoint`");
-        let affine_pt = MyAffinePoint {
-            x: BigInt::from_bytes_be(Sign::Plus, &pk[1..N + 1]),
-            y: BigInt::from_bytes_be(Sign::Plus, &pk[N + 1..97]),
-            infinity: false,
-        };
+/// `k * others_pk`, for a private scalar `k`.
+fn p384_shared_secret(k: &BigUint, others_pk: &PkP384) -> Result<SharedSecretP384> {
+    // `PkP384`'s field is public, so a key that never went through `from_bytes` is re-validated here - the
+    // co-ordinates are read straight off the `EncodedPoint`
+    let affine_pt = MyAffinePoint::<P384Params>::from_encoded_point(&others_pk.0)?;
+
+    let shared_secret = MyAffinePoint::double_and_add(affine_pt, k.clone());
+    // A peer point of small order (or one off the curve) can drive the multiplication to the
+    // point at infinity, which has no x co-ordinate to serialize. Reject it rather than
+    // completing the exchange with a degenerate secret.
+    if shared_secret.is_identity() {
+        return Err(CryptoError::PointAtInfinity);
+    }
+    if let EncodedTypes::EncodedTypeP384_SS(sharedsecret) = shared_secret.to_uncompressed_bytes(true)
+    {
+        Ok(sharedsecret)
+    } else {
+        unreachable!() // technically, should be unreachable
+    }
+}
+
+
+/// An ECDH-P521 private key is simply a scalar in the NIST P-521 field (66 bytes, big-endian).
+#[derive(Clone)]
+pub struct SkP521(GenericArray<u8, typenum::U66>);
+
+impl Drop for SkP521 {
+    fn drop(&mut self) {
+        self.0.as_mut_slice().zeroize();
+    }
+}
+/// An ECDH-P521 public key, held in its uncompressed SEC1 form i.e. `0x04 || x || y` (133 bytes).
+#[derive(Debug, Clone, PartialEq)]
+pub struct PkP521(pub GenericArray<u8, typenum::U133>);
+/// A struct to hold the computed p-521 shared secret (i.e. the x co-ordinate)
+#[derive(Debug, Clone, PartialEq)]
+pub struct SharedSecretP521(pub GenericArray<u8, typenum::U66>);
+
+/// Prints `SkP521(REDACTED)` so that the private scalar never ends up in logs.
+impl fmt::Debug for SkP521 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("SkP521(REDACTED)")
+    }
+}
+
+#[cfg(feature = "dangerous-debug")]
+impl SkP521 {
+    /// Returns a `Debug` representation that includes the raw private scalar (as hex).
+    ///
+    /// Caution: only meant for debugging - never log the output of this function.
+    pub fn expose_debug(&self) -> String {
+        alloc::format!("SkP521({})", self.to_hex())
+    }
+}
+
+impl ToBytes for SkP521 {
+    type OutputSize = typenum::U66;
+
+    fn to_bytes(&self) -> GenericArray<u8, Self::OutputSize> {
+        self.0
+    }
+}
+
+impl FromBytes for SkP521 {
+    fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        if bytes.len() != Self::OutputSize::to_usize() {
+            return Err(CryptoError::InvalidLength { expected: Self::OutputSize::to_usize(), got: bytes.len() });
+        }
+        // The scalar must be in `[1, n - 1]`
+        let (_, _, _, g_ord) = p521_constants();
+        let scalar = BigInt::from_bytes_be(Sign::Plus, bytes);
+        if scalar.is_zero() || &scalar >= g_ord {
+            return Err(CryptoError::ScalarOutOfRange);
+        }
+        Ok(SkP521(GenericArray::clone_from_slice(bytes)))
+    }
+}
 
-        let shared_secret = MyAffinePoint::<48>::double_and_add(
-            affine_pt,
-            BigUint::from_bytes_be(sk.clone().to_bytes().as_slice()),
-            &a,
-            &b,
-            &modp,
+// Everything is serialized and deserialized in uncompressed form
+impl ToBytes for PkP521 {
+    // 1 + 2 * 66 bytes
+    type OutputSize = typenum::U133;
+
+    fn to_bytes(&self) -> GenericArray<u8, Self::OutputSize> {
+        self.0
+    }
+}
+
+impl FromBytes for PkP521 {
+    fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        // Only the uncompressed form `0x04 || x || y` is accepted.
+        if bytes.len() != Self::OutputSize::to_usize() {
+            return Err(CryptoError::InvalidLength { expected: Self::OutputSize::to_usize(), got: bytes.len() });
+        }
+        if bytes[0] != 0x04 {
+            return Err(CryptoError::InvalidEncoding);
+        }
+        p521_affine(bytes)?;
+        Ok(PkP521(GenericArray::clone_from_slice(bytes)))
+    }
+}
+
+/// Derives the public key, i.e. `ECDHNISTP521::generate_public_key`.
+impl From<&SkP521> for PkP521 {
+    fn from(sk: &SkP521) -> Self {
+        ECDHNISTP521::generate_public_key(sk)
+    }
+}
+
+impl ToBytes for SharedSecretP521 {
+    type OutputSize = typenum::U66;
+
+    fn to_bytes(&self) -> GenericArray<u8, Self::OutputSize> {
+        // §4.1: Representation of the KEX result is the serialization of the x-coordinate
+        self.0
+    }
+}
+
+/// A struct that represents the ECDH implementation for the p-521 curve. It uses the same affine-point
+/// arithmetic as p-384, as RustCrypto has no p-521 arithmetic yet.
+pub struct ECDHNISTP521;
+
+impl KeyExchange for ECDHNISTP521 {
+    type SKey = SkP521;
+    type PubKey = PkP521;
+    type CompSecret = SharedSecretP521;
+
+    fn generate_private_key(seed: [u8; 32]) -> Self::SKey {
+        let mut rng = ChaCha20Rng::from_seed(seed); // test seed value.
+        let mut dest = [0; 66];
+        // Only the low bit of the first byte is used - resample until we land in `[1, n - 1]`
+        loop {
+            rng.fill_bytes(&mut dest);
+            dest[0] &= 0x01;
+            if let Ok(sk) = SkP521::from_bytes(&dest) {
+                dest.zeroize();
+                return sk;
+            }
+        }
+    }
+
+    fn generate_public_key(sk: &Self::SKey) -> Self::PubKey {
+        let pub_key = MyAffinePoint::<P521Params>::double_and_add(
+            MyAffinePoint::generator(),
+            BigUint::from_bytes_be(sk.0.as_slice()),
         );
-        if let EncodedTypes::EncodedTypeP384_SS(sharedsecret) = shared_secret.to_uncompressed_bytes(true)
+        if let EncodedTypes::EncodedTypeP521(pubkey) = pub_key.to_uncompressed_bytes(false) {
+            pubkey
+        } else {
+            unreachable!() // technically, should be unreachable
+        }
+    }
+
+    fn generate_shared_secret(
+        sk: &Self::SKey,
+        others_pk: &Self::PubKey,
+    ) -> Result<Self::CompSecret> {
+        // The field is public, so a key that never went through `from_bytes` is re-validated here
+        let affine_pt = p521_affine(&others_pk.0)?;
+        let shared_secret = MyAffinePoint::double_and_add(affine_pt, BigUint::from_bytes_be(sk.0.as_slice()));
+        if shared_secret.is_identity() {
+            return Err(CryptoError::PointAtInfinity);
+        }
+        if let EncodedTypes::EncodedTypeP521_SS(sharedsecret) = shared_secret.to_uncompressed_bytes(true)
         {
             Ok(sharedsecret)
         } else {
@@ -330,3 +1162,522 @@ impl<const N: usize> KeyExchange for ECDHNISTP384<N> {
     }
 }
 
+/// Parses and validates (see `MyAffinePoint::validate`) the co-ordinates of an uncompressed P-521 point: the 7 spare
+/// top bits of each 66 byte co-ordinate must be zero (i.e. `x, y < p`), the point must be on the curve and `(0, 0)`
+/// (the identity) is rejected.
+fn p521_affine(bytes: &[u8]) -> Result<MyAffinePoint<P521Params>> {
+    let point = MyAffinePoint::new(
+        BigInt::from_bytes_be(Sign::Plus, &bytes[1..67]),
+        BigInt::from_bytes_be(Sign::Plus, &bytes[67..]),
+    );
+    let (a, b, modp, _) = p521_constants();
+    point.validate(a, b, modp)?;
+    Ok(point)
+}
+
+/// A public key for any of the supported curves, as returned by `read_public_key`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum CurvePublicKey {
+    /// A secp256k1 public key
+    K256(Pkk256),
+    /// A NIST P-384 public key
+    P384(PkP384),
+    /// A NIST P-521 public key
+    P521(PkP521),
+}
+
+impl CurvePublicKey {
+    /// The curve this key belongs to
+    pub fn curve_id(&self) -> CurveId {
+        match self {
+            CurvePublicKey::K256(_) => CurveId::Secp256k1,
+            CurvePublicKey::P384(_) => CurveId::NistP384,
+            CurvePublicKey::P521(_) => CurveId::NistP521,
+        }
+    }
+
+    /// The uncompressed SEC1 encoding, `0x04 || x || y`
+    pub fn to_uncompressed_bytes(&self) -> Vec<u8> {
+        match self {
+            CurvePublicKey::K256(pk) => pk.to_bytes().to_vec(),
+            CurvePublicKey::P384(pk) => pk.to_bytes().to_vec(),
+            CurvePublicKey::P521(pk) => pk.to_bytes().to_vec(),
+        }
+    }
+
+    /// The compressed SEC1 encoding, `0x02 || x` for an even y or `0x03 || x` for an odd one
+    pub fn to_compressed_bytes(&self) -> Vec<u8> {
+        compress(&self.to_uncompressed_bytes())
+    }
+
+    /// A short identifier for the key: the hex SHA-256 digest of its uncompressed encoding
+    pub fn fingerprint(&self) -> String {
+        encoding::to_hex(&SHA256Digest.digest(&self.to_uncompressed_bytes()))
+    }
+}
+
+impl ToSec1Bytes for Pkk256 {
+    fn to_sec1_bytes(&self, compress: bool) -> Vec<u8> {
+        k256::EncodedPoint::encode(self.0, compress).as_bytes().to_vec()
+    }
+}
+
+impl ToSec1Bytes for PkP384 {
+    fn to_sec1_bytes(&self, compress: bool) -> Vec<u8> {
+        sec1_bytes(&self.to_bytes(), compress)
+    }
+}
+
+impl ToSec1Bytes for PkP521 {
+    fn to_sec1_bytes(&self, compress: bool) -> Vec<u8> {
+        sec1_bytes(&self.to_bytes(), compress)
+    }
+}
+
+impl ToSec1Bytes for CurvePublicKey {
+    fn to_sec1_bytes(&self, compress: bool) -> Vec<u8> {
+        match self {
+            CurvePublicKey::K256(pk) => pk.to_sec1_bytes(compress),
+            CurvePublicKey::P384(pk) => pk.to_sec1_bytes(compress),
+            CurvePublicKey::P521(pk) => pk.to_sec1_bytes(compress),
+        }
+    }
+}
+
+/// Picks the SEC1 form of an uncompressed encoding.
+fn sec1_bytes(uncompressed: &[u8], compress_point: bool) -> Vec<u8> {
+    if compress_point {
+        compress(uncompressed)
+    } else {
+        uncompressed.to_vec()
+    }
+}
+
+/// A private key together with its public key, for any of the supported curves.
+#[derive(Clone)]
+pub enum KeyPair {
+    /// A secp256k1 keypair
+    K256(Skk256, Pkk256),
+    /// A NIST P-384 keypair
+    P384(SkP384, PkP384),
+    /// A NIST P-521 keypair
+    P521(SkP521, PkP521),
+}
+
+/// Every form of a keypair's public key (and, with `dangerous-debug`, its secret) - as returned by `KeyPair::describe`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct KeyInfo {
+    /// The curve of the key
+    pub curve_id: CurveId,
+    /// The uncompressed SEC1 encoding of the public key
+    pub uncompressed: Vec<u8>,
+    /// The compressed SEC1 encoding of the public key
+    pub compressed: Vec<u8>,
+    /// The uncompressed public key, hex encoded
+    pub hex: String,
+    /// See `CurvePublicKey::fingerprint`
+    pub fingerprint: String,
+    /// The raw private scalar, hex encoded. Treat it with the same care as the key itself.
+    #[cfg(feature = "dangerous-debug")]
+    pub secret_hex: String,
+}
+
+impl KeyPair {
+    /// Generates a keypair for `curve_id`, given a 32 byte seed value.
+    pub fn generate(curve_id: CurveId, seed: [u8; 32]) -> Self {
+        match curve_id {
+            CurveId::Secp256k1 => {
+                let (sk, pk) = keypair::<ECDHNISTK256>(seed);
+                KeyPair::K256(sk, pk)
+            }
+            CurveId::NistP384 => {
+                let (sk, pk) = keypair::<ECDHNISTP384<48>>(seed);
+                KeyPair::P384(sk, pk)
+            }
+            CurveId::NistP521 => {
+                let (sk, pk) = keypair::<ECDHNISTP521>(seed);
+                KeyPair::P521(sk, pk)
+            }
+        }
+    }
+
+    /// The curve this keypair belongs to
+    pub fn curve_id(&self) -> CurveId {
+        self.public_key().curve_id()
+    }
+
+    /// The public half of the keypair
+    pub fn public_key(&self) -> CurvePublicKey {
+        match self {
+            KeyPair::K256(_, pk) => CurvePublicKey::K256(pk.clone()),
+            KeyPair::P384(_, pk) => CurvePublicKey::P384(pk.clone()),
+            KeyPair::P521(_, pk) => CurvePublicKey::P521(pk.clone()),
+        }
+    }
+
+    /// Returns every form of the key at once, for display in tools and logs. The secret is only included with
+    /// the `dangerous-debug` feature.
+    pub fn describe(&self) -> KeyInfo {
+        let pk = self.public_key();
+        let uncompressed = pk.to_uncompressed_bytes();
+        KeyInfo {
+            curve_id: pk.curve_id(),
+            compressed: pk.to_compressed_bytes(),
+            hex: encoding::to_hex(&uncompressed),
+            uncompressed,
+            fingerprint: pk.fingerprint(),
+            #[cfg(feature = "dangerous-debug")]
+            secret_hex: match self {
+                KeyPair::K256(sk, _) => sk.to_hex(),
+                KeyPair::P384(sk, _) => sk.to_hex(),
+                KeyPair::P521(sk, _) => sk.to_hex(),
+            },
+        }
+    }
+}
+
+/// Reads exactly one SEC1 encoded public key for `curve_id` from the front of `input`. The leading tag byte decides
+/// how many bytes that is - `0x04` for an uncompressed point or `0x02`/`0x03` for a compressed one (which is
+/// decompressed). Any bytes after the key are left untouched.
+///
+/// This crate is `no_std`, so rather than a `std::io::Read` it takes a slice - callers continue parsing
+/// at `&input[consumed..]`.
+///
+/// - Returns the key and the number of bytes consumed, `CryptoError::InvalidLength` if `input` is too short for the
+///   key, `CryptoError::InvalidEncoding` for an unknown tag, `CryptoError::PointNotOnCurve` for a point that
+///   isn't on the curve or `CryptoError::PointAtInfinity` for the identity.
+pub fn read_public_key(curve_id: CurveId, input: &[u8]) -> Result<(CurvePublicKey, usize)> {
+    let field_size = match curve_id {
+        CurveId::Secp256k1 => 32,
+        CurveId::NistP384 => 48,
+        CurveId::NistP521 => 66,
+    };
+    let len = match input.first() {
+        Some(0x04) => 1 + 2 * field_size,
+        Some(0x02) | Some(0x03) => 1 + field_size,
+        Some(_) => return Err(CryptoError::InvalidEncoding),
+        // Even the shortest (compressed) encoding has a tag and an x co-ordinate
+        None => return Err(CryptoError::InvalidLength { expected: 1 + field_size, got: 0 }),
+    };
+    if input.len() < len {
+        return Err(CryptoError::InvalidLength { expected: len, got: input.len() });
+    }
+    let encoded = &input[..len];
+
+    let public_key = match curve_id {
+        CurveId::Secp256k1 => {
+            // k256 decompresses by itself
+            let parsed = k256::PublicKey::from_sec1_bytes(encoded).map_err(|_| CryptoError::PointNotOnCurve)?;
+            CurvePublicKey::K256(Pkk256(parsed))
+        }
+        CurveId::NistP384 => {
+            let uncompressed = decompress::<P384Params>(encoded)?;
+            CurvePublicKey::P384(PkP384::from_bytes(&uncompressed)?)
+        }
+        CurveId::NistP521 => {
+            let uncompressed = decompress::<P521Params>(encoded)?;
+            CurvePublicKey::P521(PkP521::from_bytes(&uncompressed)?)
+        }
+    };
+    Ok((public_key, len))
+}
+
+/// Compresses an uncompressed SEC1 point, i.e. `0x02 | parity(y) || x`.
+fn compress(uncompressed: &[u8]) -> Vec<u8> {
+    let field_size = (uncompressed.len() - 1) / 2;
+    let mut compressed = uncompressed[..1 + field_size].to_vec();
+    compressed[0] = 0x02 | (uncompressed[uncompressed.len() - 1] & 1);
+    compressed
+}
+
+/// Converts a compressed SEC1 point into its uncompressed form (uncompressed and hybrid points are passed through as
+/// is).
+fn decompress<C: CurveParams>(encoded: &[u8]) -> Result<Vec<u8>> {
+    // Hybrid keys carry both co-ordinates too - `from_bytes` checks their parity
+    if matches!(encoded[0], 0x04 | 0x06 | 0x07) {
+        return Ok(encoded.to_vec());
+    }
+    let field_size = C::byte_len();
+    let x = BigInt::from_bytes_be(Sign::Plus, &encoded[1..]);
+    if &x >= C::modp() {
+        return Err(CryptoError::InvalidEncoding);
+    }
+    // y^2 = x^3 + ax + b, picking the root whose parity matches the tag
+    let field = Field::new(C::modp());
+    let rhs = field.add(&field.add(&field.mul(&field.sqr(&x), &x), &field.mul(C::a(), &x)), C::b());
+    let mut y = field.sqrt(&rhs).ok_or(CryptoError::PointNotOnCurve)?;
+    let y_is_odd = y.to_bytes_le().1[0] & 1 == 1;
+    if y_is_odd != (encoded[0] == 0x03) {
+        y = field.sub(&BigInt::from(0), &y);
+    }
+
+    let mut uncompressed = alloc::vec![0u8; 1 + 2 * field_size];
+    uncompressed[0] = 0x04;
+    uncompressed[1..1 + field_size].copy_from_slice(&encoded[1..]);
+    let y_bytes = y.to_bytes_be().1;
+    uncompressed[1 + 2 * field_size - y_bytes.len()..].copy_from_slice(&y_bytes);
+    Ok(uncompressed)
+}
+
+/// Accepts the SEC1 hybrid encodings `0x06 || x || y` (even y) and `0x07 || x || y` (odd y), emitted by some
+/// legacy systems: the tag's parity must match y, and is then rewritten to `0x04` so that keys are always held in
+/// the uncompressed form.
+///
+/// - Returns `CryptoError::InvalidEncoding` for a parity mismatch or a tag other than `0x04`, `0x06` or `0x07`.
+fn from_hybrid(encoded: &mut [u8]) -> Result<()> {
+    let y_is_odd = encoded[encoded.len() - 1] & 1;
+    match encoded[0] {
+        0x04 => Ok(()),
+        tag @ (0x06 | 0x07) if tag & 1 == y_is_odd => {
+            encoded[0] = 0x04;
+            Ok(())
+        }
+        _ => Err(CryptoError::InvalidEncoding),
+    }
+}
+
+// JSON Web Keys (RFC 7517/7518) - `crv` is `secp256k1` (RFC 8812) or `P-384`, and `x`, `y` (and `d`, for private
+// keys) are the fixed-width big-endian values, base64url-encoded.
+const JWK_CRV_K256: &str = "secp256k1";
+const JWK_CRV_P384: &str = "P-384";
+
+impl Pkk256 {
+    /// Parses a public key from an EC JWK with `"crv": "secp256k1"`. Any `d` member is ignored.
+    ///
+    /// - Returns `CryptoError::InvalidEncoding` for a wrong `kty`/`crv` or malformed co-ordinates and
+    ///   `CryptoError::PointNotOnCurve` for a point that isn't on the curve.
+    pub fn from_jwk(jwk: &str) -> Result<Self> {
+        let jwk = encoding::parse_ec_jwk(jwk, JWK_CRV_K256)?;
+        Pkk256::from_bytes(&jwk_point(&jwk, 32)?)
+    }
+
+    /// Serializes this key as an EC JWK.
+    pub fn to_jwk(&self) -> String {
+        let bytes = self.to_bytes();
+        encoding::to_ec_jwk(JWK_CRV_K256, &bytes[1..33], &bytes[33..], None)
+    }
+}
+
+impl Skk256 {
+    /// Parses a private key from an EC JWK with `"crv": "secp256k1"`.
+    ///
+    /// - Returns `CryptoError::InvalidEncoding` if `d` is missing or malformed, or if `x` and `y` aren't the
+    ///   public key for `d`, and `CryptoError::ScalarOutOfRange` if `d` isn't in `[1, n - 1]`.
+    pub fn from_jwk(jwk: &str) -> Result<Self> {
+        let jwk = encoding::parse_ec_jwk(jwk, JWK_CRV_K256)?;
+        let sk = Skk256::from_bytes_exact(jwk_scalar(&jwk, 32)?)?;
+        if ECDHNISTK256::generate_public_key(&sk).to_bytes().as_slice() != jwk_point(&jwk, 32)?.as_slice() {
+            return Err(CryptoError::InvalidEncoding);
+        }
+        Ok(sk)
+    }
+
+    /// Serializes this key (including the public co-ordinates) as an EC JWK.
+    ///
+    /// Caution: the output contains the private scalar.
+    pub fn to_jwk(&self) -> String {
+        let pk = ECDHNISTK256::generate_public_key(self).to_bytes();
+        encoding::to_ec_jwk(JWK_CRV_K256, &pk[1..33], &pk[33..], Some(&self.to_bytes()))
+    }
+}
+
+impl PkP384 {
+    /// Parses a public key from an EC JWK with `"crv": "P-384"`. Any `d` member is ignored.
+    ///
+    /// - Returns `CryptoError::InvalidEncoding` for a wrong `kty`/`crv` or malformed co-ordinates and
+    ///   `CryptoError::PointNotOnCurve` for a point that isn't on the curve.
+    pub fn from_jwk(jwk: &str) -> Result<Self> {
+        let jwk = encoding::parse_ec_jwk(jwk, JWK_CRV_P384)?;
+        PkP384::from_bytes(&jwk_point(&jwk, 48)?)
+    }
+
+    /// Serializes this key as an EC JWK.
+    pub fn to_jwk(&self) -> String {
+        let bytes = self.to_bytes();
+        encoding::to_ec_jwk(JWK_CRV_P384, &bytes[1..49], &bytes[49..], None)
+    }
+}
+
+impl SkP384 {
+    /// Parses a private key from an EC JWK with `"crv": "P-384"`.
+    ///
+    /// - Returns `CryptoError::InvalidEncoding` if `d` is missing or malformed, or if `x` and `y` aren't the
+    ///   public key for `d`, and `CryptoError::ScalarOutOfRange` if `d` isn't in `[1, n - 1]`.
+    pub fn from_jwk(jwk: &str) -> Result<Self> {
+        let jwk = encoding::parse_ec_jwk(jwk, JWK_CRV_P384)?;
+        let sk = SkP384::from_bytes(jwk_scalar(&jwk, 48)?)?;
+        if ECDHNISTP384::<48>::generate_public_key(&sk).to_bytes().as_slice() != jwk_point(&jwk, 48)?.as_slice() {
+            return Err(CryptoError::InvalidEncoding);
+        }
+        Ok(sk)
+    }
+
+    /// Serializes this key (including the public co-ordinates) as an EC JWK.
+    ///
+    /// Caution: the output contains the private scalar.
+    pub fn to_jwk(&self) -> String {
+        let pk = ECDHNISTP384::<48>::generate_public_key(self).to_bytes();
+        encoding::to_ec_jwk(JWK_CRV_P384, &pk[1..49], &pk[49..], Some(&self.to_bytes()))
+    }
+}
+
+/// Rebuilds the uncompressed SEC1 point `0x04 || x || y` from a JWK - RFC 7518 §6.2.1.2 requires both
+/// co-ordinates to be exactly `field_size` bytes.
+fn jwk_point(jwk: &encoding::EcJwk, field_size: usize) -> Result<Vec<u8>> {
+    if jwk.x.len() != field_size || jwk.y.len() != field_size {
+        return Err(CryptoError::InvalidEncoding);
+    }
+    let mut point = Vec::with_capacity(1 + 2 * field_size);
+    point.push(0x04);
+    point.extend_from_slice(&jwk.x);
+    point.extend_from_slice(&jwk.y);
+    Ok(point)
+}
+
+/// The JWK's `d` member, which must also be exactly `field_size` bytes (RFC 7518 §6.2.2.1).
+fn jwk_scalar(jwk: &encoding::EcJwk, field_size: usize) -> Result<&[u8]> {
+    match &jwk.d {
+        Some(d) if d.len() == field_size => Ok(d),
+        _ => Err(CryptoError::InvalidEncoding),
+    }
+}
+
+/// Reads the private scalar for `curve_id` from a SEC1 `ECPrivateKey` (DER), checking the curve if it's named, and
+/// returns it along with the embedded public key (if any) for the caller to check against the scalar.
+fn sec1_private_key(curve_id: CurveId, der: &[u8], field_size: usize) -> Result<(&[u8], Option<CurvePublicKey>)> {
+    let (der_curve_id, scalar, point) = encoding::parse_sec1_ec_private_key(der)?;
+    if der_curve_id.is_some_and(|id| id != curve_id) || scalar.len() != field_size {
+        return Err(CryptoError::InvalidEncoding);
+    }
+    let public_key = match point {
+        Some(point) => {
+            let (public_key, consumed) = read_public_key(curve_id, point)?;
+            if consumed != point.len() {
+                return Err(CryptoError::InvalidEncoding);
+            }
+            Some(public_key)
+        }
+        None => None,
+    };
+    Ok((scalar, public_key))
+}
+
+impl Skk256 {
+    /// Parses a private key from a SEC1 `ECPrivateKey` (DER), e.g. the output of
+    /// `openssl ecparam -name secp256k1 -genkey -noout -outform DER`.
+    ///
+    /// - Returns `CryptoError::InvalidEncoding` for malformed DER, a key for another curve or an embedded public
+    ///   key that doesn't match the private scalar, `CryptoError::UnsupportedCurve` for explicit curve parameters
+    ///   and `CryptoError::ScalarOutOfRange` if the scalar isn't in `[1, n - 1]`.
+    pub fn from_sec1_der(der: &[u8]) -> Result<Self> {
+        let (scalar, public_key) = sec1_private_key(CurveId::Secp256k1, der, 32)?;
+        let sk = Skk256::from_bytes_exact(scalar)?;
+        match public_key {
+            Some(CurvePublicKey::K256(pk)) if !pk.same_as(&ECDHNISTK256::generate_public_key(&sk)) => {
+                Err(CryptoError::InvalidEncoding)
+            }
+            _ => Ok(sk),
+        }
+    }
+}
+
+impl Skk256 {
+    /// Serializes the keypair (the private scalar and its public key) as a password-protected PKCS#8
+    /// `EncryptedPrivateKeyInfo` (DER) - PBES2 with PBKDF2-HMAC-SHA256 (100,000 iterations, random salt) and
+    /// AES-256-CBC, which `openssl pkcs8 -inform DER -in key.p8` reads.
+    ///
+    /// Note: the salt and the IV are generated from `rand::thread_rng()`, so two calls give different blobs. This
+    /// needs the `std` feature - see `to_encrypted_pkcs8_with_rng` otherwise.
+    #[cfg(feature = "std")]
+    pub fn to_encrypted_pkcs8(&self, password: &[u8]) -> Result<Vec<u8>> {
+        self.to_encrypted_pkcs8_with_rng(password, &mut rand::thread_rng())
+    }
+
+    /// `to_encrypted_pkcs8`, with the salt and the IV drawn from `rng`.
+    pub fn to_encrypted_pkcs8_with_rng<R: RngCore + CryptoRng>(&self, password: &[u8], rng: &mut R) -> Result<Vec<u8>> {
+        let public_key = ECDHNISTK256::generate_public_key(self).to_bytes();
+        let mut scalar = self.to_bytes();
+        let mut ec_private_key = encoding::to_sec1_ec_private_key(None, &scalar, Some(&public_key));
+        scalar.as_mut_slice().zeroize();
+        let der = crate::pkcs8::encrypt(CurveId::Secp256k1, &ec_private_key, password, rng);
+        ec_private_key.zeroize();
+        Ok(der)
+    }
+
+    /// Decrypts a key serialized by `to_encrypted_pkcs8` (or by OpenSSL with the same algorithms).
+    ///
+    /// - Returns `CryptoError::InvalidEncoding` for a wrong password, malformed DER, other algorithms (e.g. the
+    ///   legacy PBES1 ones), an iteration count over 10,000,000 or a key for another curve, and otherwise the same
+    ///   errors as `from_sec1_der`.
+    pub fn from_encrypted_pkcs8(der: &[u8], password: &[u8]) -> Result<Self> {
+        let ec_private_key = crate::pkcs8::decrypt(CurveId::Secp256k1, der, password)?;
+        Skk256::from_sec1_der(&ec_private_key)
+    }
+}
+
+impl SkP384 {
+    /// Parses a private key from a SEC1 `ECPrivateKey` (DER), e.g. the output of
+    /// `openssl ecparam -name secp384r1 -genkey -noout -outform DER`.
+    ///
+    /// - Returns the same errors as `Skk256::from_sec1_der`.
+    pub fn from_sec1_der(der: &[u8]) -> Result<Self> {
+        let (scalar, public_key) = sec1_private_key(CurveId::NistP384, der, 48)?;
+        let sk = SkP384::from_bytes(scalar)?;
+        match public_key {
+            Some(CurvePublicKey::P384(pk)) if !pk.same_as(&ECDHNISTP384::<48>::generate_public_key(&sk)) => {
+                Err(CryptoError::InvalidEncoding)
+            }
+            _ => Ok(sk),
+        }
+    }
+}
+
+/// Reads the peer's key for `curve_id` from an X.509 `SubjectPublicKeyInfo` (DER) - the point may be compressed.
+fn spki_public_key(curve_id: CurveId, spki_der: &[u8]) -> Result<CurvePublicKey> {
+    let (spki_curve_id, point) = encoding::parse_spki_ec_public_key(spki_der)?;
+    if spki_curve_id != curve_id {
+        return Err(CryptoError::InvalidEncoding);
+    }
+    let (public_key, consumed) = read_public_key(curve_id, point)?;
+    if consumed != point.len() {
+        return Err(CryptoError::InvalidEncoding);
+    }
+    Ok(public_key)
+}
+
+impl ECDHNISTK256 {
+    /// Computes the shared secret with a peer whose public key is given as an X.509 `SubjectPublicKeyInfo` (DER).
+    ///
+    /// - Returns `CryptoError::InvalidEncoding` for malformed DER or a key for another curve, otherwise the same
+    ///   errors as parsing the key with `read_public_key` and then calling `generate_shared_secret`.
+    pub fn agree_with_spki_der(sk: &Skk256, spki_der: &[u8]) -> Result<SharedSecretk256> {
+        match spki_public_key(CurveId::Secp256k1, spki_der)? {
+            CurvePublicKey::K256(pk) => Self::generate_shared_secret(sk, &pk),
+            _ => unreachable!(),
+        }
+    }
+}
+
+impl<const N: usize> ECDHNISTP384<N> {
+    /// Computes the shared secret with a peer whose public key is given as an X.509 `SubjectPublicKeyInfo` (DER).
+    ///
+    /// - Returns the same errors as `ECDHNISTK256::agree_with_spki_der`.
+    pub fn agree_with_spki_der(sk: &SkP384, spki_der: &[u8]) -> Result<SharedSecretP384> {
+        match spki_public_key(CurveId::NistP384, spki_der)? {
+            CurvePublicKey::P384(pk) => Self::generate_shared_secret(sk, &pk),
+            _ => unreachable!(),
+        }
+    }
+}
+
+impl ECDHNISTP521 {
+    /// Computes the shared secret with a peer whose public key is given as an X.509 `SubjectPublicKeyInfo` (DER).
+    ///
+    /// - Returns the same errors as `ECDHNISTK256::agree_with_spki_der`.
+    pub fn agree_with_spki_der(sk: &SkP521, spki_der: &[u8]) -> Result<SharedSecretP521> {
+        match spki_public_key(CurveId::NistP521, spki_der)? {
+            CurvePublicKey::P521(pk) => Self::generate_shared_secret(sk, &pk),
+            _ => unreachable!(),
+        }
+    }
+}
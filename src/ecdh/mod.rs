@@ -1,4 +1,9 @@
 /// A module for an ECDH implementation 
+#[allow(clippy::module_inception)]
 pub mod ecdh;
 /// A module for Affine-Point arithmetic 
-pub mod affine_math;
\ No newline at end of file
+pub mod affine_math;
+/// A module for X25519, the one supported curve with a cofactor
+pub mod x25519;
+/// A module for brainpoolP384r1, on the affine-point arithmetic
+pub mod brainpool;
@@ -0,0 +1,200 @@
+//! X25519 (RFC 7748) - ECDH over the Montgomery form of Curve25519.
+//!
+//! Unlike secp256k1, P-256, P-384 and P-521 (all of prime order, i.e. cofactor 1), Curve25519's group has order
+//! `8 * l`. A public key can put the exchange in a small subgroup, so two things keep it contributory here:
+//!
+//! - the private scalar is clamped (see `clamp`) - being a multiple of 8, it cancels any small-order component;
+//! - `generate_shared_secret` rejects the all-zero output that a low-order public key gives, with
+//!   `CryptoError::PointAtInfinity`.
+//!
+//! The prime-order curves need neither: their public keys are validated (on the curve, not the identity) and every
+//! such point has order `n`. Note - like the affine-point math, the ladder below uses `num_bigint_dig` and isn't
+//! constant time.
+
+use generic_array::{typenum, GenericArray};
+use lazy_static::lazy_static;
+use num_bigint_dig::{BigInt, Sign};
+use num_traits::{One, Zero};
+use rand_chacha::rand_core::{RngCore, SeedableRng};
+use rand_chacha::ChaCha20Rng;
+use zeroize::Zeroize;
+
+use core::fmt;
+
+use elliptic_curve::subtle::ConstantTimeEq;
+
+use super::affine_math::Field;
+use super::ecdh::{FromBytes, KeyExchange, ToBytes};
+use crate::{CryptoError, Result};
+
+lazy_static! {
+    /// The field prime `2^255 - 19`.
+    static ref X25519_MODP: BigInt = (BigInt::one() << 255) - BigInt::from(19);
+    /// `(A - 2) / 4` for the curve constant `A = 486662`.
+    static ref A24: BigInt = BigInt::from(121665);
+}
+
+/// The u co-ordinate of the base point
+const BASE_POINT_U: u8 = 9;
+
+/// An X25519 private key: 32 bytes, as imported or generated. It is clamped (see `clamp`) before every use, so
+/// `to_bytes` gives back exactly what was passed to `from_bytes`.
+#[derive(Clone)]
+pub struct SkX25519([u8; 32]);
+
+impl Drop for SkX25519 {
+    fn drop(&mut self) {
+        self.0.zeroize();
+    }
+}
+
+/// Prints `SkX25519(REDACTED)` so that the private scalar never ends up in logs.
+impl fmt::Debug for SkX25519 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("SkX25519(REDACTED)")
+    }
+}
+
+/// An X25519 public key i.e. a u co-ordinate (32 bytes, little-endian).
+///
+/// Every 32 byte string is accepted (RFC 7748 Section 5) - low-order keys are caught by
+/// `generate_shared_secret` instead.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PkX25519(pub [u8; 32]);
+
+/// A struct to hold the computed X25519 shared secret (32 bytes, little-endian).
+#[derive(Debug, Clone, PartialEq)]
+pub struct SharedSecretX25519(pub [u8; 32]);
+
+impl ToBytes for SkX25519 {
+    type OutputSize = typenum::U32;
+
+    fn to_bytes(&self) -> GenericArray<u8, Self::OutputSize> {
+        GenericArray::clone_from_slice(&self.0)
+    }
+}
+
+impl FromBytes for SkX25519 {
+    fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        Ok(SkX25519(to_array(bytes)?))
+    }
+}
+
+impl ToBytes for PkX25519 {
+    type OutputSize = typenum::U32;
+
+    fn to_bytes(&self) -> GenericArray<u8, Self::OutputSize> {
+        GenericArray::clone_from_slice(&self.0)
+    }
+}
+
+impl FromBytes for PkX25519 {
+    fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        Ok(PkX25519(to_array(bytes)?))
+    }
+}
+
+impl ToBytes for SharedSecretX25519 {
+    type OutputSize = typenum::U32;
+
+    fn to_bytes(&self) -> GenericArray<u8, Self::OutputSize> {
+        GenericArray::clone_from_slice(&self.0)
+    }
+}
+
+/// A struct that represents the ECDH implementation for X25519.
+pub struct ECDHX25519;
+
+impl KeyExchange for ECDHX25519 {
+    type SKey = SkX25519;
+    type PubKey = PkX25519;
+    type CompSecret = SharedSecretX25519;
+
+    fn generate_private_key(seed: [u8; 32]) -> Self::SKey {
+        let mut rng = ChaCha20Rng::from_seed(seed); // test seed value.
+        let mut dest = [0; 32];
+        rng.fill_bytes(&mut dest);
+        SkX25519(dest)
+    }
+
+    fn generate_public_key(sk: &Self::SKey) -> Self::PubKey {
+        let mut base_point = [0; 32];
+        base_point[0] = BASE_POINT_U;
+        PkX25519(x25519(&clamp(sk.0), &base_point))
+    }
+
+    /// Computes `X25519(clamp(sk), pk)`.
+    ///
+    /// - Returns `CryptoError::PointAtInfinity` if the result is all-zero i.e. `pk` is a low-order point.
+    fn generate_shared_secret(sk: &Self::SKey, others_pk: &Self::PubKey) -> Result<Self::CompSecret> {
+        let shared_secret = x25519(&clamp(sk.0), &others_pk.0);
+        if bool::from(shared_secret.ct_eq(&[0; 32])) {
+            return Err(CryptoError::PointAtInfinity);
+        }
+        Ok(SharedSecretX25519(shared_secret))
+    }
+}
+
+/// Clamps an X25519 scalar (RFC 7748 Section 5): clears the 3 low bits, so the scalar is a multiple of the cofactor
+/// 8, clears bit 255 and sets bit 254.
+pub fn clamp(mut scalar: [u8; 32]) -> [u8; 32] {
+    scalar[0] &= 248;
+    scalar[31] &= 127;
+    scalar[31] |= 64;
+    scalar
+}
+
+/// The X25519 function on an already clamped `scalar` and a u co-ordinate - the Montgomery ladder of RFC 7748
+/// Section 5. Bit 255 of `u` is ignored and non-canonical values (`>= p`) are reduced.
+fn x25519(scalar: &[u8; 32], u: &[u8; 32]) -> [u8; 32] {
+    let field = Field::new(&X25519_MODP);
+    let mut u = *u;
+    u[31] &= 127;
+    let x_1 = field.reduce(&BigInt::from_bytes_le(Sign::Plus, &u));
+
+    let (mut x_2, mut z_2) = (BigInt::one(), BigInt::zero());
+    let (mut x_3, mut z_3) = (x_1.clone(), BigInt::one());
+    let mut swap = false;
+    for t in (0..255).rev() {
+        let k_t = (scalar[t / 8] >> (t % 8)) & 1 == 1;
+        if swap != k_t {
+            core::mem::swap(&mut x_2, &mut x_3);
+            core::mem::swap(&mut z_2, &mut z_3);
+        }
+        swap = k_t;
+
+        let a = field.add(&x_2, &z_2);
+        let aa = field.sqr(&a);
+        let b = field.sub(&x_2, &z_2);
+        let bb = field.sqr(&b);
+        let e = field.sub(&aa, &bb);
+        let c = field.add(&x_3, &z_3);
+        let d = field.sub(&x_3, &z_3);
+        let da = field.mul(&d, &a);
+        let cb = field.mul(&c, &b);
+        x_3 = field.sqr(&field.add(&da, &cb));
+        z_3 = field.mul(&x_1, &field.sqr(&field.sub(&da, &cb)));
+        x_2 = field.mul(&aa, &bb);
+        z_2 = field.mul(&e, &field.add(&aa, &field.mul(&A24, &e)));
+    }
+    if swap {
+        core::mem::swap(&mut x_2, &mut x_3);
+        core::mem::swap(&mut z_2, &mut z_3);
+    }
+
+    // `z_2 = 0` (the point at infinity) encodes as zero, as `0^(p - 2) = 0` would
+    let result = field.inv(&z_2).map_or_else(BigInt::zero, |z_inverse| field.mul(&x_2, &z_inverse));
+    let mut bytes = [0; 32];
+    let le = result.to_bytes_le().1;
+    bytes[..le.len()].copy_from_slice(&le);
+    bytes
+}
+
+fn to_array(bytes: &[u8]) -> Result<[u8; 32]> {
+    let mut array = [0; 32];
+    if bytes.len() != array.len() {
+        return Err(CryptoError::InvalidLength { expected: 32, got: bytes.len() });
+    }
+    array.copy_from_slice(bytes);
+    Ok(array)
+}
@@ -0,0 +1,181 @@
+//! ECIES-style hybrid encryption on top of the `KeyExchange` impls.
+//!
+//! A sender generates an ephemeral keypair, does ECDH with the recipient's static public key and runs the
+//! shared secret through HKDF-SHA256 to get a 32 byte session key. The session key can then protect any number
+//! of records, each identified by a sequence number.
+//!
+//! Records are encrypted with AES-256-GCM under the session key, with the 96-bit nonce `0u32 || seq` (big-endian),
+//! so any GCM implementation can open them. `seal`/`open` take an explicit nonce and associated data.
+//!
+//! For a single message, `encrypt`/`decrypt` do all of the above in one go, with a random nonce.
+//!
+//! Note: the AES is a table-based textbook implementation and isn't constant time.
+
+use alloc::vec::Vec;
+
+use generic_array::GenericArray;
+use rand_chacha::rand_core::{CryptoRng, RngCore};
+use zeroize::{Zeroize, Zeroizing};
+
+use crate::aes::{gcm_open, gcm_seal, GCM_NONCE_LEN, GCM_TAG_LEN};
+use crate::digest::hkdf_sha256;
+use crate::ecdh::ecdh::{FromBytes, KeyExchange, ToBytes};
+use crate::{CryptoError, Result};
+
+/// Domain separation for the session key derivation.
+const SESSION_KEY_INFO: &[u8] = b"static-dh-ecdh ecies session key";
+/// Length of the GCM tag appended to every record.
+pub const TAG_LEN: usize = GCM_TAG_LEN;
+/// Length of a GCM nonce (and of the nonce in the output of `encrypt`).
+pub const NONCE_LEN: usize = GCM_NONCE_LEN;
+
+/// The serialized ephemeral public key for the curve `C`.
+pub type EphemeralPublicKey<C> = GenericArray<u8, <<C as KeyExchange>::PubKey as ToBytes>::OutputSize>;
+
+/// Sets up a sealing session for `recipient_pk`.
+///
+/// - Returns the serialized ephemeral public key (to be sent to the recipient) and the derived session key.
+///
+/// Note: the ephemeral key is generated from `rand::thread_rng()`, so this needs the `std` feature - see
+/// `begin_seal_with_rng` otherwise.
+#[cfg(feature = "std")]
+pub fn begin_seal<C: KeyExchange>(
+    recipient_pk: &C::PubKey,
+    salt: &[u8],
+) -> Result<(EphemeralPublicKey<C>, Zeroizing<[u8; 32]>)> {
+    begin_seal_with_rng::<C, _>(recipient_pk, salt, &mut rand::thread_rng())
+}
+
+/// `begin_seal`, with the ephemeral key generated from `rng` (see `KeyExchange::generate_private_key_with_rng`).
+pub fn begin_seal_with_rng<C: KeyExchange, R: RngCore + CryptoRng>(
+    recipient_pk: &C::PubKey,
+    salt: &[u8],
+    rng: &mut R,
+) -> Result<(EphemeralPublicKey<C>, Zeroizing<[u8; 32]>)> {
+    let ephemeral_sk = C::generate_private_key_with_rng(rng);
+    let ephemeral_pk = C::generate_public_key(&ephemeral_sk).to_bytes();
+    let mut shared_secret = C::generate_shared_secret(&ephemeral_sk, recipient_pk)?.to_bytes();
+    let session_key = session_key(&shared_secret, salt, &ephemeral_pk, &recipient_pk.to_bytes());
+    shared_secret.as_mut_slice().zeroize();
+    Ok((ephemeral_pk, session_key))
+}
+
+/// Recovers the session key set up by `begin_seal`, given the recipient's private key and the
+/// serialized ephemeral public key.
+pub fn begin_open<C: KeyExchange>(
+    recipient_sk: &C::SKey,
+    ephemeral_pk: &[u8],
+    salt: &[u8],
+) -> Result<Zeroizing<[u8; 32]>> {
+    let peer_pk = C::PubKey::from_bytes(ephemeral_pk)?;
+    let mut shared_secret = C::generate_shared_secret(recipient_sk, &peer_pk)?.to_bytes();
+    let session_key = session_key(
+        &shared_secret,
+        salt,
+        &peer_pk.to_bytes(),
+        &C::generate_public_key(recipient_sk).to_bytes(),
+    );
+    shared_secret.as_mut_slice().zeroize();
+    Ok(session_key)
+}
+
+/// AES-256-GCM encryption of `plaintext` and authentication of `aad` under a session key. `nonce` must be unique
+/// for every message sealed with the same key.
+///
+/// - Returns `ciphertext || tag`
+pub fn seal(session_key: &[u8; 32], nonce: &[u8; NONCE_LEN], aad: &[u8], plaintext: &[u8]) -> Vec<u8> {
+    gcm_seal(session_key, nonce, aad, plaintext)
+}
+
+/// Authenticates and decrypts the output of `seal` with the same session key, `nonce` and `aad`.
+///
+/// - Returns the plaintext or `CryptoError::DecryptionError` if it was tampered with (or the key/nonce/`aad` differ).
+pub fn open(session_key: &[u8; 32], nonce: &[u8; NONCE_LEN], aad: &[u8], sealed: &[u8]) -> Result<Vec<u8>> {
+    gcm_open(session_key, nonce, aad, sealed)
+}
+
+/// Encrypts a record under a session key (`seal` with the nonce `0u32 || seq`). `seq` must be unique for every
+/// record sealed with the same key.
+///
+/// - Returns `ciphertext || tag`
+pub fn seal_record(session_key: &[u8; 32], seq: u64, plaintext: &[u8]) -> Vec<u8> {
+    seal(session_key, &record_nonce(seq), &[], plaintext)
+}
+
+/// Authenticates and decrypts a record produced by `seal_record` with the same session key and `seq`.
+///
+/// - Returns the plaintext or `CryptoError::DecryptionError` if the record was tampered with (or the key/`seq` differ).
+pub fn open_record(session_key: &[u8; 32], seq: u64, record: &[u8]) -> Result<Vec<u8>> {
+    open(session_key, &record_nonce(seq), &[], record)
+}
+
+/// Encrypts `plaintext` for `recipient_pk` under a fresh ephemeral key (i.e. a one-record session).
+///
+/// - Returns `ephemeral_pk || nonce || ciphertext || tag`
+///
+/// Note: the ephemeral key and the nonce are generated from `rand::thread_rng()`, so this needs the `std` feature -
+/// see `encrypt_with_rng` otherwise.
+#[cfg(feature = "std")]
+pub fn encrypt<C: KeyExchange>(recipient_pk: &C::PubKey, plaintext: &[u8]) -> Result<Vec<u8>> {
+    encrypt_with_rng::<C, _>(recipient_pk, plaintext, &mut rand::thread_rng())
+}
+
+/// `encrypt`, with the ephemeral key and the nonce drawn from `rng`.
+pub fn encrypt_with_rng<C: KeyExchange, R: RngCore + CryptoRng>(
+    recipient_pk: &C::PubKey,
+    plaintext: &[u8],
+    rng: &mut R,
+) -> Result<Vec<u8>> {
+    let (ephemeral_pk, session_key) = begin_seal_with_rng::<C, R>(recipient_pk, &[], rng)?;
+    let mut nonce = [0; NONCE_LEN];
+    rng.fill_bytes(&mut nonce);
+    let sealed = seal(&session_key, &nonce, &[], plaintext);
+
+    let mut out = Vec::with_capacity(ephemeral_pk.len() + NONCE_LEN + sealed.len());
+    out.extend_from_slice(&ephemeral_pk);
+    out.extend_from_slice(&nonce);
+    out.extend_from_slice(&sealed);
+    Ok(out)
+}
+
+/// Decrypts the output of `encrypt` with the recipient's private key.
+///
+/// - Returns the plaintext, `CryptoError::DecryptionError` if the message is truncated or was tampered with and
+///   the errors of `begin_open` if the ephemeral public key is invalid.
+pub fn decrypt<C: KeyExchange>(recipient_sk: &C::SKey, message: &[u8]) -> Result<Vec<u8>> {
+    let pk_len = <C::PubKey as ToBytes>::size();
+    if message.len() < pk_len + NONCE_LEN + TAG_LEN {
+        return Err(CryptoError::DecryptionError);
+    }
+    let (ephemeral_pk, rest) = message.split_at(pk_len);
+    let (nonce_bytes, sealed) = rest.split_at(NONCE_LEN);
+    let mut nonce = [0; NONCE_LEN];
+    nonce.copy_from_slice(nonce_bytes);
+
+    let session_key = begin_open::<C>(recipient_sk, ephemeral_pk, &[])?;
+    open(&session_key, &nonce, &[], sealed)
+}
+
+/// `HKDF(salt, shared_secret, info || ephemeral_pk || recipient_pk)` - binding both public keys into the key.
+fn session_key(
+    shared_secret: &[u8],
+    salt: &[u8],
+    ephemeral_pk: &[u8],
+    recipient_pk: &[u8],
+) -> Zeroizing<[u8; 32]> {
+    let mut info = Vec::with_capacity(SESSION_KEY_INFO.len() + ephemeral_pk.len() + recipient_pk.len());
+    info.extend_from_slice(SESSION_KEY_INFO);
+    info.extend_from_slice(ephemeral_pk);
+    info.extend_from_slice(recipient_pk);
+
+    let mut key = Zeroizing::new([0; 32]);
+    hkdf_sha256(salt, shared_secret, &info, &mut *key);
+    key
+}
+
+/// `0u32 || seq`, big-endian.
+fn record_nonce(seq: u64) -> [u8; NONCE_LEN] {
+    let mut nonce = [0; NONCE_LEN];
+    nonce[4..].copy_from_slice(&seq.to_be_bytes());
+    nonce
+}
@@ -0,0 +1,537 @@
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use zeroize::Zeroize;
+
+use crate::digest::SHA256Digest;
+use crate::{CryptoError, CurveId, Result};
+
+const HEX_CHARS: &[u8; 16] = b"0123456789abcdef";
+const BASE58_CHARS: &[u8; 58] = b"123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+const BASE64URL_CHARS: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+/// How deeply arrays and objects may nest inside a JWK member before it's rejected - `skip_value` recurses, so
+/// without a limit a deeply nested input would overflow the stack.
+const MAX_JSON_DEPTH: usize = 32;
+
+/// Encodes a slice of bytes as a lower-case hex-string (without a `0x` prefix).
+pub fn to_hex(bytes: &[u8]) -> String {
+    let mut hex_string = String::with_capacity(bytes.len() * 2);
+    for byte in bytes {
+        hex_string.push(HEX_CHARS[(byte >> 4) as usize] as char);
+        hex_string.push(HEX_CHARS[(byte & 0x0f) as usize] as char);
+    }
+    hex_string
+}
+
+/// Decodes a hex-string into bytes. The string may carry a `0x` (or `0X`) prefix and may use
+/// mixed case.
+///
+/// Returns `CryptoError::InvalidEncoding` if the string has an odd length or contains a
+/// non-hex character.
+pub fn from_hex(hex_string: &str) -> Result<Vec<u8>> {
+    let hex_string = hex_string
+        .strip_prefix("0x")
+        .or_else(|| hex_string.strip_prefix("0X"))
+        .unwrap_or(hex_string)
+        .as_bytes();
+    if hex_string.len() & 1 != 0 {
+        return Err(CryptoError::InvalidEncoding);
+    }
+    hex_string
+        .chunks(2)
+        .map(|pair| Ok((hex_value(pair[0])? << 4) | hex_value(pair[1])?))
+        .collect()
+}
+
+fn hex_value(c: u8) -> Result<u8> {
+    match c {
+        b'0'..=b'9' => Ok(c - b'0'),
+        b'a'..=b'f' => Ok(c - b'a' + 10),
+        b'A'..=b'F' => Ok(c - b'A' + 10),
+        _ => Err(CryptoError::InvalidEncoding),
+    }
+}
+
+/// Encodes a payload as Base58Check i.e. `base58(payload || sha256(sha256(payload))[..4])`, as used
+/// by Bitcoin addresses.
+pub fn to_base58check(payload: &[u8]) -> String {
+    let checksum = SHA256Digest.digest(&SHA256Digest.digest(payload));
+    let mut bytes = Vec::with_capacity(payload.len() + 4);
+    bytes.extend_from_slice(payload);
+    bytes.extend_from_slice(&checksum[..4]);
+
+    // Repeatedly divide the big-endian number in `bytes` by 58, collecting the remainders.
+    let mut digits: Vec<u8> = Vec::with_capacity(bytes.len() * 138 / 100 + 1);
+    for byte in &bytes {
+        let mut carry = *byte as u32;
+        for digit in digits.iter_mut() {
+            carry += (*digit as u32) << 8;
+            *digit = (carry % 58) as u8;
+            carry /= 58;
+        }
+        while carry > 0 {
+            digits.push((carry % 58) as u8);
+            carry /= 58;
+        }
+    }
+
+    // Every leading zero byte is encoded as a leading '1'
+    let leading_zeros = bytes.iter().take_while(|b| **b == 0).count();
+    let mut encoded = String::with_capacity(leading_zeros + digits.len());
+    for _ in 0..leading_zeros {
+        encoded.push('1');
+    }
+    for digit in digits.iter().rev() {
+        encoded.push(BASE58_CHARS[*digit as usize] as char);
+    }
+    encoded
+}
+
+/// Encodes an ECDSA signature's `r` and `s` components (big-endian bytes) as an ASN.1 DER
+/// `SEQUENCE { INTEGER r, INTEGER s }` into a caller-provided buffer, without allocating.
+///
+/// - Returns the number of bytes written or `CryptoError::InvalidEncoding` if `out` is too small.
+pub fn der_signature_into(r: &[u8], s: &[u8], out: &mut [u8]) -> Result<usize> {
+    let r = der_integer_bytes(r);
+    let s = der_integer_bytes(s);
+    let r_len = 2 + r.1 + r.0.len();
+    let s_len = 2 + s.1 + s.0.len();
+    let body_len = r_len + s_len;
+    // Long-form length (`0x81 nn`) kicks in at 128 bytes e.g. for P-521 signatures
+    let header_len = if body_len < 0x80 { 2 } else { 3 };
+    if body_len > 0xff || out.len() < header_len + body_len {
+        return Err(CryptoError::InvalidEncoding);
+    }
+
+    out[0] = 0x30;
+    if header_len == 2 {
+        out[1] = body_len as u8;
+    } else {
+        out[1] = 0x81;
+        out[2] = body_len as u8;
+    }
+    let mut pos = header_len;
+    for (int, pad) in [r, s].iter() {
+        out[pos] = 0x02;
+        out[pos + 1] = (pad + int.len()) as u8;
+        pos += 2;
+        if *pad == 1 {
+            out[pos] = 0x00;
+            pos += 1;
+        }
+        out[pos..pos + int.len()].copy_from_slice(int);
+        pos += int.len();
+    }
+    Ok(pos)
+}
+
+/// Same as `der_signature_into` but returns the encoding as a `Vec`.
+pub fn to_der_signature(r: &[u8], s: &[u8]) -> Vec<u8> {
+    // 2 (sequence) + 1 (long-form length) + 2 * (2 + 1 (padding)) + the components
+    let mut out = alloc::vec![0; 9 + r.len() + s.len()];
+    let len = der_signature_into(r, s, &mut out).expect("buffer is sized for the worst case");
+    out.truncate(len);
+    out
+}
+
+/// Strips leading zeroes (keeping at least one byte) and returns whether a `0x00` pad
+/// is required to keep the DER INTEGER positive.
+fn der_integer_bytes(bytes: &[u8]) -> (&[u8], usize) {
+    let leading_zeros = bytes.iter().take_while(|b| **b == 0).count();
+    let int = if leading_zeros == bytes.len() {
+        &bytes[bytes.len().saturating_sub(1)..]
+    } else {
+        &bytes[leading_zeros..]
+    };
+    let pad = match int.first() {
+        Some(b) if b & 0x80 != 0 => 1,
+        _ => 0,
+    };
+    (int, pad)
+}
+
+/// `id-ecPublicKey` (1.2.840.10045.2.1)
+pub(crate) const OID_EC_PUBLIC_KEY: &[u8] = &[0x2a, 0x86, 0x48, 0xce, 0x3d, 0x02, 0x01];
+/// The named curves (RFC 5480 / SEC 2): secp256k1 (1.3.132.0.10), P-384 (1.3.132.0.34) and P-521 (1.3.132.0.35)
+pub(crate) const OID_CURVES: [(&[u8], CurveId); 3] = [
+    (&[0x2b, 0x81, 0x04, 0x00, 0x0a], CurveId::Secp256k1),
+    (&[0x2b, 0x81, 0x04, 0x00, 0x22], CurveId::NistP384),
+    (&[0x2b, 0x81, 0x04, 0x00, 0x23], CurveId::NistP521),
+];
+
+/// Parses an X.509 `SubjectPublicKeyInfo` (DER) holding an EC public key i.e.
+/// `SEQUENCE { SEQUENCE { OID id-ecPublicKey, OID namedCurve }, BIT STRING point }`.
+///
+/// - Returns the curve and the SEC1 encoded point (which is not validated here), `CryptoError::InvalidEncoding` for
+///   malformed DER or a key that isn't an EC key and `CryptoError::UnsupportedCurve` for an unknown named curve.
+pub fn parse_spki_ec_public_key(der: &[u8]) -> Result<(CurveId, &[u8])> {
+    let (spki, rest) = der_tlv(der, 0x30)?;
+    if !rest.is_empty() {
+        return Err(CryptoError::InvalidEncoding);
+    }
+    let (algorithm, rest) = der_tlv(spki, 0x30)?;
+    let (point, rest) = der_tlv(rest, 0x03)?;
+    if !rest.is_empty() {
+        return Err(CryptoError::InvalidEncoding);
+    }
+
+    let (key_type, params) = der_tlv(algorithm, 0x06)?;
+    let (curve, rest) = der_tlv(params, 0x06)?;
+    if key_type != OID_EC_PUBLIC_KEY || !rest.is_empty() {
+        return Err(CryptoError::InvalidEncoding);
+    }
+    let curve_id = OID_CURVES
+        .iter()
+        .find(|(oid, _)| *oid == curve)
+        .map(|(_, curve_id)| *curve_id)
+        .ok_or(CryptoError::UnsupportedCurve)?;
+
+    // The BIT STRING's first byte is the number of unused bits, which must be 0 for a SEC1 point
+    match point.split_first() {
+        Some((0, point)) => Ok((curve_id, point)),
+        _ => Err(CryptoError::InvalidEncoding),
+    }
+}
+
+/// The parts of a SEC1 `ECPrivateKey`: the named curve (if any), the private scalar's octets and the SEC1 encoded
+/// public key (if any).
+pub type Sec1PrivateKey<'a> = (Option<CurveId>, &'a [u8], Option<&'a [u8]>);
+
+/// Parses a SEC1 `ECPrivateKey` (DER, RFC 5915), as written by e.g. `openssl ec -outform DER` i.e.
+/// `SEQUENCE { INTEGER 1, OCTET STRING privateKey, [0] { OID namedCurve } OPTIONAL, [1] { BIT STRING publicKey } OPTIONAL }`.
+///
+/// - Returns the curve (if the parameters are present), the private scalar's octets and the SEC1 encoded public key
+///   (if present) - neither of which is validated here. Malformed DER gives `CryptoError::InvalidEncoding` and an
+///   unknown named curve (or explicit curve parameters) `CryptoError::UnsupportedCurve`.
+pub fn parse_sec1_ec_private_key(der: &[u8]) -> Result<Sec1PrivateKey<'_>> {
+    let (key, rest) = der_tlv(der, 0x30)?;
+    if !rest.is_empty() {
+        return Err(CryptoError::InvalidEncoding);
+    }
+    let (version, rest) = der_tlv(key, 0x02)?;
+    if version != [1] {
+        return Err(CryptoError::InvalidEncoding);
+    }
+    let (scalar, mut rest) = der_tlv(rest, 0x04)?;
+
+    let mut curve_id = None;
+    if let Ok((params, remaining)) = der_tlv(rest, 0xa0) {
+        let (curve, params_rest) = der_tlv(params, 0x06).map_err(|_| CryptoError::UnsupportedCurve)?;
+        if !params_rest.is_empty() {
+            return Err(CryptoError::InvalidEncoding);
+        }
+        curve_id = Some(
+            OID_CURVES
+                .iter()
+                .find(|(oid, _)| *oid == curve)
+                .map(|(_, curve_id)| *curve_id)
+                .ok_or(CryptoError::UnsupportedCurve)?,
+        );
+        rest = remaining;
+    }
+    let mut point = None;
+    if !rest.is_empty() {
+        let (public_key, remaining) = der_tlv(rest, 0xa1)?;
+        let (bits, bits_rest) = der_tlv(public_key, 0x03)?;
+        if !remaining.is_empty() || !bits_rest.is_empty() {
+            return Err(CryptoError::InvalidEncoding);
+        }
+        // As in the SPKI, the BIT STRING's first byte is the number of unused bits
+        point = match bits.split_first() {
+            Some((0, point)) => Some(point),
+            _ => return Err(CryptoError::InvalidEncoding),
+        };
+    }
+    Ok((curve_id, scalar, point))
+}
+
+/// Encodes a SEC1 `ECPrivateKey` (DER, RFC 5915) - the inverse of `parse_sec1_ec_private_key`. The named curve and
+/// the public key are only included if given (e.g. PKCS#8 carries the curve outside of the `ECPrivateKey`).
+pub fn to_sec1_ec_private_key(curve_id: Option<CurveId>, scalar: &[u8], public_key: Option<&[u8]>) -> Vec<u8> {
+    let mut key = der_write(0x02, &[1]);
+    key.extend_from_slice(&der_write(0x04, scalar));
+    if let Some(curve_id) = curve_id {
+        key.extend_from_slice(&der_write(0xa0, &der_write(0x06, curve_oid(curve_id))));
+    }
+    if let Some(public_key) = public_key {
+        // The BIT STRING's first byte is the number of unused bits
+        let bits = [&[0], public_key].concat();
+        key.extend_from_slice(&der_write(0xa1, &der_write(0x03, &bits)));
+    }
+    der_write(0x30, &key)
+}
+
+/// The named curve OID (its DER contents) for `curve_id`.
+pub(crate) fn curve_oid(curve_id: CurveId) -> &'static [u8] {
+    OID_CURVES
+        .iter()
+        .find(|(_, id)| *id == curve_id)
+        .map(|(oid, _)| *oid)
+        .expect("every `CurveId` has an OID")
+}
+
+/// Encodes a DER TLV, with a minimal definite length - the inverse of `der_tlv`.
+pub(crate) fn der_write(tag: u8, contents: &[u8]) -> Vec<u8> {
+    let len = contents.len();
+    let mut out = Vec::with_capacity(4 + len);
+    out.push(tag);
+    match len {
+        0..=0x7f => out.push(len as u8),
+        0x80..=0xff => out.extend_from_slice(&[0x81, len as u8]),
+        _ => out.extend_from_slice(&[0x82, (len >> 8) as u8, len as u8]),
+    }
+    out.extend_from_slice(contents);
+    out
+}
+
+/// Splits a DER TLV with the given `tag` off the front of `input` - returns its contents and the remaining bytes.
+/// Only definite, minimally encoded lengths (up to 2 length bytes) are accepted.
+pub(crate) fn der_tlv(input: &[u8], tag: u8) -> Result<(&[u8], &[u8])> {
+    let (len, header_len) = match input {
+        [t, len, ..] if *t == tag && *len < 0x80 => (*len as usize, 2),
+        [t, 0x81, len, ..] if *t == tag && *len >= 0x80 => (*len as usize, 3),
+        [t, 0x82, hi, lo, ..] if *t == tag && *hi != 0 => ((*hi as usize) << 8 | *lo as usize, 4),
+        _ => return Err(CryptoError::InvalidEncoding),
+    };
+    if input.len() < header_len + len {
+        return Err(CryptoError::InvalidEncoding);
+    }
+    Ok(input[header_len..].split_at(len))
+}
+
+/// Encodes bytes as unpadded base64url (RFC 4648 §5), as used by JWK/JOSE.
+pub fn to_base64url(bytes: &[u8]) -> String {
+    let mut encoded = String::with_capacity((bytes.len() * 4).div_ceil(3));
+    for chunk in bytes.chunks(3) {
+        let n = chunk
+            .iter()
+            .enumerate()
+            .fold(0u32, |n, (i, b)| n | (*b as u32) << (16 - 8 * i));
+        // 1, 2 or 3 input bytes give 2, 3 or 4 output characters
+        for i in 0..=chunk.len() {
+            encoded.push(BASE64URL_CHARS[(n >> (18 - 6 * i) & 0x3f) as usize] as char);
+        }
+    }
+    encoded
+}
+
+/// Decodes unpadded base64url (RFC 4648 §5).
+///
+/// Returns `CryptoError::InvalidEncoding` for padding, characters outside the base64url alphabet, an impossible
+/// length or non-zero trailing bits (so that every byte string has exactly one encoding).
+pub fn from_base64url(encoded: &str) -> Result<Vec<u8>> {
+    let encoded = encoded.as_bytes();
+    if encoded.len() % 4 == 1 {
+        return Err(CryptoError::InvalidEncoding);
+    }
+    let mut bytes = Vec::with_capacity(encoded.len() * 3 / 4);
+    for chunk in encoded.chunks(4) {
+        let mut n = 0u32;
+        for (i, c) in chunk.iter().enumerate() {
+            n |= (base64url_value(*c)? as u32) << (18 - 6 * i);
+        }
+        let len = chunk.len() - 1;
+        if n & (0xffffff >> (8 * len)) != 0 {
+            return Err(CryptoError::InvalidEncoding);
+        }
+        for i in 0..len {
+            bytes.push((n >> (16 - 8 * i)) as u8);
+        }
+    }
+    Ok(bytes)
+}
+
+fn base64url_value(c: u8) -> Result<u8> {
+    match c {
+        b'A'..=b'Z' => Ok(c - b'A'),
+        b'a'..=b'z' => Ok(c - b'a' + 26),
+        b'0'..=b'9' => Ok(c - b'0' + 52),
+        b'-' => Ok(62),
+        b'_' => Ok(63),
+        _ => Err(CryptoError::InvalidEncoding),
+    }
+}
+
+/// The members of an EC JSON Web Key (RFC 7517/7518) that we care about, base64url-decoded.
+pub(crate) struct EcJwk {
+    pub(crate) x: Vec<u8>,
+    pub(crate) y: Vec<u8>,
+    pub(crate) d: Option<Vec<u8>>,
+}
+
+impl Drop for EcJwk {
+    fn drop(&mut self) {
+        if let Some(d) = self.d.as_mut() {
+            d.as_mut_slice().zeroize();
+        }
+    }
+}
+
+/// Parses an EC JWK, checking that `kty` is `"EC"` and `crv` is `crv`. Members other than `kty`, `crv`, `x`, `y`
+/// and `d` (e.g. `kid`, `use`, `key_ops`) are ignored.
+///
+/// - Returns `CryptoError::InvalidEncoding` for malformed JSON, a wrong `kty`/`crv` or a missing co-ordinate.
+pub(crate) fn parse_ec_jwk(jwk: &str, crv: &str) -> Result<EcJwk> {
+    let members = json_string_members(jwk)?;
+    let member = |name: &str| members.iter().find(|(k, _)| k == name).map(|(_, v)| v.as_str());
+    if member("kty") != Some("EC") || member("crv") != Some(crv) {
+        return Err(CryptoError::InvalidEncoding);
+    }
+    let decode = |name: &str| member(name).map(from_base64url).transpose();
+    Ok(EcJwk {
+        x: decode("x")?.ok_or(CryptoError::InvalidEncoding)?,
+        y: decode("y")?.ok_or(CryptoError::InvalidEncoding)?,
+        d: decode("d")?,
+    })
+}
+
+/// Serializes an EC JWK - the co-ordinates (and `d`) are base64url-encoded.
+pub(crate) fn to_ec_jwk(crv: &str, x: &[u8], y: &[u8], d: Option<&[u8]>) -> String {
+    let mut jwk = alloc::format!(
+        r#"{{"kty":"EC","crv":"{}","x":"{}","y":"{}""#,
+        crv,
+        to_base64url(x),
+        to_base64url(y)
+    );
+    if let Some(d) = d {
+        jwk.push_str(&alloc::format!(r#","d":"{}""#, to_base64url(d)));
+    }
+    jwk.push('}');
+    jwk
+}
+
+/// Reads the top-level members of a JSON object, keeping those with string values (which is all an EC JWK needs).
+/// Nested values are skipped over.
+fn json_string_members(json: &str) -> Result<Vec<(String, String)>> {
+    let mut reader = JsonReader { json: json.as_bytes(), pos: 0 };
+    let mut members = Vec::new();
+    reader.expect(b'{')?;
+    if reader.peek() == Some(b'}') {
+        reader.pos += 1;
+    } else {
+        loop {
+            let key = reader.string()?;
+            reader.expect(b':')?;
+            if reader.peek() == Some(b'"') {
+                let value = reader.string()?;
+                members.push((key, value));
+            } else {
+                reader.skip_value(0)?;
+            }
+            match reader.next()? {
+                b',' => continue,
+                b'}' => break,
+                _ => return Err(CryptoError::InvalidEncoding),
+            }
+        }
+    }
+    if reader.peek().is_some() {
+        return Err(CryptoError::InvalidEncoding);
+    }
+    Ok(members)
+}
+
+struct JsonReader<'a> {
+    json: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> JsonReader<'a> {
+    /// The next non-whitespace byte, without consuming it
+    fn peek(&mut self) -> Option<u8> {
+        while self.pos < self.json.len() && self.json[self.pos].is_ascii_whitespace() {
+            self.pos += 1;
+        }
+        self.json.get(self.pos).copied()
+    }
+
+    fn next(&mut self) -> Result<u8> {
+        let c = self.peek().ok_or(CryptoError::InvalidEncoding)?;
+        self.pos += 1;
+        Ok(c)
+    }
+
+    fn expect(&mut self, c: u8) -> Result<()> {
+        if self.next()? != c {
+            return Err(CryptoError::InvalidEncoding);
+        }
+        Ok(())
+    }
+
+    fn string(&mut self) -> Result<String> {
+        self.expect(b'"')?;
+        let mut value = Vec::new();
+        loop {
+            let c = *self.json.get(self.pos).ok_or(CryptoError::InvalidEncoding)?;
+            self.pos += 1;
+            match c {
+                b'"' => break,
+                b'\\' => {
+                    let escaped = *self.json.get(self.pos).ok_or(CryptoError::InvalidEncoding)?;
+                    self.pos += 1;
+                    match escaped {
+                        b'"' | b'\\' | b'/' => value.push(escaped),
+                        b'b' => value.push(0x08),
+                        b'f' => value.push(0x0c),
+                        b'n' => value.push(b'\n'),
+                        b'r' => value.push(b'\r'),
+                        b't' => value.push(b'\t'),
+                        b'u' => {
+                            let hex = self.json.get(self.pos..self.pos + 4).ok_or(CryptoError::InvalidEncoding)?;
+                            let code = hex.iter().try_fold(0u32, |n, c| hex_value(*c).map(|v| (n << 4) | v as u32))?;
+                            let c = core::char::from_u32(code).ok_or(CryptoError::InvalidEncoding)?;
+                            let mut buf = [0; 4];
+                            value.extend_from_slice(c.encode_utf8(&mut buf).as_bytes());
+                            self.pos += 4;
+                        }
+                        _ => return Err(CryptoError::InvalidEncoding),
+                    }
+                }
+                _ => value.push(c),
+            }
+        }
+        String::from_utf8(value).map_err(|_| CryptoError::InvalidEncoding)
+    }
+
+    /// Skips a non-string value i.e. a number, `true`/`false`/`null` or a (nested) array or object. `depth` is the
+    /// number of arrays and objects the value is nested in - past `MAX_JSON_DEPTH` this is a
+    /// `CryptoError::InvalidEncoding`.
+    fn skip_value(&mut self, depth: usize) -> Result<()> {
+        match self.peek().ok_or(CryptoError::InvalidEncoding)? {
+            b'"' => self.string().map(|_| ()),
+            open @ b'[' | open @ b'{' => {
+                if depth == MAX_JSON_DEPTH {
+                    return Err(CryptoError::InvalidEncoding);
+                }
+                let close = if open == b'[' { b']' } else { b'}' };
+                self.pos += 1;
+                if self.peek() == Some(close) {
+                    self.pos += 1;
+                    return Ok(());
+                }
+                loop {
+                    if open == b'{' {
+                        self.string()?;
+                        self.expect(b':')?;
+                    }
+                    self.skip_value(depth + 1)?;
+                    match self.next()? {
+                        b',' => continue,
+                        c if c == close => return Ok(()),
+                        _ => return Err(CryptoError::InvalidEncoding),
+                    }
+                }
+            }
+            _ => {
+                let start = self.pos;
+                while self.pos < self.json.len() && (self.json[self.pos].is_ascii_alphanumeric() || b"+-.".contains(&self.json[self.pos])) {
+                    self.pos += 1;
+                }
+                if self.pos == start {
+                    return Err(CryptoError::InvalidEncoding);
+                }
+                Ok(())
+            }
+        }
+    }
+}
@@ -0,0 +1,61 @@
+//! A one-shot ephemeral-ephemeral key-exchange, for forward secrecy.
+//!
+//! Each party calls `ephemeral_handshake`, sends the returned public key to the other and then passes the
+//! peer's public key to `PendingHandshake::complete`. Both end up with the same 32 byte key:
+//!
+//! - `key = HKDF-SHA256(salt = "", ikm = shared secret, info = "static-dh-ecdh ephemeral handshake" || pk_lo || pk_hi)`
+//!
+//! (see `KeyExchange::generate_shared_secret_labeled`). The ephemeral private keys never leave the
+//! `PendingHandshake`s and are erased when they are dropped, so a later compromise of either party doesn't
+//! reveal the key.
+
+use rand_chacha::rand_core::{CryptoRng, RngCore};
+use zeroize::{Zeroize, Zeroizing};
+
+use crate::ecdh::ecdh::{FromBytes, KeyExchange, ToBytes};
+use crate::ecies::EphemeralPublicKey;
+use crate::Result;
+
+/// Domain separation for the handshake key.
+const HANDSHAKE_LABEL: &[u8] = b"static-dh-ecdh ephemeral handshake";
+
+/// One side of an ephemeral-ephemeral handshake that is waiting for the peer's public key.
+pub struct PendingHandshake<C: KeyExchange> {
+    ephemeral_sk: C::SKey,
+}
+
+/// Starts a handshake for the curve `C`.
+///
+/// - Returns the serialized ephemeral public key (to be sent to the peer) and the pending handshake.
+///
+/// Note: the ephemeral key is generated from `rand::thread_rng()`, so this needs the `std` feature - see
+/// `ephemeral_handshake_with_rng` otherwise.
+#[cfg(feature = "std")]
+pub fn ephemeral_handshake<C: KeyExchange>() -> (EphemeralPublicKey<C>, PendingHandshake<C>) {
+    ephemeral_handshake_with_rng::<C, _>(&mut rand::thread_rng())
+}
+
+/// `ephemeral_handshake`, with the ephemeral key generated from `rng` (see
+/// `KeyExchange::generate_private_key_with_rng`).
+pub fn ephemeral_handshake_with_rng<C: KeyExchange, R: RngCore + CryptoRng>(
+    rng: &mut R,
+) -> (EphemeralPublicKey<C>, PendingHandshake<C>) {
+    let ephemeral_sk = C::generate_private_key_with_rng(rng);
+    let ephemeral_pk = C::generate_public_key(&ephemeral_sk).to_bytes();
+    (ephemeral_pk, PendingHandshake { ephemeral_sk })
+}
+
+impl<C: KeyExchange> PendingHandshake<C> {
+    /// Completes the handshake with the peer's serialized ephemeral public key, consuming (and so erasing)
+    /// our ephemeral private key.
+    ///
+    /// - Returns the 32 byte handshake key or an error if `peer_pk` isn't a valid public key for `C`.
+    pub fn complete(self, peer_pk: &[u8]) -> Result<Zeroizing<[u8; 32]>> {
+        let peer_pk = C::PubKey::from_bytes(peer_pk)?;
+        let mut derived = C::generate_shared_secret_labeled(&self.ephemeral_sk, &peer_pk, HANDSHAKE_LABEL)?;
+        let mut key = Zeroizing::new([0; 32]);
+        key.copy_from_slice(&derived);
+        derived.zeroize();
+        Ok(key)
+    }
+}
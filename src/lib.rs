@@ -4,6 +4,13 @@
 #![no_std]
 #![deny(unsafe_code)]
 #![deny(missing_docs)]
+// generic-array 0.14.9 deprecates its entire API in favour of 1.x, but the RustCrypto
+// (elliptic-curve 0.8) types we build on are still expressed in terms of 0.14.
+#![allow(deprecated)]
+
+extern crate alloc;
+#[cfg(feature = "std")]
+extern crate std;
 
 /// ECDH implementation 
 pub mod ecdh;
@@ -11,14 +18,36 @@ pub mod ecdh;
 pub mod dh;
 /// A module to import Hash Types from RustCrypto
 pub mod digest;
-/// ECDSA implementation
+/// Constants for the supported DH groups and elliptic curves
 pub mod constants;
+/// ECDSA implementation
+pub mod signatures;
+/// ECIES-style hybrid encryption built on the ECDH key-exchange
+pub mod ecies;
+/// One-shot ephemeral-ephemeral key-exchange (forward secrecy)
+pub mod handshake;
+/// A forward-secret key ratchet seeded by an ECDH shared secret
+pub mod ratchet;
+/// A compact wire format for signed ephemeral public keys
+pub mod wire;
+/// Text and binary encodings (hex, base58check, base64url, DER, SPKI, JWK) for keys and signatures
+pub mod encoding;
+/// AES-256 and AES-256-GCM
+mod aes;
+/// Password-based encryption of PKCS#8 private keys
+mod pkcs8;
+/// Deterministic keypairs for tests and examples
+#[cfg(feature = "testing")]
+pub mod testing;
 
 
 use core::fmt;
 
 /// The CryptoError type.
+///
+/// New variants may be added in minor releases, so matches on it need a wildcard arm.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
 pub enum CryptoError {
 
     /// Error while performing an EC Crypto operation
@@ -27,9 +56,37 @@ pub enum CryptoError {
     InvalidEncoding,
     /// Signature Error
     SignatureError,
+    /// A key that is well-formed but trivially weak (e.g. a tiny private scalar)
+    WeakKey,
+    /// A ciphertext failed authentication (it was tampered with or the wrong key was used)
+    DecryptionError,
+    /// The requested operation isn't implemented for this curve
+    UnsupportedCurve,
+    /// An input (key, point, digest or signature) has the wrong number of bytes
+    InvalidLength {
+        /// The number of bytes the input must have
+        expected: usize,
+        /// The number of bytes that were passed in
+        got: usize,
+    },
+    /// A public key's co-ordinates don't satisfy the curve equation
+    PointNotOnCurve,
+    /// A public key or the result of the key-exchange is the point at infinity (for X25519, the all-zero output of
+    /// a low-order public key)
+    PointAtInfinity,
+    /// A private scalar isn't in `[1, n - 1]`
+    ScalarOutOfRange,
+}
 
-    #[doc(hidden)]
-    __Nonexhaustive,
+/// Identifies a curve at runtime, for APIs that dispatch on the curve rather than on a type.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CurveId {
+    /// secp256k1 (`ECDHNISTK256`, `ECDSASHA256Signature`)
+    Secp256k1,
+    /// NIST P-384 (`ECDHNISTP384`, `ECDSASHA384Signature`)
+    NistP384,
+    /// NIST P-521 (`ECDHNISTP521`) - key-exchange only
+    NistP521,
 }
 
 /// The result type for Crypto operations
@@ -38,17 +95,28 @@ pub type Result<T> = core::result::Result<T, CryptoError>;
 impl fmt::Display for CryptoError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
-            &CryptoError::ECCError              => write!(f, "EC Crypto operation failed"),
-            &CryptoError::InvalidEncoding       => write!(f, "Invalid encoding"),
-            &CryptoError::SignatureError        => write!(f, "Signature Error"),
-            &CryptoError::__Nonexhaustive       => unreachable!(),
+            CryptoError::ECCError              => write!(f, "EC Crypto operation failed"),
+            CryptoError::InvalidEncoding       => write!(f, "Invalid encoding"),
+            CryptoError::SignatureError        => write!(f, "Signature Error"),
+            CryptoError::WeakKey               => write!(f, "Weak key"),
+            CryptoError::DecryptionError       => write!(f, "Decryption failed"),
+            CryptoError::UnsupportedCurve      => write!(f, "Unsupported curve"),
+            CryptoError::InvalidLength { expected, got } => {
+                write!(f, "Invalid length: expected {} bytes, got {}", expected, got)
+            }
+            CryptoError::PointNotOnCurve       => write!(f, "Point is not on the curve"),
+            CryptoError::PointAtInfinity       => write!(f, "Point at infinity"),
+            CryptoError::ScalarOutOfRange      => write!(f, "Scalar out of range"),
         }
     }   
 }
 
+/// Lets `CryptoError` be returned through `Box<dyn Error>` (and error-handling crates built on it).
+#[cfg(feature = "std")]
+impl std::error::Error for CryptoError {}
+
 impl From<k256::elliptic_curve::Error> for CryptoError {
     fn from(_error: k256::elliptic_curve::Error) -> Self {
         CryptoError::ECCError
     }
 }
-
@@ -0,0 +1,239 @@
+//! Password-protected PKCS#8 (RFC 5958 `EncryptedPrivateKeyInfo`) with PBES2 (RFC 8018): PBKDF2-HMAC-SHA256 for the
+//! key derivation and AES-256-CBC for the encryption - the `openssl pkcs8 -topk8 -v2 aes-256-cbc` default.
+//!
+//! CBC isn't authenticated. A wrong password is caught by the padding and the DER of the decrypted
+//! `PrivateKeyInfo` not parsing (and, for a key that does parse, by its embedded public key not matching), which
+//! is how every PKCS#8 reader detects it.
+//!
+//! Note: the salt and the IV are generated from the caller's rng. The AES (see `aes`) is a table-based textbook
+//! implementation and isn't constant time.
+
+use alloc::vec::Vec;
+
+use rand_chacha::rand_core::{CryptoRng, RngCore};
+use zeroize::{Zeroize, Zeroizing};
+
+use crate::aes::{decrypt_block, encrypt_block, expand_key, BLOCK_LEN, KEY_LEN};
+use crate::digest::pbkdf2_hmac_sha256;
+use crate::encoding::{curve_oid, der_tlv, der_write, OID_CURVES, OID_EC_PUBLIC_KEY};
+use crate::{CryptoError, CurveId, Result};
+
+/// `id-PBES2` (1.2.840.113549.1.5.13)
+const OID_PBES2: &[u8] = &[0x2a, 0x86, 0x48, 0x86, 0xf7, 0x0d, 0x01, 0x05, 0x0d];
+/// `id-PBKDF2` (1.2.840.113549.1.5.12)
+const OID_PBKDF2: &[u8] = &[0x2a, 0x86, 0x48, 0x86, 0xf7, 0x0d, 0x01, 0x05, 0x0c];
+/// `id-hmacWithSHA256` (1.2.840.113549.2.9)
+const OID_HMAC_SHA256: &[u8] = &[0x2a, 0x86, 0x48, 0x86, 0xf7, 0x0d, 0x02, 0x09];
+/// `aes256-CBC-PAD` (2.16.840.1.101.3.4.1.42)
+const OID_AES256_CBC: &[u8] = &[0x60, 0x86, 0x48, 0x01, 0x65, 0x03, 0x04, 0x01, 0x2a];
+
+/// The PBKDF2 iteration count for new blobs.
+const ITERATIONS: u32 = 100_000;
+/// The largest iteration count accepted on import, so that a crafted blob can't make decryption run for minutes.
+const MAX_ITERATIONS: u32 = 10_000_000;
+
+const SALT_LEN: usize = 16;
+
+/// Wraps a SEC1 `ECPrivateKey` in a PKCS#8 `PrivateKeyInfo` for `curve_id` and encrypts it under `password`, with
+/// the salt and the IV drawn from `rng`.
+pub(crate) fn encrypt<R: RngCore + CryptoRng>(curve_id: CurveId, ec_private_key: &[u8], password: &[u8], rng: &mut R) -> Vec<u8> {
+    let mut salt = [0; SALT_LEN];
+    let mut iv = [0; BLOCK_LEN];
+    rng.fill_bytes(&mut salt);
+    rng.fill_bytes(&mut iv);
+
+    let plaintext = Zeroizing::new(private_key_info(curve_id, ec_private_key));
+    let key = derive_key(password, &salt, ITERATIONS);
+    let ciphertext = cbc_encrypt(&key, &iv, &plaintext);
+
+    let kdf_params = [
+        der_write(0x04, &salt),
+        der_write(0x02, &der_uint(ITERATIONS)),
+        der_write(0x30, &[der_write(0x06, OID_HMAC_SHA256), der_write(0x05, &[])].concat()),
+    ]
+    .concat();
+    let kdf = der_write(0x30, &[der_write(0x06, OID_PBKDF2), der_write(0x30, &kdf_params)].concat());
+    let cipher = der_write(0x30, &[der_write(0x06, OID_AES256_CBC), der_write(0x04, &iv)].concat());
+    let pbes2_params = der_write(0x30, &[kdf, cipher].concat());
+    let algorithm = der_write(0x30, &[der_write(0x06, OID_PBES2), pbes2_params].concat());
+    der_write(0x30, &[algorithm, der_write(0x04, &ciphertext)].concat())
+}
+
+/// The inverse of `encrypt` - decrypts an `EncryptedPrivateKeyInfo` and returns the `ECPrivateKey` inside it.
+///
+/// - Returns `CryptoError::InvalidEncoding` for malformed DER, an algorithm other than PBES2 with
+///   PBKDF2-HMAC-SHA256 and AES-256-CBC, too many iterations, a key for another curve or a wrong password.
+pub(crate) fn decrypt(curve_id: CurveId, der: &[u8], password: &[u8]) -> Result<Zeroizing<Vec<u8>>> {
+    let (info, rest) = der_tlv(der, 0x30)?;
+    if !rest.is_empty() {
+        return Err(CryptoError::InvalidEncoding);
+    }
+    let (algorithm, rest) = der_tlv(info, 0x30)?;
+    let (ciphertext, rest) = der_tlv(rest, 0x04)?;
+    if !rest.is_empty() {
+        return Err(CryptoError::InvalidEncoding);
+    }
+    let (oid, rest) = der_tlv(algorithm, 0x06)?;
+    let (pbes2_params, tail) = der_tlv(rest, 0x30)?;
+    if oid != OID_PBES2 || !tail.is_empty() {
+        return Err(CryptoError::InvalidEncoding);
+    }
+    let (kdf, rest) = der_tlv(pbes2_params, 0x30)?;
+    let (cipher, tail) = der_tlv(rest, 0x30)?;
+    if !tail.is_empty() {
+        return Err(CryptoError::InvalidEncoding);
+    }
+    let (salt, iterations) = parse_pbkdf2(kdf)?;
+    let iv = parse_aes256_cbc(cipher)?;
+
+    let key = derive_key(password, salt, iterations);
+    let plaintext = cbc_decrypt(&key, iv, ciphertext)?;
+    parse_private_key_info(curve_id, &plaintext).map(|key| Zeroizing::new(key.to_vec()))
+}
+
+/// Returns the salt and the iteration count of a PBKDF2 `AlgorithmIdentifier`.
+fn parse_pbkdf2(kdf: &[u8]) -> Result<(&[u8], u32)> {
+    let (oid, rest) = der_tlv(kdf, 0x06)?;
+    let (params, tail) = der_tlv(rest, 0x30)?;
+    if oid != OID_PBKDF2 || !tail.is_empty() {
+        return Err(CryptoError::InvalidEncoding);
+    }
+    let (salt, rest) = der_tlv(params, 0x04)?;
+    let (iterations, mut rest) = der_tlv(rest, 0x02)?;
+    let iterations = parse_uint(iterations)?;
+    if iterations == 0 || iterations > MAX_ITERATIONS {
+        return Err(CryptoError::InvalidEncoding);
+    }
+    // The optional keyLength has to match AES-256's
+    if rest.first() == Some(&0x02) {
+        let (key_len, tail) = der_tlv(rest, 0x02)?;
+        if parse_uint(key_len)? != KEY_LEN as u32 {
+            return Err(CryptoError::InvalidEncoding);
+        }
+        rest = tail;
+    }
+    // The prf defaults to hmacWithSHA1, which isn't supported
+    let (prf, tail) = der_tlv(rest, 0x30)?;
+    let (prf_oid, prf_params) = der_tlv(prf, 0x06)?;
+    if prf_oid != OID_HMAC_SHA256 || !(prf_params.is_empty() || prf_params == [0x05, 0x00]) || !tail.is_empty() {
+        return Err(CryptoError::InvalidEncoding);
+    }
+    Ok((salt, iterations))
+}
+
+/// Returns the IV of an AES-256-CBC `AlgorithmIdentifier`.
+fn parse_aes256_cbc(cipher: &[u8]) -> Result<&[u8]> {
+    let (oid, rest) = der_tlv(cipher, 0x06)?;
+    let (iv, tail) = der_tlv(rest, 0x04)?;
+    if oid != OID_AES256_CBC || iv.len() != BLOCK_LEN || !tail.is_empty() {
+        return Err(CryptoError::InvalidEncoding);
+    }
+    Ok(iv)
+}
+
+/// `PrivateKeyInfo` (version 0) with an `id-ecPublicKey` algorithm on the named curve `curve_id`.
+fn private_key_info(curve_id: CurveId, ec_private_key: &[u8]) -> Vec<u8> {
+    let algorithm =
+        der_write(0x30, &[der_write(0x06, OID_EC_PUBLIC_KEY), der_write(0x06, curve_oid(curve_id))].concat());
+    let mut contents = der_write(0x02, &[0]);
+    contents.extend_from_slice(&algorithm);
+    let mut key = der_write(0x04, ec_private_key);
+    contents.extend_from_slice(&key);
+    key.zeroize();
+    let info = der_write(0x30, &contents);
+    contents.zeroize();
+    info
+}
+
+/// Returns the `ECPrivateKey` of a `PrivateKeyInfo` for `curve_id` - trailing attributes (`[0]`) are ignored.
+fn parse_private_key_info(curve_id: CurveId, der: &[u8]) -> Result<&[u8]> {
+    let (info, rest) = der_tlv(der, 0x30)?;
+    if !rest.is_empty() {
+        return Err(CryptoError::InvalidEncoding);
+    }
+    let (version, rest) = der_tlv(info, 0x02)?;
+    let (algorithm, rest) = der_tlv(rest, 0x30)?;
+    let (ec_private_key, _attributes) = der_tlv(rest, 0x04)?;
+    let (oid, rest) = der_tlv(algorithm, 0x06)?;
+    let (curve, tail) = der_tlv(rest, 0x06)?;
+    let named_curve = OID_CURVES.iter().find(|(oid, _)| *oid == curve).map(|(_, id)| *id);
+    if version != [0] || oid != OID_EC_PUBLIC_KEY || named_curve != Some(curve_id) || !tail.is_empty() {
+        return Err(CryptoError::InvalidEncoding);
+    }
+    Ok(ec_private_key)
+}
+
+fn derive_key(password: &[u8], salt: &[u8], iterations: u32) -> Zeroizing<[u8; KEY_LEN]> {
+    let mut key = Zeroizing::new([0; KEY_LEN]);
+    pbkdf2_hmac_sha256(password, salt, iterations, key.as_mut());
+    key
+}
+
+/// The minimal DER INTEGER contents of `value`.
+fn der_uint(value: u32) -> Vec<u8> {
+    let bytes = value.to_be_bytes();
+    let start = bytes.iter().position(|b| *b != 0).unwrap_or(3);
+    // A leading 1 bit would make it negative
+    let mut out = if bytes[start] & 0x80 != 0 { alloc::vec![0] } else { Vec::new() };
+    out.extend_from_slice(&bytes[start..]);
+    out
+}
+
+/// Parses the contents of a non-negative DER INTEGER that fits in a `u32`.
+fn parse_uint(contents: &[u8]) -> Result<u32> {
+    if contents.is_empty() || contents[0] & 0x80 != 0 {
+        return Err(CryptoError::InvalidEncoding);
+    }
+    let digits = if contents.len() > 1 && contents[0] == 0 { &contents[1..] } else { contents };
+    if digits.len() > 4 {
+        return Err(CryptoError::InvalidEncoding);
+    }
+    Ok(digits.iter().fold(0, |acc, b| acc << 8 | *b as u32))
+}
+
+/// AES-256-CBC with PKCS#7 padding.
+fn cbc_encrypt(key: &[u8; KEY_LEN], iv: &[u8], plaintext: &[u8]) -> Vec<u8> {
+    let round_keys = Zeroizing::new(expand_key(key));
+    let pad = BLOCK_LEN - plaintext.len() % BLOCK_LEN;
+    let mut out = Vec::with_capacity(plaintext.len() + pad);
+    out.extend_from_slice(plaintext);
+    out.resize(plaintext.len() + pad, pad as u8);
+
+    let mut chain = [0; BLOCK_LEN];
+    chain.copy_from_slice(iv);
+    for block in out.chunks_mut(BLOCK_LEN) {
+        block.iter_mut().zip(chain.iter()).for_each(|(b, c)| *b ^= c);
+        encrypt_block(&round_keys, block);
+        chain.copy_from_slice(block);
+    }
+    out
+}
+
+/// The inverse of `cbc_encrypt`.
+///
+/// - Returns `CryptoError::InvalidEncoding` if the ciphertext isn't a whole number of blocks or the padding is
+///   wrong (i.e. most likely a wrong key).
+fn cbc_decrypt(key: &[u8; KEY_LEN], iv: &[u8], ciphertext: &[u8]) -> Result<Zeroizing<Vec<u8>>> {
+    if ciphertext.is_empty() || !ciphertext.len().is_multiple_of(BLOCK_LEN) {
+        return Err(CryptoError::InvalidEncoding);
+    }
+    let round_keys = Zeroizing::new(expand_key(key));
+    let mut out = Zeroizing::new(ciphertext.to_vec());
+    let mut chain = [0; BLOCK_LEN];
+    chain.copy_from_slice(iv);
+    for block in out.chunks_mut(BLOCK_LEN) {
+        let mut next = [0; BLOCK_LEN];
+        next.copy_from_slice(block);
+        decrypt_block(&round_keys, block);
+        block.iter_mut().zip(chain.iter()).for_each(|(b, c)| *b ^= c);
+        chain = next;
+    }
+
+    let pad = *out.last().expect("ciphertext isn't empty") as usize;
+    if pad == 0 || pad > BLOCK_LEN || out[out.len() - pad..].iter().any(|b| *b as usize != pad) {
+        return Err(CryptoError::InvalidEncoding);
+    }
+    let len = out.len() - pad;
+    out.truncate(len);
+    Ok(out)
+}
@@ -0,0 +1,48 @@
+//! A symmetric (hash) ratchet seeded by a static ECDH shared secret.
+//!
+//! Each step derives a message key from the current chain key and then replaces the chain key:
+//!
+//! - `message_key_i = HKDF(chain_key_i, "msg")`
+//! - `chain_key_{i+1} = HKDF(chain_key_i, "chain")`
+//!
+//! As the old chain key is overwritten, a compromise of the ratchet's state doesn't reveal earlier message keys.
+
+use zeroize::{Zeroize, Zeroizing};
+
+use crate::digest::hkdf_sha256;
+use crate::ecdh::ecdh::{SharedSecretk256, ToBytes};
+
+/// Domain separation for the initial chain key.
+const ROOT_INFO: &[u8] = b"static-dh-ecdh ratchet";
+const CHAIN_INFO: &[u8] = b"chain";
+const MESSAGE_INFO: &[u8] = b"msg";
+
+/// A forward-secret sequence of 32 byte keys, derived from a `SharedSecretk256`.
+///
+/// Both parties to the key-exchange get the same sequence of keys, as long as they call `next_key` in lock-step.
+pub struct Ratchet {
+    chain_key: Zeroizing<[u8; 32]>,
+}
+
+impl Ratchet {
+    /// Initializes a ratchet, with `chain_key_0 = HKDF(shared_secret, "static-dh-ecdh ratchet")`.
+    pub fn new(shared_secret: &SharedSecretk256) -> Self {
+        let mut chain_key = Zeroizing::new([0; 32]);
+        let mut shared_secret = shared_secret.to_bytes();
+        hkdf_sha256(&[], &shared_secret, ROOT_INFO, &mut *chain_key);
+        shared_secret.as_mut_slice().zeroize();
+        Ratchet { chain_key }
+    }
+
+    /// Returns the next message key and advances the chain, erasing the previous chain key.
+    pub fn next_key(&mut self) -> Zeroizing<[u8; 32]> {
+        let mut message_key = Zeroizing::new([0; 32]);
+        hkdf_sha256(&[], &*self.chain_key, MESSAGE_INFO, &mut *message_key);
+
+        let mut next_chain_key = Zeroizing::new([0; 32]);
+        hkdf_sha256(&[], &*self.chain_key, CHAIN_INFO, &mut *next_chain_key);
+        // The old chain key is zeroized as it's dropped
+        self.chain_key = next_chain_key;
+        message_key
+    }
+}
@@ -3,20 +3,32 @@
 use core::convert::TryFrom;
 use core::convert::TryInto;
 
-use k256::ecdsa::{signature::Signer, signature::Verifier, Signature, SigningKey, VerifyingKey};
-use p256::EncodedPoint;
+use k256::ecdsa::{recoverable, signature::Signer, signature::Verifier, Signature, SigningKey, VerifyingKey};
+use k256::{AffinePoint, EncodedPoint, NonZeroScalar, ProjectivePoint, Scalar};
+use ecdsa::hazmat::{RecoverableSignPrimitive, VerifyPrimitive};
+use sha2::{Digest, Sha256, Sha384};
 use p384::NistP384;
 
-use rand_chacha::rand_core::{RngCore, SeedableRng};
+use rand_chacha::rand_core::{CryptoRng, RngCore, SeedableRng};
 use rand_chacha::ChaChaRng;
 
-use crate::ecdh::affine_math::ECSignerType;
-use crate::ecdh::ecdh::{KeyExchange, ToBytes, ECDHNISTP384};
-use elliptic_curve::sec1::EncodedPoint as EncodedPointP384;
+use crate::digest::{HmacDrbg, RIPEMD160Digest, SHA256Digest, SHA384Digest};
+#[cfg(feature = "sha3")]
+use crate::digest::Keccak256Digest;
+use crate::ecdh::affine_math::{invert_mod_prime, p256_constants, p384_constants, ECSignerType, MyAffinePoint, P256Params};
+use crate::ecdh::ecdh::{FromBytes, KeyExchange, PkP384, Pkk256, SkP384, Skk256, ToBytes, ECDHNISTP384};
+use elliptic_curve::sec1::{EncodedPoint as EncodedPointP384, ToEncodedPoint};
+use elliptic_curve::subtle::{Choice, ConstantTimeEq};
+use elliptic_curve::weierstrass::point::Decompress;
 
-use generic_array::GenericArray;
+use generic_array::{typenum::{self, Unsigned}, ArrayLength, GenericArray};
+use num_bigint_dig::{BigInt, BigUint, ModInverse, Sign};
+use num_traits::Zero;
 
-use crate::{CryptoError, Result};
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use crate::{encoding, CryptoError, CurveId, Result};
 
 // use libc_print::libc_println;
 
@@ -33,7 +45,9 @@ pub trait ECSignature {
     /// Type `s` represents the s component of an ECDSA signature.
     type s: AsRef<[u8]>;
     /// A type to hold the raw-signature i.e. `r + s in bytes`.
-    type sbytes: AsRef<[u8]>;
+    type sbytes: AsRef<[u8]> + for<'a> TryFrom<&'a [u8]>;
+    /// The size of each of the `r` and `s` components in bytes (i.e. the curve's field size).
+    type FieldSize: ArrayLength<u8>;
 
     /// Generate a ECDSA keypair.
     ///
@@ -42,6 +56,8 @@ pub trait ECSignature {
     ///
     /// For other impls, we use a mix of `SecretKey`, `PublicKey`, `EncodedPoint` types.
     /// borrowed from the elliptic-curve crate.
+    ///
+    /// Note - each impl also has a `generate(seed)` constructor, which spares callers building a placeholder first.
     fn generate_keypair(&mut self, seed: [u8; 32]);
     /// Function to sign messages of arbitrary length.
     ///
@@ -51,38 +67,133 @@ pub trait ECSignature {
     fn sign(&self, data: &[u8]) -> Result<Self::sbytes>;
     /// Function to verify a signature.
     ///
-    /// - Returns a `bool` is successful or an Error.
+    /// - Returns a `bool` is successful or an Error - `CryptoError::InvalidLength` (checked before anything else)
+    ///   if `signature` isn't exactly twice the field size.
     ///
     /// Note - we use affine point arithmetic of ECDSA calculation for curves other than p256
     fn verify(&self, data: &[u8], signature: &[u8]) -> Result<bool>;
+    /// Same as `sign` but returns a structured `EcdsaSignature` instead of raw bytes.
+    fn sign_typed(&self, data: &[u8]) -> Result<EcdsaSignature<Self::FieldSize>> {
+        EcdsaSignature::from_bytes(self.sign(data)?.as_ref())
+    }
+    /// Same as `verify` but takes a structured `EcdsaSignature`.
+    fn verify_typed(&self, data: &[u8], signature: &EcdsaSignature<Self::FieldSize>) -> Result<bool> {
+        self.verify(data, &signature.to_bytes())
+    }
     /// The raw `r` component of a signature in bytes
     fn r(s: Self::sbytes) -> Self::r;
     /// The raw `s` component of a signature in bytes
     fn s(s: Self::sbytes) -> Self::s;
+    /// The raw signature (`r + s`) as a lower-case hex-string
+    fn to_hex(s: &Self::sbytes) -> String {
+        encoding::to_hex(s.as_ref())
+    }
+    /// Parses a raw signature (`r + s`) from a hex-string, with or without a `0x` prefix.
+    ///
+    /// - Returns `CryptoError::InvalidEncoding` on malformed hex or `CryptoError::InvalidLength` for a wrong signature length.
+    fn from_hex(hex_string: &str) -> Result<Self::sbytes> {
+        Self::from_slice(&encoding::from_hex(hex_string)?)
+    }
+    /// The raw signature (`r + s`) as unpadded base64url, e.g. for a JWS
+    #[cfg(feature = "base64")]
+    fn to_base64url(s: &Self::sbytes) -> String {
+        encoding::to_base64url(s.as_ref())
+    }
+    /// Parses a raw signature (`r + s`) from unpadded base64url.
+    ///
+    /// - Returns `CryptoError::InvalidEncoding` for padding or characters outside the base64url alphabet or
+    ///   `CryptoError::InvalidLength` for a wrong signature length.
+    #[cfg(feature = "base64")]
+    fn from_base64url(encoded: &str) -> Result<Self::sbytes> {
+        Self::from_slice(&encoding::from_base64url(encoded)?)
+    }
+    /// Parses a raw signature (`r + s`) from a byte slice e.g. straight off the wire.
+    ///
+    /// - Returns `CryptoError::InvalidLength` unless the slice is exactly `2 * FieldSize` bytes long.
+    fn from_slice(bytes: &[u8]) -> Result<Self::sbytes> {
+        Self::sbytes::try_from(bytes)
+            .map_err(|_| CryptoError::InvalidLength { expected: 2 * Self::FieldSize::to_usize(), got: bytes.len() })
+    }
+    /// The raw signature (`r + s`) as an ASN.1 DER `SEQUENCE { INTEGER r, INTEGER s }`
+    fn to_der(s: &Self::sbytes) -> Vec<u8> {
+        let (r, s) = s.as_ref().split_at(s.as_ref().len() / 2);
+        encoding::to_der_signature(r, s)
+    }
+    /// Same as `to_der` but writes into a caller-provided (e.g. stack) buffer, for targets without an allocator.
+    ///
+    /// - Returns the number of bytes written or `CryptoError::InvalidEncoding` if `out` is too small.
+    fn to_der_into(s: &Self::sbytes, out: &mut [u8]) -> Result<usize> {
+        let (r, s) = s.as_ref().split_at(s.as_ref().len() / 2);
+        encoding::der_signature_into(r, s, out)
+    }
+}
+
+/// An ECDSA signature i.e. its `r` and `s` components, each `N` (the curve's field size) bytes long.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EcdsaSignature<N: ArrayLength<u8>> {
+    r: GenericArray<u8, N>,
+    s: GenericArray<u8, N>,
 }
 
-/// A type to represent an ECDSA-SHA256 Signature. Tuple elements 0 and 1 represent the `signing and verifying` keys
+impl<N: ArrayLength<u8>> EcdsaSignature<N> {
+    /// Builds a signature from its `r` and `s` components.
+    pub fn new(r: GenericArray<u8, N>, s: GenericArray<u8, N>) -> Self {
+        EcdsaSignature { r, s }
+    }
+
+    /// Parses a raw signature i.e. `r || s`.
+    ///
+    /// - Returns `CryptoError::InvalidLength` if `bytes` isn't exactly `2 * N` bytes long.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        if bytes.len() != 2 * N::to_usize() {
+            return Err(CryptoError::InvalidLength { expected: 2 * N::to_usize(), got: bytes.len() });
+        }
+        let (r, s) = bytes.split_at(N::to_usize());
+        Ok(EcdsaSignature {
+            r: GenericArray::clone_from_slice(r),
+            s: GenericArray::clone_from_slice(s),
+        })
+    }
+
+    /// Serializes the signature as `r || s`.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(2 * N::to_usize());
+        bytes.extend_from_slice(&self.r);
+        bytes.extend_from_slice(&self.s);
+        bytes
+    }
+
+    /// The `r` component
+    pub fn r(&self) -> &GenericArray<u8, N> {
+        &self.r
+    }
+
+    /// The `s` component
+    pub fn s(&self) -> &GenericArray<u8, N> {
+        &self.s
+    }
+}
+
+impl<N: ArrayLength<u8>> TryFrom<&[u8]> for EcdsaSignature<N> {
+    type Error = CryptoError;
+
+    fn try_from(bytes: &[u8]) -> Result<Self> {
+        Self::from_bytes(bytes)
+    }
+}
+
+/// A type to represent an ECDSA-SHA256 Signature over secp256k1 (for NIST P-256, see `ECDSAP256SHA256Signature`).
+/// Tuple elements 0 and 1 represent the `signing and verifying` keys
 pub struct ECDSASHA256Signature(pub [u8; 32], pub [u8; 64]);
 
 impl ECSignature for ECDSASHA256Signature {
     type r = [u8; 32];
     type s = [u8; 32];
     type sbytes = [u8; 64];
+    type FieldSize = typenum::U32;
 
     fn generate_keypair(&mut self, seed: [u8; 32]) {
-        let mut rng = ChaChaRng::from_seed(seed); // test seed value.
-        let mut dest = [0; 32];
-        rng.fill_bytes(&mut dest);
-        let signing_key = SigningKey::from_bytes(&dest).unwrap();
-        let verifying_key = VerifyingKey::from(&signing_key);
-        self.0 = signing_key.to_bytes().as_slice().try_into().unwrap();
-        self.1 = verifying_key
-            .to_encoded_point(false)
-            .to_untagged_bytes()
-            .unwrap()
-            .as_slice()
-            .try_into()
-            .unwrap();
+        *self = ECDSASHA256Signature::generate(seed);
     }
 
     fn sign(&self, data: &[u8]) -> Result<Self::sbytes> {
@@ -91,23 +202,19 @@ impl ECSignature for ECDSASHA256Signature {
             .map(|sk| sk.sign(data))
             .map_err(|_| CryptoError::SignatureError);
         signature
-            .map(|s| s.as_ref().try_into().unwrap())
+            .map(|s: Signature| s.as_ref().try_into().unwrap())
             .map_err(|_| CryptoError::SignatureError)
     }
 
     fn verify(&self, data: &[u8], signature: &[u8]) -> Result<bool> {
+        check_signature_len(signature, 64)?;
         let verifying_key = self.1;
         let generic_arr = GenericArray::clone_from_slice(&verifying_key);
         let encoded_vk = EncodedPoint::from_untagged_bytes(&generic_arr);
         let verifying_key = VerifyingKey::from_encoded_point(&encoded_vk)
             .map_err(|_| CryptoError::SignatureError)?;
-        Ok(verifying_key
-            .verify(
-                data,
-                &Signature::try_from(signature)
-                    .expect("failed to parse serialized siganture bytes"),
-            )
-            .is_ok())
+        let signature = Signature::try_from(signature).map_err(|_| CryptoError::SignatureError)?;
+        Ok(verifying_key.verify(data, &signature).is_ok())
     }
 
     fn r(s: Self::sbytes) -> [u8; 32] {
@@ -121,6 +228,496 @@ impl ECSignature for ECDSASHA256Signature {
     }
 }
 
+impl ECDSASHA256Signature {
+    /// Generates a signer with a fresh keypair, given a 32 byte seed value.
+    pub fn generate(seed: [u8; 32]) -> Self {
+        let mut rng = ChaChaRng::from_seed(seed); // test seed value.
+        let mut dest = [0; 32];
+        rng.fill_bytes(&mut dest);
+        let signing_key = SigningKey::from_bytes(&dest).unwrap();
+        let verifying_key = VerifyingKey::from(&signing_key);
+        ECDSASHA256Signature(
+            signing_key.to_bytes().as_slice().try_into().unwrap(),
+            verifying_key
+                .to_encoded_point(false)
+                .to_untagged_bytes()
+                .unwrap()
+                .as_slice()
+                .try_into()
+                .unwrap(),
+        )
+    }
+
+    /// Builds a signer from a stored 32 byte signing key (i.e. the private scalar), deriving the verifying key - no
+    /// RNG involved, unlike `generate`.
+    ///
+    /// - Returns `CryptoError::ScalarOutOfRange` if the scalar isn't in `[1, n - 1]`.
+    pub fn from_signing_key(signing: &[u8; 32]) -> Result<Self> {
+        let signing_key = SigningKey::from_bytes(signing).map_err(|_| CryptoError::ScalarOutOfRange)?;
+        let verifying_key = VerifyingKey::from(&signing_key).to_encoded_point(false);
+        Ok(ECDSASHA256Signature(*signing, verifying_key.as_bytes()[1..].try_into().unwrap()))
+    }
+
+    /// Starts signing a message incrementally, see `StreamSigner`.
+    pub fn sign_stream(&self) -> StreamSigner<'_, Self, Sha256> {
+        StreamSigner { signer: self, hasher: Sha256::new() }
+    }
+
+    /// Starts verifying `signature` over a message fed incrementally, see `StreamVerifier`.
+    pub fn verify_stream<'a>(&'a self, signature: &'a [u8]) -> StreamVerifier<'a, Self, Sha256> {
+        StreamVerifier { verifier: self, signature, hasher: Sha256::new() }
+    }
+
+    /// Parses the signing key once, for repeated signing.
+    pub fn prepare_signer(&self) -> Result<PreparedSigner> {
+        PreparedSigner::new(&self.0)
+    }
+
+    /// Parses the verifying key once, for repeated verification.
+    pub fn prepare_verifier(&self) -> Result<PreparedVerifier> {
+        PreparedVerifier::new(&self.1)
+    }
+
+    /// Verifies a signature over `data` against an explicitly supplied public key, e.g. a third party's - no
+    /// signing key needed. `pubkey` is either the 64 byte untagged `x || y` form stored in this type or a SEC1
+    /// encoded (33 byte compressed or 65 byte uncompressed) key.
+    ///
+    /// - Returns a `bool` if successful or an Error (`CryptoError::InvalidEncoding` if `pubkey` isn't a
+    /// valid public key).
+    pub fn verify_with_key(pubkey: &[u8], data: &[u8], signature: &[u8]) -> Result<bool> {
+        check_signature_len(signature, 64)?;
+        let verifying_key = if pubkey.len() == 64 {
+            VerifyingKey::from_encoded_point(&EncodedPoint::from_untagged_bytes(GenericArray::from_slice(pubkey)))
+        } else {
+            VerifyingKey::from_sec1_bytes(pubkey)
+        }
+        .map_err(|_| CryptoError::InvalidEncoding)?;
+        let signature = Signature::try_from(signature).map_err(|_| CryptoError::SignatureError)?;
+        Ok(verifying_key.verify(data, &signature).is_ok())
+    }
+
+    /// Verifies several signatures over the same `message`, each against its own (uncompressed SEC1) public key,
+    /// e.g. the signers of a multisig. The message is hashed once, and the result says which signatures are valid -
+    /// `result[i]` is what `verify_with_key` would return for `sigs_and_keys[i]`.
+    ///
+    /// - A signature that doesn't parse (e.g. `r` or `s` out of range) is `false`, as it is invalid for whoever signed.
+    /// - Returns `CryptoError::InvalidEncoding` if any public key isn't a valid point - the keys are the caller's,
+    ///   so that is an input error rather than a failed signature.
+    pub fn verify_multi(message: &[u8], sigs_and_keys: &[([u8; 64], [u8; 65])]) -> Result<Vec<bool>> {
+        let digest = SHA256Digest.digest(message);
+        let z = Scalar::from_bytes_reduced(GenericArray::from_slice(&digest));
+        let public_keys = sigs_and_keys
+            .iter()
+            .map(|(_, pubkey)| k256::PublicKey::from_sec1_bytes(pubkey).map_err(|_| CryptoError::InvalidEncoding))
+            .collect::<Result<Vec<_>>>()?;
+        Ok(sigs_and_keys
+            .iter()
+            .zip(public_keys)
+            .map(|((signature, _), public_key)| match Signature::try_from(&signature[..]) {
+                Ok(signature) => public_key.as_affine().verify_prehashed(&z, &signature).is_ok(),
+                Err(_) => false,
+            })
+            .collect())
+    }
+
+    /// Returns this signer's keypair as ECDH keys, for a protocol that both signs and key-exchanges with it.
+    ///
+    /// Note: using the same key for ECDSA and ECDH is common but not ideal, as neither scheme's security proof
+    /// covers the other's use of the key. Prefer separate keypairs where the protocol allows it.
+    pub fn as_ecdh_keys(&self) -> Result<(Skk256, Pkk256)> {
+        Ok((Skk256::from_bytes(&self.0)?, Pkk256::from_untagged_bytes(&self.1)?))
+    }
+
+    /// Signs an already computed SHA-256 digest, skipping the hashing step in `sign`.
+    ///
+    /// The ephemeral scalar is derived as per RFC 6979, so `sign_prehashed(&sha256(data))` returns the same
+    /// signature as `sign(data)`.
+    pub fn sign_prehashed(&self, digest: &[u8; 32]) -> Result<[u8; 64]> {
+        let (signature, _) = self.sign_prehashed_recoverable(digest)?;
+        Ok(signature.as_ref().try_into().unwrap())
+    }
+
+    /// Signs a digest and also returns the recovery-id (i.e. whether the `y` co-ordinate of `R` is odd).
+    fn sign_prehashed_recoverable(&self, digest: &[u8; 32]) -> Result<(Signature, bool)> {
+        let secret_key =
+            k256::SecretKey::from_bytes(&self.0).map_err(|_| CryptoError::SignatureError)?;
+        let x = secret_key.secret_scalar();
+        let z = Scalar::from_bytes_reduced(GenericArray::from_slice(digest));
+
+        let mut hmac_drbg = HmacDrbg::<Sha256>::new(&x.to_bytes(), &z.to_bytes());
+        let k = loop {
+            if let Some(k) = NonZeroScalar::from_repr(hmac_drbg.next()) {
+                break k;
+            }
+        };
+        x.try_sign_recoverable_prehashed(k.as_ref(), &z)
+            .map_err(|_| CryptoError::SignatureError)
+    }
+
+    /// Verifies a signature over an already computed SHA-256 digest.
+    ///
+    /// - Returns a `bool` is successful or an Error.
+    pub fn verify_prehashed(&self, digest: &[u8; 32], signature: &[u8]) -> Result<bool> {
+        check_signature_len(signature, 64)?;
+        let generic_arr = GenericArray::clone_from_slice(&self.1);
+        let encoded_vk = EncodedPoint::from_untagged_bytes(&generic_arr);
+        let public_key = k256::PublicKey::from_sec1_bytes(encoded_vk.as_bytes())
+            .map_err(|_| CryptoError::SignatureError)?;
+        let signature = Signature::try_from(signature).map_err(|_| CryptoError::SignatureError)?;
+        let z = Scalar::from_bytes_reduced(GenericArray::from_slice(digest));
+        Ok(public_key
+            .as_affine()
+            .verify_prehashed(&z, &signature)
+            .is_ok())
+    }
+
+    /// Returns `true` if the `s` component of a raw (`r + s`) signature is at most `n / 2`.
+    ///
+    /// Nothing is normalized - this is for protocols that must reject high-S signatures while keeping accepted ones
+    /// bit-for-bit as received. A signature of the wrong length or with `s = 0` is never low-S.
+    ///
+    /// Note: `k256::Scalar::is_high` can't be used here as it also counts `s = n / 2` as high.
+    pub fn is_low_s(sig: &[u8]) -> bool {
+        sig.len() == 64 && s_is_low(&sig[32..], &BigInt::from_bytes_be(Sign::Plus, &SECP256K1_N))
+    }
+
+    /// Returns `true` if the `s` component of a signature is in `(n / 2, n)`, i.e. `normalize_s` would change it.
+    ///
+    /// An out of range `s` (`0` or `>= n`) is neither low nor high - such a signature never verifies.
+    pub fn is_high_s(sig: &[u8; 64]) -> bool {
+        s_is_high(&sig[32..], &BigInt::from_bytes_be(Sign::Plus, &SECP256K1_N))
+    }
+
+    /// Replaces a high `s` with `n - s` (BIP 62 low-S form) using k256's `Signature::normalize_s`. Both forms
+    /// verify, so this is for protocols like Bitcoin that only accept the low one.
+    ///
+    /// Signatures that aren't high-S are left as they are, as is one whose `r` is out of range.
+    pub fn normalize_s(sig: &mut [u8; 64]) {
+        // k256 counts `s = n / 2` as high too, so it only gets to see the ones that are high here
+        if !Self::is_high_s(sig) {
+            return;
+        }
+        if let Ok(mut signature) = Signature::try_from(&sig[..]) {
+            if signature.normalize_s().is_ok() {
+                sig.copy_from_slice(signature.as_ref());
+            }
+        }
+    }
+
+    /// Recovers the signer's public key from a signature over `msg` (hashed with SHA-256, as in `sign`) and its
+    /// recovery-id, the way Ethereum's `ecrecover` does.
+    ///
+    /// - `recovery_id` is `0..=3` - bit 0 is the parity of `R`'s y co-ordinate and bit 1 is set when `R`'s x
+    /// co-ordinate is `r + n` (practically never, for secp256k1).
+    /// - Returns the uncompressed (65 byte) public key or `CryptoError::SignatureError` if no key can be recovered.
+    pub fn recover(msg: &[u8], sig: &[u8], recovery_id: u8) -> Result<[u8; 65]> {
+        let digest = SHA256Digest.digest(msg);
+        let public_key = recover_public_key(&digest, sig, recovery_id)?;
+        Ok(EncodedPoint::encode(public_key, false).as_bytes().try_into().unwrap())
+    }
+}
+
+/// A secp256k1 ECDSA-SHA256 signing key that is parsed once, for signing many messages (`ECSignature::sign`
+/// re-parses `ECDSASHA256Signature`'s key bytes on every call). Signatures are the same as `sign`'s.
+pub struct PreparedSigner(SigningKey);
+
+impl PreparedSigner {
+    /// Parses a 32 byte signing key.
+    ///
+    /// - Returns `CryptoError::SignatureError` if the key isn't a valid scalar.
+    pub fn new(signing_key: &[u8; 32]) -> Result<Self> {
+        SigningKey::from_bytes(signing_key).map(PreparedSigner).map_err(|_| CryptoError::SignatureError)
+    }
+
+    /// Signs `data` (hashed with SHA-256).
+    pub fn sign(&self, data: &[u8]) -> Result<[u8; 64]> {
+        let signature: Signature = self.0.sign(data);
+        Ok(signature.as_ref().try_into().unwrap())
+    }
+
+    /// The verifier for this key.
+    pub fn verifier(&self) -> PreparedVerifier {
+        PreparedVerifier(VerifyingKey::from(&self.0))
+    }
+}
+
+/// A secp256k1 ECDSA-SHA256 verifying key that is parsed once, for verifying many signatures - the counterpart
+/// of `PreparedSigner`.
+pub struct PreparedVerifier(VerifyingKey);
+
+impl PreparedVerifier {
+    /// Parses a 64 byte (untagged, uncompressed i.e. `x || y`) verifying key, as stored in `ECDSASHA256Signature`.
+    ///
+    /// - Returns `CryptoError::SignatureError` if the key isn't a point on the curve.
+    pub fn new(verifying_key: &[u8; 64]) -> Result<Self> {
+        let encoded_vk = EncodedPoint::from_untagged_bytes(GenericArray::from_slice(verifying_key));
+        VerifyingKey::from_encoded_point(&encoded_vk)
+            .map(PreparedVerifier)
+            .map_err(|_| CryptoError::SignatureError)
+    }
+
+    /// Verifies a signature over `data`, like `ECSignature::verify`.
+    pub fn verify(&self, data: &[u8], signature: &[u8]) -> Result<bool> {
+        check_signature_len(signature, 64)?;
+        let signature = Signature::try_from(signature).map_err(|_| CryptoError::SignatureError)?;
+        Ok(self.0.verify(data, &signature).is_ok())
+    }
+}
+
+/// An incremental signer, for messages that are too large to hold in memory: feed the message to `update` in
+/// chunks and `finalize` signs its digest. Created by `ECDSASHA256Signature::sign_stream` (SHA-256) or
+/// `ECDSASHA384Signature::sign_stream` (SHA-384).
+pub struct StreamSigner<'a, S, D> {
+    signer: &'a S,
+    hasher: D,
+}
+
+impl<'a, S, D: Digest> StreamSigner<'a, S, D> {
+    /// Hashes the next chunk of the message.
+    pub fn update(&mut self, chunk: &[u8]) {
+        self.hasher.update(chunk);
+    }
+}
+
+impl<'a> StreamSigner<'a, ECDSASHA256Signature, Sha256> {
+    /// Signs the message fed so far - the same signature as `sign` over the concatenated chunks.
+    pub fn finalize(self) -> Result<[u8; 64]> {
+        self.signer.sign_prehashed(&self.hasher.finalize().into())
+    }
+}
+
+impl<'a> StreamSigner<'a, ECDSASHA384Signature, Sha384> {
+    /// Signs the message fed so far, as `sign` would sign the concatenated chunks.
+    pub fn finalize(self) -> Result<[u8; 96]> {
+        self.signer.sign_prehashed(self.hasher.finalize().as_slice().try_into().unwrap())
+    }
+}
+
+/// The verifying counterpart of `StreamSigner`, e.g. for checking a large download without buffering it: feed the
+/// message to `update` in chunks and `finalize` verifies the signature over its digest. Created by
+/// `ECDSASHA256Signature::verify_stream` (SHA-256) or `ECDSASHA384Signature::verify_stream` (SHA-384).
+pub struct StreamVerifier<'a, S, D> {
+    verifier: &'a S,
+    signature: &'a [u8],
+    hasher: D,
+}
+
+impl<'a, S, D: Digest> StreamVerifier<'a, S, D> {
+    /// Hashes the next chunk of the message.
+    pub fn update(&mut self, chunk: &[u8]) {
+        self.hasher.update(chunk);
+    }
+}
+
+impl<'a> StreamVerifier<'a, ECDSASHA256Signature, Sha256> {
+    /// Verifies the signature over the message fed so far - the same result as `verify` over the concatenated
+    /// chunks, errors included (e.g. `CryptoError::InvalidLength` for a signature that isn't 64 bytes).
+    pub fn finalize(self) -> Result<bool> {
+        self.verifier.verify_prehashed(&self.hasher.finalize().into(), self.signature)
+    }
+}
+
+impl<'a> StreamVerifier<'a, ECDSASHA384Signature, Sha384> {
+    /// Verifies the signature over the message fed so far, as `verify` would over the concatenated chunks.
+    pub fn finalize(self) -> Result<bool> {
+        self.verifier.verify_prehashed(self.hasher.finalize().as_slice().try_into().unwrap(), self.signature)
+    }
+}
+
+/// The secp256k1 field prime `p` and group order `n` (big-endian).
+const SECP256K1_P: [u8; 32] = [
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xfe, 0xff, 0xff, 0xfc, 0x2f,
+];
+const SECP256K1_N: [u8; 32] = [
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xfe,
+    0xba, 0xae, 0xdc, 0xe6, 0xaf, 0x48, 0xa0, 0x3b, 0xbf, 0xd2, 0x5e, 0x8c, 0xd0, 0x36, 0x41, 0x41,
+];
+
+/// `Q = r^-1 * (s * R - z * G)`, where `R` is the point whose x co-ordinate is `r` (or `r + n`) and whose y
+/// co-ordinate has the parity given by the recovery-id.
+fn recover_public_key(digest: &[u8; 32], sig: &[u8], recovery_id: u8) -> Result<k256::PublicKey> {
+    if recovery_id > 3 || sig.len() != 64 {
+        return Err(CryptoError::SignatureError);
+    }
+    let r = NonZeroScalar::from_repr(GenericArray::clone_from_slice(&sig[..32]))
+        .ok_or(CryptoError::SignatureError)?;
+    let s = NonZeroScalar::from_repr(GenericArray::clone_from_slice(&sig[32..]))
+        .ok_or(CryptoError::SignatureError)?;
+
+    let mut x: [u8; 32] = sig[..32].try_into().unwrap();
+    if recovery_id & 2 != 0 {
+        // x = r + n, which has to stay below p
+        let mut carry = 0u16;
+        for (x_i, n_i) in x.iter_mut().zip(SECP256K1_N.iter()).rev() {
+            let sum = *x_i as u16 + *n_i as u16 + carry;
+            *x_i = sum as u8;
+            carry = sum >> 8;
+        }
+        if carry != 0 || x >= SECP256K1_P {
+            return Err(CryptoError::SignatureError);
+        }
+    }
+    let big_r = AffinePoint::decompress(GenericArray::from_slice(&x), Choice::from(recovery_id & 1));
+    if !bool::from(big_r.is_some()) {
+        return Err(CryptoError::SignatureError);
+    }
+
+    let z = Scalar::from_bytes_reduced(GenericArray::from_slice(digest));
+    let r_inv = r.invert().unwrap();
+    let u1 = -(r_inv * z);
+    let u2 = r_inv * *s;
+    let q = (ProjectivePoint::generator() * u1) + (ProjectivePoint::from(big_r.unwrap()) * u2);
+    // Fails for the point at infinity
+    k256::PublicKey::from_affine(q.to_affine()).map_err(|_| CryptoError::SignatureError)
+}
+
+/// A type to represent an ECDSA-Keccak256 Signature over secp256k1 (as used by Ethereum). Tuple elements 0 and 1
+/// represent the `signing and verifying` keys.
+///
+/// This is the same as `ECDSASHA256Signature` except that messages are hashed with Keccak-256.
+#[cfg(feature = "sha3")]
+pub struct ECDSAKeccak256Signature(pub [u8; 32], pub [u8; 64]);
+
+#[cfg(feature = "sha3")]
+impl ECDSAKeccak256Signature {
+    /// Generates a signer with a fresh keypair, given a 32 byte seed value.
+    pub fn generate(seed: [u8; 32]) -> Self {
+        let inner = ECDSASHA256Signature::generate(seed);
+        ECDSAKeccak256Signature(inner.0, inner.1)
+    }
+
+    fn inner(&self) -> ECDSASHA256Signature {
+        ECDSASHA256Signature(self.0, self.1)
+    }
+}
+
+#[cfg(feature = "sha3")]
+impl ECSignature for ECDSAKeccak256Signature {
+    type r = [u8; 32];
+    type s = [u8; 32];
+    type sbytes = [u8; 64];
+    type FieldSize = typenum::U32;
+
+    fn generate_keypair(&mut self, seed: [u8; 32]) {
+        *self = ECDSAKeccak256Signature::generate(seed);
+    }
+
+    fn sign(&self, data: &[u8]) -> Result<Self::sbytes> {
+        self.inner().sign_prehashed(&Keccak256Digest.digest(data))
+    }
+
+    fn verify(&self, data: &[u8], signature: &[u8]) -> Result<bool> {
+        check_signature_len(signature, 64)?;
+        self.inner().verify_prehashed(&Keccak256Digest.digest(data), signature)
+    }
+
+    fn r(s: Self::sbytes) -> [u8; 32] {
+        ECDSASHA256Signature::r(s)
+    }
+
+    fn s(s: Self::sbytes) -> [u8; 32] {
+        ECDSASHA256Signature::s(s)
+    }
+}
+
+/// The magic prefix Bitcoin prepends to a message (`0x18` is the length of the string that follows).
+const BITCOIN_MESSAGE_MAGIC: &[u8] = b"\x18Bitcoin Signed Message:\n";
+
+impl ECDSASHA256Signature {
+    /// Returns the (compressed-key) P2PKH Bitcoin address of this signer's verifying key.
+    pub fn bitcoin_address(&self) -> String {
+        let generic_arr = GenericArray::clone_from_slice(&self.1);
+        let encoded_vk = EncodedPoint::from_untagged_bytes(&generic_arr).compress();
+        p2pkh_address(encoded_vk.as_bytes())
+    }
+
+    /// Signs a message the way Bitcoin's `signmessage` does - a recoverable signature over
+    /// `sha256(sha256(magic || varint(len) || message))`.
+    ///
+    /// - Returns the 65 byte compact form i.e. `header || r || s`, where the header byte is `31 + recovery-id`
+    /// (signatures are always produced for the compressed public key).
+    pub fn sign_bitcoin_message(&self, message: &str) -> Result<[u8; 65]> {
+        let digest = bitcoin_message_digest(message);
+        let (signature, recovery_id) = self.sign_prehashed_recoverable(&digest)?;
+        let mut compact = [0; 65];
+        compact[0] = 27 + 4 + recovery_id as u8;
+        compact[1..].copy_from_slice(signature.as_ref());
+        Ok(compact)
+    }
+
+    /// Verifies a Bitcoin `signmessage` signature against a P2PKH address, by recovering the public key
+    /// from the signature and comparing the address derived from it.
+    ///
+    /// - Returns a `bool` is successful or an Error (for a malformed header byte or signature).
+    pub fn verify_bitcoin_message(message: &str, signature: &[u8; 65], address: &str) -> Result<bool> {
+        let (recovery_id, compressed) = match signature[0] {
+            27..=30 => (signature[0] - 27, false),
+            31..=34 => (signature[0] - 31, true),
+            _ => return Err(CryptoError::SignatureError),
+        };
+        if recovery_id > 1 {
+            // `r` overflowed the group order - practically impossible for secp256k1
+            return Ok(false);
+        }
+        let digest = bitcoin_message_digest(message);
+        let recoverable_signature = recoverable::Signature::new(
+            &Signature::try_from(&signature[1..]).map_err(|_| CryptoError::SignatureError)?,
+                recoverable::Id::new(recovery_id).map_err(|_| CryptoError::SignatureError)?,
+        )
+        .map_err(|_| CryptoError::SignatureError)?;
+        let verifying_key = match recoverable_signature
+            .recover_verify_key_from_digest_bytes(GenericArray::from_slice(&digest))
+        {
+            Ok(verifying_key) => verifying_key,
+            Err(_) => return Ok(false),
+        };
+        // k256 does not check that the recovered key actually verifies the signature
+        let z = Scalar::from_bytes_reduced(GenericArray::from_slice(&digest));
+        let public_key = k256::PublicKey::from_sec1_bytes(verifying_key.to_encoded_point(false).as_bytes())
+            .map_err(|_| CryptoError::SignatureError)?;
+        if public_key
+            .as_affine()
+            .verify_prehashed(&z, &recoverable_signature.into())
+            .is_err()
+        {
+            return Ok(false);
+        }
+        Ok(p2pkh_address(verifying_key.to_encoded_point(compressed).as_bytes()) == address)
+    }
+}
+
+/// `sha256(sha256(magic || varint(len) || message))`
+fn bitcoin_message_digest(message: &str) -> [u8; 32] {
+    let len = message.len();
+    let mut data = Vec::with_capacity(BITCOIN_MESSAGE_MAGIC.len() + 9 + len);
+    data.extend_from_slice(BITCOIN_MESSAGE_MAGIC);
+    match len {
+        0..=0xfc => data.push(len as u8),
+        0xfd..=0xffff => {
+            data.push(0xfd);
+            data.extend_from_slice(&(len as u16).to_le_bytes());
+        }
+        0x1_0000..=0xffff_ffff => {
+            data.push(0xfe);
+            data.extend_from_slice(&(len as u32).to_le_bytes());
+        }
+        _ => {
+            data.push(0xff);
+            data.extend_from_slice(&(len as u64).to_le_bytes());
+        }
+    }
+    data.extend_from_slice(message.as_bytes());
+    SHA256Digest.digest(&SHA256Digest.digest(&data))
+}
+
+/// A mainnet P2PKH address i.e. `base58check(0x00 || ripemd160(sha256(pubkey)))`
+fn p2pkh_address(sec1_pubkey: &[u8]) -> String {
+    let mut payload = [0; 21];
+    payload[1..].copy_from_slice(&RIPEMD160Digest.digest(&SHA256Digest.digest(sec1_pubkey)));
+    encoding::to_base58check(&payload)
+}
+
 /// A type to represent an ECDSA-SHA384 Signature. Tuple elements 0 and 1 represent the `signing and verifying` keys
 pub struct ECDSASHA384Signature(pub [u8; 48], pub EncodedPointP384<NistP384>);
 
@@ -128,29 +725,19 @@ impl ECSignature for ECDSASHA384Signature {
     type r = [u8; 48];
     type s = [u8; 48];
     type sbytes = [u8; 96]; // signature bytes
+    type FieldSize = typenum::U48;
 
     fn generate_keypair(&mut self, seed: [u8; 32]) {
-        let signing_key = ECDHNISTP384::<48>::generate_private_key(seed); // reusing functionality from ECDH module
-        let verifying_key = ECDHNISTP384::<48>::generate_public_key(&signing_key);
-        self.0 = signing_key.to_bytes().as_slice().try_into().unwrap();
-        self.1 = verifying_key.0;
+        *self = ECDSASHA384Signature::generate(seed);
     }
 
     fn sign(&self, data: &[u8]) -> Result<Self::sbytes> {
-        let (r, s) = ECSignerType::<48>::sign(data, &self.0);
-        let r_bytes: [u8; 48] = r.to_bytes_be().1.as_slice().try_into().unwrap();
-        let s_bytes: [u8; 48] = s.to_bytes_be().1.as_slice().try_into().unwrap();
-        let mut sbytes = [0; 96];
-        let _temp: () = r_bytes
-            .iter()
-            .chain(s_bytes.iter())
-            .enumerate()
-            .map(|(i, x)| sbytes[i] = *x)
-            .collect();
-        Ok(sbytes)
+        let digest = SHA384Digest.digest(data);
+        self.sign_prehashed(&digest)
     }
 
     fn verify(&self, data: &[u8], signature: &[u8]) -> Result<bool> {
+        check_signature_len(signature, 96)?;
         let verification_status = ECSignerType::<48>::verify(data, signature, self.1);
         verification_status
     }
@@ -165,3 +752,379 @@ impl ECSignature for ECDSASHA384Signature {
         s_bytes
     }
 }
+
+impl ECDSASHA384Signature {
+    /// Generates a signer with a fresh keypair, given a 32 byte seed value.
+    pub fn generate(seed: [u8; 32]) -> Self {
+        let signing_key = ECDHNISTP384::<48>::generate_private_key(seed); // reusing functionality from ECDH module
+        let verifying_key = ECDHNISTP384::<48>::generate_public_key(&signing_key);
+        ECDSASHA384Signature(signing_key.to_bytes().as_slice().try_into().unwrap(), verifying_key.0)
+    }
+
+    /// Recomputes the verifying key for a stored 48 byte signing key (i.e. the private scalar).
+    ///
+    /// - Returns the verifying key or `CryptoError::ScalarOutOfRange` if the scalar isn't in `[1, n - 1]`.
+    pub fn verifying_key_from_signing(signing: &[u8; 48]) -> Result<EncodedPointP384<NistP384>> {
+        // `SkP384::from_bytes` range-checks the scalar
+        let signing_key = SkP384::from_bytes(signing)?;
+        Ok(ECDHNISTP384::<48>::generate_public_key(&signing_key).0)
+    }
+
+    /// Builds a signer from a stored 48 byte signing key, deriving the verifying key - no RNG involved, unlike
+    /// `generate`.
+    ///
+    /// - Returns `CryptoError::ScalarOutOfRange` if the scalar isn't in `[1, n - 1]`.
+    pub fn from_signing_key(signing: &[u8; 48]) -> Result<Self> {
+        Ok(ECDSASHA384Signature(*signing, Self::verifying_key_from_signing(signing)?))
+    }
+
+    /// Starts signing a message incrementally, see `StreamSigner`.
+    pub fn sign_stream(&self) -> StreamSigner<'_, Self, Sha384> {
+        StreamSigner { signer: self, hasher: Sha384::new() }
+    }
+
+    /// Starts verifying `signature` over a message fed incrementally, see `StreamVerifier`.
+    pub fn verify_stream<'a>(&'a self, signature: &'a [u8]) -> StreamVerifier<'a, Self, Sha384> {
+        StreamVerifier { verifier: self, signature, hasher: Sha384::new() }
+    }
+
+    /// Signs an already computed SHA-384 digest, skipping the hashing step in `sign`.
+    pub fn sign_prehashed(&self, digest: &[u8; 48]) -> Result<[u8; 96]> {
+        let (r, s) = ECSignerType::<48>::sign_prehashed(digest, &self.0)?;
+        Ok(p384_signature_bytes(&r, &s))
+    }
+
+    /// Same as `sign` but draws the nonce (see `ECSignerType::sign_prehashed_with_rng`) from `rng` rather than
+    /// `rand::thread_rng()` - e.g. without the `std` feature, where `sign` falls back to the deterministic RFC 6979
+    /// nonce.
+    pub fn sign_with_rng<R: RngCore + CryptoRng>(&self, data: &[u8], rng: &mut R) -> Result<[u8; 96]> {
+        let digest = SHA384Digest.digest(data);
+        let (r, s) = ECSignerType::<48>::sign_prehashed_with_rng(&digest, &self.0, rng)?;
+        Ok(p384_signature_bytes(&r, &s))
+    }
+
+    /// Signs `data` with a hedged nonce: RFC 6979 with `extra_entropy` mixed into its HMAC-DRBG (see
+    /// `ECSignerType::sign_prehashed_hedged`). Different entropy gives a different (but equally valid)
+    /// signature, and an all-zero `extra_entropy` gives the deterministic RFC 6979 one.
+    ///
+    /// `extra_entropy` should be fresh randomness - unlike `sign`, a repeated or predictable value only costs the
+    /// fault-attack resistance, not the key.
+    pub fn sign_hedged(&self, data: &[u8], extra_entropy: &[u8; 32]) -> Result<[u8; 96]> {
+        let digest = SHA384Digest.digest(data);
+        let (r, s) = ECSignerType::<48>::sign_prehashed_hedged(&digest, &self.0, extra_entropy)?;
+        Ok(p384_signature_bytes(&r, &s))
+    }
+
+    /// Verifies a signature over an already computed SHA-384 digest.
+    ///
+    /// - Returns `Ok(false)` if the signature doesn't verify or an Error if it's malformed (see
+    ///   `ECSignerType::verify_prehashed`).
+    pub fn verify_prehashed(&self, digest: &[u8; 48], signature: &[u8]) -> Result<bool> {
+        ECSignerType::<48>::verify_prehashed(digest, signature, self.1)
+    }
+
+    /// Signs `data` hashed with `D` rather than SHA-384 (e.g. `sha2::Sha256` or `sha2::Sha512`, as some legacy
+    /// systems pair P-384 with them). The digest is truncated or padded to 48 bytes as per FIPS 186-4.
+    pub fn sign_with_digest<D: Digest>(&self, data: &[u8]) -> Result<[u8; 96]> {
+        self.sign_prehashed(&ECSignerType::<48>::digest_to_field(&D::digest(data)))
+    }
+
+    /// Verifies a signature made with `sign_with_digest::<D>`.
+    pub fn verify_with_digest<D: Digest>(&self, data: &[u8], signature: &[u8]) -> Result<bool> {
+        check_signature_len(signature, 96)?;
+        ECSignerType::<48>::verify_with_digest::<D>(data, signature, self.1)
+    }
+
+    /// Returns `true` if the `s` component of a raw (`r + s`) signature is at most `n / 2`.
+    ///
+    /// As with `ECDSASHA256Signature::is_low_s`, the signature isn't modified and a wrong length or `s = 0` gives
+    /// `false`. Note that `sign` doesn't normalize either, so roughly half of its signatures are high-S.
+    pub fn is_low_s(sig: &[u8]) -> bool {
+        let (_, _, _, g_ord) = p384_constants();
+        sig.len() == 96 && s_is_low(&sig[48..], g_ord)
+    }
+
+    /// Returns `true` if the `s` component of a signature is in `(n / 2, n)`, i.e. `normalize_s` would change it.
+    ///
+    /// An out of range `s` (`0` or `>= n`) is neither low nor high - such a signature never verifies.
+    pub fn is_high_s(sig: &[u8; 96]) -> bool {
+        let (_, _, _, g_ord) = p384_constants();
+        s_is_high(&sig[48..], g_ord)
+    }
+
+    /// Replaces a high `s` with `n - s`, so that e.g. signatures from `sign` (which doesn't normalize) can be
+    /// passed to a verifier that enforces low-S. Signatures that aren't high-S are left as they are.
+    pub fn normalize_s(sig: &mut [u8; 96]) {
+        if !Self::is_high_s(sig) {
+            return;
+        }
+        let (_, _, _, g_ord) = p384_constants();
+        let s = g_ord - BigInt::from_bytes_be(Sign::Plus, &sig[48..]);
+        let r = BigInt::from_bytes_be(Sign::Plus, &sig[..48]);
+        *sig = p384_signature_bytes(&r, &s);
+    }
+
+    /// Parses the `r` and `s` components of a raw signature as integers reduced mod `n`, e.g. to cross-check them
+    /// against another library or to recompute the verification equation by hand.
+    ///
+    /// Note: a valid signature has both in `[1, n - 1]`, which the reduction leaves as they are - only out of range
+    /// components (which `verify` rejects) are changed by it.
+    pub fn r_s_bigint(sig: &[u8; 96]) -> (BigUint, BigUint) {
+        let n = p384_constants().3.to_biguint().unwrap();
+        let r = BigUint::from_bytes_be(&sig[..48]) % &n;
+        let s = BigUint::from_bytes_be(&sig[48..]) % &n;
+        (r, s)
+    }
+}
+
+/// The raw `r + s` encoding of a p384 signature - both are left-padded, as either can have leading zero bytes.
+fn p384_signature_bytes(r: &BigInt, s: &BigInt) -> [u8; 96] {
+    let r_bytes = r.to_bytes_be().1;
+    let s_bytes = s.to_bytes_be().1;
+    let mut sbytes = [0; 96];
+    sbytes[48 - r_bytes.len()..48].copy_from_slice(&r_bytes);
+    sbytes[96 - s_bytes.len()..].copy_from_slice(&s_bytes);
+    sbytes
+}
+
+/// A type to represent an ECDSA-SHA256 Signature over NIST P-256 (secp256r1 - the curve that e.g. TLS and COSE's
+/// `ES256` mean by ECDSA-SHA256, unlike `ECDSASHA256Signature`'s secp256k1). Tuple elements 0 and 1 represent the
+/// `signing and verifying` keys, the latter in uncompressed SEC1 form i.e. `0x04 || x || y`.
+///
+/// As with `ECDSASHA256Signature`, the nonce is derived as per RFC 6979, so signatures are deterministic.
+///
+/// Note: this runs on the `num_bigint_dig` affine-point arithmetic (RustCrypto's `p256` crate isn't a dependency),
+/// which is variable time - signing leaks timing information about the key and nonce. Don't use it where an
+/// attacker can time signing operations.
+pub struct ECDSAP256SHA256Signature(pub [u8; 32], pub [u8; 65]);
+
+impl ECSignature for ECDSAP256SHA256Signature {
+    type r = [u8; 32];
+    type s = [u8; 32];
+    type sbytes = [u8; 64];
+    type FieldSize = typenum::U32;
+
+    fn generate_keypair(&mut self, seed: [u8; 32]) {
+        *self = ECDSAP256SHA256Signature::generate(seed);
+    }
+
+    fn sign(&self, data: &[u8]) -> Result<Self::sbytes> {
+        self.sign_prehashed(&SHA256Digest.digest(data))
+    }
+
+    fn verify(&self, data: &[u8], signature: &[u8]) -> Result<bool> {
+        check_signature_len(signature, 64)?;
+        self.verify_prehashed(&SHA256Digest.digest(data), signature)
+    }
+
+    fn r(s: Self::sbytes) -> [u8; 32] {
+        s[..32].try_into().unwrap()
+    }
+
+    fn s(s: Self::sbytes) -> [u8; 32] {
+        s[32..].try_into().unwrap()
+    }
+}
+
+impl ECDSAP256SHA256Signature {
+    /// Generates a signer with a fresh keypair, given a 32 byte seed value.
+    pub fn generate(seed: [u8; 32]) -> Self {
+        let mut rng = ChaChaRng::from_seed(seed);
+        let mut signing_key = [0; 32];
+        // Draw until the scalar is in `[1, n - 1]` - for P-256, about 1 in 2^32 draws isn't.
+        let verifying_key = loop {
+            rng.fill_bytes(&mut signing_key);
+            if let Ok(verifying_key) = Self::verifying_key_from_signing(&signing_key) {
+                break verifying_key;
+            }
+        };
+        ECDSAP256SHA256Signature(signing_key, verifying_key)
+    }
+
+    /// Builds a signer from a stored 32 byte signing key, deriving the verifying key - no RNG involved, unlike
+    /// `generate`.
+    ///
+    /// - Returns `CryptoError::ScalarOutOfRange` if the scalar isn't in `[1, n - 1]`.
+    pub fn from_signing_key(signing: &[u8; 32]) -> Result<Self> {
+        Ok(ECDSAP256SHA256Signature(*signing, Self::verifying_key_from_signing(signing)?))
+    }
+
+    /// Recomputes the (uncompressed) verifying key for a stored 32 byte signing key.
+    ///
+    /// - Returns the verifying key or `CryptoError::ScalarOutOfRange` if the scalar isn't in `[1, n - 1]`.
+    pub fn verifying_key_from_signing(signing: &[u8; 32]) -> Result<[u8; 65]> {
+        let d = p256_scalar(signing)?;
+        let point = MyAffinePoint::<P256Params>::double_and_add(MyAffinePoint::generator(), d);
+        let mut encoded = [0x04; 65];
+        encoded[1..33].copy_from_slice(&p256_field_bytes(&point.x));
+        encoded[33..].copy_from_slice(&p256_field_bytes(&point.y));
+        Ok(encoded)
+    }
+
+    /// Signs an already computed SHA-256 digest, skipping the hashing step in `sign`.
+    ///
+    /// - Returns `CryptoError::ScalarOutOfRange` if the signing key isn't in `[1, n - 1]`.
+    pub fn sign_prehashed(&self, digest: &[u8; 32]) -> Result<[u8; 64]> {
+        let n = &p256_constants().3;
+        let d = BigInt::from_biguint(Sign::Plus, p256_scalar(&self.0)?);
+        // The digest and `n` are both 256 bits, so `bits2int(digest)` is the digest itself
+        let z = BigInt::from_bytes_be(Sign::Plus, digest);
+        let mut hmac_drbg = HmacDrbg::<Sha256>::new(&self.0, &p256_field_bytes(&(&z % n)));
+        loop {
+            let k = BigInt::from_bytes_be(Sign::Plus, &hmac_drbg.next());
+            if k.is_zero() || &k >= n {
+                continue;
+            }
+            let k_mul = MyAffinePoint::<P256Params>::double_and_add(MyAffinePoint::generator(), k.to_biguint().unwrap());
+            let r = k_mul.x % n;
+            if r.is_zero() {
+                continue;
+            }
+            let s = (invert_mod_prime(&k, n) * ((&z + &r * &d) % n)) % n;
+            if s.is_zero() {
+                continue;
+            }
+            let mut sbytes = [0; 64];
+            sbytes[..32].copy_from_slice(&p256_field_bytes(&r));
+            sbytes[32..].copy_from_slice(&p256_field_bytes(&s));
+            return Ok(sbytes);
+        }
+    }
+
+    /// Verifies a signature over an already computed SHA-256 digest.
+    ///
+    /// - Returns `Ok(false)` if the signature doesn't verify, `CryptoError::InvalidLength` if it isn't 64 bytes,
+    ///   `CryptoError::InvalidEncoding` if `r` or `s` is outside `[1, n - 1]` (or the verifying key isn't
+    ///   uncompressed SEC1) and `CryptoError::PointNotOnCurve` for a verifying key that's off the curve.
+    pub fn verify_prehashed(&self, digest: &[u8; 32], signature: &[u8]) -> Result<bool> {
+        check_signature_len(signature, 64)?;
+        let n = &p256_constants().3;
+        let r = BigInt::from_bytes_be(Sign::Plus, &signature[..32]);
+        let s = BigInt::from_bytes_be(Sign::Plus, &signature[32..]);
+        if r.is_zero() || &r >= n || s.is_zero() || &s >= n {
+            return Err(CryptoError::InvalidEncoding);
+        }
+        let public_key = p256_point(&self.1)?;
+
+        let z = BigInt::from_bytes_be(Sign::Plus, digest);
+        let s_inverse = s.mod_inverse(n).unwrap();
+        let u1 = (z * &s_inverse) % n;
+        let u2 = (&r * &s_inverse) % n;
+        let u1_mul = MyAffinePoint::<P256Params>::double_and_add(MyAffinePoint::generator(), u1.to_biguint().unwrap());
+        let u2_mul = MyAffinePoint::double_and_add(public_key, u2.to_biguint().unwrap());
+        let result = u1_mul.do_the_math(u2_mul);
+        if result.is_identity() {
+            return Ok(false);
+        }
+        Ok(bool::from(signature[..32].ct_eq(&p256_field_bytes(&(result.x % n)))))
+    }
+}
+
+/// Parses a big-endian P-256 scalar, which must be in `[1, n - 1]`.
+fn p256_scalar(bytes: &[u8; 32]) -> Result<BigUint> {
+    let d = BigUint::from_bytes_be(bytes);
+    if d.is_zero() || d >= p256_constants().3.to_biguint().unwrap() {
+        return Err(CryptoError::ScalarOutOfRange);
+    }
+    Ok(d)
+}
+
+/// Parses an uncompressed SEC1 P-256 point, with the checks of `MyAffinePoint::validate`: `x, y < p`
+/// (`CryptoError::InvalidEncoding`), on the curve (`CryptoError::PointNotOnCurve`) and not `(0, 0)`, which `new`
+/// reads as the identity (`CryptoError::PointAtInfinity`).
+fn p256_point(bytes: &[u8; 65]) -> Result<MyAffinePoint<P256Params>> {
+    if bytes[0] != 0x04 {
+        return Err(CryptoError::InvalidEncoding);
+    }
+    let x = BigInt::from_bytes_be(Sign::Plus, &bytes[1..33]);
+    let y = BigInt::from_bytes_be(Sign::Plus, &bytes[33..]);
+    let point = MyAffinePoint::new(x, y);
+    let (a, b, modp, _) = p256_constants();
+    point.validate(a, b, modp)?;
+    Ok(point)
+}
+
+/// Left-pads a P-256 field element or scalar to 32 big-endian bytes.
+fn p256_field_bytes(x: &BigInt) -> [u8; 32] {
+    let be = x.to_bytes_be().1;
+    let mut bytes = [0; 32];
+    bytes[32 - be.len()..].copy_from_slice(&be);
+    bytes
+}
+
+/// `0 < s <= n / 2` (rounding down, as `n` is odd)
+fn s_is_low(s: &[u8], n: &BigInt) -> bool {
+    let s = BigInt::from_bytes_be(Sign::Plus, s);
+    s > BigInt::from(0) && s <= (n >> 1usize)
+}
+
+/// `true` if a big-endian `s` is in `(n / 2, n)`.
+fn s_is_high(s: &[u8], n: &BigInt) -> bool {
+    let s = BigInt::from_bytes_be(Sign::Plus, s);
+    s > (n >> 1usize) && &s < n
+}
+
+/// Rejects a raw signature (`r + s`) that isn't exactly `expected` bytes, before any parsing or hashing.
+fn check_signature_len(signature: &[u8], expected: usize) -> Result<()> {
+    if signature.len() != expected {
+        return Err(CryptoError::InvalidLength { expected, got: signature.len() });
+    }
+    Ok(())
+}
+
+/// Signs `message` with the ECDSA scheme for `curve_id` i.e. ECDSA-SHA256 for secp256k1 and ECDSA-SHA384 for p384.
+///
+/// - `signing_key_bytes` is the raw private scalar (32 or 48 bytes).
+/// - Returns the raw signature (`r + s`), `CryptoError::InvalidLength` for a key of the wrong length,
+/// `CryptoError::ScalarOutOfRange` for a P-384 key outside `[1, n - 1]` or `CryptoError::UnsupportedCurve` if there's
+/// no signature scheme for the curve.
+pub fn sign_with_curve(curve_id: CurveId, signing_key_bytes: &[u8], message: &[u8]) -> Result<Vec<u8>> {
+    match curve_id {
+        CurveId::Secp256k1 => {
+            let sk = signing_key_bytes
+                .try_into()
+                .map_err(|_| CryptoError::InvalidLength { expected: 32, got: signing_key_bytes.len() })?;
+            let signature = ECDSASHA256Signature(sk, [0; 64]).sign(message)?;
+            Ok(signature.to_vec())
+        }
+        CurveId::NistP384 => {
+            // Checks the length and that the scalar is in `[1, n - 1]`
+            SkP384::from_bytes(signing_key_bytes)?;
+            let sk = signing_key_bytes.try_into().unwrap();
+            let signature = ECDSASHA384Signature(sk, EncodedPointP384::identity()).sign(message)?;
+            Ok(signature.to_vec())
+        }
+        CurveId::NistP521 => Err(CryptoError::UnsupportedCurve),
+    }
+}
+
+/// Verifies a signature produced by `sign_with_curve`.
+///
+/// - `pubkey_bytes` is the SEC1 encoded verifying key (uncompressed, or for secp256k1 compressed too).
+/// - Returns `Ok(false)` if the signature doesn't verify, `CryptoError::InvalidLength` for a key or signature of the wrong
+/// length, `CryptoError::InvalidEncoding` for a malformed key or signature or `CryptoError::UnsupportedCurve` if there's
+/// no signature scheme for the curve. P-384 keys are parsed by `PkP384::from_bytes`, so a point off the curve is
+/// `CryptoError::PointNotOnCurve` and the identity `CryptoError::PointAtInfinity`.
+pub fn verify_with_curve(
+    curve_id: CurveId,
+    pubkey_bytes: &[u8],
+    message: &[u8],
+    signature: &[u8],
+) -> Result<bool> {
+    match curve_id {
+        CurveId::Secp256k1 => {
+            check_signature_len(signature, 64)?;
+            let public_key = k256::PublicKey::from_sec1_bytes(pubkey_bytes)
+                .map_err(|_| CryptoError::InvalidEncoding)?;
+            let vk = EncodedPoint::encode(public_key, false);
+            let verifier = ECDSASHA256Signature([0; 32], vk.as_bytes()[1..].try_into().unwrap());
+            verifier.verify(message, signature)
+        }
+        CurveId::NistP384 => {
+            check_signature_len(signature, 96)?;
+            let verifier = ECDSASHA384Signature([0; 48], PkP384::from_bytes(pubkey_bytes)?.0);
+            verifier.verify(message, signature)
+        }
+        CurveId::NistP521 => Err(CryptoError::UnsupportedCurve),
+    }
+}
@@ -0,0 +1,57 @@
+//! Deterministic keypairs for tests and examples, so that "Alice" and "Bob" are the same keys everywhere.
+//!
+//! Keypair `i` is generated from a fixed base seed with `i` mixed into its last byte, so every index gives a
+//! distinct (and stable) keypair for each curve. For more than 256 keys, `deterministic_keypair` hashes a `u64`
+//! counter into the seed instead.
+//!
+//! Caution: the seed is public - these keys must never be used outside of tests.
+
+use alloc::vec::Vec;
+
+use crate::digest::SHA256Digest;
+use crate::ecdh::ecdh::{
+    keypair, KeyExchange, PkP384, PkP521, Pkk256, SkP384, SkP521, Skk256, ECDHNISTK256, ECDHNISTP384,
+    ECDHNISTP521,
+};
+
+const BASE_SEED: [u8; 32] = *b"static-dh-ecdh test keypair seed";
+/// Domain separation for the seeds of `deterministic_keypair`.
+const DETERMINISTIC_SEED_LABEL: &[u8] = b"static-dh-ecdh deterministic keypair";
+
+/// The seed behind keypair `index`.
+pub fn test_seed(index: u8) -> [u8; 32] {
+    let mut seed = BASE_SEED;
+    seed[31] ^= index;
+    seed
+}
+
+/// Keypair `index` for the curve `C`.
+pub fn test_keypair_for<C: KeyExchange>(index: u8) -> (C::SKey, C::PubKey) {
+    keypair::<C>(test_seed(index))
+}
+
+/// Keypair `index` for secp256k1.
+pub fn test_keypair(index: u8) -> (Skk256, Pkk256) {
+    test_keypair_for::<ECDHNISTK256>(index)
+}
+
+/// Keypair `index` for P-384.
+pub fn test_keypair_p384(index: u8) -> (SkP384, PkP384) {
+    test_keypair_for::<ECDHNISTP384<48>>(index)
+}
+
+/// Keypair `index` for P-521.
+pub fn test_keypair_p521(index: u8) -> (SkP521, PkP521) {
+    test_keypair_for::<ECDHNISTP521>(index)
+}
+
+/// Keypair `index` for the curve `C`, from the seed `SHA-256("static-dh-ecdh deterministic keypair" || index)`
+/// (`index` as 8 big-endian bytes) - stable across runs and platforms.
+///
+/// Caution: test-only, like the rest of this module.
+pub fn deterministic_keypair<C: KeyExchange>(index: u64) -> (C::SKey, C::PubKey) {
+    let mut input = Vec::with_capacity(DETERMINISTIC_SEED_LABEL.len() + 8);
+    input.extend_from_slice(DETERMINISTIC_SEED_LABEL);
+    input.extend_from_slice(&index.to_be_bytes());
+    keypair::<C>(SHA256Digest.digest(&input))
+}
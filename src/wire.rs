@@ -0,0 +1,93 @@
+//! A compact binary format for the common "signed ephemeral key" handshake message.
+//!
+//! A `HandshakeMessage` bundles an ephemeral public key with an `ECDSASHA256Signature` (secp256k1) over the key's
+//! serialized form. On the wire, it's the two as length-prefixed fields:
+//!
+//! - `u16 BE len(pk) || pk || u16 BE len(signature) || signature`
+//!
+//! Both lengths are fixed for a curve, so they're checked (rather than trusted) when parsing.
+
+use core::convert::TryFrom;
+
+use alloc::vec::Vec;
+use generic_array::typenum::Unsigned;
+use k256::ecdsa::Signature;
+
+use crate::ecdh::ecdh::{FromBytes, KeyExchange, ToBytes};
+use crate::signatures::{ECDSASHA256Signature, ECSignature};
+use crate::{CryptoError, Result};
+
+/// The length of the raw `r || s` signature.
+const SIGNATURE_LEN: usize = 64;
+
+/// An ephemeral public key for the curve `C` along with a signature over it.
+pub struct HandshakeMessage<C: KeyExchange> {
+    /// The sender's ephemeral public key.
+    pub ephemeral_pk: C::PubKey,
+    /// The raw `r || s` signature over `ephemeral_pk.to_bytes()`.
+    pub signature: [u8; 64],
+}
+
+impl<C: KeyExchange> HandshakeMessage<C> {
+    /// Signs `ephemeral_pk` (i.e. its serialized form) with the long-term `signer`.
+    pub fn sign(ephemeral_pk: C::PubKey, signer: &ECDSASHA256Signature) -> Result<Self> {
+        let signature = signer.sign(&ephemeral_pk.to_bytes())?;
+        Ok(HandshakeMessage { ephemeral_pk, signature })
+    }
+
+    /// Verifies the signature against the sender's long-term verifying key (see
+    /// `ECDSASHA256Signature::verify_with_key` for the accepted encodings).
+    pub fn verify(&self, verifying_key: &[u8]) -> Result<bool> {
+        ECDSASHA256Signature::verify_with_key(verifying_key, &self.ephemeral_pk.to_bytes(), &self.signature)
+    }
+
+    /// Serializes the message as `u16 BE len(pk) || pk || u16 BE len(signature) || signature`.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let pk = self.ephemeral_pk.to_bytes();
+        let mut bytes = Vec::with_capacity(4 + pk.len() + SIGNATURE_LEN);
+        write_field(&mut bytes, &pk);
+        write_field(&mut bytes, &self.signature);
+        bytes
+    }
+
+    /// Parses a message produced by `to_bytes`, validating both the public key and the signature's encoding
+    /// (the signature itself is checked by `verify`).
+    ///
+    /// - Returns `CryptoError::InvalidEncoding` for a truncated buffer, trailing bytes, a length prefix that
+    ///   doesn't match the curve or a signature with `r` or `s` out of range, and the errors of
+    ///   `C::PubKey::from_bytes` (e.g. `CryptoError::PointNotOnCurve`) for an invalid key.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        let mut rest = bytes;
+        let pk = read_field(&mut rest, <C::PubKey as ToBytes>::OutputSize::to_usize())?;
+        let signature = read_field(&mut rest, SIGNATURE_LEN)?;
+        if !rest.is_empty() {
+            return Err(CryptoError::InvalidEncoding);
+        }
+
+        let ephemeral_pk = C::PubKey::from_bytes(pk)?;
+        Signature::try_from(signature).map_err(|_| CryptoError::InvalidEncoding)?;
+        let mut raw_signature = [0; SIGNATURE_LEN];
+        raw_signature.copy_from_slice(signature);
+        Ok(HandshakeMessage { ephemeral_pk, signature: raw_signature })
+    }
+}
+
+/// Appends `field` with its `u16` big-endian length prefix.
+fn write_field(bytes: &mut Vec<u8>, field: &[u8]) {
+    bytes.extend_from_slice(&(field.len() as u16).to_be_bytes());
+    bytes.extend_from_slice(field);
+}
+
+/// Reads a length-prefixed field of exactly `expected` bytes off the front of `bytes`.
+fn read_field<'a>(bytes: &mut &'a [u8], expected: usize) -> Result<&'a [u8]> {
+    if bytes.len() < 2 {
+        return Err(CryptoError::InvalidEncoding);
+    }
+    let len = u16::from_be_bytes([bytes[0], bytes[1]]) as usize;
+    if len != expected || bytes.len() - 2 < len {
+        return Err(CryptoError::InvalidEncoding);
+    }
+    let (field, rest) = bytes[2..].split_at(len);
+    *bytes = rest;
+    Ok(field)
+}